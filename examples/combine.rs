@@ -0,0 +1,49 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{Demo, DrawContext};
+use onion::graphics::render_pass::{basic::BasicMSAADrawPass, overlay::OverlayDrawPass};
+use onion::prelude::*;
+use vulkano::image::Image;
+
+/// Textures, shapes and the overlay pass in one frame, like `src/bin/graphics.rs`.
+struct Combine {
+    image: Arc<Image>,
+}
+
+impl Demo for Combine {
+    fn draw(&mut self, ctx: &mut DrawContext, pass: &mut BasicMSAADrawPass) {
+        let cb = Texture::new(0.5).draw(
+            ctx.memory_allocator.clone(),
+            &mut ctx.pipelines.texture,
+            self.image.clone(),
+            pass.viewport_dimensions(),
+        );
+        pass.execute(cb).unwrap();
+
+        let cb = Square::new(0.1, Color::red()).draw(
+            ctx.memory_allocator.clone(),
+            &mut ctx.pipelines.basic,
+            pass.viewport_dimensions(),
+        );
+        pass.execute(cb).unwrap();
+    }
+
+    fn draw_overlay(&mut self, ctx: &mut DrawContext, pass: &mut OverlayDrawPass) {
+        let cb = Square::new(0.05, Color::black()).draw(
+            ctx.memory_allocator.clone(),
+            &mut ctx.pipelines.overlay,
+            pass.viewport_dimensions(),
+        );
+        pass.execute(cb).unwrap();
+    }
+}
+
+fn main() {
+    common::run("combine", |gfx| Combine {
+        image: gfx
+            .upload_png(include_bytes!("../src/bin/img.png"))
+            .unwrap(),
+    });
+}
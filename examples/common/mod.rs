@@ -0,0 +1,254 @@
+//! Shared harness for the examples: window and [`GraphicsContext`] setup, the managed frame loop,
+//! keyboard camera controls, and a debug overlay in the window title with frame timings and the
+//! [`DebugView`] of the lit pipeline, cycled with F3.
+//!
+//! Each example implements [`Demo`] and hands it to [`run`], so it only contains the parts that
+//! exercise the library.
+#![allow(dead_code)]
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use cgmath::Deg;
+use onion::graphics::{
+    context::Pipelines,
+    render_pass::{
+        basic::{BasicMSAADrawPass, BasicMSAAPass},
+        depth::{DepthDrawPass, DepthPass},
+        overlay::{OverlayDrawPass, OverlayPass},
+    },
+};
+use onion::prelude::*;
+use vulkano::memory::allocator::StandardMemoryAllocator;
+use winit::{
+    event::{ElementState, Event, KeyEvent, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+pub const CLEAR_COLOR: [f32; 4] = [0.7, 0.7, 0.7, 1.0];
+
+/// What a demo gets to draw with each frame.
+pub struct DrawContext<'a> {
+    pub pipelines: &'a mut Pipelines,
+    pub memory_allocator: Arc<StandardMemoryAllocator>,
+    pub camera: &'a PerspectiveCamera,
+}
+
+pub trait Demo {
+    /// Renders the scene with the depth pass and [`Demo::draw_3d`] instead of [`Demo::draw`].
+    const DEPTH: bool = false;
+
+    /// Draws the scene into the multisampled pass.
+    fn draw(&mut self, _ctx: &mut DrawContext, _pass: &mut BasicMSAADrawPass) {}
+
+    /// Draws the scene into the depth pass, for the lit pipelines.
+    fn draw_3d(&mut self, _ctx: &mut DrawContext, _pass: &mut DepthDrawPass) {}
+
+    /// Draws on top of the resolved scene.
+    fn draw_overlay(&mut self, _ctx: &mut DrawContext, _pass: &mut OverlayDrawPass) {}
+}
+
+/// Rotates the camera with the arrow keys and moves it with WASD, Q and E.
+pub struct CameraControls {
+    pub degrees_per_press: f32,
+    pub units_per_press: f32,
+}
+
+impl Default for CameraControls {
+    fn default() -> Self {
+        CameraControls {
+            degrees_per_press: 5.0,
+            units_per_press: 1.0,
+        }
+    }
+}
+
+impl CameraControls {
+    pub fn handle(&self, event: &KeyEvent, camera: &mut PerspectiveCamera) {
+        if event.state != ElementState::Pressed {
+            return;
+        }
+
+        let deg = Deg(self.degrees_per_press);
+        let units = self.units_per_press;
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::ArrowLeft) => camera.rotate_y(-deg),
+            PhysicalKey::Code(KeyCode::ArrowRight) => camera.rotate_y(deg),
+            PhysicalKey::Code(KeyCode::ArrowUp) => camera.rotate_x(deg),
+            PhysicalKey::Code(KeyCode::ArrowDown) => camera.rotate_x(-deg),
+            PhysicalKey::Code(KeyCode::KeyW) => camera.translate_z(units),
+            PhysicalKey::Code(KeyCode::KeyS) => camera.translate_z(-units),
+            PhysicalKey::Code(KeyCode::KeyA) => camera.translate_x(-units),
+            PhysicalKey::Code(KeyCode::KeyD) => camera.translate_x(units),
+            PhysicalKey::Code(KeyCode::KeyQ) => camera.translate_y(-units),
+            PhysicalKey::Code(KeyCode::KeyE) => camera.translate_y(units),
+            _ => (),
+        }
+    }
+}
+
+/// Averages frame times and reports them once a second.
+struct FrameStats {
+    frames: u32,
+    since: Instant,
+}
+
+impl FrameStats {
+    fn new() -> Self {
+        FrameStats {
+            frames: 0,
+            since: Instant::now(),
+        }
+    }
+
+    /// Returns the average frame time when a full second has passed.
+    fn tick(&mut self) -> Option<Duration> {
+        self.frames += 1;
+        let elapsed = self.since.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            return None;
+        }
+
+        let average = elapsed / self.frames;
+        self.frames = 0;
+        self.since = Instant::now();
+        Some(average)
+    }
+}
+
+/// Opens a window titled `title`, builds the demo with `setup` and runs the frame loop until the
+/// window is closed.
+pub fn run<D, F>(title: &str, setup: F)
+where
+    D: Demo + 'static,
+    F: FnOnce(&mut GraphicsContext) -> D,
+{
+    let event_loop = EventLoop::new().unwrap();
+    let mut gfx = GraphicsContext::builder()
+        .title(title)
+        .build(&event_loop)
+        .unwrap();
+
+    let mut demo = setup(&mut gfx);
+    let mut camera = PerspectiveCamera::default();
+    let controls = CameraControls::default();
+    let mut view = DebugView::Shaded;
+    let mut stats = FrameStats::new();
+    let title = title.to_owned();
+
+    event_loop
+        .run(move |event, elwt| {
+            elwt.set_control_flow(ControlFlow::Poll);
+
+            match event {
+                Event::WindowEvent { event, .. } => match event {
+                    WindowEvent::CloseRequested => elwt.exit(),
+                    WindowEvent::Resized(size) => {
+                        gfx.recreate_swapchain = true;
+                        camera.set_aspect_ratio(size.width as f32 / size.height.max(1) as f32);
+                    }
+                    WindowEvent::KeyboardInput { event, .. } => {
+                        if event.state == ElementState::Pressed
+                            && event.physical_key == PhysicalKey::Code(KeyCode::F3)
+                        {
+                            view = view.next();
+                            gfx.pipelines.lit.set_debug_view(view);
+                        }
+                        controls.handle(&event, &mut camera)
+                    }
+                    WindowEvent::RedrawRequested => {
+                        draw_frame(&mut gfx, &mut demo, &camera);
+                        if let Some(average) = stats.tick() {
+                            gfx.window.set_title(&format!(
+                                "{title} - {:.2} ms ({:.0} fps) - {view:?}",
+                                average.as_secs_f64() * 1000.0,
+                                1.0 / average.as_secs_f64()
+                            ));
+                        }
+                    }
+                    _ => (),
+                },
+                Event::AboutToWait => gfx.window.request_redraw(),
+                _ => (),
+            }
+        })
+        .unwrap();
+}
+
+fn draw_frame<D: Demo>(gfx: &mut GraphicsContext, demo: &mut D, camera: &PerspectiveCamera) {
+    let Ok(future) = gfx.start_frame() else {
+        return;
+    };
+    let scene_image = gfx.scene_image();
+    let final_image = gfx.final_images[gfx.image_index as usize].clone();
+
+    let mut ctx = DrawContext {
+        pipelines: &mut gfx.pipelines,
+        memory_allocator: gfx.memory_allocator.clone(),
+        camera,
+    };
+
+    let mut after_scene = None;
+    if D::DEPTH {
+        let mut frame = gfx
+            .render_passes
+            .depth
+            .frame(
+                CLEAR_COLOR,
+                future,
+                scene_image,
+                gfx.memory_allocator.clone(),
+            )
+            .unwrap();
+        while let Some(pass) = frame.next_pass().unwrap() {
+            match pass {
+                DepthPass::Prepass(_) => (),
+                DepthPass::Draw(mut draw_pass) => demo.draw_3d(&mut ctx, &mut draw_pass),
+                DepthPass::Finished(af) => after_scene = Some(af),
+            }
+        }
+    } else {
+        let mut frame = gfx
+            .render_passes
+            .basic_msaa
+            .frame(
+                CLEAR_COLOR,
+                future,
+                scene_image,
+                gfx.memory_allocator.clone(),
+            )
+            .unwrap();
+        while let Some(pass) = frame.next_pass().unwrap() {
+            match pass {
+                BasicMSAAPass::Draw(mut draw_pass) => demo.draw(&mut ctx, &mut draw_pass),
+                BasicMSAAPass::Finished(af) => after_scene = Some(af),
+            }
+        }
+    }
+    let after_scene = gfx.resolve_anti_aliasing(after_scene.unwrap());
+
+    let mut ctx = DrawContext {
+        pipelines: &mut gfx.pipelines,
+        memory_allocator: gfx.memory_allocator.clone(),
+        camera,
+    };
+
+    let mut frame = gfx
+        .render_passes
+        .overlay
+        .frame(after_scene, final_image, gfx.memory_allocator.clone())
+        .unwrap();
+
+    let mut after_overlay = None;
+    while let Some(pass) = frame.next_pass().unwrap() {
+        match pass {
+            OverlayPass::Draw(mut draw_pass) => demo.draw_overlay(&mut ctx, &mut draw_pass),
+            OverlayPass::Finished(af) => after_overlay = Some(af),
+        }
+    }
+
+    gfx.finish_frame(after_overlay.unwrap()).unwrap();
+}
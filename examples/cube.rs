@@ -0,0 +1,67 @@
+mod common;
+
+use common::{Demo, DrawContext};
+use glam::Vec3;
+use onion::graphics::{
+    layers, light::LightsUniform, pipelines::lit::PSOLit, render_pass::depth::DepthDrawPass,
+};
+use onion::prelude::*;
+
+/// Only drawn by cameras that see it, the camera here doesn't.
+const HIDDEN: u8 = 1;
+
+/// Lit cubes drawn from the world with [`layers::for_each_visible`]. F3 cycles the debug views.
+struct Cubes {
+    world: World,
+    lights: Vec<Light>,
+}
+
+impl Demo for Cubes {
+    const DEPTH: bool = true;
+
+    fn draw_3d(&mut self, ctx: &mut DrawContext, pass: &mut DepthDrawPass) {
+        let lights = PSOLit::lights_buffer(
+            ctx.memory_allocator.clone(),
+            LightsUniform::new(ctx.camera.position(), Color::grey(), &self.lights),
+        );
+
+        let mut draws = Vec::new();
+        layers::for_each_visible::<&Cube>(&self.world, ctx.camera, |_, cube| {
+            draws.push(cube.draw(
+                ctx.memory_allocator.clone(),
+                &mut ctx.pipelines.lit,
+                pass.viewport_dimensions(),
+                ctx.camera,
+                lights.clone(),
+                None,
+            ));
+        });
+        for cb in draws {
+            pass.execute(cb).unwrap();
+        }
+    }
+}
+
+fn cube(color: Color, x: f32) -> Cube {
+    let mut cube = Cube::new();
+    cube.set_color(color);
+    cube.translate_x(x);
+    cube.translate_z(12.0);
+    cube
+}
+
+fn main() {
+    let mut world = World::new();
+    world.spawn((cube(Color::red(), -3.0),));
+    world.spawn((cube(Color::white(), 0.0), RenderLayers::layer(0)));
+    world.spawn((cube(Color::black(), 3.0), RenderLayers::layer(HIDDEN)));
+
+    common::run("cube", |_| Cubes {
+        world,
+        lights: vec![Light::directional(
+            Vec3::new(-0.5, -1.0, 0.5),
+            Color::white(),
+            1.0,
+        )],
+    });
+}
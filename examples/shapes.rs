@@ -0,0 +1,41 @@
+mod common;
+
+use common::{Demo, DrawContext};
+use onion::graphics::render_pass::{basic::BasicMSAADrawPass, overlay::OverlayDrawPass};
+use onion::prelude::*;
+
+struct Shapes {
+    squares: Vec<Square>,
+}
+
+impl Demo for Shapes {
+    fn draw(&mut self, ctx: &mut DrawContext, pass: &mut BasicMSAADrawPass) {
+        for square in self.squares.iter() {
+            let cb = square.draw(
+                ctx.memory_allocator.clone(),
+                &mut ctx.pipelines.basic,
+                pass.viewport_dimensions(),
+            );
+            pass.execute(cb).unwrap();
+        }
+    }
+
+    fn draw_overlay(&mut self, ctx: &mut DrawContext, pass: &mut OverlayDrawPass) {
+        let cb = Square::new(0.05, Color::white()).draw(
+            ctx.memory_allocator.clone(),
+            &mut ctx.pipelines.overlay,
+            pass.viewport_dimensions(),
+        );
+        pass.execute(cb).unwrap();
+    }
+}
+
+fn main() {
+    common::run("shapes", |_| Shapes {
+        squares: vec![
+            Square::new(0.8, Color::grey()),
+            Square::new(0.5, Color::rgb(40, 90, 200)),
+            Square::new(0.2, Color::red()),
+        ],
+    });
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::{Demo, DrawContext};
+use onion::graphics::{render_pass::basic::BasicMSAADrawPass, texture::WrapMode};
+use onion::prelude::*;
+use vulkano::image::Image;
+
+struct Textured {
+    image: Arc<Image>,
+    sprites: Vec<Texture>,
+}
+
+impl Demo for Textured {
+    fn draw(&mut self, ctx: &mut DrawContext, pass: &mut BasicMSAADrawPass) {
+        for sprite in self.sprites.iter() {
+            let cb = sprite.draw(
+                ctx.memory_allocator.clone(),
+                &mut ctx.pipelines.texture,
+                self.image.clone(),
+                pass.viewport_dimensions(),
+            );
+            pass.execute(cb).unwrap();
+        }
+    }
+}
+
+fn main() {
+    common::run("texture", |gfx| Textured {
+        image: gfx
+            .upload_png(include_bytes!("../src/bin/img.png"))
+            .unwrap(),
+        sprites: vec![
+            Texture::new(0.9).with_tiling([4.0, 4.0], WrapMode::MirroredRepeat),
+            Texture::new(0.4).with_flip(true, false),
+        ],
+    });
+}
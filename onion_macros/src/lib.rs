@@ -0,0 +1,191 @@
+//! Derive macros for onion. Use them through the re-exports in `onion`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Index, Member, Type};
+
+/// Implements `onion::bundle::Bundle` for a struct of components, so it can be spawned as one
+/// with `world.spawn_bundle(...)`. Fields marked `#[bundle]` are bundles themselves and add
+/// their components instead.
+///
+/// ```ignore
+/// #[derive(Bundle)]
+/// pub struct SpriteBundle {
+///     pub transform: Transform,
+///     pub animation: SpriteAnimation,
+///     pub layers: RenderLayers,
+/// }
+///
+/// #[derive(Bundle)]
+/// pub struct EnemyBundle {
+///     #[bundle]
+///     pub sprite: SpriteBundle,
+///     pub name: Name,
+/// }
+/// ```
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn derive_bundle(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match bundle(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn bundle(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "Bundle can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut adds = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        };
+        let nested = field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("bundle"));
+        adds.push(if nested {
+            quote!(::onion::bundle::Bundle::add_to(self.#member, builder);)
+        } else {
+            quote!(builder.add(self.#member);)
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::onion::bundle::Bundle for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn add_to(self, builder: &mut ::onion::bundle::EntityBuilder) {
+                #(#adds)*
+            }
+        }
+    })
+}
+
+/// Implements the id API for a newtype over `NonZeroU32` or `NonZeroU64`: `new`, `get`,
+/// `Display` and a conversion into the raw integer, plus `Serialize`/`Deserialize` as the raw
+/// integer with the `serde` feature. The non-zero field means `Option<Id>` is as small as `Id`.
+///
+/// ```ignore
+/// #[derive(IdType, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+/// pub struct PlayerId(NonZeroU32);
+/// ```
+#[proc_macro_derive(IdType)]
+pub fn derive_id_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match id_type(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn id_type(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let name_str = name.to_string();
+
+    let field = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "IdType needs a tuple struct with one field",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "IdType can only be derived for structs",
+            ))
+        }
+    };
+
+    let last_segment = match &field.ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    };
+    let raw = match last_segment.as_deref() {
+        Some("NonZeroU32") => quote!(u32),
+        Some("NonZeroU64") => quote!(u64),
+        _ => {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                "IdType needs a NonZeroU32 or NonZeroU64 field",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "IdType can't be derived for generic structs",
+        ));
+    }
+    let non_zero = &field.ty;
+
+    let serde_impls = if cfg!(feature = "serde") {
+        quote! {
+            impl ::serde::Serialize for #name {
+                fn serialize<S: ::serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&self.0.get(), serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                fn deserialize<D: ::serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<Self, D::Error> {
+                    let raw = <#raw as ::serde::Deserialize>::deserialize(deserializer)?;
+                    Self::new(raw).ok_or_else(|| {
+                        <D::Error as ::serde::de::Error>::custom(::core::concat!(#name_str, " can't be 0"))
+                    })
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    Ok(quote! {
+        impl #name {
+            /// `None` for 0, which is reserved so that `Option` of the id costs nothing.
+            pub const fn new(raw: #raw) -> ::core::option::Option<Self> {
+                match <#non_zero>::new(raw) {
+                    ::core::option::Option::Some(raw) => ::core::option::Option::Some(Self(raw)),
+                    ::core::option::Option::None => ::core::option::Option::None,
+                }
+            }
+
+            pub const fn get(self) -> #raw {
+                self.0.get()
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::write!(f, "{}({})", #name_str, self.0)
+            }
+        }
+
+        impl ::core::convert::From<#name> for #raw {
+            fn from(id: #name) -> #raw {
+                id.0.get()
+            }
+        }
+
+        #serde_impls
+    })
+}
@@ -0,0 +1,82 @@
+// The derive refers to `::onion::bundle`, stood in for here by a builder that records the
+// components it gets.
+extern crate self as onion;
+
+pub mod bundle {
+    use std::any::{type_name, Any};
+
+    #[derive(Default)]
+    pub struct EntityBuilder(pub Vec<(&'static str, Box<dyn Any>)>);
+
+    impl EntityBuilder {
+        pub fn add<T: 'static>(&mut self, component: T) -> &mut Self {
+            self.0.push((type_name::<T>(), Box::new(component)));
+            self
+        }
+    }
+
+    pub trait Bundle {
+        fn add_to(self, builder: &mut EntityBuilder);
+    }
+}
+
+use bundle::{Bundle as _, EntityBuilder};
+use onion_macros::Bundle;
+
+#[derive(Debug, PartialEq)]
+struct Position(f32);
+
+#[derive(Debug, PartialEq)]
+struct Speed(f32);
+
+#[derive(Bundle)]
+struct Body {
+    position: Position,
+    speed: Speed,
+}
+
+#[derive(Bundle)]
+struct Player(#[bundle] Body, u32);
+
+#[derive(Bundle)]
+struct Tagged<T: 'static> {
+    tag: T,
+}
+
+fn components(bundle: impl bundle::Bundle) -> Vec<&'static str> {
+    let mut builder = EntityBuilder::default();
+    bundle.add_to(&mut builder);
+    builder.0.iter().map(|(name, _)| *name).collect()
+}
+
+#[test]
+fn test_fields_in_order() {
+    let body = Body {
+        position: Position(1.0),
+        speed: Speed(2.0),
+    };
+    let mut builder = EntityBuilder::default();
+    body.add_to(&mut builder);
+    assert_eq!(Some(&Position(1.0)), builder.0[0].1.downcast_ref());
+    assert_eq!(Some(&Speed(2.0)), builder.0[1].1.downcast_ref());
+}
+
+#[test]
+fn test_nested() {
+    let player = Player(
+        Body {
+            position: Position(0.0),
+            speed: Speed(0.0),
+        },
+        7,
+    );
+    assert_eq!(
+        vec!["bundle::Position", "bundle::Speed", "u32"],
+        components(player)
+    );
+}
+
+#[test]
+fn test_generic() {
+    assert_eq!(vec!["&str"], components(Tagged { tag: "enemy" }));
+}
@@ -0,0 +1,39 @@
+use std::mem::size_of;
+use std::num::{NonZeroU32, NonZeroU64};
+
+use onion_macros::IdType;
+
+#[derive(IdType, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SmallId(NonZeroU32);
+
+#[derive(IdType, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct BigId(std::num::NonZeroU64);
+
+#[test]
+fn test_zero_is_reserved() {
+    assert_eq!(None, SmallId::new(0));
+    assert_eq!(7, SmallId::new(7).unwrap().get());
+    assert_eq!(u64::MAX, BigId::new(u64::MAX).unwrap().get());
+}
+
+#[test]
+fn test_niche() {
+    assert_eq!(size_of::<NonZeroU32>(), size_of::<Option<SmallId>>());
+    assert_eq!(size_of::<NonZeroU64>(), size_of::<Option<BigId>>());
+}
+
+#[test]
+fn test_display_and_into() {
+    let id = SmallId::new(3).unwrap();
+    assert_eq!("SmallId(3)", id.to_string());
+    assert_eq!(3, u32::from(id));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_as_raw_integer() {
+    let id = BigId::new(42).unwrap();
+    assert_eq!("42", serde_json::to_string(&id).unwrap());
+    assert_eq!(id, serde_json::from_str("42").unwrap());
+    assert!(serde_json::from_str::<BigId>("0").is_err());
+}
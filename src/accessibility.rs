@@ -0,0 +1,83 @@
+//! Player-facing accessibility options. Keep one [`Accessibility`] with the game's settings and
+//! hand it to the parts of the engine that respect it.
+
+use crate::graphics::pipelines::Projection2D;
+
+/// A kind of color vision deficiency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorBlindness {
+    /// No red cones.
+    Protanopia,
+    /// No green cones, the most common.
+    Deuteranopia,
+    /// No blue cones.
+    Tritanopia,
+}
+
+/// A post-processing filter for color vision deficiencies, see
+/// `PostProcess::set_color_filter`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorFilter {
+    /// Shows the frame as seen with the deficiency, so developers can check their palette.
+    Simulate(ColorBlindness),
+    /// Shifts the colors the deficiency confuses towards ones that stay distinct, by `strength`
+    /// from 0 to 1.
+    Correct(ColorBlindness, f32),
+}
+
+impl ColorFilter {
+    /// The effect's `params`: the deficiency, whether to correct and the strength.
+    pub(crate) fn params(&self) -> [f32; 4] {
+        let (kind, correct, strength) = match *self {
+            ColorFilter::Simulate(kind) => (kind, 0.0, 1.0),
+            ColorFilter::Correct(kind, strength) => (kind, 1.0, strength),
+        };
+        let kind = match kind {
+            ColorBlindness::Protanopia => 0.0,
+            ColorBlindness::Deuteranopia => 1.0,
+            ColorBlindness::Tritanopia => 2.0,
+        };
+        [kind, correct, strength, 0.0]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accessibility {
+    pub color_filter: Option<ColorFilter>,
+    /// Multiplies the size of UI laid out with [`Accessibility::ui_projection`].
+    pub ui_scale: f32,
+    /// Asks for camera shake, flashes and tweens to be toned down or skipped. The engine has no
+    /// animation of its own to reduce yet, so games check it themselves.
+    pub reduced_motion: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Accessibility {
+            color_filter: None,
+            ui_scale: 1.0,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl Accessibility {
+    /// Like `GraphicsContext::pixel_projection`, with UI laid out in logical pixels scaled by
+    /// [`Accessibility::ui_scale`].
+    pub fn ui_projection(&self, logical_size: [f32; 2]) -> Projection2D {
+        Projection2D::pixels([
+            logical_size[0] / self.ui_scale,
+            logical_size[1] / self.ui_scale,
+        ])
+    }
+
+    /// How much of an optional motion, like camera shake, to play: 0 with reduced motion,
+    /// otherwise 1.
+    pub fn motion_scale(&self) -> f32 {
+        if self.reduced_motion {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
@@ -0,0 +1,693 @@
+use crate::{
+    commands::{self, CommandQueue},
+    crash,
+    diagnostics::{self, Diagnostics},
+    event::{self, Event, Events, Trigger},
+    hierarchy,
+    redraw::{self, RedrawMode},
+    removal::{RemovalTracker, Removals, RemovedComponents},
+    resource::{self, FromWorld},
+    schedule::{self, OrderError},
+    state::{State, StateSchedule, States, Transitions},
+    system::{Access, IntoSystem},
+    time, transform, IdType,
+};
+#[cfg(feature = "graphics")]
+use crate::{
+    engine, file_drop,
+    file_drop::FileDrop,
+    graphics::{picking, reset},
+    input, settings,
+};
+use hecs::{Component, Entity, World};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    num::NonZeroU32,
+    rc::Rc,
+    time::Duration,
+};
+// `std::time::Instant` panics in browsers.
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+#[cfg(feature = "graphics")]
+use winit::{event::WindowEvent, event_loop::ControlFlow};
+
+mod tests;
+
+pub type System = Box<dyn Fn(&mut World) -> Result<(), Box<dyn Error>>>;
+
+/// Decides whether a system runs this time, see [`App::run_if`].
+pub type Condition = Rc<dyn Fn(&World) -> bool>;
+
+/// Stage that systems added with [`App::add_system`] belong to.
+pub const DEFAULT_STAGE: &str = "update";
+
+/// Stage that runs at the fixed tick rate set with [`App::set_tick_rate`], e.g. physics and the
+/// netcode tick.
+pub const FIXED_STAGE: &str = "fixed_update";
+
+/// Most simulation ticks run by one update. When the simulation can't keep up, time beyond this
+/// is dropped instead of piling up.
+pub const MAX_TICKS_PER_UPDATE: u32 = 8;
+
+/// Identifies a system added to an [`App`], in the order they were added.
+#[derive(IdType, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SystemId(NonZeroU32);
+
+struct SystemEntry {
+    id: SystemId,
+    name: String,
+    stage: String,
+    enabled: bool,
+    access: Access,
+    sets: Vec<String>,
+    conditions: Vec<Condition>,
+    system: System,
+}
+
+/// Snapshot of a system's toggle state, for debug UIs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemState {
+    pub id: SystemId,
+    pub name: String,
+    pub stage: String,
+    pub enabled: bool,
+    /// False when the system is enabled but its whole stage is disabled.
+    pub runs: bool,
+}
+
+/// The [`App::system_states`] as of the start of the update, kept on a single entity of the
+/// world for the [`inspector`](crate::inspector).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemStates(pub Vec<SystemState>);
+
+pub struct App {
+    pub world: World,
+    systems: Vec<SystemEntry>,
+    /// `(first, then)` names of systems or sets.
+    orderings: Vec<(String, String)>,
+    /// `(first, then)` names of stages.
+    stage_orderings: Vec<(String, String)>,
+    /// Indices into `systems`, `None` after systems or orderings changed.
+    run_order: Option<Vec<usize>>,
+    states: Vec<Box<dyn Transitions>>,
+    /// Updates the [`Events`] of each type added with [`App::add_event`].
+    event_updaters: Vec<fn(&mut World)>,
+    removals: Vec<Box<dyn Removals>>,
+    disabled_stages: HashSet<String>,
+    /// Whether [`SystemStates`] is out of date.
+    system_states_changed: bool,
+    frame: u64,
+    fixed_step: Option<Duration>,
+    accumulator: Duration,
+    tick: u64,
+    alpha: f32,
+    last_update: Option<Instant>,
+    redraw_mode: RedrawMode,
+    redraw_pending: bool,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        let mut world = World::new();
+        world.spawn((CommandQueue::default(),));
+        Self {
+            world,
+            systems: Vec::new(),
+            orderings: Vec::new(),
+            stage_orderings: Vec::new(),
+            run_order: None,
+            states: Vec::new(),
+            event_updaters: Vec::new(),
+            removals: Vec::new(),
+            disabled_stages: HashSet::new(),
+            system_states_changed: true,
+            frame: 0,
+            fixed_step: None,
+            accumulator: Duration::ZERO,
+            tick: 0,
+            alpha: 1.0,
+            last_update: None,
+            redraw_mode: RedrawMode::default(),
+            redraw_pending: true,
+        }
+    }
+}
+
+impl App {
+    pub fn new() -> Self {
+        App::default()
+    }
+
+    /// Adds an unnamed system to [`DEFAULT_STAGE`]. It is called `system_` and a number that no
+    /// other system uses, and can only be toggled through its stage.
+    pub fn add_system<M>(&mut self, system: impl IntoSystem<M>) -> &mut Self {
+        let name = (self.systems.len()..)
+            .map(|n| format!("system_{n}"))
+            .find(|name| self.systems.iter().all(|e| e.name != *name))
+            .unwrap();
+        self.add_named_system(DEFAULT_STAGE, &name, system)
+    }
+
+    /// Adds a system that can be toggled by `name` or together with the rest of `stage`.
+    /// Systems run in the order they were added regardless of stage, unless ordered with
+    /// [`App::run_before`]. Takes a boxed [`System`],
+    /// a function taking `&mut World`, or one taking [`SystemParam`](crate::system::SystemParam)s.
+    ///
+    /// Panics if another system is called `name`.
+    pub fn add_named_system<M>(
+        &mut self,
+        stage: &str,
+        name: &str,
+        system: impl IntoSystem<M>,
+    ) -> &mut Self {
+        assert!(
+            self.systems.iter().all(|e| e.name != name),
+            "a system called {name} was already added"
+        );
+        let id = SystemId::new(self.systems.len() as u32 + 1).unwrap();
+        self.systems.push(SystemEntry {
+            id,
+            name: name.to_owned(),
+            stage: stage.to_owned(),
+            enabled: true,
+            access: system.access(),
+            sets: Vec::new(),
+            conditions: Vec::new(),
+            system: system.into_system(),
+        });
+        self.run_order = None;
+        self.system_states_changed = true;
+        self
+    }
+
+    /// Adds the system called `name` to `set`, so orderings can name the set instead.
+    pub fn add_to_set(&mut self, name: &str, set: &str) -> &mut Self {
+        for entry in self.systems.iter_mut().filter(|e| e.name == name) {
+            entry.sets.push(set.to_owned());
+        }
+        self.run_order = None;
+        self.system_states_changed = true;
+        self
+    }
+
+    /// Only runs the system called `name` while `condition` holds, e.g.
+    /// [`in_state`](crate::state::in_state). With several conditions all have to hold.
+    pub fn run_if(
+        &mut self,
+        name: &str,
+        condition: impl Fn(&World) -> bool + 'static,
+    ) -> &mut Self {
+        let condition: Condition = Rc::new(condition);
+        for entry in self.systems.iter_mut().filter(|e| e.name == name) {
+            entry.conditions.push(condition.clone());
+        }
+        self
+    }
+
+    /// Sets the resource of type `T`, replacing the one the world has, see [`resource`].
+    pub fn insert_resource<T: Component>(&mut self, value: T) -> &mut Self {
+        resource::insert(&mut self.world, value);
+        self
+    }
+
+    /// Adds the resource of type `T` made with [`FromWorld`], unless the world has one already.
+    pub fn init_resource<T: Component + FromWorld>(&mut self) -> &mut Self {
+        resource::init::<T>(&mut self.world);
+        self
+    }
+
+    /// Adds the events of type `E`, sent with an
+    /// [`EventWriter`](crate::event::EventWriter) and read with an
+    /// [`EventReader`](crate::event::EventReader). Adding them twice does nothing.
+    pub fn add_event<E: Event>(&mut self) -> &mut Self {
+        if self.world.query::<&Events<E>>().iter().next().is_none() {
+            self.world.spawn((Events::<E>::default(),));
+            self.event_updaters.push(event::update::<E>);
+        }
+        self
+    }
+
+    /// Runs `observer` whenever an event of type `E` is triggered, see [`event::trigger`].
+    pub fn add_observer<E: Event>(
+        &mut self,
+        observer: impl Fn(&mut World, Trigger<E>) + Send + Sync + 'static,
+    ) -> &mut Self {
+        event::add_observer(&mut self.world, observer);
+        self
+    }
+
+    /// Keeps a [`RemovedComponents`] of the entities that lost their `T` each update. Adding it
+    /// twice does nothing.
+    pub fn track_removals<T: Component>(&mut self) -> &mut Self {
+        if resource::entity::<RemovedComponents<T>>(&self.world).is_none() {
+            resource::init::<RemovedComponents<T>>(&mut self.world);
+            self.removals.push(Box::new(RemovalTracker::<T>::new()));
+        }
+        self
+    }
+
+    /// Runs `hook` at the end of each update for every entity that lost its `T` during it, see
+    /// [`removal`](crate::removal).
+    pub fn on_remove<T: Component>(
+        &mut self,
+        hook: impl Fn(&mut World, Entity) + 'static,
+    ) -> &mut Self {
+        self.track_removals::<T>();
+        self.removals
+            .iter_mut()
+            .find_map(|removals| removals.as_any_mut().downcast_mut::<RemovalTracker<T>>())
+            .unwrap()
+            .add_hook(Box::new(hook));
+        self
+    }
+
+    /// Adds the state `S`, starting as `initial`, see [`State`].
+    pub fn add_state<S: States>(&mut self, initial: S) -> &mut Self {
+        assert!(
+            State::<S>::get(&self.world).is_none(),
+            "the state was already added"
+        );
+        self.world.spawn((State::new(initial),));
+        self.states.push(Box::new(StateSchedule::<S>::new()));
+        self
+    }
+
+    /// Runs `system` whenever state `S` becomes `state`, and on the first update if it starts as
+    /// `state`.
+    pub fn on_enter<S: States, M>(&mut self, state: S, system: impl IntoSystem<M>) -> &mut Self {
+        self.state_schedule::<S>()
+            .add_on_enter(state, system.into_system());
+        self
+    }
+
+    /// Runs `system` whenever state `S` stops being `state`.
+    pub fn on_exit<S: States, M>(&mut self, state: S, system: impl IntoSystem<M>) -> &mut Self {
+        self.state_schedule::<S>()
+            .add_on_exit(state, system.into_system());
+        self
+    }
+
+    /// Runs `system` whenever state `S` changes from `from` to `to`, between the exit and enter
+    /// systems.
+    pub fn on_transition<S: States, M>(
+        &mut self,
+        from: S,
+        to: S,
+        system: impl IntoSystem<M>,
+    ) -> &mut Self {
+        self.state_schedule::<S>()
+            .add_on_transition(from, to, system.into_system());
+        self
+    }
+
+    fn state_schedule<S: States>(&mut self) -> &mut StateSchedule<S> {
+        self.states
+            .iter_mut()
+            .find_map(|states| states.as_any_mut().downcast_mut::<StateSchedule<S>>())
+            .expect("the state has to be added with App::add_state first")
+    }
+
+    /// Runs the systems or sets called `first` before those called `then`, where they share a
+    /// stage. Checked by [`App::build_run_order`].
+    pub fn run_before(&mut self, first: &str, then: &str) -> &mut Self {
+        self.orderings.push((first.to_owned(), then.to_owned()));
+        self.run_order = None;
+        self.system_states_changed = true;
+        self
+    }
+
+    /// Runs the systems or sets called `then` after those called `first`, see
+    /// [`App::run_before`].
+    pub fn run_after(&mut self, then: &str, first: &str) -> &mut Self {
+        self.run_before(first, then)
+    }
+
+    /// Runs every system of stage `first` before those of stage `then`, e.g. a "late_update"
+    /// after [`DEFAULT_STAGE`]. [`FIXED_STAGE`] runs on its own, ordering it against other
+    /// stages only matters on updates without a tick rate.
+    pub fn run_stage_before(&mut self, first: &str, then: &str) -> &mut Self {
+        self.stage_orderings
+            .push((first.to_owned(), then.to_owned()));
+        self.run_order = None;
+        self.system_states_changed = true;
+        self
+    }
+
+    /// Orders the systems, e.g. to report a bad ordering at startup. The first update does it
+    /// otherwise, and panics on errors.
+    pub fn build_run_order(&mut self) -> Result<(), OrderError> {
+        if self.run_order.is_none() {
+            self.run_order = Some(self.sorted_systems()?);
+        }
+        Ok(())
+    }
+
+    fn sorted_systems(&self) -> Result<Vec<usize>, OrderError> {
+        let nodes: Vec<_> = self
+            .systems
+            .iter()
+            .map(|e| schedule::Node {
+                name: &e.name,
+                stage: &e.stage,
+                sets: &e.sets,
+            })
+            .collect();
+        schedule::run_order(&nodes, &self.orderings, &self.stage_orderings)
+    }
+
+    /// Enables or disables the system called `name`. Returns false if there is none.
+    pub fn set_system_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let Some(entry) = self.systems.iter_mut().find(|e| e.name == name) else {
+            return false;
+        };
+        entry.enabled = enabled;
+        self.system_states_changed = true;
+        true
+    }
+
+    /// Enables or disables a whole stage, e.g. physics while the game is paused. The systems keep
+    /// their own toggle state.
+    pub fn set_stage_enabled(&mut self, stage: &str, enabled: bool) {
+        if enabled {
+            self.disabled_stages.remove(stage);
+        } else {
+            self.disabled_stages.insert(stage.to_owned());
+        }
+        self.system_states_changed = true;
+    }
+
+    pub fn is_system_enabled(&self, name: &str) -> Option<bool> {
+        self.systems
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| e.enabled)
+    }
+
+    /// What the system called `name` reads and writes.
+    pub fn system_access(&self, name: &str) -> Option<&Access> {
+        self.systems
+            .iter()
+            .find(|e| e.name == name)
+            .map(|e| &e.access)
+    }
+
+    pub fn is_stage_enabled(&self, stage: &str) -> bool {
+        !self.disabled_stages.contains(stage)
+    }
+
+    /// The toggle state of every system, in run order.
+    pub fn system_states(&self) -> Vec<SystemState> {
+        let order = match &self.run_order {
+            Some(order) => order.clone(),
+            None => self
+                .sorted_systems()
+                .unwrap_or_else(|_| (0..self.systems.len()).collect()),
+        };
+        order
+            .into_iter()
+            .map(|i| &self.systems[i])
+            .map(|e| SystemState {
+                id: e.id,
+                name: e.name.clone(),
+                stage: e.stage.clone(),
+                enabled: e.enabled,
+                runs: e.enabled && self.is_stage_enabled(&e.stage),
+            })
+            .collect()
+    }
+
+    /// Spawns drag-and-drop events into the world as [`FileDrop`] entities, which live until the
+    /// end of the next update, and marks a redraw as needed after input. Other events are
+    /// ignored.
+    #[cfg(feature = "graphics")]
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        if let Some(drop) = FileDrop::from_window_event(event) {
+            self.world.spawn((drop,));
+        }
+        if redraw::is_input(event) {
+            self.redraw_pending = true;
+        }
+    }
+
+    pub fn set_redraw_mode(&mut self, mode: RedrawMode) -> &mut Self {
+        self.redraw_mode = mode;
+        self.redraw_pending = true;
+        self
+    }
+
+    pub fn redraw_mode(&self) -> RedrawMode {
+        self.redraw_mode
+    }
+
+    /// Marks a redraw as needed, like spawning a `RedrawRequested` from a system.
+    pub fn request_redraw(&mut self) {
+        self.redraw_pending = true;
+    }
+
+    /// Whether a frame should be rendered now, and resets the request. Always true with
+    /// [`RedrawMode::Continuous`].
+    pub fn take_redraw(&mut self) -> bool {
+        let pending = std::mem::take(&mut self.redraw_pending);
+        pending || self.redraw_mode == RedrawMode::Continuous
+    }
+
+    /// What the event loop should do after this update. [`RedrawMode::OnDemand`] sleeps until
+    /// the next event, or until the next simulation tick if there is a tick rate.
+    #[cfg(feature = "graphics")]
+    pub fn control_flow(&self) -> ControlFlow {
+        match (self.redraw_mode, self.fixed_step, self.last_update) {
+            (RedrawMode::Continuous, _, _) => ControlFlow::Poll,
+            (RedrawMode::OnDemand, Some(step), Some(last)) => {
+                ControlFlow::WaitUntil(last + step.saturating_sub(self.accumulator))
+            }
+            (RedrawMode::OnDemand, _, _) => ControlFlow::Wait,
+        }
+    }
+
+    /// How many times [`App::update`] has run.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Runs [`FIXED_STAGE`] `hz` times per second of real time, independent of how often
+    /// [`App::update`] is called. `None` runs every stage once per update, which is the default.
+    pub fn set_tick_rate(&mut self, hz: Option<f64>) -> &mut Self {
+        self.fixed_step = hz.map(|hz| Duration::from_secs_f64(1.0 / hz));
+        self.accumulator = Duration::ZERO;
+        self.last_update = None;
+        self
+    }
+
+    pub fn tick_rate(&self) -> Option<f64> {
+        self.fixed_step.map(|step| 1.0 / step.as_secs_f64())
+    }
+
+    /// How many simulation ticks have run. Equal to [`App::frame`] without a tick rate.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// How far the last update got between the previous simulation tick and the next one, from
+    /// 0 to 1. `RenderTransform`s are interpolated by it.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Runs every enabled system once. With a tick rate, [`FIXED_STAGE`] instead runs as many
+    /// times as fit in the scaled time since the last update, see [`Time`](crate::time::Time).
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        let elapsed = self.last_update.map_or(Duration::ZERO, |last| now - last);
+        self.last_update = Some(now);
+        self.advance(elapsed);
+    }
+
+    /// Like [`App::update`], but with `elapsed` instead of the real time since the last update.
+    /// Useful for tests and replays.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let started = Instant::now();
+        self.run_update(elapsed, None);
+        diagnostics::record_update(&self.world, elapsed, started.elapsed());
+    }
+
+    /// Runs exactly one update without looking at the clock. With a tick rate it covers one
+    /// tick, running [`FIXED_STAGE`] once whatever the time scale, otherwise no time passes.
+    pub fn step(&mut self) {
+        match self.fixed_step {
+            Some(_) => self.run_fixed(1),
+            None => self.advance(Duration::ZERO),
+        }
+    }
+
+    /// Runs `ticks` updates of one simulation tick each, e.g. for tests or to resimulate after a
+    /// rollback. The time left over from [`App::update`] stays for the next one.
+    ///
+    /// Panics without a tick rate.
+    pub fn run_fixed(&mut self, ticks: u32) {
+        let step = self.fixed_step.expect("run_fixed needs a tick rate");
+        for _ in 0..ticks {
+            let started = Instant::now();
+            self.run_update(step, Some(1));
+            diagnostics::record_update(&self.world, step, started.elapsed());
+        }
+    }
+
+    /// Runs an update `elapsed` long. `exact_ticks` runs [`FIXED_STAGE`] that many times instead
+    /// of as often as the accumulated time allows.
+    fn run_update(&mut self, elapsed: Duration, exact_ticks: Option<u32>) {
+        self.frame += 1;
+        crash::set_frame(self.frame);
+        let _span = tracing::trace_span!(target: "ecs", "update", frame = self.frame).entered();
+        let elapsed = time::advance(&mut self.world, self.frame, elapsed, self.fixed_step);
+        if let Err(e) = self.build_run_order() {
+            panic!("invalid system order: {e}");
+        }
+        if std::mem::take(&mut self.system_states_changed) {
+            let states = SystemStates(self.system_states());
+            resource::insert(&mut self.world, states);
+        }
+        for states in self.states.iter_mut() {
+            states.apply(&mut self.world);
+        }
+        commands::apply(&mut self.world);
+
+        let Some(step) = self.fixed_step else {
+            self.tick += 1;
+            transform::snapshot_previous(&mut self.world);
+            self.run_systems(|_| true);
+            self.alpha = 1.0;
+            transform::interpolate(&mut self.world, self.alpha);
+            hierarchy::propagate_transforms(&mut self.world);
+            self.clear_events();
+            return;
+        };
+
+        if exact_ticks.is_none() {
+            self.accumulator += elapsed;
+        }
+        let mut ticks = 0;
+        loop {
+            let due = match exact_ticks {
+                Some(exact) => ticks < exact,
+                None => self.accumulator >= step,
+            };
+            if !due {
+                break;
+            }
+            if exact_ticks.is_none() && ticks == MAX_TICKS_PER_UPDATE {
+                tracing::warn!(
+                    target: "ecs",
+                    dropped = ?self.accumulator,
+                    "simulation fell behind, dropping time"
+                );
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            transform::snapshot_previous(&mut self.world);
+            self.run_systems(|stage| stage == FIXED_STAGE);
+            if exact_ticks.is_none() {
+                self.accumulator -= step;
+            }
+            self.tick += 1;
+            ticks += 1;
+        }
+
+        self.run_systems(|stage| stage != FIXED_STAGE);
+        self.alpha = self.accumulator.as_secs_f32() / step.as_secs_f32();
+        transform::interpolate(&mut self.world, self.alpha);
+        hierarchy::propagate_transforms(&mut self.world);
+        self.clear_events();
+    }
+
+    /// Despawns the events of this update, so the next one doesn't see them again, and moves the
+    /// added [`Events`] on by an update.
+    fn clear_events(&mut self) {
+        #[cfg(feature = "graphics")]
+        {
+            engine::clear(&mut self.world);
+            file_drop::clear(&mut self.world);
+            settings::clear(&mut self.world);
+            reset::clear(&mut self.world);
+            picking::clear(&mut self.world);
+            input::end_frame(&mut self.world);
+        }
+        for removals in self.removals.iter_mut() {
+            removals.apply(&mut self.world);
+        }
+        for update in &self.event_updaters {
+            update(&mut self.world);
+        }
+        self.redraw_pending |= redraw::take(&mut self.world);
+    }
+
+    fn run_systems(&mut self, stage_filter: impl Fn(&str) -> bool) {
+        let order = self.run_order.as_deref().unwrap_or_default();
+        let timed = resource::entity::<Diagnostics>(&self.world).is_some();
+        let mut timings = Vec::new();
+        let mut stage = None;
+        for entry in order.iter().map(|&i| &self.systems[i]) {
+            if !entry.enabled
+                || self.disabled_stages.contains(&entry.stage)
+                || !stage_filter(&entry.stage)
+            {
+                continue;
+            }
+            // The sync point between stages: what the last stage queued becomes visible to
+            // this one.
+            if stage.is_some_and(|stage| stage != entry.stage.as_str()) {
+                commands::apply(&mut self.world);
+            }
+            stage = Some(entry.stage.as_str());
+            if !entry
+                .conditions
+                .iter()
+                .all(|condition| condition(&self.world))
+            {
+                continue;
+            }
+            crash::enter_system(&entry.stage, &entry.name);
+            let _span = tracing::trace_span!(
+                target: "ecs",
+                "system",
+                stage = entry.stage.as_str(),
+                name = entry.name.as_str()
+            )
+            .entered();
+            let started = timed.then(Instant::now);
+            if let Err(e) = (entry.system)(&mut self.world) {
+                panic!("system errors aren't supported yet: {e:?}");
+            }
+            if let Some(started) = started {
+                timings.push((entry, started.elapsed()));
+            }
+        }
+        if let Some(mut diagnostics) = resource::get_mut::<Diagnostics>(&self.world) {
+            let mut sets: HashMap<&str, Duration> = HashMap::new();
+            for (entry, duration) in timings {
+                diagnostics.record_system(&entry.name, duration);
+                for set in &entry.sets {
+                    *sets.entry(set.as_str()).or_default() += duration;
+                }
+            }
+            for (set, duration) in sets {
+                diagnostics.record_set(set, duration);
+            }
+        }
+        commands::apply(&mut self.world);
+    }
+
+    /// Updates forever. With a tick rate it sleeps until the next tick in between, otherwise it
+    /// updates as fast as it can.
+    pub fn run(&mut self) {
+        loop {
+            self.update();
+            if let Some(step) = self.fixed_step {
+                std::thread::sleep(step.saturating_sub(self.accumulator));
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use crate::app::{App, DEFAULT_STAGE, FIXED_STAGE};
+    use crate::commands::Commands;
+    use crate::inspector;
+    use crate::resource;
+    use hecs::World;
+    use std::time::Duration;
+
+    struct Marker;
+
+    #[derive(Default)]
+    struct Seen(Vec<usize>);
+
+    /// How often the fixed and the other stages ran.
+    #[derive(Default)]
+    struct Runs {
+        fixed: u32,
+        update: u32,
+    }
+
+    fn counting_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Runs>()
+            .add_named_system(FIXED_STAGE, "fixed", |world: &mut World| {
+                resource::get_mut::<Runs>(world).unwrap().fixed += 1;
+            })
+            .add_named_system(DEFAULT_STAGE, "update", |world: &mut World| {
+                resource::get_mut::<Runs>(world).unwrap().update += 1;
+            });
+        app
+    }
+
+    fn runs(app: &App) -> (u32, u32) {
+        let runs = resource::get::<Runs>(&app.world).unwrap();
+        (runs.fixed, runs.update)
+    }
+
+    #[test]
+    fn test_commands_applied_between_stages() {
+        let mut app = App::new();
+        app.init_resource::<Seen>()
+            .add_named_system("spawn", "spawn", |world: &mut World| {
+                Commands::get(world).unwrap().spawn((Marker,));
+            })
+            .add_named_system("count", "count", |world: &mut World| {
+                let markers = world.query::<&Marker>().iter().count();
+                resource::get_mut::<Seen>(world).unwrap().0.push(markers);
+            })
+            .run_stage_before("spawn", "count");
+        app.step();
+        app.step();
+        assert_eq!(vec![1, 2], resource::get::<Seen>(&app.world).unwrap().0);
+    }
+
+    #[test]
+    fn test_run_fixed_ignores_accumulated_time() {
+        let mut app = counting_app();
+        app.set_tick_rate(Some(10.0));
+        // Two ticks and 50ms left over.
+        app.advance(Duration::from_millis(250));
+        assert_eq!((2, 1), runs(&app));
+
+        app.run_fixed(3);
+        assert_eq!((5, 4), runs(&app));
+        assert_eq!(5, app.tick());
+        // The left over time still counts for the next update.
+        app.advance(Duration::from_millis(50));
+        assert_eq!((6, 5), runs(&app));
+    }
+
+    #[test]
+    fn test_step_runs_one_update() {
+        let mut app = counting_app();
+        app.step();
+        assert_eq!((1, 1), runs(&app));
+        assert_eq!(1, app.frame());
+
+        app.set_tick_rate(Some(10.0));
+        app.step();
+        assert_eq!((2, 2), runs(&app));
+        assert_eq!(2, app.frame());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_duplicate_name_panics() {
+        let mut app = App::new();
+        app.add_named_system(DEFAULT_STAGE, "physics", |_: &mut World| {})
+            .add_named_system(FIXED_STAGE, "physics", |_: &mut World| {});
+    }
+
+    #[test]
+    fn test_unnamed_systems_avoid_used_names() {
+        let mut app = App::new();
+        app.add_named_system(DEFAULT_STAGE, "system_0", |_: &mut World| {})
+            .add_system(|_: &mut World| {});
+        let names: Vec<_> = app.system_states().into_iter().map(|s| s.name).collect();
+        assert_eq!(vec!["system_0", "system_1"], names);
+    }
+
+    #[test]
+    fn test_inspect_shows_toggles() {
+        let mut app = counting_app();
+        app.add_named_system("late", "camera", |_: &mut World| {});
+        app.set_system_enabled("update", false);
+        app.set_stage_enabled("late", false);
+        app.step();
+        let listing = inspector::inspect(&app.world);
+        assert!(listing.contains("3 systems\n"), "{listing}");
+        assert!(listing.contains(&format!("{FIXED_STAGE} fixed: enabled\n")));
+        assert!(listing.contains(&format!("{DEFAULT_STAGE} update: disabled\n")));
+        assert!(listing.contains("late camera: stage disabled\n"));
+    }
+}
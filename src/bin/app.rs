@@ -1,33 +1,26 @@
-use onion::app::App;
-use std::{error::Error, time::Duration};
+use onion::app::FIXED_STAGE;
+use onion::prelude::*;
 
-fn death_system(world: &mut hecs::World) -> Result<(), Box<dyn Error>> {
-    for (_, health) in &mut world.query::<&mut f64>() {
-        *health = (*health) - (0.1);
+fn death_system(time: Res<Time>, mut health: Query<&mut f64>) {
+    let dt = time.fixed_delta().unwrap_or_default();
+    for (_, health) in health.iter() {
+        // 0.1hp per second.
+        *health -= 0.1 * dt.as_secs_f64();
     }
-
-    Ok(())
 }
 
-fn name_system(world: &mut hecs::World) -> Result<(), Box<dyn Error>> {
-    for (_, (name, health)) in &mut world.query::<(&&str, &mut f64)>() {
+fn name_system(mut players: Query<(&&'static str, &f64)>) {
+    for (_, (name, health)) in players.iter() {
         println!("{} has {:.2}hp", name, health);
     }
-    Ok(())
-}
-
-fn sleep_system(_: &mut hecs::World) -> Result<(), Box<dyn Error>> {
-    std::thread::sleep(Duration::from_secs(1));
-    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
     let mut app = App::new();
     app.world.spawn(("p1", 100.0));
     app.world.spawn(("p2", 50.0));
-    app.add_system(Box::new(death_system))
-        .add_system(Box::new(name_system))
-        .add_system(Box::new(sleep_system))
+    app.set_tick_rate(Some(1.0))
+        .add_named_system(FIXED_STAGE, "death", death_system)
+        .add_named_system(FIXED_STAGE, "names", name_system)
         .run();
-    Ok(())
 }
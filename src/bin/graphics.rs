@@ -1,8 +1,11 @@
 use onion::graphics::{
     context::GraphicsContext,
+    error::GraphicsError,
     render_pass::{basic::BasicMSAAPass, overlay::OverlayPass},
     shape, Color,
 };
+use onion::launch::LaunchOptions;
+use onion::settings::Settings;
 use std::error::Error;
 use vulkano::sync::future::GpuFuture;
 use winit::{
@@ -13,9 +16,17 @@ use winit::{
 use onion::graphics::texture::Texture;
 
 fn main() -> Result<(), impl Error> {
+    let options = LaunchOptions::from_env_or_exit();
+    let mut settings = Settings::default();
+    options.apply(&mut settings);
+
     let event_loop = EventLoop::new().unwrap();
 
-    let mut gfx = GraphicsContext::new(&event_loop);
+    let mut gfx = GraphicsContext::builder()
+        .settings(&settings)
+        .debug(options.debug_level())
+        .build(&event_loop)
+        .unwrap();
 
     // Read the font data.
     let font = include_bytes!("Roboto-Regular.ttf") as &[u8];
@@ -35,7 +46,9 @@ fn main() -> Result<(), impl Error> {
 
     println!("{:?}", buf);
 
-    let image = gfx.upload_rgba(buf, [metrics.width as u32, metrics.height as u32, 1]);
+    let image = gfx
+        .upload_rgba(buf, [metrics.width as u32, metrics.height as u32, 1])
+        .unwrap();
 
     event_loop.run(move |event, elwt| {
         elwt.set_control_flow(ControlFlow::Poll);
@@ -57,7 +70,11 @@ fn main() -> Result<(), impl Error> {
                 event: WindowEvent::RedrawRequested,
                 ..
             } => {
-                let future = gfx.start_frame().unwrap();
+                let future = match gfx.start_frame() {
+                    Ok(future) => future,
+                    Err(GraphicsError::SwapchainOutOfDate) => return,
+                    Err(e) => panic!("failed to start frame: {e}"),
+                };
 
                 let render_pass = &mut gfx.render_passes.basic_msaa;
                 let basic_pipeline = &mut gfx.pipelines.basic;
@@ -128,7 +145,7 @@ fn main() -> Result<(), impl Error> {
                     }
                 }
 
-                gfx.finish_frame(after_future2.unwrap());
+                gfx.finish_frame(after_future2.unwrap()).unwrap();
             }
             Event::AboutToWait => gfx.window.request_redraw(),
             _ => (),
@@ -1,3 +1,4 @@
+use onion::launch::LaunchOptions;
 use onion::netcode::replay;
 use std::time::Duration;
 use std::time::Instant;
@@ -5,6 +6,8 @@ use std::time::Instant;
 const TIME_PER_TICK: Duration = Duration::new(0, 13000000); // roughly 60 fps
 
 fn main() {
+    // The server is always headless and only takes the shared options for now.
+    let _options = LaunchOptions::from_env_or_exit();
     let mut adder = replay::Replayable::new(|i: &i64, s: &i64| -> i64 { i + s }, 0, 0);
     let start_time = Instant::now();
     let mut last_commit = 0;
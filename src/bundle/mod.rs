@@ -0,0 +1,54 @@
+//! Bundles: components grouped in a struct, spawned together, e.g. everything a sprite needs.
+//!
+//! ```ignore
+//! #[derive(Bundle)]
+//! pub struct SpriteBundle {
+//!     pub transform: Transform,
+//!     pub animation: SpriteAnimation,
+//!     pub layers: RenderLayers,
+//! }
+//!
+//! #[derive(Bundle)]
+//! pub struct EnemyBundle {
+//!     #[bundle]
+//!     pub sprite: SpriteBundle,
+//!     pub name: Name,
+//! }
+//!
+//! let enemy = world.spawn_bundle(EnemyBundle { sprite, name: Name::new("slime") });
+//! ```
+
+use hecs::{Entity, NoSuchEntity, World};
+
+pub use hecs::EntityBuilder;
+
+mod tests;
+
+/// Components that are spawned together. Derive it with `#[derive(Bundle)]` rather than
+/// implementing it.
+pub trait Bundle {
+    /// Adds the components to `builder`.
+    fn add_to(self, builder: &mut EntityBuilder);
+}
+
+/// Spawning and inserting [`Bundle`]s, for the `World` of hecs.
+pub trait SpawnBundle {
+    fn spawn_bundle(&mut self, bundle: impl Bundle) -> Entity;
+
+    /// Adds the components of `bundle` to `entity`, replacing those it has already.
+    fn insert_bundle(&mut self, entity: Entity, bundle: impl Bundle) -> Result<(), NoSuchEntity>;
+}
+
+impl SpawnBundle for World {
+    fn spawn_bundle(&mut self, bundle: impl Bundle) -> Entity {
+        let mut builder = EntityBuilder::new();
+        bundle.add_to(&mut builder);
+        self.spawn(builder.build())
+    }
+
+    fn insert_bundle(&mut self, entity: Entity, bundle: impl Bundle) -> Result<(), NoSuchEntity> {
+        let mut builder = EntityBuilder::new();
+        bundle.add_to(&mut builder);
+        self.insert(entity, builder.build())
+    }
+}
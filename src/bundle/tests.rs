@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use crate::bundle::SpawnBundle;
+    use crate::Bundle;
+    use hecs::World;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32);
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Label(&'static str);
+
+    #[derive(Bundle)]
+    struct Body {
+        position: Position,
+        health: Health,
+    }
+
+    #[derive(Bundle)]
+    struct Enemy {
+        #[bundle]
+        body: Body,
+        label: Label,
+    }
+
+    fn body() -> Body {
+        Body {
+            position: Position(3),
+            health: Health(10),
+        }
+    }
+
+    #[test]
+    fn test_spawn_nested_bundle() {
+        let mut world = World::new();
+        let enemy = world.spawn_bundle(Enemy {
+            body: body(),
+            label: Label("slime"),
+        });
+
+        assert_eq!(*world.get::<&Position>(enemy).unwrap(), Position(3));
+        assert_eq!(*world.get::<&Health>(enemy).unwrap(), Health(10));
+        assert_eq!(*world.get::<&Label>(enemy).unwrap(), Label("slime"));
+    }
+
+    #[test]
+    fn test_insert_bundle_replaces_components() {
+        let mut world = World::new();
+        let entity = world.spawn((Health(1), Label("crate")));
+        world.insert_bundle(entity, body()).unwrap();
+
+        assert_eq!(*world.get::<&Position>(entity).unwrap(), Position(3));
+        assert_eq!(*world.get::<&Health>(entity).unwrap(), Health(10));
+        assert_eq!(*world.get::<&Label>(entity).unwrap(), Label("crate"));
+
+        world.despawn(entity).unwrap();
+        assert!(world.insert_bundle(entity, body()).is_err());
+    }
+}
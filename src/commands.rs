@@ -0,0 +1,184 @@
+//! Deferred changes to the world, for systems that can't spawn or despawn while they iterate.
+//!
+//! Commands are queued during a stage and applied by the [`App`](crate::app::App) once the
+//! stage's systems have run, in the order they were queued.
+//!
+//! ```ignore
+//! fn die(mut commands: Commands, mut query: Query<(&Health, &Transform)>) {
+//!     for (entity, (health, transform)) in query.iter() {
+//!         if health.0 <= 0.0 {
+//!             commands.despawn(entity);
+//!             commands.spawn((Explosion, *transform));
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::any::TypeId;
+
+use hecs::{Bundle, Component, Entity, RefMut, World};
+
+use crate::{
+    bundle::{self, SpawnBundle},
+    event::{self, Event},
+    hierarchy,
+    resource::{self, FromWorld},
+    system::{Access, ParamError, SystemParam},
+};
+
+type Command = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// The commands queued since the last sync point, kept on a single entity of the world.
+#[derive(Default)]
+pub struct CommandQueue {
+    commands: Vec<Command>,
+}
+
+impl CommandQueue {
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Queues changes to the world. Take it as a system parameter, or get it with
+/// [`Commands::get`] in systems taking the world.
+pub struct Commands<'w> {
+    world: &'w World,
+    queue: RefMut<'w, CommandQueue>,
+}
+
+impl<'w> Commands<'w> {
+    /// The queue of `world`. Fails if the world has none, which an app's world always has.
+    pub fn get(world: &'w World) -> Result<Self, ParamError> {
+        let name = std::any::type_name::<CommandQueue>();
+        let entity = world
+            .query::<&CommandQueue>()
+            .iter()
+            .next()
+            .map(|(entity, _)| entity)
+            .ok_or(ParamError::MissingResource(name))?;
+        let queue = world
+            .get::<&mut CommandQueue>(entity)
+            .map_err(|_| ParamError::Borrowed(name))?;
+        Ok(Commands { world, queue })
+    }
+
+    /// Queues `f` to run with the world at the sync point.
+    pub fn add(&mut self, f: impl FnOnce(&mut World) + Send + Sync + 'static) {
+        self.queue.commands.push(Box::new(f));
+    }
+
+    /// Spawns an entity with `components`. The entity is reserved right away, so later commands
+    /// can refer to it.
+    pub fn spawn(&mut self, components: impl Bundle + Send + Sync + 'static) -> Entity {
+        let entity = self.world.reserve_entity();
+        self.add(move |world| {
+            let _ = world.insert(entity, components);
+        });
+        entity
+    }
+
+    /// Like [`Commands::spawn`], with a [`bundle::Bundle`] made with `#[derive(Bundle)]`.
+    pub fn spawn_bundle(&mut self, bundle: impl bundle::Bundle + Send + Sync + 'static) -> Entity {
+        let entity = self.world.reserve_entity();
+        self.add(move |world| {
+            let _ = world.insert_bundle(entity, bundle);
+        });
+        entity
+    }
+
+    /// Despawns `entity`, if it still exists by then.
+    pub fn despawn(&mut self, entity: Entity) {
+        self.add(move |world| {
+            let _ = world.despawn(entity);
+        });
+    }
+
+    /// Despawns `entity` and its descendants, see [`hierarchy::despawn_recursive`].
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        self.add(move |world| {
+            let _ = hierarchy::despawn_recursive(world, entity);
+        });
+    }
+
+    /// Makes `child` a child of `parent`, if both still exist by then, see
+    /// [`hierarchy::set_parent`].
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        self.add(move |world| {
+            let _ = hierarchy::set_parent(world, child, parent);
+        });
+    }
+
+    /// Adds `components` to `entity`, replacing those it has.
+    pub fn insert(&mut self, entity: Entity, components: impl Bundle + Send + Sync + 'static) {
+        self.add(move |world| {
+            let _ = world.insert(entity, components);
+        });
+    }
+
+    /// Removes the components of `B` from `entity`, if it has all of them.
+    pub fn remove<B: Bundle + 'static>(&mut self, entity: Entity) {
+        self.add(move |world| {
+            let _ = world.remove::<B>(entity);
+        });
+    }
+
+    /// Runs the global observers of `event`, see [`event::trigger`].
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.add(move |world| event::trigger(world, event));
+    }
+
+    /// Runs the observers of `event` for each of `targets`, see [`event::trigger_targets`].
+    pub fn trigger_targets<E: Event>(&mut self, event: E, targets: Vec<Entity>) {
+        self.add(move |world| event::trigger_targets(world, event, targets));
+    }
+
+    /// Sets the resource of type `T`, the component of a single entity like
+    /// [`Time`](crate::time::Time).
+    pub fn insert_resource<T: Component>(&mut self, value: T) {
+        self.add(move |world| resource::insert(world, value));
+    }
+
+    /// Adds the resource of type `T` unless there is one, see [`resource::init`].
+    pub fn init_resource<T: Component + FromWorld>(&mut self) {
+        self.add(resource::init::<T>);
+    }
+
+    /// Removes the resource of type `T`.
+    pub fn remove_resource<T: Component>(&mut self) {
+        self.add(|world| {
+            resource::remove::<T>(world);
+        });
+    }
+}
+
+impl SystemParam for Commands<'_> {
+    type Item<'w> = Commands<'w>;
+    type State = ();
+
+    fn fetch<'w>(world: &'w World, _: &'w mut ()) -> Result<Commands<'w>, ParamError> {
+        Commands::get(world)
+    }
+
+    fn access(access: &mut Access) {
+        access.add_write(TypeId::of::<CommandQueue>());
+    }
+}
+
+/// Runs the queued commands, adding a queue to the world if it has none.
+pub(crate) fn apply(world: &mut World) {
+    let queued = match world.query_mut::<&mut CommandQueue>().into_iter().next() {
+        Some((_, queue)) => std::mem::take(&mut queue.commands),
+        None => {
+            world.spawn((CommandQueue::default(),));
+            return;
+        }
+    };
+    for command in queued {
+        command(world);
+    }
+}
@@ -0,0 +1,87 @@
+//! An opt-in panic hook that reports what the engine was doing when it panicked.
+//!
+//! Most of the graphics stack unwraps on failure, so a bare panic message rarely says which frame
+//! or system triggered it. [`install_panic_hook`] prints the frame number, the stage and system
+//! that were running and the most recent debug labels, then aborts.
+
+use std::{
+    collections::VecDeque,
+    panic,
+    sync::{Mutex, TryLockError},
+};
+
+/// How many debug labels are kept for the report.
+pub const LABEL_HISTORY: usize = 16;
+
+struct CrashContext {
+    frame: u64,
+    stage: Option<String>,
+    system: Option<String>,
+    labels: VecDeque<String>,
+}
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    frame: 0,
+    stage: None,
+    system: None,
+    labels: VecDeque::new(),
+});
+
+fn with_context(f: impl FnOnce(&mut CrashContext)) {
+    // A poisoned lock only means some thread panicked mid-update, the data is still useful.
+    let mut context = CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut context);
+}
+
+/// Records the frame that is starting. Called by `App::update`.
+pub fn set_frame(frame: u64) {
+    with_context(|c| c.frame = frame);
+}
+
+/// Records the system about to run. Called by `App::update`.
+pub fn enter_system(stage: &str, system: &str) {
+    with_context(|c| {
+        c.stage = Some(stage.to_owned());
+        c.system = Some(system.to_owned());
+    });
+}
+
+/// Records a debug label, e.g. the name of a render pass being recorded. Only the last
+/// [`LABEL_HISTORY`] labels are kept.
+pub fn push_label(label: impl Into<String>) {
+    with_context(|c| {
+        if c.labels.len() == LABEL_HISTORY {
+            c.labels.pop_front();
+        }
+        c.labels.push_back(label.into());
+    });
+}
+
+/// Replaces the panic hook with one that prints the crash context after the regular panic
+/// message and aborts the process.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+
+        // The panic may have happened while the context was locked on this thread.
+        match CONTEXT.try_lock() {
+            Ok(context) => report(&context),
+            Err(TryLockError::Poisoned(e)) => report(&e.into_inner()),
+            Err(TryLockError::WouldBlock) => eprintln!("crash context unavailable"),
+        }
+
+        std::process::abort();
+    }));
+}
+
+fn report(context: &CrashContext) {
+    eprintln!("crash context:");
+    eprintln!("\tframe: {}", context.frame);
+    eprintln!("\tstage: {}", context.stage.as_deref().unwrap_or("none"));
+    eprintln!("\tsystem: {}", context.system.as_deref().unwrap_or("none"));
+    eprintln!("\trecent labels:");
+    for label in context.labels.iter() {
+        eprintln!("\t\t{label}");
+    }
+}
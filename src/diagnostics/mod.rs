@@ -0,0 +1,182 @@
+//! How long systems and updates take, for finding what makes a frame slow.
+//!
+//! Adding the [`Diagnostics`] resource turns the measuring on; without it systems aren't timed.
+//!
+//! ```ignore
+//! app.init_resource::<Diagnostics>()
+//!     .add_system(diagnostics::log_every(Duration::from_secs(5)));
+//!
+//! // Or read them yourself, e.g. for an overlay.
+//! let diagnostics = resource::get::<Diagnostics>(&app.world).unwrap();
+//! for (name, timing) in diagnostics.systems() {
+//!     println!("{name}: {:?} on average, {:?} at most", timing.average(), timing.max());
+//! }
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write,
+    time::Duration,
+};
+
+use hecs::World;
+
+use crate::{
+    resource,
+    time::{Time, Timer, TimerMode},
+};
+
+mod tests;
+
+/// How many samples the averages and maximums cover.
+pub const WINDOW: usize = 120;
+
+/// The durations of the last [`WINDOW`] runs of something.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timing {
+    samples: VecDeque<Duration>,
+    total: Duration,
+}
+
+impl Timing {
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() == WINDOW {
+            self.total -= self.samples.pop_front().unwrap();
+        }
+        self.samples.push_back(duration);
+        self.total += duration;
+    }
+
+    /// The latest duration, zero before the first.
+    pub fn last(&self) -> Duration {
+        self.samples.back().copied().unwrap_or_default()
+    }
+
+    pub fn average(&self) -> Duration {
+        match self.samples.len() {
+            0 => Duration::ZERO,
+            n => self.total / n as u32,
+        }
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().copied().max().unwrap_or_default()
+    }
+}
+
+/// The timings of the app, kept on a single entity of the world and updated by `App`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnostics {
+    systems: BTreeMap<String, Timing>,
+    sets: BTreeMap<String, Timing>,
+    frame: Timing,
+    update: Timing,
+    world_entities: u32,
+}
+
+impl Diagnostics {
+    /// The time each system took, per run, by name.
+    pub fn systems(&self) -> impl Iterator<Item = (&str, &Timing)> {
+        self.systems
+            .iter()
+            .map(|(name, timing)| (name.as_str(), timing))
+    }
+
+    pub fn system(&self, name: &str) -> Option<&Timing> {
+        self.systems.get(name)
+    }
+
+    /// The time the systems of each set took together, per stage run, by set name.
+    pub fn sets(&self) -> impl Iterator<Item = (&str, &Timing)> {
+        self.sets
+            .iter()
+            .map(|(name, timing)| (name.as_str(), timing))
+    }
+
+    pub fn set(&self, name: &str) -> Option<&Timing> {
+        self.sets.get(name)
+    }
+
+    /// The real time between updates.
+    pub fn frame(&self) -> &Timing {
+        &self.frame
+    }
+
+    /// The time spent updating the app, a part of the frame.
+    pub fn update(&self) -> &Timing {
+        &self.update
+    }
+
+    /// The number of entities in the world after the last update. Counts those holding
+    /// resources, events and observers too, so it is a few more than the game spawned.
+    pub fn world_entities(&self) -> u32 {
+        self.world_entities
+    }
+
+    pub(crate) fn record_system(&mut self, name: &str, duration: Duration) {
+        self.systems
+            .entry(name.to_owned())
+            .or_default()
+            .record(duration);
+    }
+
+    pub(crate) fn record_set(&mut self, name: &str, duration: Duration) {
+        self.sets
+            .entry(name.to_owned())
+            .or_default()
+            .record(duration);
+    }
+
+    /// A line per timing, slowest systems first.
+    pub fn report(&self) -> String {
+        let line = |report: &mut String, name: &str, timing: &Timing| {
+            let _ = writeln!(
+                report,
+                "{name}: {:.3}ms avg, {:.3}ms max",
+                timing.average().as_secs_f64() * 1000.0,
+                timing.max().as_secs_f64() * 1000.0,
+            );
+        };
+
+        let mut report = String::new();
+        line(&mut report, "frame", &self.frame);
+        line(&mut report, "update", &self.update);
+        let _ = writeln!(report, "world entities: {}", self.world_entities);
+        let mut systems: Vec<_> = self.systems().collect();
+        systems.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.average()));
+        for (name, timing) in systems {
+            line(&mut report, &format!("system {name}"), timing);
+        }
+        for (name, timing) in self.sets() {
+            line(&mut report, &format!("set {name}"), timing);
+        }
+        report
+    }
+}
+
+/// Records an update that took `update` of a frame lasting `frame`, if the world has
+/// [`Diagnostics`].
+pub(crate) fn record_update(world: &World, frame: Duration, update: Duration) {
+    let Some(mut diagnostics) = resource::get_mut::<Diagnostics>(world) else {
+        return;
+    };
+    diagnostics.frame.record(frame);
+    diagnostics.update.record(update);
+    diagnostics.world_entities = world.len();
+}
+
+/// A system that logs [`Diagnostics::report`] every `interval` of real time.
+pub fn log_every(interval: Duration) -> impl Fn(&mut World) + 'static {
+    let timer = std::cell::Cell::new(Timer::new(interval, TimerMode::Repeating));
+    move |world| {
+        let mut ticked = timer.get();
+        ticked.tick(Time::get(world).raw_delta());
+        timer.set(ticked);
+        if !ticked.just_finished() {
+            return;
+        }
+        if let Some(diagnostics) = resource::get::<Diagnostics>(world) {
+            tracing::info!(target: "diagnostics", "\n{}", diagnostics.report());
+        }
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{Timing, WINDOW};
+    use std::time::Duration;
+
+    #[test]
+    fn test_timing_window() {
+        let mut timing = Timing::default();
+        assert_eq!(Duration::ZERO, timing.average());
+        // A slow run, then enough fast ones to push it out of the window.
+        timing.record(Duration::from_millis(50));
+        for _ in 0..WINDOW - 1 {
+            timing.record(Duration::from_millis(2));
+        }
+        assert_eq!(Duration::from_millis(50), timing.max());
+        assert!(timing.average() > Duration::from_millis(2));
+
+        timing.record(Duration::from_millis(2));
+        assert_eq!(Duration::from_millis(2), timing.max());
+        assert_eq!(Duration::from_millis(2), timing.average());
+        assert_eq!(Duration::from_millis(2), timing.last());
+    }
+}
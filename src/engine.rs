@@ -0,0 +1,200 @@
+//! Runs the winit event loop for an [`App`] and a [`GraphicsContext`], so binaries only add
+//! systems instead of matching on events themselves.
+//!
+//! Every update the [`Engine`] spawns the window events that arrived as [`WindowInput`]s and
+//! applies them to the world's [`Input`], reports graphics resets, runs the app's systems, applies
+//! the world's [`Cursor`] and, when a redraw is due, runs the render stage. Render systems get
+//! the context and the future of the frame so far, and return the future after their draws:
+//!
+//! ```ignore
+//! let mut engine = Engine::new(App::new(), GraphicsContext::builder().title("demo"))?;
+//! engine.app.add_system(Box::new(move_players));
+//! engine.add_render_system(
+//!     "scene",
+//!     Box::new(|world, gfx, future| {
+//!         let mut frame = gfx.render_passes.depth.frame(/* ... */).unwrap();
+//!         // ... draw the world
+//!         after_future
+//!     }),
+//! );
+//! engine.run()?;
+//! ```
+
+use hecs::World;
+use vulkano::sync::GpuFuture;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::EventLoop,
+};
+
+use crate::{
+    app::App,
+    graphics::{
+        context::{GraphicsContext, GraphicsContextBuilder},
+        cursor::Cursor,
+        error::GraphicsError,
+    },
+    input::Input,
+};
+
+/// Stage name of the systems added with [`Engine::add_render_system`], e.g. for crash reports.
+pub const RENDER_STAGE: &str = "render";
+
+/// Draws part of a frame: takes the future of what was submitted before and returns the future
+/// after its own work.
+pub type RenderSystem =
+    Box<dyn FnMut(&mut World, &mut GraphicsContext, Box<dyn GpuFuture>) -> Box<dyn GpuFuture>>;
+
+/// A window event that arrived since the last update, e.g. for input handling systems. Despawned
+/// at the end of the update like a [`FileDrop`](crate::file_drop::FileDrop).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInput(pub WindowEvent);
+
+struct RenderEntry {
+    name: String,
+    system: RenderSystem,
+}
+
+pub struct Engine {
+    pub app: App,
+    pub gfx: GraphicsContext,
+    event_loop: EventLoop<()>,
+    render_systems: Vec<RenderEntry>,
+}
+
+impl Engine {
+    /// Opens the window described by `builder` for `app`.
+    pub fn new(mut app: App, builder: GraphicsContextBuilder) -> Result<Self, GraphicsError> {
+        let event_loop = EventLoop::new()?;
+        let gfx = builder.build(&event_loop)?;
+        let input = Input::new(gfx.window.inner_size(), gfx.window.scale_factor());
+        app.world.spawn((input,));
+        Ok(Engine {
+            app,
+            gfx,
+            event_loop,
+            render_systems: Vec::new(),
+        })
+    }
+
+    /// Adds a system to the render stage. Render systems run in the order they were added, each
+    /// after the previous one's work.
+    pub fn add_render_system(&mut self, name: &str, system: RenderSystem) -> &mut Self {
+        self.render_systems.push(RenderEntry {
+            name: name.to_owned(),
+            system,
+        });
+        self
+    }
+
+    /// Runs until the window is closed. Resizes recreate the swapchain, the rest of the window
+    /// events go to [`App::handle_window_event`] and the [`Input`], and are spawned as
+    /// [`WindowInput`]s.
+    ///
+    /// A frame error the context can't recover from stops the loop and is returned.
+    pub fn run(self) -> Result<(), GraphicsError> {
+        let Engine {
+            mut app,
+            mut gfx,
+            event_loop,
+            mut render_systems,
+        } = self;
+        let mut cursor = Cursor::default();
+        let mut error = None;
+        let error_slot = &mut error;
+
+        event_loop.run(move |event, elwt| match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => elwt.exit(),
+            Event::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                ..
+            } => {
+                if let Err(e) = render(&mut app.world, &mut gfx, &mut render_systems) {
+                    *error_slot = Some(e);
+                    elwt.exit();
+                }
+            }
+            Event::WindowEvent { event, .. } => {
+                if let WindowEvent::Resized(_) = event {
+                    gfx.recreate_swapchain = true;
+                }
+                app.handle_window_event(&event);
+                Input::update(&mut app.world, &event);
+                app.world.spawn((WindowInput(event),));
+            }
+            Event::DeviceEvent { event, .. } => Input::update_device(&mut app.world, &event),
+            Event::AboutToWait => {
+                gfx.emit_resets(&mut app.world);
+                app.update();
+                apply_cursor(&app.world, &gfx, &mut cursor);
+                if app.take_redraw() {
+                    gfx.window.request_redraw();
+                }
+                elwt.set_control_flow(app.control_flow());
+            }
+            _ => (),
+        })?;
+        error.map_or(Ok(()), Err)
+    }
+}
+
+/// Applies the world's [`Cursor`] if it differs from `applied`, and keeps a centered cursor in
+/// the middle while the window is focused.
+fn apply_cursor(world: &World, gfx: &GraphicsContext, applied: &mut Cursor) {
+    if let Some(cursor) = Cursor::get(world) {
+        if cursor != *applied {
+            gfx.apply_cursor(&cursor);
+            *applied = cursor;
+        }
+    }
+    let focused = Input::get(world).is_none_or(|input| input.focused());
+    if applied.center && focused {
+        gfx.center_cursor();
+    }
+}
+
+/// Runs the render stage for one frame. Frames the context can't start are skipped, it recovers
+/// by itself. Other errors are returned.
+fn render(
+    world: &mut World,
+    gfx: &mut GraphicsContext,
+    render_systems: &mut [RenderEntry],
+) -> Result<(), GraphicsError> {
+    let mut future = match gfx.start_frame() {
+        Ok(future) => future,
+        Err(
+            GraphicsError::SwapchainOutOfDate
+            | GraphicsError::DeviceLost
+            | GraphicsError::SurfaceLost,
+        ) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    for entry in render_systems.iter_mut() {
+        crate::crash::enter_system(RENDER_STAGE, &entry.name);
+        let _span = tracing::trace_span!(
+            target: "ecs",
+            "system",
+            stage = RENDER_STAGE,
+            name = entry.name.as_str()
+        )
+        .entered();
+        future = (entry.system)(world, gfx, future);
+    }
+
+    gfx.finish_frame(future)
+}
+
+pub(crate) fn clear(world: &mut World) {
+    let entities: Vec<_> = world
+        .query::<&WindowInput>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.despawn(entity).unwrap();
+    }
+}
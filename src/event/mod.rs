@@ -0,0 +1,324 @@
+//! Events between systems that every reader sees exactly once, whatever order the systems run
+//! in.
+//!
+//! An [`Events`] resource keeps what was sent during this update and the last one. Each
+//! [`EventReader`] remembers how far it read, so a system running before the sender still gets
+//! the event on the next update, and one running after it gets it on this one.
+//!
+//! ```ignore
+//! struct Hit {
+//!     target: Entity,
+//!     damage: f32,
+//! }
+//!
+//! app.add_event::<Hit>();
+//!
+//! fn shoot(mut hits: EventWriter<Hit>, /* ... */) {
+//!     hits.send(Hit { target, damage: 10.0 });
+//! }
+//!
+//! fn take_damage(mut hits: EventReader<Hit>, mut health: Query<&mut Health>) {
+//!     for hit in hits.iter() {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! Events can also be triggered, which runs their observers right away instead of waiting for
+//! readers. A trigger can target entities, running the observers of those entities besides the
+//! global ones:
+//!
+//! ```ignore
+//! app.add_observer(|world: &mut World, trigger: Trigger<Hit>| {
+//!     // Runs for every hit.
+//! });
+//! event::observe(&mut app.world, boss, |world: &mut World, trigger: Trigger<Hit>| {
+//!     // Runs for hits on the boss only.
+//! });
+//!
+//! event::trigger_targets(&mut app.world, Hit { damage: 10.0 }, [boss]);
+//! ```
+
+use std::{
+    any::{type_name, TypeId},
+    sync::Arc,
+};
+
+use hecs::{Entity, Ref, RefMut, World};
+
+use crate::system::{resource_entity, Access, ParamError, SystemParam};
+
+mod tests;
+
+/// Types that can be sent as events.
+pub trait Event: Send + Sync + 'static {}
+
+impl<E: Send + Sync + 'static> Event for E {}
+
+/// The events of type `E` sent during this update and the last one, kept on a single entity of
+/// the world by [`App::add_event`](crate::app::App::add_event). Events are numbered in the
+/// order they were sent.
+pub struct Events<E: Event> {
+    previous: Vec<E>,
+    current: Vec<E>,
+    /// The number of `previous[0]`.
+    previous_start: u64,
+    /// The number of `current[0]`.
+    current_start: u64,
+}
+
+impl<E: Event> Default for Events<E> {
+    fn default() -> Self {
+        Events {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+            current_start: 0,
+        }
+    }
+}
+
+impl<E: Event> Events<E> {
+    pub fn send(&mut self, event: E) {
+        self.current.push(event);
+    }
+
+    /// The number the next event sent gets.
+    pub fn next_number(&self) -> u64 {
+        self.current_start + self.current.len() as u64
+    }
+
+    /// The events numbered `first` and later that are still kept, oldest first.
+    pub fn since(&self, first: u64) -> impl Iterator<Item = &E> {
+        let skip_previous = first.saturating_sub(self.previous_start) as usize;
+        let skip_current = first.saturating_sub(self.current_start) as usize;
+        self.previous
+            .iter()
+            .skip(skip_previous)
+            .chain(self.current.iter().skip(skip_current))
+    }
+
+    /// Drops the events of the last update and keeps this one's for the next. Called once per
+    /// update by the app.
+    pub fn update(&mut self) {
+        self.previous_start = self.current_start;
+        self.current_start += self.current.len() as u64;
+        std::mem::swap(&mut self.previous, &mut self.current);
+        // Reuses the dropped events' allocation for the next update.
+        self.current.clear();
+    }
+
+    /// How many events are kept.
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous.is_empty() && self.current.is_empty()
+    }
+}
+
+/// Sends events of type `E`.
+pub struct EventWriter<'w, E: Event> {
+    events: RefMut<'w, Events<E>>,
+}
+
+impl<E: Event> EventWriter<'_, E> {
+    pub fn send(&mut self, event: E) {
+        self.events.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+impl<E: Event> SystemParam for EventWriter<'_, E> {
+    type Item<'w> = EventWriter<'w, E>;
+    type State = Option<Entity>;
+
+    fn fetch<'w>(
+        world: &'w World,
+        entity: &'w mut Option<Entity>,
+    ) -> Result<EventWriter<'w, E>, ParamError> {
+        let entity = resource_entity::<Events<E>>(world, entity)?;
+        let events = world
+            .get::<&mut Events<E>>(entity)
+            .map_err(|_| ParamError::Borrowed(type_name::<Events<E>>()))?;
+        Ok(EventWriter { events })
+    }
+
+    fn access(access: &mut Access) {
+        access.add_write(TypeId::of::<Events<E>>());
+    }
+}
+
+/// The events of type `E` this system hasn't read yet. Events it didn't read within two updates
+/// are missed.
+pub struct EventReader<'w, E: Event> {
+    events: Ref<'w, Events<E>>,
+    /// The number of the first unread event, kept by the system.
+    next: &'w mut u64,
+}
+
+impl<E: Event> EventReader<'_, E> {
+    /// The unread events, oldest first. They count as read afterwards.
+    pub fn iter(&mut self) -> impl Iterator<Item = &E> {
+        let first = std::mem::replace(self.next, self.events.next_number());
+        self.events.since(first)
+    }
+
+    /// How many events are unread.
+    pub fn len(&self) -> usize {
+        self.events.since(*self.next).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Marks every event as read without looking at them.
+    pub fn clear(&mut self) {
+        *self.next = self.events.next_number();
+    }
+}
+
+impl<E: Event> SystemParam for EventReader<'_, E> {
+    type Item<'w> = EventReader<'w, E>;
+    /// The number of the first unread event, and the entity of the events.
+    type State = (u64, Option<Entity>);
+
+    fn fetch<'w>(
+        world: &'w World,
+        (next, entity): &'w mut (u64, Option<Entity>),
+    ) -> Result<EventReader<'w, E>, ParamError> {
+        let entity = resource_entity::<Events<E>>(world, entity)?;
+        let events = world
+            .get::<&Events<E>>(entity)
+            .map_err(|_| ParamError::Borrowed(type_name::<Events<E>>()))?;
+        Ok(EventReader { events, next })
+    }
+
+    fn access(access: &mut Access) {
+        access.add_read(TypeId::of::<Events<E>>());
+    }
+}
+
+/// Updates the [`Events`] of type `E` in `world`, see [`Events::update`].
+pub(crate) fn update<E: Event>(world: &mut World) {
+    for (_, events) in world.query_mut::<&mut Events<E>>() {
+        events.update();
+    }
+}
+
+/// A triggered event, passed to its observers.
+pub struct Trigger<'a, E: Event> {
+    pub event: &'a E,
+    /// The entity the event was triggered on, `None` for [`trigger`].
+    pub target: Option<Entity>,
+}
+
+type Observer<E> = Arc<dyn Fn(&mut World, Trigger<E>) + Send + Sync>;
+
+/// The observers of events of type `E`, kept on a single entity of the world.
+pub struct Observers<E: Event> {
+    /// The entity each observer watches, `None` for global observers.
+    observers: Vec<(Option<Entity>, Observer<E>)>,
+}
+
+impl<E: Event> Observers<E> {
+    pub fn len(&self) -> usize {
+        self.observers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+}
+
+/// Runs `observer` whenever an event of type `E` is triggered, targeted or not.
+pub fn add_observer<E: Event>(
+    world: &mut World,
+    observer: impl Fn(&mut World, Trigger<E>) + Send + Sync + 'static,
+) {
+    push_observer(world, None, Arc::new(observer));
+}
+
+/// Runs `observer` whenever an event of type `E` is triggered on `entity`, until the entity is
+/// despawned.
+pub fn observe<E: Event>(
+    world: &mut World,
+    entity: Entity,
+    observer: impl Fn(&mut World, Trigger<E>) + Send + Sync + 'static,
+) {
+    push_observer(world, Some(entity), Arc::new(observer));
+}
+
+fn push_observer<E: Event>(world: &mut World, entity: Option<Entity>, observer: Observer<E>) {
+    if let Some((_, observers)) = world.query_mut::<&mut Observers<E>>().into_iter().next() {
+        observers.observers.push((entity, observer));
+        return;
+    }
+    world.spawn((Observers {
+        observers: vec![(entity, observer)],
+    },));
+}
+
+/// Runs the global observers of `event` now.
+pub fn trigger<E: Event>(world: &mut World, event: E) {
+    for observer in observers_of::<E>(world, None) {
+        observer(
+            world,
+            Trigger {
+                event: &event,
+                target: None,
+            },
+        );
+    }
+}
+
+/// Runs the observers of `event` for each of `targets` now: the global ones and those watching
+/// the target.
+pub fn trigger_targets<E: Event>(
+    world: &mut World,
+    event: E,
+    targets: impl IntoIterator<Item = Entity>,
+) {
+    for target in targets {
+        for observer in observers_of::<E>(world, Some(target)) {
+            observer(
+                world,
+                Trigger {
+                    event: &event,
+                    target: Some(target),
+                },
+            );
+        }
+    }
+}
+
+/// The observers to run for a trigger on `target`, cloned so they can change the world. Drops
+/// the observers of despawned entities on the way.
+fn observers_of<E: Event>(world: &mut World, target: Option<Entity>) -> Vec<Observer<E>> {
+    let alive: Vec<_> = match world.query::<&Observers<E>>().iter().next() {
+        Some((_, observers)) => observers
+            .observers
+            .iter()
+            .map(|(entity, _)| entity.is_none_or(|entity| world.contains(entity)))
+            .collect(),
+        None => return Vec::new(),
+    };
+    let Some((_, observers)) = world.query_mut::<&mut Observers<E>>().into_iter().next() else {
+        return Vec::new();
+    };
+    let mut alive = alive.into_iter();
+    observers.observers.retain(|_| alive.next().unwrap_or(true));
+    observers
+        .observers
+        .iter()
+        .filter(|(entity, _)| entity.is_none() || *entity == target)
+        .map(|(_, observer)| observer.clone())
+        .collect()
+}
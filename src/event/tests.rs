@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use crate::event::{self, EventReader, Events, Observers, Trigger};
+    use crate::resource;
+    use crate::system::SystemParam;
+    use hecs::{Entity, World};
+    use std::sync::{Arc, Mutex};
+
+    fn world() -> World {
+        let mut world = World::new();
+        resource::insert(&mut world, Events::<u32>::default());
+        world
+    }
+
+    fn send(world: &World, event: u32) {
+        resource::get_mut::<Events<u32>>(world).unwrap().send(event);
+    }
+
+    /// Runs a reader with the state its system keeps between runs.
+    fn read(world: &World, state: &mut (u64, Option<Entity>)) -> Vec<u32> {
+        let mut reader = EventReader::<u32>::fetch(world, state).unwrap();
+        reader.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_reader_before_sender() {
+        let mut world = world();
+        let mut reader = Default::default();
+        assert!(read(&world, &mut reader).is_empty());
+        send(&world, 1);
+        event::update::<u32>(&mut world);
+        assert_eq!(vec![1], read(&world, &mut reader));
+        assert!(read(&world, &mut reader).is_empty());
+    }
+
+    #[test]
+    fn test_events_dropped_after_two_updates() {
+        let mut world = world();
+        send(&world, 1);
+        event::update::<u32>(&mut world);
+        assert_eq!(1, resource::get::<Events<u32>>(&world).unwrap().len());
+        event::update::<u32>(&mut world);
+        assert!(resource::get::<Events<u32>>(&world).unwrap().is_empty());
+        assert!(read(&world, &mut Default::default()).is_empty());
+    }
+
+    #[test]
+    fn test_readers_keep_own_cursor() {
+        let world = world();
+        let (mut first, mut second) = Default::default();
+        send(&world, 1);
+        assert_eq!(vec![1], read(&world, &mut first));
+        send(&world, 2);
+        assert_eq!(vec![1, 2], read(&world, &mut second));
+        assert_eq!(vec![2], read(&world, &mut first));
+    }
+
+    #[test]
+    fn test_targeted_observers_run_for_target() {
+        let mut world = World::new();
+        let (boss, minion) = (world.spawn(()), world.spawn(()));
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let global = seen.clone();
+        event::add_observer(&mut world, move |_, trigger: Trigger<u32>| {
+            global.lock().unwrap().push(("global", trigger.target));
+        });
+        let targeted = seen.clone();
+        event::observe(&mut world, boss, move |_, trigger: Trigger<u32>| {
+            targeted.lock().unwrap().push(("boss", trigger.target));
+        });
+
+        event::trigger_targets(&mut world, 1u32, [boss, minion]);
+        event::trigger(&mut world, 2u32);
+        assert_eq!(
+            vec![
+                ("global", Some(boss)),
+                ("boss", Some(boss)),
+                ("global", Some(minion)),
+                ("global", None),
+            ],
+            *seen.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_observers_dropped_after_despawn() {
+        let mut world = World::new();
+        let (boss, minion) = (world.spawn(()), world.spawn(()));
+        let runs = Arc::new(Mutex::new(0));
+        let counted = runs.clone();
+        event::observe(&mut world, boss, move |_, _: Trigger<u32>| {
+            *counted.lock().unwrap() += 1;
+        });
+        event::observe(&mut world, minion, |_, _: Trigger<u32>| {});
+        let observers = |world: &World| resource::get::<Observers<u32>>(world).unwrap().len();
+        assert_eq!(2, observers(&world));
+
+        world.despawn(boss).unwrap();
+        event::trigger_targets(&mut world, 1u32, [boss, minion]);
+        assert_eq!(0, *runs.lock().unwrap());
+        assert_eq!(1, observers(&world));
+    }
+}
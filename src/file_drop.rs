@@ -0,0 +1,92 @@
+//! Files dragged onto the window, as events systems can query.
+//!
+//! Pass window events to [`App::handle_window_event`](crate::app::App::handle_window_event). Each
+//! drag-and-drop event is spawned into the world as an entity with a [`FileDrop`] component and
+//! despawned at the end of the next update, so every system sees it exactly once.
+
+use std::{path::PathBuf, sync::Arc};
+
+use hecs::World;
+use vulkano::image::Image;
+use winit::event::WindowEvent;
+
+use crate::graphics::context::GraphicsContext;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileDrop {
+    /// A file is being dragged over the window. Sent once per file.
+    Hovered(PathBuf),
+    /// A file was dropped onto the window. Sent once per file.
+    Dropped(PathBuf),
+    /// The files being dragged left the window or the drag was cancelled.
+    Cancelled,
+}
+
+impl FileDrop {
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        match event {
+            WindowEvent::HoveredFile(path) => Some(FileDrop::Hovered(path.clone())),
+            WindowEvent::DroppedFile(path) => Some(FileDrop::Dropped(path.clone())),
+            WindowEvent::HoveredFileCancelled => Some(FileDrop::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// The kind of asset the dragged file is, going by its extension.
+    pub fn asset_kind(&self) -> Option<AssetKind> {
+        let path = match self {
+            FileDrop::Hovered(path) | FileDrop::Dropped(path) => path,
+            FileDrop::Cancelled => return None,
+        };
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(AssetKind::Png),
+            "obj" => Some(AssetKind::Obj),
+            "gltf" | "glb" => Some(AssetKind::Gltf),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Png,
+    Obj,
+    Gltf,
+}
+
+/// Uploads the PNGs dropped this update, with the path they came from. Files that can't be read
+/// or uploaded are skipped. There is no mesh import yet, so OBJ and glTF files are left to the caller.
+pub fn load_dropped_textures(
+    world: &World,
+    gfx: &mut GraphicsContext,
+) -> Vec<(PathBuf, Arc<Image>)> {
+    let mut textures = Vec::new();
+    for (_, drop) in world.query::<&FileDrop>().iter() {
+        let FileDrop::Dropped(path) = drop else {
+            continue;
+        };
+        if drop.asset_kind() != Some(AssetKind::Png) {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(path) else {
+            continue;
+        };
+        match gfx.upload_png(&bytes) {
+            Ok(image) => textures.push((path.clone(), image)),
+            Err(e) => tracing::warn!(target: "graphics", "failed to upload {path:?}: {e}"),
+        }
+    }
+    textures
+}
+
+pub(crate) fn clear(world: &mut World) {
+    let entities: Vec<_> = world
+        .query::<&FileDrop>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.despawn(entity).unwrap();
+    }
+}
@@ -0,0 +1,160 @@
+//! Lists the GPUs on the machine and picks the one to render with.
+
+use std::sync::Arc;
+
+use vulkano::{
+    device::physical::{PhysicalDevice, PhysicalDeviceType},
+    instance::{Instance, InstanceCreateFlags, InstanceCreateInfo},
+    Version, VulkanLibrary,
+};
+
+/// Overrides the device preference of the app, e.g. `ONION_DEVICE=1` or `ONION_DEVICE=nvidia`.
+pub const DEVICE_ENV_VAR: &str = "ONION_DEVICE";
+
+/// Which GPU to render with, instead of the first one by device type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DevicePreference {
+    /// The index in [`enumerate_adapters`].
+    Index(usize),
+    /// Part of the device name, ignoring case.
+    Name(String),
+}
+
+impl DevicePreference {
+    /// A number is an index, anything else part of a name.
+    pub fn parse(s: &str) -> Self {
+        match s.trim().parse() {
+            Ok(index) => DevicePreference::Index(index),
+            Err(_) => DevicePreference::Name(s.trim().to_owned()),
+        }
+    }
+
+    /// The preference set with [`DEVICE_ENV_VAR`], if any.
+    pub fn from_env() -> Option<Self> {
+        std::env::var(DEVICE_ENV_VAR)
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| DevicePreference::parse(&s))
+    }
+
+    pub fn matches(&self, index: usize, name: &str) -> bool {
+        match self {
+            DevicePreference::Index(i) => *i == index,
+            DevicePreference::Name(n) => name.to_lowercase().contains(&n.to_lowercase()),
+        }
+    }
+}
+
+impl From<usize> for DevicePreference {
+    fn from(index: usize) -> Self {
+        DevicePreference::Index(index)
+    }
+}
+
+impl From<&str> for DevicePreference {
+    fn from(name: &str) -> Self {
+        DevicePreference::Name(name.to_owned())
+    }
+}
+
+impl From<String> for DevicePreference {
+    fn from(name: String) -> Self {
+        DevicePreference::Name(name)
+    }
+}
+
+/// A GPU as listed by [`enumerate_adapters`].
+#[derive(Debug, Clone)]
+pub struct AdapterInfo {
+    pub index: usize,
+    pub name: String,
+    pub device_type: PhysicalDeviceType,
+    pub api_version: Version,
+    pub driver_version: u32,
+    pub vendor_id: u32,
+    pub device_id: u32,
+}
+
+impl AdapterInfo {
+    fn new(index: usize, device: &PhysicalDevice) -> Self {
+        let properties = device.properties();
+        AdapterInfo {
+            index,
+            name: properties.device_name.clone(),
+            device_type: properties.device_type,
+            api_version: device.api_version(),
+            driver_version: properties.driver_version,
+            vendor_id: properties.vendor_id,
+            device_id: properties.device_id,
+        }
+    }
+}
+
+/// Every GPU Vulkan reports, without creating a window or device, e.g. to fill a settings menu
+/// before the context is built. Whether one can present to a window is only known once the
+/// window exists.
+pub fn enumerate_adapters() -> Vec<AdapterInfo> {
+    let library = VulkanLibrary::new().unwrap();
+    let instance = Instance::new(
+        library,
+        InstanceCreateInfo {
+            flags: InstanceCreateFlags::ENUMERATE_PORTABILITY,
+            ..Default::default()
+        },
+    )
+    .expect("failed to create Vulkan instance");
+
+    instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .enumerate()
+        .map(|(index, device)| AdapterInfo::new(index, &device))
+        .collect()
+}
+
+/// Lower is preferred when picking a physical device.
+pub(crate) fn device_type_priority(device_type: PhysicalDeviceType) -> u32 {
+    match device_type {
+        PhysicalDeviceType::DiscreteGpu => 0,
+        PhysicalDeviceType::IntegratedGpu => 1,
+        PhysicalDeviceType::VirtualGpu => 2,
+        PhysicalDeviceType::Cpu => 3,
+        PhysicalDeviceType::Other => 4,
+        _ => 5,
+    }
+}
+
+/// Picks from the suitable devices of `instance`, each with its queue family index from
+/// `suitable`. [`DEVICE_ENV_VAR`] takes precedence over `preference`; a preference that matches
+/// no suitable device falls back to the device type order.
+pub(crate) fn pick_physical_device(
+    instance: &Arc<Instance>,
+    preference: Option<&DevicePreference>,
+    suitable: impl Fn(&Arc<PhysicalDevice>) -> Option<u32>,
+) -> Option<(Arc<PhysicalDevice>, u32)> {
+    let candidates: Vec<(usize, Arc<PhysicalDevice>, u32)> = instance
+        .enumerate_physical_devices()
+        .unwrap()
+        .enumerate()
+        .filter_map(|(index, p)| suitable(&p).map(|queue_family| (index, p, queue_family)))
+        .collect();
+
+    let env_preference = DevicePreference::from_env();
+    if let Some(preference) = env_preference.as_ref().or(preference) {
+        let preferred = candidates
+            .iter()
+            .find(|(index, p, _)| preference.matches(*index, &p.properties().device_name));
+        match preferred {
+            Some((_, p, queue_family)) => return Some((p.clone(), *queue_family)),
+            None => tracing::warn!(
+                target: "graphics",
+                "no suitable device matches {preference:?}, picking by type"
+            ),
+        }
+    }
+
+    candidates
+        .into_iter()
+        .min_by_key(|(_, p, _)| device_type_priority(p.properties().device_type))
+        .map(|(_, p, queue_family)| (p, queue_family))
+}
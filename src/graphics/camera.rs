@@ -1,10 +1,58 @@
 use cgmath::prelude::*;
 use cgmath::{Deg, Matrix4, Rad, Vector3, Vector4};
-use glam::Mat4;
+use glam::{Mat4, Vec3};
+
+use super::{layers::RenderLayers, picking::Ray, viewport::ViewportRegion};
 
 pub trait Camera {
     fn mvp_mat(&self) -> Mat4;
 
+    /// Where the camera is in world space, e.g. for specular lighting.
+    fn position(&self) -> Vec3;
+
+    /// The world space direction pointing right on screen, e.g. to face billboards to the camera.
+    fn right(&self) -> Vec3;
+
+    /// The world space direction pointing up on screen.
+    fn up(&self) -> Vec3;
+
+    /// The layers of entities this camera renders. See [`RenderLayers`].
+    fn render_layers(&self) -> RenderLayers {
+        RenderLayers::default()
+    }
+
+    /// Where `world` appears when the camera draws into `viewport`: x and y in pixels from the
+    /// top left of the target, z the depth from 0 at the near plane to 1 at the far plane.
+    /// `None` behind the camera. Points off screen still project, e.g. to point an arrow at
+    /// them, check [`ViewportRegion::contains`] to keep labels on screen.
+    fn project(&self, world: Vec3, viewport: ViewportRegion) -> Option<Vec3> {
+        let clip = self.mvp_mat() * world.extend(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        let [x, y] = viewport.ndc_to_pixel([ndc.x, ndc.y]);
+        Some(Vec3::new(x, y, ndc.z))
+    }
+
+    /// The world space point at `pixel` and `depth`, the inverse of [`Camera::project`]. Depth
+    /// 0 is on the near plane, e.g. to drag an object keep the depth it projected to.
+    fn unproject(&self, pixel: [f32; 2], depth: f32, viewport: ViewportRegion) -> Vec3 {
+        let [x, y] = viewport.pixel_to_ndc(pixel);
+        self.mvp_mat()
+            .inverse()
+            .project_point3(Vec3::new(x, y, depth))
+    }
+
+    /// The ray from the camera through `pixel`, in pixels from the top left of the target, when
+    /// the camera draws into `viewport`. Test it against bounding boxes with
+    /// [`Ray::closest_hit`] to find what the cursor points at.
+    fn screen_to_ray(&self, pixel: [f32; 2], viewport: ViewportRegion) -> Ray {
+        let near = self.unproject(pixel, 0.0, viewport);
+        let far = self.unproject(pixel, 1.0, viewport);
+        Ray::new(near, far - near)
+    }
+
     fn rotate_x(&mut self, degs: Deg<f32>);
 
     fn rotate_y(&mut self, degs: Deg<f32>);
@@ -51,6 +99,7 @@ pub struct PerspectiveCamera {
     camera: Matrix4<f32>,
     // view: Matrix4<f32>,
     proj: Matrix4<f32>,
+    layers: RenderLayers,
 }
 
 impl Default for PerspectiveCamera {
@@ -65,6 +114,16 @@ impl Default for PerspectiveCamera {
 }
 
 impl PerspectiveCamera {
+    fn trace_matrices(&self) {
+        tracing::trace!(
+            target: "graphics",
+            model = ?self.model,
+            camera = ?self.camera,
+            proj = ?self.proj,
+            "camera moved"
+        );
+    }
+
     pub fn new(fov: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
         let angle_rad: Rad<f32> = Deg(fov).into();
         let focal_length = 1.0 / Rad::tan(angle_rad / 2.0);
@@ -95,9 +154,14 @@ impl PerspectiveCamera {
             // view,
             camera,
             proj,
+            layers: RenderLayers::default(),
         }
     }
 
+    pub fn set_render_layers(&mut self, layers: RenderLayers) {
+        self.layers = layers;
+    }
+
     pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
         let angle_rad: Rad<f32> = Deg(self.fov).into();
         let focal_length = 1.0 / Rad::tan(angle_rad / 2.0);
@@ -117,6 +181,19 @@ impl PerspectiveCamera {
         self.aspect_ratio = aspect_ratio;
         self.proj = proj;
     }
+
+    /// This camera mirrored in the horizontal plane at `height`, for rendering planar reflections,
+    /// e.g. for `PSOWater`. The mirrored image lines up with the surface on screen, so it is
+    /// sampled at the same screen position.
+    pub fn reflected(&self, height: f32) -> PerspectiveCamera {
+        let mirror = Matrix4::from_translation(Vector3::new(0.0, 2.0 * height, 0.0))
+            * Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0);
+
+        PerspectiveCamera {
+            camera: mirror * self.camera,
+            ..*self
+        }
+    }
 }
 
 impl Camera for PerspectiveCamera {
@@ -127,57 +204,58 @@ impl Camera for PerspectiveCamera {
         Mat4::from_cols_array_2d(&t)
     }
 
+    fn position(&self) -> Vec3 {
+        let w = self.camera.w;
+        Vec3::new(w.x, w.y, w.z)
+    }
+
+    fn right(&self) -> Vec3 {
+        let x = self.camera.x;
+        Vec3::new(x.x, x.y, x.z).normalize()
+    }
+
+    fn up(&self) -> Vec3 {
+        let y = self.camera.y;
+        Vec3::new(y.x, y.y, y.z).normalize()
+    }
+
+    fn render_layers(&self) -> RenderLayers {
+        self.layers
+    }
+
     fn rotate_x(&mut self, degs: Deg<f32>) {
         let rotation = Matrix4::from_angle_x(degs);
         self.camera = self.camera * rotation;
-        println!(
-            "m: {:?}\nc: {:?}\np: {:?}",
-            self.model, self.camera, self.proj
-        );
+        self.trace_matrices();
     }
 
     fn rotate_y(&mut self, degs: Deg<f32>) {
         let rotation = Matrix4::from_angle_y(degs);
         self.camera = self.camera * rotation;
-        println!(
-            "m: {:?}\nc: {:?}\np: {:?}",
-            self.model, self.camera, self.proj
-        );
+        self.trace_matrices();
     }
 
     fn rotate_z(&mut self, degs: Deg<f32>) {
         let rotation = Matrix4::from_angle_z(degs);
         self.camera = self.camera * rotation;
-        println!(
-            "m: {:?}\nc: {:?}\np: {:?}",
-            self.model, self.camera, self.proj
-        );
+        self.trace_matrices();
     }
 
     fn translate_x(&mut self, amount: f32) {
         let translation = Matrix4::from_translation(Vector3::new(amount, 0.0, 0.0));
         self.camera = self.camera * translation;
-        println!(
-            "m: {:?}\nc: {:?}\np: {:?}",
-            self.model, self.camera, self.proj
-        );
+        self.trace_matrices();
     }
 
     fn translate_y(&mut self, amount: f32) {
         let translation = Matrix4::from_translation(Vector3::new(0.0, amount, 0.0));
         self.camera = self.camera * translation;
-        println!(
-            "m: {:?}\nc: {:?}\np: {:?}",
-            self.model, self.camera, self.proj
-        );
+        self.trace_matrices();
     }
 
     fn translate_z(&mut self, amount: f32) {
         let translation = Matrix4::from_translation(Vector3::new(0.0, 0.0, amount));
         self.camera = self.camera * translation;
-        println!(
-            "m: {:?}\nc: {:?}\np: {:?}",
-            self.model, self.camera, self.proj
-        );
+        self.trace_matrices();
     }
 }
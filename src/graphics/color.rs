@@ -0,0 +1,314 @@
+//! RGBA colors and the conversions apps keep needing: hex strings, HSV and HSL, sRGB, blending
+//! and a few named colors in [`palette`].
+
+use std::fmt;
+
+/// Red, green, blue and alpha from 0 to 1, as the pipelines take them. Components are linear,
+/// convert sRGB values like those from color pickers with [`Color::srgb_to_linear`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color([f32; 4]);
+
+impl From<[f32; 4]> for Color {
+    fn from(a: [f32; 4]) -> Color {
+        Color(a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(c: Color) -> [f32; 4] {
+        c.0
+    }
+}
+
+impl From<[f32; 3]> for Color {
+    fn from(a: [f32; 3]) -> Color {
+        let b = [a[0], a[1], a[2], 1.0];
+        Color(b)
+    }
+}
+
+impl From<Color> for [f32; 3] {
+    fn from(c: Color) -> [f32; 3] {
+        [c.0[0], c.0[1], c.0[2]]
+    }
+}
+
+impl Color {
+    pub fn rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+        Color([
+            r as f32 / 255.,
+            g as f32 / 255.,
+            b as f32 / 255.,
+            a as f32 / 255.,
+        ])
+    }
+
+    pub fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color([r as f32 / 255., g as f32 / 255., b as f32 / 255., 1.0])
+    }
+
+    pub fn black() -> Color {
+        Color([0.0, 0.0, 0.0, 1.0])
+    }
+
+    pub fn grey() -> Color {
+        Color([0.25, 0.25, 0.25, 1.0])
+    }
+
+    pub fn white() -> Color {
+        Color([1.0, 1.0, 1.0, 1.0])
+    }
+
+    pub fn red() -> Color {
+        Color([1.0, 0.05, 0.05, 1.0])
+    }
+
+    pub fn transparent() -> Color {
+        Color([0.0, 0.0, 0.0, 0.0])
+    }
+
+    /// Parses `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`, with or without the `#`.
+    pub fn from_hex(hex: &str) -> Result<Color, ParseColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ParseColorError::InvalidDigit(c));
+        }
+        let nibble = |i: usize| u8::from_str_radix(&digits[i..i + 1], 16).unwrap();
+        let byte = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+        match digits.len() {
+            3 | 4 => {
+                let channel = |i: usize| nibble(i) * 17;
+                let a = if digits.len() == 4 { channel(3) } else { 255 };
+                Ok(Color::rgba(channel(0), channel(1), channel(2), a))
+            }
+            6 | 8 => {
+                let a = if digits.len() == 8 { byte(6) } else { 255 };
+                Ok(Color::rgba(byte(0), byte(2), byte(4), a))
+            }
+            len => Err(ParseColorError::InvalidLength(len)),
+        }
+    }
+
+    /// `#rrggbbaa`, rounded to the nearest byte.
+    pub fn to_hex(&self) -> String {
+        let [r, g, b, a] = self.0.map(to_byte);
+        format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+    }
+
+    /// `hue` in degrees, `saturation` and `value` from 0 to 1, opaque.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        from_hue(hue, chroma, value - chroma)
+    }
+
+    /// Hue in degrees, saturation and value from 0 to 1. Alpha is dropped.
+    pub fn to_hsv(&self) -> [f32; 3] {
+        let (hue, max, min) = self.hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        [hue, saturation, max]
+    }
+
+    /// `hue` in degrees, `saturation` and `lightness` from 0 to 1, opaque.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Hue in degrees, saturation and lightness from 0 to 1. Alpha is dropped.
+    pub fn to_hsl(&self) -> [f32; 3] {
+        let (hue, max, min) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        [hue, saturation, lightness]
+    }
+
+    /// Hue in degrees, and the largest and smallest of red, green and blue.
+    fn hue(&self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.0;
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        (hue, max, min)
+    }
+
+    /// Decodes sRGB encoded components, e.g. from a color picker or hex code, to the linear
+    /// values shading works with. Alpha is left as is.
+    pub fn srgb_to_linear(self) -> Color {
+        let [r, g, b, a] = self.0;
+        Color([srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a])
+    }
+
+    /// Encodes linear components as sRGB, e.g. to show them in a color picker. Alpha is left as
+    /// is.
+    pub fn linear_to_srgb(self) -> Color {
+        let [r, g, b, a] = self.0;
+        Color([linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a])
+    }
+
+    /// The color `t` of the way from this to `other`, alpha included. `t` isn't clamped.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        Color([0, 1, 2, 3].map(|i| self.0[i] + (other.0[i] - self.0[i]) * t))
+    }
+
+    /// Red, green and blue scaled by alpha, for `BlendMode::Premultiplied`.
+    pub fn premultiplied(self) -> Color {
+        let [r, g, b, a] = self.0;
+        Color([r * a, g * a, b * a, a])
+    }
+
+    /// Undoes [`Color::premultiplied`]. Fully transparent colors stay black.
+    pub fn unpremultiplied(self) -> Color {
+        let [r, g, b, a] = self.0;
+        if a == 0.0 {
+            return Color::transparent();
+        }
+        Color([r / a, g / a, b / a, a])
+    }
+
+    pub fn with_alpha(self, alpha: f32) -> Color {
+        let [r, g, b, _] = self.0;
+        Color([r, g, b, alpha])
+    }
+
+    pub fn alpha(&self) -> f32 {
+        self.0[3]
+    }
+
+    pub fn as_u8_arr(&self) -> [u8; 4] {
+        let mut arr = [0u8; 4];
+        arr[0] = (self.0[0] * 255.) as u8;
+        arr[1] = (self.0[1] * 255.) as u8;
+        arr[2] = (self.0[2] * 255.) as u8;
+        arr[3] = (self.0[3] * 255.) as u8;
+        arr
+    }
+
+    pub fn as_u8_vec(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        v.push((self.0[0] * 255.) as u8);
+        v.push((self.0[1] * 255.) as u8);
+        v.push((self.0[2] * 255.) as u8);
+        v.push((self.0[3] * 255.) as u8);
+        v
+    }
+}
+
+impl Default for Color {
+    fn default() -> Color {
+        Color::black()
+    }
+}
+
+/// Why [`Color::from_hex`] failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseColorError {
+    /// Not 3, 4, 6 or 8 digits.
+    InvalidLength(usize),
+    InvalidDigit(char),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidLength(len) => {
+                write!(f, "expected 3, 4, 6 or 8 hex digits, got {len}")
+            }
+            ParseColorError::InvalidDigit(c) => write!(f, "{c:?} is not a hex digit"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Decodes one sRGB encoded component.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes one linear component as sRGB.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_byte(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// An opaque color from its hue in degrees, chroma and the amount added to every component.
+fn from_hue(hue: f32, chroma: f32, m: f32) -> Color {
+    let h = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let [r, g, b] = match h as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    Color([r + m, g + m, b + m, 1.0])
+}
+
+/// Named colors, e.g. for debug drawing and prototypes.
+pub mod palette {
+    use super::Color;
+
+    pub const BLACK: Color = Color([0.0, 0.0, 0.0, 1.0]);
+    pub const WHITE: Color = Color([1.0, 1.0, 1.0, 1.0]);
+    pub const GREY: Color = Color([0.5, 0.5, 0.5, 1.0]);
+    pub const RED: Color = Color([1.0, 0.0, 0.0, 1.0]);
+    pub const GREEN: Color = Color([0.0, 1.0, 0.0, 1.0]);
+    pub const BLUE: Color = Color([0.0, 0.0, 1.0, 1.0]);
+    pub const YELLOW: Color = Color([1.0, 1.0, 0.0, 1.0]);
+    pub const CYAN: Color = Color([0.0, 1.0, 1.0, 1.0]);
+    pub const MAGENTA: Color = Color([1.0, 0.0, 1.0, 1.0]);
+    pub const ORANGE: Color = Color([1.0, 0.5, 0.0, 1.0]);
+    pub const PURPLE: Color = Color([0.5, 0.0, 1.0, 1.0]);
+    pub const CORNFLOWER_BLUE: Color = Color([0.392, 0.584, 0.929, 1.0]);
+    pub const TRANSPARENT: Color = Color([0.0, 0.0, 0.0, 0.0]);
+
+    /// Every named color, e.g. to list them in a picker.
+    pub const ALL: [(&str, Color); 13] = [
+        ("black", BLACK),
+        ("white", WHITE),
+        ("grey", GREY),
+        ("red", RED),
+        ("green", GREEN),
+        ("blue", BLUE),
+        ("yellow", YELLOW),
+        ("cyan", CYAN),
+        ("magenta", MAGENTA),
+        ("orange", ORANGE),
+        ("purple", PURPLE),
+        ("cornflower_blue", CORNFLOWER_BLUE),
+        ("transparent", TRANSPARENT),
+    ];
+
+    /// The color called `name`, ignoring case, e.g. from a config file.
+    pub fn by_name(name: &str) -> Option<Color> {
+        ALL.iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, color)| *color)
+    }
+}
@@ -0,0 +1,189 @@
+//! Moves long-lived resources into freshly allocated device-local memory, to undo the
+//! fragmentation left behind by streaming assets in and out during long sessions.
+//!
+//! Every call allocates the copies from a new allocator, so they end up packed next to each other
+//! instead of scattered over half-empty blocks, and the old blocks are released once nothing
+//! references the originals anymore. The copies run on the graphics queue, which owns the
+//! resources, and block until they are done, so compact during loading screens. Sources need
+//! `TRANSFER_SRC` usage, which textures uploaded through the context have; others are skipped.
+//!
+//! The resources are replaced in place once the copies are done, and left untouched if
+//! compaction fails. Image views, descriptor sets and command buffers created from the originals
+//! keep using the old memory until they are recreated.
+
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::CommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, CopyBufferInfo, CopyImageInfo, ImageCopy, RecordingCommandBuffer,
+    },
+    device::Queue,
+    image::{mip_level_extent, Image, ImageCreateInfo, ImageSubresourceLayers, ImageUsage},
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+    sync::GpuFuture,
+    DeviceSize,
+};
+
+use super::error::GraphicsError;
+
+/// What a compaction did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// Resources that were moved.
+    pub moved: usize,
+    /// Resources left where they were, because they can't be copied from.
+    pub skipped: usize,
+    /// Memory used by the moved resources.
+    pub bytes: DeviceSize,
+}
+
+/// Replaces `images` with compacted copies after `before_future`.
+pub fn compact_images(
+    queue: Arc<Queue>,
+    cb_allocator: Arc<dyn CommandBufferAllocator>,
+    before_future: Box<dyn GpuFuture>,
+    images: &mut [Arc<Image>],
+) -> Result<CompactionStats, GraphicsError> {
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(queue.device().clone()));
+    let mut cb = primary(&queue, cb_allocator)?;
+    let mut stats = CompactionStats::default();
+
+    let mut copies = Vec::new();
+    for (index, image) in images.iter().enumerate() {
+        if !image.usage().intersects(ImageUsage::TRANSFER_SRC) {
+            stats.skipped += 1;
+            continue;
+        }
+
+        let copy = Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: image.image_type(),
+                format: image.format(),
+                extent: image.extent(),
+                array_layers: image.array_layers(),
+                mip_levels: image.mip_levels(),
+                samples: image.samples(),
+                usage: image.usage() | ImageUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            device_local(),
+        )?;
+
+        let regions = (0..image.mip_levels())
+            .map(|mip_level| {
+                let subresource = ImageSubresourceLayers {
+                    mip_level,
+                    ..image.subresource_layers()
+                };
+                ImageCopy {
+                    src_subresource: subresource.clone(),
+                    dst_subresource: subresource,
+                    extent: mip_level_extent(image.extent(), mip_level).unwrap(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        cb.copy_image(CopyImageInfo {
+            regions,
+            ..CopyImageInfo::images(image.clone(), copy.clone())
+        })?;
+
+        stats.moved += 1;
+        stats.bytes += image
+            .memory_requirements()
+            .iter()
+            .map(|requirements| requirements.layout.size())
+            .sum::<DeviceSize>();
+        copies.push((index, copy));
+    }
+
+    submit_and_wait(queue, before_future, cb)?;
+    for (index, copy) in copies {
+        images[index] = copy;
+    }
+    Ok(stats)
+}
+
+/// Replaces `buffers` with compacted copies after `before_future`, e.g. mesh vertex and index
+/// buffers.
+pub fn compact_buffers<T>(
+    queue: Arc<Queue>,
+    cb_allocator: Arc<dyn CommandBufferAllocator>,
+    before_future: Box<dyn GpuFuture>,
+    buffers: &mut [Subbuffer<[T]>],
+) -> Result<CompactionStats, GraphicsError>
+where
+    T: BufferContents,
+{
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(queue.device().clone()));
+    let mut cb = primary(&queue, cb_allocator)?;
+    let mut stats = CompactionStats::default();
+
+    let mut copies = Vec::new();
+    for (index, buffer) in buffers.iter().enumerate() {
+        let usage = buffer.buffer().usage();
+        if !usage.intersects(BufferUsage::TRANSFER_SRC) {
+            stats.skipped += 1;
+            continue;
+        }
+
+        let copy = Buffer::new_slice::<T>(
+            memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: usage | BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            device_local(),
+            buffer.len(),
+        )?;
+        cb.copy_buffer(CopyBufferInfo::buffers(buffer.clone(), copy.clone()))?;
+
+        stats.moved += 1;
+        stats.bytes += buffer.size();
+        copies.push((index, copy));
+    }
+
+    submit_and_wait(queue, before_future, cb)?;
+    for (index, copy) in copies {
+        buffers[index] = copy;
+    }
+    Ok(stats)
+}
+
+fn device_local() -> AllocationCreateInfo {
+    AllocationCreateInfo {
+        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+        ..Default::default()
+    }
+}
+
+fn primary(
+    queue: &Queue,
+    cb_allocator: Arc<dyn CommandBufferAllocator>,
+) -> Result<RecordingCommandBuffer, GraphicsError> {
+    Ok(RecordingCommandBuffer::new(
+        cb_allocator,
+        queue.queue_family_index(),
+        CommandBufferLevel::Primary,
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )?)
+}
+
+fn submit_and_wait(
+    queue: Arc<Queue>,
+    before_future: Box<dyn GpuFuture>,
+    cb: RecordingCommandBuffer,
+) -> Result<(), GraphicsError> {
+    before_future
+        .then_execute(queue, cb.end()?)
+        .unwrap()
+        .then_signal_fence_and_flush()?
+        .wait(None)?;
+    Ok(())
+}
@@ -1,24 +1,26 @@
 use core::result::Result::Ok;
 use std::sync::Arc;
 
+use hecs::World;
 use vulkano::{
-    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
     command_buffer::{
         allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo},
-        CommandBufferBeginInfo, CommandBufferLevel, CommandBufferUsage, CopyBufferToImageInfo,
-        RecordingCommandBuffer,
+        BufferImageCopy, CommandBuffer, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, CopyBufferToImageInfo, RecordingCommandBuffer,
     },
     descriptor_set::allocator::StandardDescriptorSetAllocator,
-    device::{
-        physical::PhysicalDeviceType, Device, DeviceCreateInfo, DeviceExtensions, Queue,
-        QueueCreateInfo, QueueFlags,
+    device::{Device, DeviceCreateInfo, DeviceExtensions, Queue, QueueCreateInfo, QueueFlags},
+    format::{Format, NumericFormat},
+    image::{
+        view::{ImageView, ImageViewCreateInfo, ImageViewType},
+        Image, ImageCreateFlags, ImageCreateInfo, ImageSubresourceLayers, ImageType, ImageUsage,
     },
-    format::Format,
-    image::{Image, ImageCreateInfo, ImageType, ImageUsage},
     instance::{
         debug::{
             DebugUtilsMessageSeverity, DebugUtilsMessageType, DebugUtilsMessenger,
-            DebugUtilsMessengerCallback, DebugUtilsMessengerCreateInfo,
+            DebugUtilsMessengerCallback, DebugUtilsMessengerCallbackData,
+            DebugUtilsMessengerCreateInfo,
         },
         Instance, InstanceCreateFlags, InstanceCreateInfo, InstanceExtensions,
     },
@@ -27,45 +29,629 @@ use vulkano::{
         StandardMemoryAllocator,
     },
     swapchain::{
-        acquire_next_image, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo,
+        acquire_next_image, ColorSpace, Surface, Swapchain, SwapchainCreateInfo,
+        SwapchainPresentInfo,
     },
     sync::{self, GpuFuture},
-    DeviceSize, Validated, VulkanError, VulkanLibrary,
+    DeviceSize, VulkanLibrary,
 };
 use winit::{
-    dpi::PhysicalSize,
+    dpi::{LogicalPosition, PhysicalSize},
     event_loop::EventLoop,
-    window::{Window, WindowBuilder},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 
+pub use vulkano::swapchain::PresentMode;
+pub use winit::window::Icon;
+
 use super::{
-    pipelines::{basic::PSOBasic, texture::PSOTexture},
+    adapter::{self, DevicePreference},
+    compaction::{self, CompactionStats},
+    culling::CullingPass,
+    cursor::{self, Cursor, CursorGrab, CursorIcon, CursorImage},
+    debug,
+    error::GraphicsError,
+    memory::{MemoryReport, TextureTracker},
+    picking::PickingPass,
+    pipelines::{
+        basic::PSOBasic,
+        billboard::PSOBillboard,
+        foliage::PSOFoliage,
+        lit::PSOLit,
+        outline::PSOOutline,
+        points::PSOPoints,
+        texture::PSOTexture,
+        vat::{PSOVat, VertexAnimation},
+        water::{PSOWater, WaterTargets},
+        Projection2D,
+    },
+    post::{Fxaa, PostProcess},
+    profiler::GpuProfiler,
+    readback,
     render_pass::{
         basic::{RenderPassBasic, RenderPassBasicMSAA},
+        depth::RenderPassDepth,
         overlay::RenderPassOverlay,
     },
+    render_target::RenderTarget,
+    reset::GraphicsReset,
+    scene_cache::SceneCache,
+    texture::UploadOptions,
 };
+use crate::settings::Settings;
 
 pub struct Pipelines {
     pub basic: PSOBasic,
     pub texture: PSOTexture,
     pub overlay: PSOBasic,
+    pub lit: PSOLit,
+    /// [`PSOLit`] for `RenderPasses::depth_prepass`.
+    pub lit_prepass: PSOLit,
+    /// Outlines meshes drawn with `lit`.
+    pub outline: PSOOutline,
+    pub billboard: PSOBillboard,
+    pub points: PSOPoints,
+    pub vat: PSOVat,
+    pub foliage: PSOFoliage,
+    pub water: PSOWater,
+}
+
+impl Pipelines {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        render_passes: &RenderPasses,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let pipelines = Pipelines {
+            basic: PSOBasic::new(
+                gfx_queue.clone(),
+                render_passes.basic.draw_pass(),
+                cb_allocator.clone(),
+            ),
+            texture: PSOTexture::new(
+                gfx_queue.clone(),
+                render_passes.basic.draw_pass(),
+                cb_allocator.clone(),
+                ds_allocator.clone(),
+            ),
+            overlay: PSOBasic::new(
+                gfx_queue.clone(),
+                render_passes.overlay.draw_pass(),
+                cb_allocator.clone(),
+            ),
+            lit: PSOLit::new(
+                gfx_queue.clone(),
+                render_passes.depth.draw_pass(),
+                cb_allocator.clone(),
+                ds_allocator.clone(),
+            ),
+            lit_prepass: PSOLit::with_prepass(
+                gfx_queue.clone(),
+                render_passes.depth_prepass.prepass().unwrap(),
+                render_passes.depth_prepass.draw_pass(),
+                cb_allocator.clone(),
+                ds_allocator.clone(),
+            ),
+            outline: PSOOutline::new(
+                gfx_queue.clone(),
+                render_passes.depth.draw_pass(),
+                cb_allocator.clone(),
+            ),
+            billboard: PSOBillboard::new(
+                gfx_queue.clone(),
+                render_passes.depth.draw_pass(),
+                cb_allocator.clone(),
+                ds_allocator.clone(),
+            ),
+            points: PSOPoints::new(
+                gfx_queue.clone(),
+                render_passes.depth.draw_pass(),
+                cb_allocator.clone(),
+            ),
+            vat: PSOVat::new(
+                gfx_queue.clone(),
+                render_passes.depth.draw_pass(),
+                cb_allocator.clone(),
+                ds_allocator.clone(),
+            ),
+            foliage: PSOFoliage::new(
+                gfx_queue.clone(),
+                render_passes.depth.draw_pass(),
+                cb_allocator.clone(),
+                ds_allocator.clone(),
+            ),
+            water: PSOWater::new(
+                gfx_queue,
+                render_passes.depth.draw_pass(),
+                cb_allocator,
+                ds_allocator,
+            ),
+        };
+        pipelines.set_debug_names();
+        pipelines
+    }
+
+    fn set_debug_names(&self) {
+        debug::set_name(&*self.basic.pipeline, "PSOBasic");
+        debug::set_name(&*self.texture.pipeline, "PSOTexture");
+        debug::set_name(&*self.overlay.pipeline, "PSOBasic (overlay)");
+        debug::set_name(&*self.lit.pipeline, "PSOLit");
+        debug::set_name(&*self.lit_prepass.pipeline, "PSOLit (prepass)");
+        debug::set_name(&*self.outline.mark, "PSOOutline (mark)");
+        debug::set_name(&*self.outline.outline, "PSOOutline");
+        debug::set_name(&*self.billboard.pipeline, "PSOBillboard");
+        debug::set_name(&*self.points.pipeline, "PSOPoints");
+        debug::set_name(&*self.points.round, "PSOPoints (round)");
+        debug::set_name(&*self.vat.pipeline, "PSOVat");
+        debug::set_name(&*self.foliage.pipeline, "PSOFoliage");
+        debug::set_name(&*self.water.pipeline, "PSOWater");
+    }
 }
 
 pub struct RenderPasses {
     pub basic: RenderPassBasic,
     pub basic_msaa: RenderPassBasicMSAA,
     pub overlay: RenderPassOverlay,
+    pub depth: RenderPassDepth,
+    /// Like `depth`, with a depth prepass for scenes with heavy fragment shaders.
+    pub depth_prepass: RenderPassDepth,
+}
+
+impl RenderPasses {
+    pub fn new(gfx_queue: Arc<Queue>, format: Format) -> Self {
+        let render_passes = RenderPasses {
+            basic: RenderPassBasic::new(gfx_queue.clone(), format).unwrap(),
+            basic_msaa: RenderPassBasicMSAA::new(gfx_queue.clone(), format).unwrap(),
+            overlay: RenderPassOverlay::new(gfx_queue.clone(), format).unwrap(),
+            depth: RenderPassDepth::new(gfx_queue.clone(), format).unwrap(),
+            depth_prepass: RenderPassDepth::with_prepass(gfx_queue, format).unwrap(),
+        };
+        debug::set_name(&*render_passes.basic.render_pass, "basic");
+        debug::set_name(&*render_passes.basic_msaa.render_pass, "basic_msaa");
+        debug::set_name(&*render_passes.overlay.render_pass, "overlay");
+        debug::set_name(&*render_passes.depth.render_pass, "depth");
+        debug::set_name(&*render_passes.depth_prepass.render_pass, "depth_prepass");
+        render_passes
+    }
+}
+
+/// How [`GraphicsContext`] smooths edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AntiAliasing {
+    /// Render with `RenderPasses::basic` straight into the swapchain image.
+    None,
+    /// Render with `RenderPasses::basic_msaa`, which resolves into the swapchain image.
+    #[default]
+    Msaa,
+    /// Render with `RenderPasses::basic` into [`GraphicsContext::scene_image`] and filter it with
+    /// FXAA in [`GraphicsContext::resolve_anti_aliasing`]. Much cheaper in memory and bandwidth
+    /// than MSAA.
+    Fxaa,
+}
+
+/// `preferred` if the surface supports it, otherwise the closest supported mode. Fifo is always
+/// supported, so vsync is the last resort.
+fn negotiate_present_mode(preferred: PresentMode, supported: &[PresentMode]) -> PresentMode {
+    let fallbacks: &[PresentMode] = match preferred {
+        PresentMode::Immediate => &[PresentMode::Mailbox, PresentMode::FifoRelaxed],
+        PresentMode::Mailbox => &[PresentMode::Immediate],
+        _ => &[],
+    };
+    std::iter::once(&preferred)
+        .chain(fallbacks)
+        .find(|mode| supported.contains(mode))
+        .copied()
+        .unwrap_or(PresentMode::Fifo)
+}
+
+fn supported_present_modes(device: &Device, surface: &Surface) -> Vec<PresentMode> {
+    device
+        .physical_device()
+        .surface_present_modes(surface, Default::default())
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+/// Swapchain formats tried in order when the app doesn't ask for one. sRGB formats let the
+/// hardware encode the shaders' linear output.
+const PREFERRED_SURFACE_FORMATS: [Format; 2] = [Format::B8G8R8A8_SRGB, Format::R8G8B8A8_SRGB];
+
+/// `requested` if the surface supports it, then the first preferred sRGB format the surface
+/// supports, then whatever the driver lists first.
+fn choose_surface_format(requested: Option<Format>, supported: &[(Format, ColorSpace)]) -> Format {
+    requested
+        .into_iter()
+        .chain(PREFERRED_SURFACE_FORMATS)
+        .find(|format| {
+            supported
+                .iter()
+                .any(|(f, color_space)| f == format && *color_space == ColorSpace::SrgbNonLinear)
+        })
+        .unwrap_or(supported[0].0)
+}
+
+fn create_surface(
+    instance: &Arc<Instance>,
+    window: &Arc<Window>,
+) -> Result<Arc<Surface>, GraphicsError> {
+    Surface::from_window(instance.clone(), window.clone())
+        .map_err(|e| GraphicsError::Surface(e.to_string()))
+}
+
+/// Creates a logical device with one graphics queue that can present to `surface`.
+fn create_device(
+    instance: &Arc<Instance>,
+    surface: &Surface,
+    preference: Option<&DevicePreference>,
+) -> Result<(Arc<Device>, Arc<Queue>), GraphicsError> {
+    let device_extensions = DeviceExtensions {
+        khr_swapchain: true,
+        ..Default::default()
+    };
+
+    let (physical_device, queue_family_index) =
+        adapter::pick_physical_device(instance, preference, |p| {
+            if !p.supported_extensions().contains(&device_extensions) {
+                return None;
+            }
+            p.queue_family_properties()
+                .iter()
+                .enumerate()
+                .position(|(i, q)| {
+                    q.queue_flags.intersects(QueueFlags::GRAPHICS)
+                        && p.surface_support(i as u32, surface).unwrap_or(false)
+                })
+                .map(|i| i as u32)
+        })
+        .ok_or(GraphicsError::NoSuitableDevice)?;
+
+    tracing::info!(
+        target: "graphics",
+        name = %physical_device.properties().device_name,
+        device_type = ?physical_device.properties().device_type,
+        "using device"
+    );
+
+    let mut create_info = DeviceCreateInfo {
+        enabled_extensions: device_extensions,
+        queue_create_infos: vec![QueueCreateInfo {
+            queue_family_index,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    // Enabled where supported. `SamplerCache` leaves anisotropy off without it,
+    // `PSOLit::draw_indirect` needs `draw_indirect_first_instance` and issues a draw per command
+    // without `multi_draw_indirect`, `DebugView::Wireframe` needs `fill_mode_non_solid` and
+    // `PSOPoints` draws one pixel points without `large_points`.
+    let supported = physical_device.supported_features();
+    create_info.enabled_features.large_points = supported.large_points;
+    create_info.enabled_features.sampler_anisotropy = supported.sampler_anisotropy;
+    create_info.enabled_features.fill_mode_non_solid = supported.fill_mode_non_solid;
+    create_info.enabled_features.multi_draw_indirect = supported.multi_draw_indirect;
+    create_info.enabled_features.draw_indirect_first_instance =
+        supported.draw_indirect_first_instance;
+
+    let (device, mut queues) = Device::new(physical_device, create_info)?;
+
+    Ok((device, queues.next().unwrap()))
+}
+
+fn create_swapchain(
+    device: &Arc<Device>,
+    surface: &Arc<Surface>,
+    window: &Window,
+    surface_format: Option<Format>,
+    present_mode: PresentMode,
+) -> Result<(Arc<Swapchain>, Vec<Arc<Image>>), GraphicsError> {
+    let surface_capabilities = device
+        .physical_device()
+        .surface_capabilities(surface, Default::default())?;
+
+    let image_format = choose_surface_format(
+        surface_format,
+        &device
+            .physical_device()
+            .surface_formats(surface, Default::default())?,
+    );
+
+    let (swapchain, images) = Swapchain::new(
+        device.clone(),
+        surface.clone(),
+        SwapchainCreateInfo {
+            min_image_count: surface_capabilities.min_image_count.max(2),
+            image_format,
+            image_extent: window.inner_size().into(),
+            image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
+            present_mode: negotiate_present_mode(
+                present_mode,
+                &supported_present_modes(device, surface),
+            ),
+            composite_alpha: surface_capabilities
+                .supported_composite_alpha
+                .into_iter()
+                .next()
+                .unwrap(),
+            ..Default::default()
+        },
+    )?;
+    name_swapchain_images(&images);
+    Ok((swapchain, images))
+}
+
+fn name_swapchain_images(images: &[Arc<Image>]) {
+    for (i, image) in images.iter().enumerate() {
+        debug::set_name(&**image, &format!("swapchain_image_{i}"));
+    }
+}
+
+fn create_allocators(
+    device: &Arc<Device>,
+) -> (
+    Arc<StandardMemoryAllocator>,
+    Arc<StandardCommandBufferAllocator>,
+    Arc<StandardDescriptorSetAllocator>,
+) {
+    let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+    let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+        device.clone(),
+        StandardCommandBufferAllocatorCreateInfo {
+            secondary_buffer_count: 32,
+            ..Default::default()
+        },
+    ));
+
+    let ds_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+        device.clone(),
+        Default::default(),
+    ));
+
+    (memory_allocator, cb_allocator, ds_allocator)
+}
+
+const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
+
+/// How much Vulkan debugging [`GraphicsContext`] enables. Messages are traced under the
+/// `graphics::vulkan` target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugLevel {
+    Off,
+    /// Validation errors and warnings.
+    Errors,
+    /// Every validation, general and performance message, down to verbose.
+    Full,
+}
+
+impl Default for DebugLevel {
+    /// `Errors` in debug builds, `Off` in release builds.
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            DebugLevel::Errors
+        } else {
+            DebugLevel::Off
+        }
+    }
+}
+
+impl DebugLevel {
+    fn messages(self) -> (DebugUtilsMessageSeverity, DebugUtilsMessageType) {
+        match self {
+            DebugLevel::Off => (
+                DebugUtilsMessageSeverity::empty(),
+                DebugUtilsMessageType::empty(),
+            ),
+            DebugLevel::Errors => (
+                DebugUtilsMessageSeverity::ERROR | DebugUtilsMessageSeverity::WARNING,
+                DebugUtilsMessageType::GENERAL | DebugUtilsMessageType::VALIDATION,
+            ),
+            DebugLevel::Full => (
+                DebugUtilsMessageSeverity::ERROR
+                    | DebugUtilsMessageSeverity::WARNING
+                    | DebugUtilsMessageSeverity::INFO
+                    | DebugUtilsMessageSeverity::VERBOSE,
+                DebugUtilsMessageType::GENERAL
+                    | DebugUtilsMessageType::VALIDATION
+                    | DebugUtilsMessageType::PERFORMANCE,
+            ),
+        }
+    }
+}
+
+fn trace_debug_message(
+    severity: DebugUtilsMessageSeverity,
+    ty: DebugUtilsMessageType,
+    data: DebugUtilsMessengerCallbackData<'_>,
+) {
+    let ty = if ty.intersects(DebugUtilsMessageType::VALIDATION) {
+        "validation"
+    } else if ty.intersects(DebugUtilsMessageType::PERFORMANCE) {
+        "performance"
+    } else {
+        "general"
+    };
+    let id = data.message_id_name.unwrap_or("unknown");
+    let message = data.message;
+
+    if severity.intersects(DebugUtilsMessageSeverity::ERROR) {
+        tracing::error!(target: "graphics::vulkan", id, ty, "{message}");
+    } else if severity.intersects(DebugUtilsMessageSeverity::WARNING) {
+        tracing::warn!(target: "graphics::vulkan", id, ty, "{message}");
+    } else if severity.intersects(DebugUtilsMessageSeverity::INFO) {
+        tracing::info!(target: "graphics::vulkan", id, ty, "{message}");
+    } else {
+        tracing::trace!(target: "graphics::vulkan", id, ty, "{message}");
+    }
+}
+
+/// How the window covers the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// A window covering the monitor, keeping its video mode.
+    Borderless,
+    /// Exclusive fullscreen at the monitor's largest video mode.
+    Fullscreen,
+}
+
+impl WindowMode {
+    fn fullscreen<E>(self, event_loop: &EventLoop<E>) -> Option<Fullscreen> {
+        match self {
+            WindowMode::Windowed => None,
+            WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+            WindowMode::Fullscreen => {
+                let monitor = event_loop
+                    .primary_monitor()
+                    .or_else(|| event_loop.available_monitors().next())?;
+                let video_mode = monitor.video_modes().max_by_key(|mode| {
+                    let size = mode.size();
+                    (size.width * size.height, mode.refresh_rate_millihertz())
+                })?;
+                Some(Fullscreen::Exclusive(video_mode))
+            }
+        }
+    }
+}
+
+/// Configures the window a [`GraphicsContext`] is created with.
+///
+/// # Examples
+/// ```ignore
+/// let gfx = GraphicsContext::builder()
+///     .title("My game")
+///     .inner_size([1280, 720])
+///     .mode(WindowMode::Borderless)
+///     .cursor_grab(CursorGrab::Locked)
+///     .build(&event_loop)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct GraphicsContextBuilder {
+    title: String,
+    inner_size: [u32; 2],
+    resizable: bool,
+    mode: WindowMode,
+    decorations: bool,
+    icon: Option<Icon>,
+    cursor_grab: CursorGrab,
+    present_mode: PresentMode,
+    surface_format: Option<Format>,
+    device: Option<DevicePreference>,
+    debug: DebugLevel,
+}
+
+impl Default for GraphicsContextBuilder {
+    fn default() -> Self {
+        GraphicsContextBuilder {
+            title: "onion".to_owned(),
+            inner_size: [512, 512],
+            resizable: true,
+            mode: WindowMode::default(),
+            decorations: true,
+            icon: None,
+            cursor_grab: CursorGrab::default(),
+            present_mode: PresentMode::Fifo,
+            surface_format: None,
+            device: None,
+            debug: DebugLevel::default(),
+        }
+    }
+}
+
+impl GraphicsContextBuilder {
+    pub fn new() -> Self {
+        GraphicsContextBuilder::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Size of the window's contents in physical pixels, when windowed.
+    pub fn inner_size(mut self, inner_size: [u32; 2]) -> Self {
+        self.inner_size = inner_size;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn mode(mut self, mode: WindowMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Whether the window has a title bar and borders.
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    /// The icon in the title bar and task bar, on platforms that take it from the window.
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Applied once the window is created, see [`GraphicsContext::set_cursor_grab`].
+    pub fn cursor_grab(mut self, grab: CursorGrab) -> Self {
+        self.cursor_grab = grab;
+        self
+    }
+
+    /// The preferred present mode, see [`GraphicsContext::set_present_mode`].
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// The swapchain format to use instead of the preferred sRGB ones, if the surface supports
+    /// it. Check [`GraphicsContext::is_srgb`] for what was picked.
+    pub fn surface_format(mut self, format: Format) -> Self {
+        self.surface_format = Some(format);
+        self
+    }
+
+    /// The GPU to render with, by index in [`adapter::enumerate_adapters`] or by part of its
+    /// name. Ignored if no suitable device matches, and overridden by
+    /// [`adapter::DEVICE_ENV_VAR`].
+    pub fn prefer_device(mut self, device: impl Into<DevicePreference>) -> Self {
+        self.device = Some(device.into());
+        self
+    }
+
+    /// Enables the validation layers and debug messages, if they are installed.
+    pub fn debug(mut self, debug: DebugLevel) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Takes the window size and present mode from the player's settings.
+    pub fn settings(self, settings: &Settings) -> Self {
+        self.inner_size(settings.window_size)
+            .present_mode(settings.present_mode())
+    }
+
+    pub fn build<E>(self, event_loop: &EventLoop<E>) -> Result<GraphicsContext, GraphicsError> {
+        GraphicsContext::from_builder(self, event_loop)
+    }
 }
 
 pub struct GraphicsContext {
     _instance: Arc<Instance>,
-    _debug_callback: DebugUtilsMessenger,
+    _debug_callback: Option<DebugUtilsMessenger>,
     pub device: Arc<Device>,
     pub window: Arc<Window>,
     pub surface: Arc<Surface>,
     pub gfx_queue: Arc<Queue>,
-    pub swapchain: Arc<Swapchain>,
+    /// Only `None` while [`GraphicsContext::recover`] replaces it.
+    swapchain: Option<Arc<Swapchain>>,
     pub image_index: u32,
     pub final_images: Vec<Arc<Image>>,
     pub recreate_swapchain: bool,
@@ -74,240 +660,129 @@ pub struct GraphicsContext {
     pub render_passes: RenderPasses,
     pub memory_allocator: Arc<GenericMemoryAllocator<FreeListAllocator>>,
     pub cb_allocator: Arc<StandardCommandBufferAllocator>,
+    pub ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    anti_aliasing: AntiAliasing,
+    fxaa: Option<Fxaa>,
+    /// Requested, the swapchain may use a fallback.
+    present_mode: PresentMode,
+    /// Requested, the swapchain may use a fallback.
+    surface_format: Option<Format>,
+    device_preference: Option<DevicePreference>,
+    /// Not emitted into the world yet.
+    resets: Vec<GraphicsReset>,
+    /// A recovery that failed, retried by the next [`GraphicsContext::start_frame`].
+    pending_reset: Option<GraphicsReset>,
+    textures: TextureTracker,
 }
 
 impl GraphicsContext {
-    pub fn new<E>(event_loop: &EventLoop<E>) -> Self {
-        let library = VulkanLibrary::new().unwrap();
+    /// Creates a context with a default window, see [`GraphicsContextBuilder`] to configure it.
+    pub fn new<E>(event_loop: &EventLoop<E>) -> Result<Self, GraphicsError> {
+        GraphicsContextBuilder::default().build(event_loop)
+    }
 
-        println!("List of Vulkan debugging layers available to use:");
-        let layers = library.layer_properties().unwrap();
-        for l in layers {
-            println!("\t{}", l.name());
+    pub fn builder() -> GraphicsContextBuilder {
+        GraphicsContextBuilder::default()
+    }
+
+    fn from_builder<E>(
+        builder: GraphicsContextBuilder,
+        event_loop: &EventLoop<E>,
+    ) -> Result<Self, GraphicsError> {
+        let library = VulkanLibrary::new()?;
+
+        let validation_available = library
+            .layer_properties()?
+            .any(|l| l.name() == VALIDATION_LAYER);
+        let debug_utils_available = library.supported_extensions().ext_debug_utils;
+        let debug = builder.debug != DebugLevel::Off && debug_utils_available;
+        if builder.debug != DebugLevel::Off && !validation_available {
+            tracing::warn!(
+                target: "graphics",
+                "{VALIDATION_LAYER} is not installed, Vulkan usage won't be validated"
+            );
         }
 
-        let layers = vec!["VK_LAYER_KHRONOS_validation".to_owned()];
+        let enabled_layers = if debug && validation_available {
+            vec![VALIDATION_LAYER.to_owned()]
+        } else {
+            Vec::new()
+        };
 
         let _instance = Instance::new(
             library,
             InstanceCreateInfo {
                 flags: InstanceCreateFlags::ENUMERATE_PORTABILITY,
-                enabled_layers: layers,
+                enabled_layers,
                 enabled_extensions: InstanceExtensions {
-                    ext_debug_utils: true,
+                    ext_debug_utils: debug,
                     ..Surface::required_extensions(&event_loop).unwrap()
                 },
                 ..Default::default()
             },
-        )
-        .expect("failed to create Vulkan instance");
-
-        let _debug_callback = unsafe {
-            DebugUtilsMessenger::new(
-                _instance.clone(),
-                DebugUtilsMessengerCreateInfo {
-                    message_severity: DebugUtilsMessageSeverity::ERROR
-                        | DebugUtilsMessageSeverity::WARNING
-                        | DebugUtilsMessageSeverity::INFO
-                        | DebugUtilsMessageSeverity::VERBOSE,
-                    message_type: DebugUtilsMessageType::GENERAL
-                        | DebugUtilsMessageType::VALIDATION
-                        | DebugUtilsMessageType::PERFORMANCE,
-                    ..DebugUtilsMessengerCreateInfo::user_callback(
-                        DebugUtilsMessengerCallback::new(
-                            |message_severity, message_type, callback_data| {
-                                let severity = if message_severity
-                                    .intersects(DebugUtilsMessageSeverity::ERROR)
-                                {
-                                    "error"
-                                } else if message_severity
-                                    .intersects(DebugUtilsMessageSeverity::WARNING)
-                                {
-                                    "warning"
-                                } else if message_severity
-                                    .intersects(DebugUtilsMessageSeverity::INFO)
-                                {
-                                    "information"
-                                } else if message_severity
-                                    .intersects(DebugUtilsMessageSeverity::VERBOSE)
-                                {
-                                    "verbose"
-                                } else {
-                                    panic!("no-impl");
-                                };
-
-                                let ty = if message_type.intersects(DebugUtilsMessageType::GENERAL)
-                                {
-                                    "general"
-                                } else if message_type.intersects(DebugUtilsMessageType::VALIDATION)
-                                {
-                                    "validation"
-                                } else if message_type
-                                    .intersects(DebugUtilsMessageType::PERFORMANCE)
-                                {
-                                    "performance"
-                                } else {
-                                    panic!("no-impl");
-                                };
-
-                                println!(
-                                    "{} {} {}: {}",
-                                    callback_data.message_id_name.unwrap_or("unknown"),
-                                    ty,
-                                    severity,
-                                    callback_data.message
-                                );
-                            },
-                        ),
-                    )
-                },
-            )
-            .ok()
-        }
-        .unwrap();
+        )?;
+
+        let _debug_callback = if debug {
+            let (message_severity, message_type) = builder.debug.messages();
+            Some(unsafe {
+                DebugUtilsMessenger::new(
+                    _instance.clone(),
+                    DebugUtilsMessengerCreateInfo {
+                        message_severity,
+                        message_type,
+                        ..DebugUtilsMessengerCreateInfo::user_callback(
+                            DebugUtilsMessengerCallback::new(trace_debug_message),
+                        )
+                    },
+                )?
+            })
+        } else {
+            None
+        };
 
         let window = Arc::new(
             WindowBuilder::new()
-                .with_title("triangle test")
-                .with_inner_size(PhysicalSize::new(512.0, 512.0))
-                .build(&event_loop)
-                .unwrap(),
+                .with_title(builder.title)
+                .with_inner_size(PhysicalSize::new(
+                    builder.inner_size[0],
+                    builder.inner_size[1],
+                ))
+                .with_resizable(builder.resizable)
+                .with_decorations(builder.decorations)
+                .with_window_icon(builder.icon)
+                .with_fullscreen(builder.mode.fullscreen(event_loop))
+                .build(&event_loop)?,
         );
 
-        let surface = Surface::from_window(_instance.clone(), window.clone()).unwrap();
-
-        let device_extensions = DeviceExtensions {
-            khr_swapchain: true,
-            ..Default::default()
-        };
-
-        let (physical_device, queue_family_index) = _instance
-            .enumerate_physical_devices()
-            .unwrap()
-            .filter(|p| p.supported_extensions().contains(&device_extensions))
-            .filter_map(|p| {
-                p.queue_family_properties()
-                    .iter()
-                    .enumerate()
-                    .position(|(i, q)| {
-                        q.queue_flags.intersects(QueueFlags::GRAPHICS)
-                            && p.surface_support(i as u32, &surface).unwrap_or(false)
-                    })
-                    .map(|i| (p, i as u32))
-            })
-            .min_by_key(|(p, _)| match p.properties().device_type {
-                PhysicalDeviceType::DiscreteGpu => 0,
-                PhysicalDeviceType::IntegratedGpu => 1,
-                PhysicalDeviceType::VirtualGpu => 2,
-                PhysicalDeviceType::Cpu => 3,
-                PhysicalDeviceType::Other => 4,
-                _ => 5,
-            })
-            .expect("no suitable physical device found");
-
-        println!(
-            "Using device: {} (type: {:?})",
-            physical_device.properties().device_name,
-            physical_device.properties().device_type,
-        );
-
-        let (device, mut queues) = Device::new(
-            physical_device,
-            DeviceCreateInfo {
-                enabled_extensions: device_extensions,
-                queue_create_infos: vec![QueueCreateInfo {
-                    queue_family_index: queue_family_index,
-                    ..Default::default()
-                }],
-                ..Default::default()
-            },
-        )
-        .unwrap();
-
-        let gfx_queue = queues.next().unwrap();
-
-        let (swapchain, final_images) = {
-            let surface_capabilities = device
-                .physical_device()
-                .surface_capabilities(&surface, Default::default())
-                .unwrap();
-
-            let image_format = device
-                .physical_device()
-                .surface_formats(&surface, Default::default())
-                .unwrap()[0]
-                .0;
-
-            Swapchain::new(
-                device.clone(),
-                surface.clone(),
-                SwapchainCreateInfo {
-                    min_image_count: surface_capabilities.min_image_count.max(2),
-                    image_format,
-                    image_extent: window.inner_size().into(),
-                    image_usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_DST,
-                    composite_alpha: surface_capabilities
-                        .supported_composite_alpha
-                        .into_iter()
-                        .next()
-                        .unwrap(),
-                    ..Default::default()
-                },
-            )
-            .unwrap()
-        };
+        let surface = create_surface(&_instance, &window)?;
+        let (device, gfx_queue) = create_device(&_instance, &surface, builder.device.as_ref())?;
+        let (swapchain, final_images) = create_swapchain(
+            &device,
+            &surface,
+            &window,
+            builder.surface_format,
+            builder.present_mode,
+        )?;
 
         let previous_frame_end = Some(sync::now(device.clone()).boxed());
+        let (memory_allocator, cb_allocator, ds_allocator) = create_allocators(&device);
 
-        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
-
-        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
-            device.clone(),
-            StandardCommandBufferAllocatorCreateInfo {
-                secondary_buffer_count: 32,
-                ..Default::default()
-            },
-        ));
-
-        let ds_allocator = Arc::new(StandardDescriptorSetAllocator::new(
-            device.clone(),
-            Default::default(),
-        ));
-
-        let render_passes = RenderPasses {
-            basic: RenderPassBasic::new(gfx_queue.clone(), swapchain.image_format()).unwrap(),
-            basic_msaa: RenderPassBasicMSAA::new(gfx_queue.clone(), swapchain.image_format())
-                .unwrap(),
-            overlay: RenderPassOverlay::new(gfx_queue.clone(), swapchain.image_format()).unwrap(),
-        };
-
-        let pipelines = Pipelines {
-            basic: PSOBasic::new(
-                gfx_queue.clone(),
-                render_passes.basic.draw_pass(),
-                cb_allocator.clone(),
-            ),
-            texture: PSOTexture::new(
-                gfx_queue.clone(),
-                render_passes.basic.draw_pass(),
-                cb_allocator.clone(),
-                ds_allocator.clone(),
-            ),
-            overlay: PSOBasic::new(
-                gfx_queue.clone(),
-                render_passes.overlay.draw_pass(),
-                cb_allocator.clone(),
-            ),
-        };
+        let render_passes = RenderPasses::new(gfx_queue.clone(), swapchain.image_format());
+        let pipelines = Pipelines::new(
+            gfx_queue.clone(),
+            &render_passes,
+            cb_allocator.clone(),
+            ds_allocator.clone(),
+        );
 
-        Self {
+        let context = Self {
             _instance,
             _debug_callback,
             device,
             window,
             surface,
             gfx_queue,
-            swapchain,
+            swapchain: Some(swapchain),
             image_index: 0,
             final_images,
             recreate_swapchain: false,
@@ -316,24 +791,167 @@ impl GraphicsContext {
             pipelines,
             memory_allocator,
             cb_allocator,
+            ds_allocator,
+            anti_aliasing: AntiAliasing::default(),
+            fxaa: None,
+            present_mode: builder.present_mode,
+            surface_format: builder.surface_format,
+            device_preference: builder.device,
+            resets: Vec::new(),
+            pending_reset: None,
+            textures: TextureTracker::default(),
+        };
+
+        if builder.cursor_grab != CursorGrab::None {
+            context.set_cursor_grab(builder.cursor_grab);
+        }
+        Ok(context)
+    }
+
+    pub fn swapchain(&self) -> &Arc<Swapchain> {
+        self.swapchain.as_ref().unwrap()
+    }
+
+    pub fn anti_aliasing(&self) -> AntiAliasing {
+        self.anti_aliasing
+    }
+
+    /// Switches the anti-aliasing mode. The render pass used for the scene has to match, see
+    /// [`AntiAliasing`].
+    pub fn set_anti_aliasing(&mut self, mode: AntiAliasing) {
+        self.anti_aliasing = mode;
+        // Without a swapchain a recovery is pending, which sets the mode up again.
+        self.fxaa = match (mode, self.swapchain.as_ref()) {
+            (AntiAliasing::Fxaa, Some(swapchain)) => Some(Fxaa::new(
+                self.gfx_queue.clone(),
+                swapchain.image_format(),
+                self.window.inner_size().into(),
+                self.memory_allocator.clone(),
+                self.cb_allocator.clone(),
+                self.ds_allocator.clone(),
+            )),
+            _ => None,
+        };
+    }
+
+    /// Applies changed settings, e.g. from a [`SettingsChanged`](crate::settings::SettingsChanged)
+    /// event. The window size only applies while windowed.
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        if self.present_mode != settings.present_mode() {
+            self.set_present_mode(settings.present_mode());
+        }
+        if self.anti_aliasing != settings.anti_aliasing {
+            self.set_anti_aliasing(settings.anti_aliasing);
         }
+        if self.window.fullscreen().is_none() {
+            let [width, height] = settings.window_size;
+            let _ = self
+                .window
+                .request_inner_size(PhysicalSize::new(width, height));
+        }
+    }
+
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.window.set_cursor_icon(icon);
     }
 
-    pub fn start_frame(&mut self) -> Result<Box<dyn GpuFuture>, ()> {
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.window.set_cursor_visible(visible);
+    }
+
+    /// Confines or locks the cursor to the window. Returns false if the platform supports
+    /// neither.
+    pub fn set_cursor_grab(&self, grab: CursorGrab) -> bool {
+        cursor::set_grab(&self.window, grab)
+    }
+
+    /// Moves the cursor to `position`, in logical pixels from the top left of the window.
+    /// Returns false if the platform can't, e.g. on Wayland or the web.
+    pub fn set_cursor_position(&self, position: [f32; 2]) -> bool {
+        let [x, y] = position;
+        self.window
+            .set_cursor_position(LogicalPosition::new(x, y))
+            .is_ok()
+    }
+
+    /// Moves the cursor to the middle of the window, see
+    /// [`GraphicsContext::set_cursor_position`].
+    pub fn center_cursor(&self) -> bool {
+        let size = self
+            .window
+            .inner_size()
+            .to_logical::<f32>(self.window.scale_factor());
+        self.set_cursor_position([size.width / 2.0, size.height / 2.0])
+    }
+
+    /// Applies every part of `cursor`. A custom image hides the OS cursor, draw
+    /// [`Cursor::custom_image`] in its place. Returns false if the grab failed.
+    pub fn apply_cursor(&self, cursor: &Cursor) -> bool {
+        match &cursor.image {
+            CursorImage::Icon(icon) => {
+                self.set_cursor_icon(*icon);
+                self.set_cursor_visible(cursor.visible);
+            }
+            CursorImage::Custom { .. } => self.set_cursor_visible(false),
+        }
+        if cursor.center {
+            self.center_cursor();
+        }
+        self.set_cursor_grab(cursor.grab)
+    }
+
+    /// The image the scene should be rendered into this frame.
+    pub fn scene_image(&self) -> Arc<Image> {
+        match &self.fxaa {
+            Some(fxaa) => fxaa.scene(),
+            None => self.final_images[self.image_index as usize].clone(),
+        }
+    }
+
+    /// Applies post-render anti-aliasing to the scene, if the mode needs it, and returns the
+    /// future to draw overlays on or finish the frame with.
+    pub fn resolve_anti_aliasing(&self, before_future: Box<dyn GpuFuture>) -> Box<dyn GpuFuture> {
+        match &self.fxaa {
+            Some(fxaa) => fxaa.run(
+                before_future,
+                self.final_images[self.image_index as usize].clone(),
+            ),
+            None => before_future,
+        }
+    }
+
+    /// Acquires the next swapchain image and returns the future to render after.
+    ///
+    /// [`GraphicsError::SwapchainOutOfDate`] means the frame should be skipped, the swapchain is
+    /// recreated by the next call. After [`GraphicsError::DeviceLost`] and
+    /// [`GraphicsError::SurfaceLost`] the context has already recovered, skip the frame and handle
+    /// the [`GraphicsReset`].
+    ///
+    /// When recovering fails, e.g. because no device is available right now, its error is
+    /// returned instead and the recovery stays pending. Every following call retries it first and
+    /// returns its error until it succeeds, so keep skipping frames in the meantime.
+    pub fn start_frame(&mut self) -> Result<Box<dyn GpuFuture>, GraphicsError> {
+        if let Some(reset) = self.pending_reset {
+            self.recover(reset)?;
+        }
         if self.recreate_swapchain {
-            self.recreate_swapchain();
+            self.recreate_swapchain()?;
         }
 
         self.previous_frame_end.as_mut().unwrap().cleanup_finished();
 
         let (image_index, suboptimal, acquire_future) =
-            match acquire_next_image(self.swapchain.clone(), None).map_err(Validated::unwrap) {
+            match acquire_next_image(self.swapchain().clone(), None).map_err(GraphicsError::from) {
                 Ok(r) => r,
-                Err(VulkanError::OutOfDate) => {
-                    self.recreate_swapchain = true;
-                    return Err(());
+                Err(e) => {
+                    match e {
+                        GraphicsError::SwapchainOutOfDate => self.recreate_swapchain = true,
+                        GraphicsError::DeviceLost => self.recover(GraphicsReset::DeviceLost)?,
+                        GraphicsError::SurfaceLost => self.recover(GraphicsReset::SurfaceLost)?,
+                        _ => {}
+                    }
+                    return Err(e);
                 }
-                Err(e) => panic!("failed to acquire next image: {e}"),
             };
 
         if suboptimal {
@@ -347,48 +965,391 @@ impl GraphicsContext {
         Ok(future.boxed())
     }
 
-    pub fn finish_frame(&mut self, after_future: Box<dyn GpuFuture>) {
+    /// Presents the frame. An out of date swapchain isn't an error here, it is recreated by the
+    /// next [`GraphicsContext::start_frame`]. Neither is a lost device or surface, which are
+    /// recovered from right away and reported as a [`GraphicsReset`]. If that recovery fails its
+    /// error is returned, and `start_frame` retries it.
+    pub fn finish_frame(&mut self, after_future: Box<dyn GpuFuture>) -> Result<(), GraphicsError> {
         let future = after_future
             .then_swapchain_present(
                 self.gfx_queue.clone(),
                 SwapchainPresentInfo::swapchain_image_index(
-                    self.swapchain.clone(),
+                    self.swapchain().clone(),
                     self.image_index,
                 ),
             )
             .then_signal_fence_and_flush();
 
-        match future.map_err(Validated::unwrap) {
+        match future.map_err(GraphicsError::from) {
             Ok(future) => {
                 self.previous_frame_end = Some(future.boxed());
+                Ok(())
             }
-            Err(VulkanError::OutOfDate) => {
+            Err(GraphicsError::SwapchainOutOfDate) => {
                 self.recreate_swapchain = true;
                 self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+                Ok(())
             }
+            Err(GraphicsError::DeviceLost) => self.recover(GraphicsReset::DeviceLost),
+            Err(GraphicsError::SurfaceLost) => self.recover(GraphicsReset::SurfaceLost),
             Err(e) => {
-                panic!("failed to flush future: {e}");
+                self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+                Err(e)
             }
         }
     }
 
-    pub fn recreate_swapchain(&mut self) {
+    /// Chains a compute command buffer, e.g. from `PSOCompute::dispatch`, onto the frame so that
+    /// render passes started from the returned future see its results.
+    pub fn then_compute(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        command_buffer: Arc<CommandBuffer>,
+    ) -> Box<dyn GpuFuture> {
+        before_future
+            .then_execute(self.gfx_queue.clone(), command_buffer)
+            .unwrap()
+            .boxed()
+    }
+
+    /// Submits a compute command buffer outside of a frame. The next `start_frame` waits on it.
+    pub fn submit_compute(&mut self, command_buffer: Arc<CommandBuffer>) {
+        let before_future = self.previous_frame_end.take().unwrap();
+        self.previous_frame_end = Some(self.then_compute(before_future, command_buffer));
+    }
+
+    /// Whether the swapchain format is sRGB, so writes are encoded by the hardware. Otherwise
+    /// shaders that output linear color have to encode it themselves, e.g. with a gamma effect.
+    pub fn is_srgb(&self) -> bool {
+        self.swapchain().image_format().numeric_format_color() == Some(NumericFormat::SRGB)
+    }
+
+    /// The present mode the swapchain uses, which can be a fallback for the requested one.
+    pub fn present_mode(&self) -> PresentMode {
+        self.swapchain().create_info().present_mode
+    }
+
+    /// Requests a present mode, e.g. `Immediate` for uncapped benchmarks or `Mailbox` for low
+    /// latency without tearing. Modes the surface doesn't support fall back to the closest one
+    /// that is, down to `Fifo` vsync. Takes effect when the swapchain is recreated at the start
+    /// of the next frame.
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) {
+        self.present_mode = present_mode;
+        self.recreate_swapchain = true;
+    }
+
+    pub fn recreate_swapchain(&mut self) -> Result<(), GraphicsError> {
         let image_extent: [u32; 2] = self.window.inner_size().into();
 
-        let (new_swapchain, new_images) = match self.swapchain.recreate(SwapchainCreateInfo {
+        let present_mode = negotiate_present_mode(
+            self.present_mode,
+            &supported_present_modes(&self.device, &self.surface),
+        );
+        let (new_swapchain, new_images) = self.swapchain().recreate(SwapchainCreateInfo {
             image_extent,
-            ..self.swapchain.create_info()
-        }) {
-            Ok(r) => r,
-            Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
-        };
+            present_mode,
+            ..self.swapchain().create_info()
+        })?;
 
-        self.swapchain = new_swapchain;
+        name_swapchain_images(&new_images);
+        self.swapchain = Some(new_swapchain);
         self.final_images = new_images;
         self.recreate_swapchain = false;
+
+        if let Some(fxaa) = self.fxaa.as_mut() {
+            fxaa.resize(image_extent);
+        }
+        Ok(())
+    }
+
+    /// Rebuilds what `reset` invalidated and reports it. On failure the reset is left pending for
+    /// [`GraphicsContext::start_frame`], with a fresh frame future so the context stays usable.
+    fn recover(&mut self, reset: GraphicsReset) -> Result<(), GraphicsError> {
+        tracing::warn!(target: "graphics", ?reset, "recovering graphics context");
+
+        self.pending_reset = Some(reset);
+        if let Err(e) = self.rebuild(reset) {
+            tracing::warn!(target: "graphics", ?reset, "recovering graphics context failed: {e}");
+            self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+            return Err(e);
+        }
+        self.pending_reset = None;
+
+        self.resets.push(reset);
+        Ok(())
+    }
+
+    /// Rebuilds what `reset` invalidated: the surface and swapchain, and for a lost device also
+    /// the device, allocators, render passes and pipelines. The window and instance are kept.
+    fn rebuild(&mut self, reset: GraphicsReset) -> Result<(), GraphicsError> {
+        // Everything holding the old swapchain goes first, a window only takes one at a time.
+        self.previous_frame_end = None;
+        self.fxaa = None;
+        self.final_images.clear();
+        // Unknown when an earlier attempt already dropped the swapchain.
+        let old_format = self
+            .swapchain
+            .take()
+            .map(|swapchain| swapchain.image_format());
+
+        if reset == GraphicsReset::SurfaceLost {
+            self.surface = create_surface(&self._instance, &self.window)?;
+        }
+        if reset == GraphicsReset::DeviceLost {
+            let (device, gfx_queue) = create_device(
+                &self._instance,
+                &self.surface,
+                self.device_preference.as_ref(),
+            )?;
+            let (memory_allocator, cb_allocator, ds_allocator) = create_allocators(&device);
+            self.device = device;
+            self.gfx_queue = gfx_queue;
+            self.memory_allocator = memory_allocator;
+            self.cb_allocator = cb_allocator;
+            self.ds_allocator = ds_allocator;
+            self.textures.clear();
+        }
+
+        let (swapchain, final_images) = create_swapchain(
+            &self.device,
+            &self.surface,
+            &self.window,
+            self.surface_format,
+            self.present_mode,
+        )?;
+        let format = swapchain.image_format();
+        self.swapchain = Some(swapchain);
+        self.final_images = final_images;
+        self.recreate_swapchain = false;
+        self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+
+        if reset == GraphicsReset::DeviceLost || Some(format) != old_format {
+            self.render_passes = RenderPasses::new(self.gfx_queue.clone(), format);
+            self.pipelines = Pipelines::new(
+                self.gfx_queue.clone(),
+                &self.render_passes,
+                self.cb_allocator.clone(),
+                self.ds_allocator.clone(),
+            );
+        }
+        self.set_anti_aliasing(self.anti_aliasing);
+        Ok(())
+    }
+
+    /// Spawns a [`GraphicsReset`] into the world for every recovery since the last call. Call it
+    /// once per frame, before the app's update.
+    pub fn emit_resets(&mut self, world: &mut World) {
+        for reset in self.resets.drain(..) {
+            world.spawn((reset,));
+        }
+    }
+
+    /// The window's size in logical pixels, which stay the same physical size across screens of
+    /// different DPI.
+    pub fn logical_size(&self) -> [f32; 2] {
+        let size = self
+            .window
+            .inner_size()
+            .to_logical::<f32>(self.window.scale_factor());
+        [size.width, size.height]
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.window.scale_factor()
+    }
+
+    /// A projection for the 2D pipelines in logical pixels, with the origin at the top left of
+    /// the window. Set it again after the window resizes.
+    pub fn pixel_projection(&self) -> Projection2D {
+        Projection2D::pixels(self.logical_size())
+    }
+
+    /// Creates a render target in the swapchain format, so it can be used with the context's
+    /// render passes and pipelines.
+    pub fn create_render_target(&self, extent: [u32; 2]) -> RenderTarget {
+        RenderTarget::new(
+            self.memory_allocator.clone(),
+            self.swapchain().image_format(),
+            extent,
+        )
+    }
+
+    /// Creates the reflection and scene color targets for `PSOWater` in the swapchain format.
+    pub fn create_water_targets(&self, extent: [u32; 2]) -> WaterTargets {
+        WaterTargets::new(
+            self.memory_allocator.clone(),
+            self.swapchain().image_format(),
+            extent,
+        )
+    }
+
+    /// Creates a post-processing chain sized to the window that resolves into swapchain images.
+    pub fn create_post_process(&self) -> PostProcess {
+        PostProcess::new(
+            self.gfx_queue.clone(),
+            self.swapchain().image_format(),
+            self.window.inner_size().into(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            self.ds_allocator.clone(),
+        )
+    }
+
+    /// Creates a scene cache sized to the window in the swapchain format, so it can be rendered
+    /// with the context's render passes and composited into swapchain images.
+    pub fn create_scene_cache(&self) -> SceneCache {
+        SceneCache::new(
+            self.gfx_queue.clone(),
+            self.swapchain().image_format(),
+            self.window.inner_size().into(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+        )
+    }
+
+    /// Creates a pass culling indirect draws on the GPU, see [`CullingPass`].
+    pub fn create_culling_pass(&self) -> CullingPass {
+        CullingPass::new(
+            self.gfx_queue.clone(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            self.ds_allocator.clone(),
+        )
+    }
+
+    /// Creates a picking pass sized to the window, see [`PickingPass`].
+    pub fn create_picking_pass(&self) -> PickingPass {
+        PickingPass::new(
+            self.gfx_queue.clone(),
+            self.window.inner_size().into(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+        )
     }
 
-    pub fn upload_image(&mut self, buf: Subbuffer<[u8]>, extent: [u32; 3]) -> Arc<Image> {
+    /// Creates a profiler for up to `max_scopes` timed passes per frame, or `None` if the
+    /// graphics queue can't write timestamps.
+    pub fn create_gpu_profiler(&self, max_scopes: u32) -> Option<GpuProfiler> {
+        GpuProfiler::new(
+            self.gfx_queue.clone(),
+            self.cb_allocator.clone(),
+            max_scopes,
+        )
+    }
+
+    /// How much memory the allocator holds and how many uploaded textures are alive. Print it
+    /// every few seconds to spot leaks, e.g. allocations that keep growing.
+    pub fn memory_report(&self) -> MemoryReport {
+        MemoryReport::new(&self.memory_allocator, &self.textures)
+    }
+
+    /// Moves `textures` into compacted device-local memory and replaces them with the moved
+    /// copies, to counter fragmentation after lots of streaming. Blocks until the copies are done,
+    /// so call it during loading screens. See the [`compaction`] module for what to recreate
+    /// afterwards.
+    pub fn compact_textures(
+        &mut self,
+        textures: &mut [Arc<Image>],
+    ) -> Result<CompactionStats, GraphicsError> {
+        let before_future = self.previous_frame_end.take().unwrap();
+        let stats = compaction::compact_images(
+            self.gfx_queue.clone(),
+            self.cb_allocator.clone(),
+            before_future,
+            textures,
+        );
+        self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+
+        for texture in textures.iter() {
+            self.textures.track(texture);
+        }
+        stats
+    }
+
+    /// Like [`GraphicsContext::compact_textures`], for long-lived buffers like meshes. They need
+    /// `TRANSFER_SRC` usage to be moved.
+    pub fn compact_buffers<T>(
+        &mut self,
+        buffers: &mut [Subbuffer<[T]>],
+    ) -> Result<CompactionStats, GraphicsError>
+    where
+        T: BufferContents,
+    {
+        let before_future = self.previous_frame_end.take().unwrap();
+        let stats = compaction::compact_buffers(
+            self.gfx_queue.clone(),
+            self.cb_allocator.clone(),
+            before_future,
+            buffers,
+        );
+        self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+        stats
+    }
+
+    /// Copies `buffer` back to the host after all submitted work, e.g. to inspect a compute
+    /// kernel's output. Blocks until the GPU is idle, so keep it to debugging and tests.
+    pub fn read_buffer<T>(&mut self, buffer: Subbuffer<[T]>) -> Vec<T>
+    where
+        T: BufferContents + Clone,
+    {
+        let data = readback::read_buffer(
+            self.gfx_queue.clone(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            self.previous_frame_end.take().unwrap(),
+            buffer,
+        );
+        self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+        data
+    }
+
+    /// Like [`GraphicsContext::read_buffer`], for the `extent` pixels of `image` starting at
+    /// `offset`, tightly packed in the image's format.
+    pub fn read_image_region(
+        &mut self,
+        image: Arc<Image>,
+        offset: [u32; 2],
+        extent: [u32; 2],
+    ) -> Vec<u8> {
+        let pixels = readback::read_image_region(
+            self.gfx_queue.clone(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            self.previous_frame_end.take().unwrap(),
+            image,
+            offset,
+            extent,
+        );
+        self.previous_frame_end = Some(sync::now(self.device.clone()).boxed());
+        pixels
+    }
+
+    pub fn upload_image(
+        &mut self,
+        buf: Subbuffer<[u8]>,
+        extent: [u32; 3],
+    ) -> Result<Arc<Image>, GraphicsError> {
+        self.upload_image_with_format(buf, extent, Format::R8G8B8A8_SRGB)
+    }
+
+    fn upload_image_with_format(
+        &mut self,
+        buf: Subbuffer<[u8]>,
+        extent: [u32; 3],
+        format: Format,
+    ) -> Result<Arc<Image>, GraphicsError> {
+        self.upload_layers(buf, extent, 1, format, ImageCreateFlags::empty())
+    }
+
+    /// Uploads `array_layers` images of `extent`, stored one after the other in `buf`.
+    fn upload_layers(
+        &mut self,
+        buf: Subbuffer<[u8]>,
+        extent: [u32; 3],
+        array_layers: u32,
+        format: Format,
+        flags: ImageCreateFlags,
+    ) -> Result<Arc<Image>, GraphicsError> {
         let mut cb = RecordingCommandBuffer::new(
             self.cb_allocator.clone(),
             self.gfx_queue.queue_family_index(),
@@ -397,39 +1358,91 @@ impl GraphicsContext {
                 usage: CommandBufferUsage::OneTimeSubmit,
                 ..Default::default()
             },
-        )
-        .unwrap();
+        )?;
 
         let image = Image::new(
             self.memory_allocator.clone(),
             ImageCreateInfo {
+                flags,
                 image_type: ImageType::Dim2d,
-                format: Format::R8G8B8A8_SRGB,
+                format,
                 extent,
-                usage: ImageUsage::TRANSFER_DST | ImageUsage::SAMPLED,
+                array_layers,
+                // Source usage lets the texture be moved by `compact_textures`.
+                usage: ImageUsage::TRANSFER_DST | ImageUsage::TRANSFER_SRC | ImageUsage::SAMPLED,
                 ..Default::default()
             },
             AllocationCreateInfo::default(),
-        )
-        .unwrap();
+        )?;
 
-        cb.copy_buffer_to_image(CopyBufferToImageInfo::buffer_image(buf, image.clone()))
-            .unwrap();
+        let layer_size = buf.size() / array_layers as DeviceSize;
+        let regions = (0..array_layers)
+            .map(|layer| BufferImageCopy {
+                buffer_offset: layer as DeviceSize * layer_size,
+                image_subresource: ImageSubresourceLayers {
+                    array_layers: layer..layer + 1,
+                    ..image.subresource_layers()
+                },
+                image_extent: extent,
+                ..Default::default()
+            })
+            .collect();
+        cb.copy_buffer_to_image(CopyBufferToImageInfo {
+            regions,
+            ..CopyBufferToImageInfo::buffer_image(buf, image.clone())
+        })?;
 
-        self.previous_frame_end = Some(
-            cb.end()
-                .unwrap()
-                .execute(self.gfx_queue.clone())
-                .unwrap()
-                .boxed(),
-        );
+        self.previous_frame_end = Some(cb.end()?.execute(self.gfx_queue.clone())?.boxed());
+
+        self.textures.track(&image);
+        Ok(image)
+    }
 
-        image
+    pub fn upload_png(&mut self, image_bytes: &[u8]) -> Result<Arc<Image>, GraphicsError> {
+        self.upload_png_with(image_bytes, UploadOptions::default())
     }
 
-    pub fn upload_png(&mut self, image_bytes: &[u8]) -> Arc<Image> {
+    /// Like [`GraphicsContext::upload_png`], converting the pixels with `options` first.
+    pub fn upload_png_with(
+        &mut self,
+        image_bytes: &[u8],
+        options: UploadOptions,
+    ) -> Result<Arc<Image>, GraphicsError> {
+        self.upload_png_with_format(image_bytes, Format::R8G8B8A8_SRGB, options)
+    }
+
+    /// Uploads a tangent-space normal map PNG for `PSOLit`. Unlike color textures it is linear,
+    /// not sRGB.
+    pub fn upload_normal_map(
+        &mut self,
+        image_bytes: &[u8],
+    ) -> Result<Arc<ImageView>, GraphicsError> {
+        let image = self.upload_png_with_format(
+            image_bytes,
+            Format::R8G8B8A8_UNORM,
+            UploadOptions::default(),
+        )?;
+        Ok(ImageView::new_default(image)?)
+    }
+
+    /// Uploads a color grading LUT PNG for `PostProcess::add_color_grading`. Like normal maps it
+    /// is linear, not sRGB.
+    pub fn upload_lut(&mut self, image_bytes: &[u8]) -> Result<Arc<Image>, GraphicsError> {
+        self.upload_png_with_format(
+            image_bytes,
+            Format::R8G8B8A8_UNORM,
+            UploadOptions::default(),
+        )
+    }
+
+    fn upload_png_with_format(
+        &mut self,
+        image_bytes: &[u8],
+        format: Format,
+        options: UploadOptions,
+    ) -> Result<Arc<Image>, GraphicsError> {
         let decoder = png::Decoder::new(image_bytes);
-        let mut reader = decoder.read_info().unwrap();
+        let mut reader = decoder.read_info()?;
         let info = reader.info();
         let extent = [info.width, info.height, 1];
 
@@ -445,17 +1458,77 @@ impl GraphicsContext {
                 ..Default::default()
             },
             (info.width * info.height * 4) as DeviceSize,
-        )
-        .unwrap();
+        )?;
 
-        reader
-            .next_frame(&mut upload_buffer.write().unwrap())
-            .unwrap();
+        {
+            let mut pixels = upload_buffer.write()?;
+            reader.next_frame(&mut pixels)?;
+            options.apply(&mut pixels);
+        }
 
-        self.upload_image(upload_buffer, extent)
+        self.upload_image_with_format(upload_buffer, extent, format)
+    }
+
+    /// Like [`GraphicsContext::upload_rgba`], converting the pixels with `options` first.
+    pub fn upload_rgba_with(
+        &mut self,
+        mut buf: Vec<u8>,
+        extent: [u32; 3],
+        options: UploadOptions,
+    ) -> Result<Arc<Image>, GraphicsError> {
+        options.apply(&mut buf);
+        self.upload_rgba(buf, extent)
+    }
+
+    /// Uploads a cubemap, e.g. for a skybox, from six `size` by `size` RGBA images in the order
+    /// +X, -X, +Y, -Y, +Z, -Z.
+    pub fn upload_cubemap(
+        &mut self,
+        faces: [&[u8]; 6],
+        size: u32,
+    ) -> Result<Arc<ImageView>, GraphicsError> {
+        let image =
+            self.upload_rgba_layers(&faces, [size, size], ImageCreateFlags::CUBE_COMPATIBLE)?;
+        Ok(ImageView::new(
+            image.clone(),
+            ImageViewCreateInfo {
+                view_type: ImageViewType::Cube,
+                ..ImageViewCreateInfo::from_image(&image)
+            },
+        )?)
     }
 
-    pub fn upload_rgba(&mut self, buf: Vec<u8>, extent: [u32; 3]) -> Arc<Image> {
+    /// Uploads RGBA images of the same `extent` as the layers of one texture array, e.g. the
+    /// pages of an atlas, sampled with a `sampler2DArray`.
+    pub fn upload_texture_array(
+        &mut self,
+        layers: &[&[u8]],
+        extent: [u32; 2],
+    ) -> Result<Arc<ImageView>, GraphicsError> {
+        let image = self.upload_rgba_layers(layers, extent, ImageCreateFlags::empty())?;
+        Ok(ImageView::new(
+            image.clone(),
+            ImageViewCreateInfo {
+                view_type: ImageViewType::Dim2dArray,
+                ..ImageViewCreateInfo::from_image(&image)
+            },
+        )?)
+    }
+
+    fn upload_rgba_layers(
+        &mut self,
+        layers: &[&[u8]],
+        extent: [u32; 2],
+        flags: ImageCreateFlags,
+    ) -> Result<Arc<Image>, GraphicsError> {
+        let layer_size = (extent[0] * extent[1] * 4) as usize;
+        assert!(
+            !layers.is_empty() && layers.iter().all(|layer| layer.len() == layer_size),
+            "every layer must be {}x{} RGBA pixels",
+            extent[0],
+            extent[1]
+        );
+
         let upload_buffer = Buffer::from_iter(
             self.memory_allocator.clone(),
             BufferCreateInfo {
@@ -467,11 +1540,80 @@ impl GraphicsContext {
                     | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
                 ..Default::default()
             },
-            buf,
+            layers.iter().flat_map(|layer| layer.iter().copied()),
+        )?;
+
+        self.upload_layers(
+            upload_buffer,
+            [extent[0], extent[1], 1],
+            layers.len() as u32,
+            Format::R8G8B8A8_SRGB,
+            flags,
         )
-        .unwrap();
+    }
 
-        upload_buffer.write().unwrap();
+    /// Uploads a baked vertex animation for `PSOVat`. `offsets` holds the offset of each of
+    /// `vertex_count` vertices from its rest position, frame after frame.
+    pub fn upload_vertex_animation(
+        &mut self,
+        offsets: &[[f32; 3]],
+        vertex_count: u32,
+        frame_rate: f32,
+    ) -> Result<VertexAnimation, GraphicsError> {
+        assert!(
+            vertex_count > 0 && !offsets.is_empty() && offsets.len() % vertex_count as usize == 0,
+            "vertex animation must hold whole frames"
+        );
+        let frame_count = (offsets.len() / vertex_count as usize) as u32;
+
+        let texels = offsets
+            .iter()
+            .flat_map(|o| [o[0], o[1], o[2], 0.0])
+            .flat_map(f32::to_ne_bytes);
+        let upload_buffer = Buffer::from_iter(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            texels,
+        )?;
+
+        let image = self.upload_image_with_format(
+            upload_buffer,
+            [vertex_count, frame_count, 1],
+            Format::R32G32B32A32_SFLOAT,
+        )?;
+        Ok(VertexAnimation {
+            offsets: ImageView::new_default(image)?,
+            frame_count,
+            frame_rate,
+        })
+    }
+
+    pub fn upload_rgba(
+        &mut self,
+        buf: Vec<u8>,
+        extent: [u32; 3],
+    ) -> Result<Arc<Image>, GraphicsError> {
+        let upload_buffer = Buffer::from_iter(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_SRC,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            buf,
+        )?;
 
         self.upload_image(upload_buffer, extent)
     }
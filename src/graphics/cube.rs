@@ -1,4 +1,21 @@
+use std::sync::Arc;
+
 use glam::{Mat4, Vec3};
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::CommandBuffer,
+    image::view::ImageView,
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+};
+
+use super::camera::Camera;
+use super::frustum::Aabb;
+use super::light::LightsUniform;
+use super::pipelines::lit::{compute_tangents, PSOLit, SurfaceMaps, Transform, Vert};
+use super::pipelines::outline::{Outline, PSOOutline};
+use super::shadow::ShadowMap;
+use super::viewport::ViewportRegion;
+use super::Color;
 
 pub const TRIANGLE_LIST_UNIT_CUBE: [Vec3; 36] = [
     // Start Left
@@ -51,14 +68,183 @@ pub const TRIANGLE_LIST_UNIT_CUBE: [Vec3; 36] = [
     // End Top
 ];
 
+/// The outward normal of a face of the unit cube: the axis all three corners agree on.
+fn face_normal(triangle: &[Vec3]) -> Vec3 {
+    let mut normal = Vec3::ZERO;
+    for axis in 0..3 {
+        if triangle.iter().all(|v| v[axis] == triangle[0][axis]) {
+            normal[axis] = triangle[0][axis];
+        }
+    }
+    normal
+}
+
+/// Maps a corner onto the face's square, using the two axes the face spans.
+fn face_uv(corner: Vec3, normal: Vec3) -> [f32; 2] {
+    let (u, v) = if normal.x != 0.0 {
+        (corner.z, corner.y)
+    } else if normal.y != 0.0 {
+        (corner.x, corner.z)
+    } else {
+        (corner.x, corner.y)
+    };
+    [u * 0.5 + 0.5, v * 0.5 + 0.5]
+}
+
+/// Fraction of a lightmap cell left empty around each face, so bilinear filtering doesn't
+/// bleed light from the neighbouring face.
+const LIGHTMAP_PADDING: f32 = 0.05;
+
+/// Lays the six faces out in a 3x2 grid of the lightmap.
+fn face_lightmap_uv(face: usize, uv: [f32; 2]) -> [f32; 2] {
+    let cell = [(face % 3) as f32, (face / 3) as f32];
+    let inset = |t: f32| LIGHTMAP_PADDING + t * (1.0 - 2.0 * LIGHTMAP_PADDING);
+    [
+        (cell[0] + inset(uv[0])) / 3.0,
+        (cell[1] + inset(uv[1])) / 2.0,
+    ]
+}
+
 pub struct Cube {
     model: Mat4,
+    color: Color,
+    normal_map: Option<Arc<ImageView>>,
+    lightmap: Option<Arc<ImageView>>,
 }
 
 impl Cube {
     pub fn new() -> Self {
         let model = Mat4::IDENTITY;
-        Cube { model }
+        Cube {
+            model,
+            color: Color::white(),
+            normal_map: None,
+            lightmap: None,
+        }
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    /// Applies `normal_map` to every face, see `GraphicsContext::upload_normal_map`.
+    pub fn set_normal_map(&mut self, normal_map: Option<Arc<ImageView>>) {
+        self.normal_map = normal_map;
+    }
+
+    /// Lights the cube with baked light, e.g. from `lightmap::bake` over [`Cube::vertices`].
+    pub fn set_lightmap(&mut self, lightmap: Option<Arc<ImageView>>) {
+        self.lightmap = lightmap;
+    }
+
+    pub fn model(&self) -> Mat4 {
+        self.model
+    }
+
+    /// World space bounds, for frustum culling with a `Culler`.
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(Vec3::splat(-1.0), Vec3::ONE).transformed(self.model)
+    }
+
+    /// The 36 vertices of the cube with per-face normals, uvs and tangents, for `PSOLit`. Each
+    /// face covers the whole uv square, and its own cell of a 3x2 lightmap grid.
+    pub fn vertices(&self) -> Vec<Vert> {
+        let color: [f32; 3] = self.color.into();
+        let mut vertices: Vec<Vert> = TRIANGLE_LIST_UNIT_CUBE
+            .chunks(3)
+            .enumerate()
+            .flat_map(|(i, triangle)| {
+                let normal = face_normal(triangle);
+                triangle.iter().map(move |v| {
+                    let uv = face_uv(*v, normal);
+                    Vert {
+                        position: v.to_array(),
+                        normal: normal.to_array(),
+                        color,
+                        uv,
+                        tangent: [0.0; 4],
+                        lightmap_uv: face_lightmap_uv(i / 2, uv),
+                    }
+                })
+            })
+            .collect();
+        compute_tangents(&mut vertices);
+        vertices
+    }
+
+    /// Uploads [`Cube::vertices`], e.g. to render the cube into a `ShadowMap`.
+    pub fn vertex_buffer(&self, memory_allocator: Arc<dyn MemoryAllocator>) -> Subbuffer<[Vert]> {
+        Buffer::from_iter(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::VERTEX_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            self.vertices(),
+        )
+        .unwrap()
+    }
+
+    /// Builds a secondary command buffer that draws the lit cube as seen by `camera`.
+    pub fn draw(
+        &self,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        pipeline: &mut PSOLit,
+        viewport: impl Into<ViewportRegion>,
+        camera: &dyn Camera,
+        lights: Subbuffer<LightsUniform>,
+        shadow_map: Option<&ShadowMap>,
+    ) -> Arc<CommandBuffer> {
+        pipeline.draw(
+            viewport,
+            self.vertex_buffer(memory_allocator),
+            Transform::new(camera.mvp_mat(), self.model),
+            lights,
+            SurfaceMaps {
+                shadow_map,
+                normal_map: self.normal_map.clone(),
+                lightmap: self.lightmap.clone(),
+            },
+        )
+    }
+
+    /// Builds a secondary command buffer that draws the cube's depth in a prepass, see
+    /// [`PSOLit::draw_depth`].
+    pub fn draw_depth(
+        &self,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        pipeline: &PSOLit,
+        viewport: impl Into<ViewportRegion>,
+        camera: &dyn Camera,
+    ) -> Arc<CommandBuffer> {
+        pipeline.draw_depth(
+            viewport,
+            self.vertex_buffer(memory_allocator),
+            Transform::new(camera.mvp_mat(), self.model),
+        )
+    }
+
+    /// Builds a secondary command buffer that outlines the cube, see [`PSOOutline`]. Execute it
+    /// after [`Cube::draw`].
+    pub fn draw_outline(
+        &self,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        pipeline: &PSOOutline,
+        viewport: impl Into<ViewportRegion>,
+        camera: &dyn Camera,
+        outline: Outline,
+    ) -> Arc<CommandBuffer> {
+        pipeline.draw(
+            viewport,
+            self.vertex_buffer(memory_allocator),
+            camera.mvp_mat() * self.model,
+            outline,
+        )
     }
 
     pub fn translate_x(&mut self, amount: f32) {
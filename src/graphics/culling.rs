@@ -0,0 +1,347 @@
+//! Culling indirect draws on the GPU.
+//!
+//! A [`CullingPass`] tests the bounds of every draw in an [`IndirectDraws`] against the camera's
+//! frustum, and optionally a Hi-Z buffer, in a compute shader. The draws that pass are packed to
+//! the front of a new command buffer, the rest of it draws nothing, so the result can go
+//! straight to `PSOLit::draw_indirect`. Unlike [`Culler`](super::frustum::Culler) the CPU never
+//! sees the bounds or which draws survived.
+//!
+//! ```ignore
+//! let culling = gfx.create_culling_pass();
+//! let bounds = culling.bounds_buffer(props.iter().map(|p| p.aabb));
+//!
+//! let (future, culled) = culling.cull(future, &draws, bounds, view_proj, None);
+//! // ... in the draw pass
+//! let cb = gfx.pipelines.lit.draw_indirect(region, &batch, &culled.draws, lights, maps);
+//! draw_pass.execute(cb)?;
+//! ```
+
+use std::sync::Arc;
+
+use glam::Mat4;
+use vulkano::{
+    buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, DrawIndirectCommand, RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::{Device, Queue},
+    image::{
+        sampler::{
+            Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode,
+            LOD_CLAMP_NONE,
+        },
+        view::ImageView,
+    },
+    memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator},
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    shader::EntryPoint,
+    sync::GpuFuture,
+};
+
+use super::{
+    debug,
+    frustum::{Aabb, Frustum},
+    indirect::IndirectDraws,
+};
+
+/// Invocations per work group, matching the shader's `local_size_x`.
+const GROUP_SIZE: u32 = 64;
+
+/// World space bounds of one draw, padded to the shader's layout.
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+pub struct CullBounds {
+    pub min: [f32; 4],
+    pub max: [f32; 4],
+}
+
+impl From<Aabb> for CullBounds {
+    fn from(aabb: Aabb) -> Self {
+        CullBounds {
+            min: aabb.min.extend(0.0).into(),
+            max: aabb.max.extend(0.0).into(),
+        }
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct CullParams {
+    planes: [[f32; 4]; 6],
+    view_proj: [[f32; 4]; 4],
+    hi_z_size: [f32; 2],
+    draw_count: u32,
+    _pad: u32,
+}
+
+/// The output of [`CullingPass::cull`].
+pub struct CulledDraws {
+    /// The visible draws first, then empty ones. Shares the transforms of the culled draws.
+    pub draws: IndirectDraws,
+    /// How many draws are visible, e.g. to read back for stats.
+    pub visible: Subbuffer<u32>,
+}
+
+pub struct CullingPass {
+    gfx_queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    frustum_only: Arc<ComputePipeline>,
+    hi_z: Arc<ComputePipeline>,
+    hi_z_sampler: Arc<Sampler>,
+}
+
+impl CullingPass {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device().clone();
+        let frustum_only = build_pipeline(
+            &device,
+            cs::load(device.clone())
+                .unwrap()
+                .entry_point("main")
+                .unwrap(),
+        );
+        debug::set_name(&*frustum_only, "PSOCulling");
+        let hi_z = build_pipeline(
+            &device,
+            cs_hi_z::load(device.clone())
+                .unwrap()
+                .entry_point("main")
+                .unwrap(),
+        );
+        debug::set_name(&*hi_z, "PSOCullingHiZ");
+
+        let hi_z_sampler = Sampler::new(
+            device,
+            SamplerCreateInfo {
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Nearest,
+                mipmap_mode: SamplerMipmapMode::Nearest,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                lod: 0.0..=LOD_CLAMP_NONE,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        Self {
+            gfx_queue,
+            memory_allocator,
+            cb_allocator,
+            ds_allocator,
+            frustum_only,
+            hi_z,
+            hi_z_sampler,
+        }
+    }
+
+    /// Uploads the world space bounds of each draw, in the order of the draws.
+    pub fn bounds_buffer(&self, bounds: impl IntoIterator<Item = Aabb>) -> Subbuffer<[CullBounds]> {
+        Buffer::from_iter(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            bounds.into_iter().map(CullBounds::from),
+        )
+        .unwrap()
+    }
+
+    /// Culls `draws`, whose bounds are `bounds`, against `view_proj`. With `hi_z`, draws that
+    /// are hidden behind the depth in it are culled as well. It has to hold the farthest depth
+    /// of the texels below in each mip level, e.g. built from the previous frame's depth, with
+    /// the usual 0 near and 1 far.
+    pub fn cull(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        draws: &IndirectDraws,
+        bounds: Subbuffer<[CullBounds]>,
+        view_proj: Mat4,
+        hi_z: Option<Arc<ImageView>>,
+    ) -> (Box<dyn GpuFuture>, CulledDraws) {
+        assert_eq!(
+            bounds.len(),
+            draws.commands.len(),
+            "every draw needs bounds"
+        );
+        let draw_count = draws.len() as u32;
+        let hi_z_size = hi_z.as_ref().map_or([0.0; 2], |view| {
+            let [width, height, _] = view.image().extent();
+            [width as f32, height as f32]
+        });
+        let params = CullParams {
+            planes: Frustum::from_view_proj(view_proj)
+                .planes
+                .map(|plane| plane.into()),
+            view_proj: view_proj.to_cols_array_2d(),
+            hi_z_size,
+            draw_count,
+            _pad: 0,
+        };
+
+        let allocation = AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+            ..Default::default()
+        };
+        let params = Buffer::from_data(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::UNIFORM_BUFFER,
+                ..Default::default()
+            },
+            allocation.clone(),
+            params,
+        )
+        .unwrap();
+        let commands = Buffer::new_slice::<DrawIndirectCommand>(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::INDIRECT_BUFFER
+                    | BufferUsage::STORAGE_BUFFER
+                    | BufferUsage::TRANSFER_DST,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..Default::default()
+            },
+            draws.commands.len(),
+        )
+        .unwrap();
+        let visible = Buffer::from_data(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            allocation,
+            0u32,
+        )
+        .unwrap();
+
+        let pipeline = if hi_z.is_some() {
+            &self.hi_z
+        } else {
+            &self.frustum_only
+        };
+        let mut writes = vec![
+            WriteDescriptorSet::buffer(0, params),
+            WriteDescriptorSet::buffer(1, bounds),
+            WriteDescriptorSet::buffer(2, draws.commands.clone()),
+            WriteDescriptorSet::buffer(3, commands.clone()),
+            WriteDescriptorSet::buffer(4, visible.clone()),
+        ];
+        if let Some(view) = hi_z {
+            writes.push(WriteDescriptorSet::image_view_sampler(
+                5,
+                view,
+                self.hi_z_sampler.clone(),
+            ));
+        }
+        let set = DescriptorSet::new(
+            self.ds_allocator.clone(),
+            pipeline.layout().set_layouts()[0].clone(),
+            writes,
+            [],
+        )
+        .unwrap();
+
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        debug::begin_region(&mut cb, "culling");
+
+        // Draws past the visible ones stay zeroed, drawing nothing.
+        cb.fill_buffer(commands.clone().reinterpret::<[u32]>(), 0)
+            .unwrap()
+            .bind_pipeline_compute(pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                pipeline.layout().clone(),
+                0,
+                set,
+            )
+            .unwrap();
+        unsafe {
+            cb.dispatch([draw_count.div_ceil(GROUP_SIZE), 1, 1])
+                .unwrap();
+        }
+
+        debug::end_region(&mut cb);
+        let future = before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed();
+
+        let culled = CulledDraws {
+            draws: IndirectDraws {
+                commands,
+                transforms: draws.transforms.clone(),
+            },
+            visible,
+        };
+        (future, culled)
+    }
+}
+
+fn build_pipeline(device: &Arc<Device>, cs: EntryPoint) -> Arc<ComputePipeline> {
+    let stage = PipelineShaderStageCreateInfo::new(cs);
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    ComputePipeline::new(
+        device.clone(),
+        None,
+        ComputePipelineCreateInfo::stage_layout(stage, layout),
+    )
+    .unwrap()
+}
+
+mod cs {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "src/graphics/culling.comp",
+    }
+}
+
+mod cs_hi_z {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "src/graphics/culling.comp",
+        define: [("HI_Z", "1")],
+    }
+}
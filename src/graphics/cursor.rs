@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use hecs::World;
+use vulkano::image::Image;
+use winit::window::{CursorGrabMode, Window};
+
+pub use winit::window::CursorIcon;
+
+/// What the mouse cursor looks like.
+#[derive(Debug, Clone)]
+pub enum CursorImage {
+    /// One of the OS cursors.
+    Icon(CursorIcon),
+    /// An uploaded texture, with `hotspot` the pixel that points. winit can't hand images to the
+    /// OS yet, so the OS cursor is hidden and the app draws `image` at the cursor position.
+    Custom {
+        image: Arc<Image>,
+        hotspot: [u32; 2],
+    },
+}
+
+// Custom images are the same if they share the image.
+impl PartialEq for CursorImage {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (CursorImage::Icon(a), CursorImage::Icon(b)) => a == b,
+            (
+                CursorImage::Custom { image, hotspot },
+                CursorImage::Custom {
+                    image: other_image,
+                    hotspot: other_hotspot,
+                },
+            ) => Arc::ptr_eq(image, other_image) && hotspot == other_hotspot,
+            _ => false,
+        }
+    }
+}
+
+/// Whether the cursor can leave the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorGrab {
+    #[default]
+    None,
+    /// Kept inside the window.
+    Confined,
+    /// Kept in place, e.g. for mouse look. Only the motion is reported.
+    Locked,
+}
+
+impl From<CursorGrab> for CursorGrabMode {
+    fn from(grab: CursorGrab) -> CursorGrabMode {
+        match grab {
+            CursorGrab::None => CursorGrabMode::None,
+            CursorGrab::Confined => CursorGrabMode::Confined,
+            CursorGrab::Locked => CursorGrabMode::Locked,
+        }
+    }
+}
+
+/// The desired cursor state, kept on a single entity of the world. The
+/// [`Engine`](crate::engine::Engine) applies it when it changes, with a custom event loop call
+/// `GraphicsContext::apply_cursor`.
+///
+/// ```ignore
+/// // Mouse look while the right button is held.
+/// let look = Input::get(world).is_some_and(|i| i.button_pressed(MouseButton::Right));
+/// Cursor::set(world, if look { Cursor::mouse_look() } else { Cursor::default() });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub image: CursorImage,
+    pub visible: bool,
+    pub grab: CursorGrab,
+    /// Keeps the cursor in the middle of the window, where it can't run into the edges. The
+    /// engine moves it back after every update, for platforms that can't lock it.
+    pub center: bool,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Cursor {
+            image: CursorImage::Icon(CursorIcon::Default),
+            visible: true,
+            grab: CursorGrab::None,
+            center: false,
+        }
+    }
+}
+
+impl Cursor {
+    /// Hidden and locked in the middle of the window, for first person cameras. Read the motion
+    /// with `Input::mouse_motion`.
+    pub fn mouse_look() -> Self {
+        Cursor {
+            visible: false,
+            grab: CursorGrab::Locked,
+            center: true,
+            ..Default::default()
+        }
+    }
+
+    /// The cursor of `world`, if it has one.
+    pub fn get(world: &World) -> Option<Cursor> {
+        world
+            .query::<&Cursor>()
+            .iter()
+            .next()
+            .map(|(_, cursor)| cursor.clone())
+    }
+
+    pub fn set(world: &mut World, cursor: Cursor) {
+        if let Some((_, current)) = world.query_mut::<&mut Cursor>().into_iter().next() {
+            *current = cursor;
+            return;
+        }
+        world.spawn((cursor,));
+    }
+
+    /// The image the app has to draw itself, if the cursor is visible and custom.
+    pub fn custom_image(&self) -> Option<(Arc<Image>, [u32; 2])> {
+        match &self.image {
+            CursorImage::Custom { image, hotspot } if self.visible => {
+                Some((image.clone(), *hotspot))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Sets the grab mode, falling back to the other of confined and locked where the platform only
+/// supports one of them. Returns false if neither worked.
+pub(crate) fn set_grab(window: &Window, grab: CursorGrab) -> bool {
+    if window.set_cursor_grab(grab.into()).is_ok() {
+        return true;
+    }
+    let fallback = match grab {
+        CursorGrab::None => return false,
+        CursorGrab::Confined => CursorGrab::Locked,
+        CursorGrab::Locked => CursorGrab::Confined,
+    };
+    window.set_cursor_grab(fallback.into()).is_ok()
+}
@@ -0,0 +1,61 @@
+//! Names for Vulkan objects and command buffer regions, so frame captures in RenderDoc and
+//! validation messages say which pipeline or image they are about.
+//!
+//! Uses `VK_EXT_debug_utils`, which the context enables along with validation, see
+//! `DebugLevel`. Without it names are dropped, but regions are still recorded as crash labels for
+//! `crash::install_panic_hook`.
+
+use vulkano::{
+    command_buffer::RecordingCommandBuffer,
+    device::{Device, DeviceOwned},
+    instance::debug::DebugUtilsLabel,
+    VulkanObject,
+};
+
+use crate::crash;
+
+/// Whether names and regions reach the driver.
+pub fn is_enabled(device: &Device) -> bool {
+    device.instance().enabled_extensions().ext_debug_utils
+}
+
+/// Names `object` in captures and validation messages, e.g. `"PSOBasic"` or
+/// `"msaa_intermediary"`.
+pub fn set_name<T>(object: &T, name: &str)
+where
+    T: VulkanObject + DeviceOwned,
+{
+    let device = object.device();
+    if !is_enabled(device) {
+        return;
+    }
+    if let Err(e) = device.set_debug_utils_object_name(object, Some(name)) {
+        tracing::debug!(target: "graphics::vulkan", "failed to name {name}: {e}");
+    }
+}
+
+/// Starts a region called `name` in `cb`, which captures show the commands until
+/// [`end_region`] grouped under. Every region has to be ended in the same command buffer.
+pub fn begin_region(cb: &mut RecordingCommandBuffer, name: &str) {
+    crash::push_label(name);
+    if !is_enabled(cb.device()) {
+        return;
+    }
+    unsafe {
+        cb.begin_debug_utils_label(DebugUtilsLabel {
+            label_name: name.to_owned(),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+}
+
+/// Ends the region last started with [`begin_region`] in `cb`.
+pub fn end_region(cb: &mut RecordingCommandBuffer) {
+    if !is_enabled(cb.device()) {
+        return;
+    }
+    unsafe {
+        cb.end_debug_utils_label().unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+//! Debug views of the 3D pipeline, to look at geometry instead of shading.
+//!
+//! The current view is a component on a single entity of the world, so tools and systems can
+//! read and change it. Pass it to `PSOLit::set_debug_view` before drawing:
+//!
+//! ```ignore
+//! // In a system, F3 cycles through the views.
+//! debug_view::cycle_on_key(world, KeyCode::F3);
+//!
+//! // Before drawing the frame.
+//! gfx.pipelines.lit.set_debug_view(DebugView::get(&app.world));
+//! ```
+
+use hecs::World;
+use winit::keyboard::KeyCode;
+
+use crate::input::Input;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DebugView {
+    /// Normal shading.
+    #[default]
+    Shaded,
+    /// Triangle edges only. Needs the device's `fill_mode_non_solid` feature.
+    Wireframe,
+    /// World space normals as colors.
+    Normals,
+    /// Texture coordinates as colors, with a checkerboard.
+    Uvs,
+    /// Distance to the camera, white up close.
+    Depth,
+}
+
+impl DebugView {
+    pub const ALL: [DebugView; 5] = [
+        DebugView::Shaded,
+        DebugView::Wireframe,
+        DebugView::Normals,
+        DebugView::Uvs,
+        DebugView::Depth,
+    ];
+
+    /// The view after this one, back to `Shaded` after the last.
+    pub fn next(self) -> DebugView {
+        let index = DebugView::ALL.iter().position(|&v| v == self).unwrap();
+        DebugView::ALL[(index + 1) % DebugView::ALL.len()]
+    }
+
+    /// The view set in `world`, `Shaded` if none was.
+    pub fn get(world: &World) -> DebugView {
+        world
+            .query::<&DebugView>()
+            .iter()
+            .next()
+            .map_or(DebugView::Shaded, |(_, view)| *view)
+    }
+
+    pub fn set(world: &mut World, view: DebugView) {
+        if let Some((_, current)) = world.query_mut::<&mut DebugView>().into_iter().next() {
+            *current = view;
+            return;
+        }
+        world.spawn((view,));
+    }
+}
+
+/// Moves to the next view when `key` was just pressed, see [`Input`]. Returns whether it did.
+pub fn cycle_on_key(world: &mut World, key: KeyCode) -> bool {
+    if !Input::get(world).is_some_and(|input| input.just_pressed(key)) {
+        return false;
+    }
+    let view = DebugView::get(world).next();
+    DebugView::set(world, view);
+    true
+}
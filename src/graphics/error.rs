@@ -0,0 +1,99 @@
+use vulkano::{
+    buffer::{AllocateBufferError, HostAccessError},
+    image::AllocateImageError,
+    LoadingError, Validated, ValidationError, VulkanError,
+};
+
+/// Why a [`GraphicsContext`](super::context::GraphicsContext) call failed.
+#[derive(Debug, thiserror::Error)]
+pub enum GraphicsError {
+    /// Vulkan couldn't be loaded, e.g. no driver is installed.
+    #[error("failed to load Vulkan: {0}")]
+    Loading(#[from] LoadingError),
+    #[error("failed to create the window: {0}")]
+    Window(#[from] winit::error::OsError),
+    #[error("event loop failed: {0}")]
+    EventLoop(#[from] winit::error::EventLoopError),
+    #[error("failed to create the window surface: {0}")]
+    Surface(String),
+    /// No device can render to the window.
+    #[error("no suitable physical device found")]
+    NoSuitableDevice,
+    /// The swapchain no longer matches the window, e.g. after a resize. It is recreated at the
+    /// start of the next frame, so skip this one.
+    #[error("the swapchain is out of date")]
+    SwapchainOutOfDate,
+    /// The window's surface is gone, e.g. the window was closed. The context recreates it, see
+    /// `GraphicsReset`.
+    #[error("the surface was lost")]
+    SurfaceLost,
+    /// The GPU crashed or its driver was reset. The context recreates the device, see
+    /// `GraphicsReset`.
+    #[error("the device was lost")]
+    DeviceLost,
+    #[error("out of memory: {0}")]
+    OutOfMemory(VulkanError),
+    /// A buffer or image couldn't be allocated or mapped.
+    #[error("allocation failed: {0}")]
+    Allocation(Box<dyn std::error::Error + Send + Sync>),
+    /// Vulkan was used wrong, which is a bug in the engine or the app.
+    #[error("{0}")]
+    Validation(Box<ValidationError>),
+    #[error("Vulkan call failed: {0}")]
+    Vulkan(VulkanError),
+    #[error("failed to decode image: {0}")]
+    Decode(#[from] png::DecodingError),
+}
+
+impl From<VulkanError> for GraphicsError {
+    fn from(e: VulkanError) -> Self {
+        match e {
+            VulkanError::OutOfDate => GraphicsError::SwapchainOutOfDate,
+            VulkanError::SurfaceLost => GraphicsError::SurfaceLost,
+            VulkanError::DeviceLost => GraphicsError::DeviceLost,
+            VulkanError::OutOfHostMemory | VulkanError::OutOfDeviceMemory => {
+                GraphicsError::OutOfMemory(e)
+            }
+            e => GraphicsError::Vulkan(e),
+        }
+    }
+}
+
+impl From<Box<ValidationError>> for GraphicsError {
+    fn from(e: Box<ValidationError>) -> Self {
+        GraphicsError::Validation(e)
+    }
+}
+
+impl From<Validated<VulkanError>> for GraphicsError {
+    fn from(e: Validated<VulkanError>) -> Self {
+        match e {
+            Validated::Error(e) => e.into(),
+            Validated::ValidationError(e) => e.into(),
+        }
+    }
+}
+
+impl From<Validated<AllocateBufferError>> for GraphicsError {
+    fn from(e: Validated<AllocateBufferError>) -> Self {
+        match e {
+            Validated::Error(e) => GraphicsError::Allocation(Box::new(e)),
+            Validated::ValidationError(e) => e.into(),
+        }
+    }
+}
+
+impl From<Validated<AllocateImageError>> for GraphicsError {
+    fn from(e: Validated<AllocateImageError>) -> Self {
+        match e {
+            Validated::Error(e) => GraphicsError::Allocation(Box::new(e)),
+            Validated::ValidationError(e) => e.into(),
+        }
+    }
+}
+
+impl From<HostAccessError> for GraphicsError {
+    fn from(e: HostAccessError) -> Self {
+        GraphicsError::Allocation(Box::new(e))
+    }
+}
@@ -0,0 +1,144 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use super::camera::Camera;
+
+/// An axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// The smallest box around `points`.
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        let mut aabb = Aabb::new(Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY));
+        for p in points {
+            aabb.min = aabb.min.min(p);
+            aabb.max = aabb.max.max(p);
+        }
+        aabb
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// The box around this one after moving it by `matrix`, e.g. a mesh's model matrix.
+    pub fn transformed(&self, matrix: Mat4) -> Aabb {
+        let center = matrix.transform_point3(self.center());
+        let half = self.half_extents();
+        // Each world axis extent is the sum of the rotated and scaled local extents.
+        let extents = Vec3::new(
+            matrix.row(0).truncate().abs().dot(half),
+            matrix.row(1).truncate().abs().dot(half),
+            matrix.row(2).truncate().abs().dot(half),
+        );
+        Aabb::new(center - extents, center + extents)
+    }
+}
+
+/// The six planes bounding what a camera sees. Each plane's xyz is the inward normal and w the
+/// distance, so a point is inside when `plane.dot(point.extend(1.0)) >= 0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of a view-projection with Vulkan's 0..1 depth range.
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|p| {
+            let length = p.truncate().length();
+            if length > 0.0 {
+                p / length
+            } else {
+                p
+            }
+        });
+        Frustum { planes }
+    }
+
+    pub fn from_camera(camera: &dyn Camera) -> Self {
+        Frustum::from_view_proj(camera.mvp_mat())
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes.iter().all(|p| p.dot(point.extend(1.0)) >= 0.0)
+    }
+
+    /// False only if `aabb` is certainly outside. Boxes near the frustum's corners may be
+    /// reported as intersecting even though they aren't, which costs a draw but is never wrong.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center().extend(1.0);
+        let half = aabb.half_extents();
+        self.planes
+            .iter()
+            .all(|p| p.dot(center) + p.truncate().abs().dot(half) >= 0.0)
+    }
+}
+
+/// How many meshes a [`Culler`] let through and how many it skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub drawn: u32,
+    pub culled: u32,
+}
+
+/// Frustum culls the meshes submitted for one frame and counts them.
+///
+/// # Examples
+/// ```ignore
+/// let mut culler = Culler::new(&camera);
+/// for cube in cubes.iter() {
+///     if culler.is_visible(&cube.aabb()) {
+///         draw_pass.execute(cube.draw(...));
+///     }
+/// }
+/// println!("{:?}", culler.stats());
+/// ```
+pub struct Culler {
+    frustum: Frustum,
+    stats: CullStats,
+}
+
+impl Culler {
+    pub fn new(camera: &dyn Camera) -> Self {
+        Culler::from_frustum(Frustum::from_camera(camera))
+    }
+
+    pub fn from_frustum(frustum: Frustum) -> Self {
+        Culler {
+            frustum,
+            stats: CullStats::default(),
+        }
+    }
+
+    pub fn frustum(&self) -> &Frustum {
+        &self.frustum
+    }
+
+    /// Whether a mesh with world space bounds `aabb` should be drawn.
+    pub fn is_visible(&mut self, aabb: &Aabb) -> bool {
+        let visible = self.frustum.intersects_aabb(aabb);
+        if visible {
+            self.stats.drawn += 1;
+        } else {
+            self.stats.culled += 1;
+        }
+        visible
+    }
+
+    pub fn stats(&self) -> CullStats {
+        self.stats
+    }
+}
@@ -0,0 +1,153 @@
+//! Indirect drawing: many meshes drawn with one `vkCmdDrawIndirect` from a buffer of draw
+//! commands, instead of a command buffer per mesh.
+//!
+//! Pack the meshes into a [`MeshBatch`] once, then each frame build the [`IndirectDraws`] of the
+//! instances to draw. The command buffer is a storage buffer too, so a compute pass can cull
+//! draws by zeroing their `instance_count` before drawing.
+//!
+//! ```ignore
+//! let batch = MeshBatch::new(gfx.memory_allocator.clone(), [rock, tree, crate_mesh]);
+//! let draws = IndirectDraws::new(
+//!     gfx.memory_allocator.clone(),
+//!     &batch,
+//!     props.iter().map(|p| (p.mesh, Transform::new(view_proj, p.model))),
+//! );
+//! draw_pass.execute(gfx.pipelines.lit.draw_indirect(region, &batch, &draws, lights, maps))?;
+//! ```
+
+use std::{ops::Range, sync::Arc};
+
+use vulkano::{
+    buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::DrawIndirectCommand,
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+};
+
+use super::pipelines::lit::{Transform, Vert};
+
+/// Meshes packed into one vertex buffer, so a single indirect draw can reach all of them.
+pub struct MeshBatch {
+    vertices: Subbuffer<[Vert]>,
+    ranges: Vec<Range<u32>>,
+}
+
+impl MeshBatch {
+    /// Uploads `meshes`, triangle lists like those given to `PSOLit::draw`. Meshes are referred
+    /// to by their index in `meshes`.
+    pub fn new(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        meshes: impl IntoIterator<Item = Vec<Vert>>,
+    ) -> Self {
+        let mut ranges = Vec::new();
+        let mut vertices = Vec::new();
+        for mesh in meshes {
+            let start = vertices.len() as u32;
+            vertices.extend(mesh);
+            ranges.push(start..vertices.len() as u32);
+        }
+        assert!(!vertices.is_empty(), "a mesh batch needs vertices");
+
+        let vertices = Buffer::from_iter(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::VERTEX_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            vertices,
+        )
+        .unwrap();
+
+        MeshBatch { vertices, ranges }
+    }
+
+    pub fn vertices(&self) -> Subbuffer<[Vert]> {
+        self.vertices.clone()
+    }
+
+    /// The vertices of mesh `mesh` in [`MeshBatch::vertices`].
+    pub fn range(&self, mesh: usize) -> Range<u32> {
+        self.ranges[mesh].clone()
+    }
+
+    pub fn mesh_count(&self) -> usize {
+        self.ranges.len()
+    }
+}
+
+/// One draw command and transform per instance of a mesh in a [`MeshBatch`]. Draw `i` has
+/// `first_instance` `i`, which the shader uses to find `transforms[i]`.
+pub struct IndirectDraws {
+    pub commands: Subbuffer<[DrawIndirectCommand]>,
+    pub transforms: Subbuffer<[Transform]>,
+}
+
+impl IndirectDraws {
+    /// Draws the given meshes of `batch`, each with its transform. Panics if there are none,
+    /// buffers can't be empty.
+    pub fn new(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        batch: &MeshBatch,
+        instances: impl IntoIterator<Item = (usize, Transform)>,
+    ) -> Self {
+        let (commands, transforms): (Vec<_>, Vec<_>) = instances
+            .into_iter()
+            .enumerate()
+            .map(|(i, (mesh, transform))| {
+                let range = batch.range(mesh);
+                let command = DrawIndirectCommand {
+                    vertex_count: range.end - range.start,
+                    instance_count: 1,
+                    first_vertex: range.start,
+                    first_instance: i as u32,
+                };
+                (command, transform)
+            })
+            .unzip();
+        assert!(!commands.is_empty(), "indirect draws need an instance");
+
+        let allocation = AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+            ..Default::default()
+        };
+        let commands = Buffer::from_iter(
+            memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: BufferUsage::INDIRECT_BUFFER | BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            allocation.clone(),
+            commands,
+        )
+        .unwrap();
+        let transforms = Buffer::from_iter(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::STORAGE_BUFFER,
+                ..Default::default()
+            },
+            allocation,
+            transforms,
+        )
+        .unwrap();
+
+        IndirectDraws {
+            commands,
+            transforms,
+        }
+    }
+
+    /// How many draws there are, culled ones included.
+    pub fn len(&self) -> usize {
+        self.commands.len() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.len() == 0
+    }
+}
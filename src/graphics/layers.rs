@@ -0,0 +1,89 @@
+use hecs::{Entity, Query, World};
+
+use super::camera::Camera;
+
+/// A bitmask of the 32 render layers an entity or camera belongs to.
+///
+/// Attach it as a component to entities and set it on cameras; an entity is only drawn by a camera
+/// when their layers intersect. Entities without the component are on layer 0, as are cameras
+/// that never had their layers changed. Render systems pick the entities to draw with
+/// [`for_each_visible`].
+///
+/// # Examples
+/// ```ignore
+/// const MINIMAP: u8 = 1;
+///
+/// world.spawn((icon, RenderLayers::layer(MINIMAP)));
+/// minimap_camera.set_render_layers(RenderLayers::layer(MINIMAP));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderLayers(u32);
+
+impl RenderLayers {
+    pub const ALL: RenderLayers = RenderLayers(u32::MAX);
+    pub const NONE: RenderLayers = RenderLayers(0);
+
+    /// Only the given layer, which must be below 32.
+    pub fn layer(layer: u8) -> Self {
+        RenderLayers::NONE.with(layer)
+    }
+
+    pub fn with(self, layer: u8) -> Self {
+        assert!(layer < 32, "render layer {layer} out of range");
+        RenderLayers(self.0 | (1 << layer))
+    }
+
+    pub fn without(self, layer: u8) -> Self {
+        assert!(layer < 32, "render layer {layer} out of range");
+        RenderLayers(self.0 & !(1 << layer))
+    }
+
+    pub fn contains(&self, layer: u8) -> bool {
+        layer < 32 && self.0 & (1 << layer) != 0
+    }
+
+    pub fn intersects(&self, other: &RenderLayers) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        RenderLayers::layer(0)
+    }
+}
+
+/// Whether an entity with the given (optional) layers component is drawn by a camera with
+/// `camera_layers`. Used by [`for_each_visible`].
+pub fn is_visible(entity_layers: Option<&RenderLayers>, camera_layers: RenderLayers) -> bool {
+    entity_layers
+        .copied()
+        .unwrap_or_default()
+        .intersects(&camera_layers)
+}
+
+/// Runs `f` for every entity matching `Q` that `camera` draws, for render systems building their
+/// draws.
+///
+/// ```ignore
+/// let mut draws = Vec::new();
+/// layers::for_each_visible::<&Cube>(world, &camera, |_, cube| {
+///     draws.push(cube.draw(allocator.clone(), &mut pipeline, viewport, &camera, lights, None));
+/// });
+/// ```
+pub fn for_each_visible<Q: Query>(
+    world: &World,
+    camera: &dyn Camera,
+    mut f: impl FnMut(Entity, Q::Item<'_>),
+) {
+    let camera_layers = camera.render_layers();
+    for (entity, (item, layers)) in world.query::<(Q, Option<&RenderLayers>)>().iter() {
+        if is_visible(layers, camera_layers) {
+            f(entity, item);
+        }
+    }
+}
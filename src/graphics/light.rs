@@ -0,0 +1,168 @@
+use glam::{Mat4, Vec3};
+use hecs::World;
+use vulkano::buffer::BufferContents;
+
+use super::{shadow::ShadowMap, Color};
+
+/// Most lights `PSOLit` shades with, extra lights are ignored.
+pub const MAX_LIGHTS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightKind {
+    /// Infinitely far away, shining along `direction`, e.g. the sun.
+    Directional { direction: Vec3 },
+    /// Shines in every direction from `position`, fading out towards `range`.
+    Point { position: Vec3, range: f32 },
+}
+
+/// Shadow map settings of a directional light, see `ShadowMap`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowSettings {
+    /// Width and height of the shadow map in texels.
+    pub resolution: u32,
+    /// Subtracted from the depth before comparing, trades shadow acne for peter-panning.
+    pub bias: f32,
+    /// Half the width of the square area around the focus point that casts shadows.
+    pub extent: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        ShadowSettings {
+            resolution: 2048,
+            bias: 0.005,
+            extent: 20.0,
+        }
+    }
+}
+
+/// A light source. Spawn it as a component and gather the lights of a world with
+/// [`Light::collect`] each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: Color,
+    pub intensity: f32,
+    /// Only directional lights cast shadows.
+    pub shadows: Option<ShadowSettings>,
+}
+
+impl Light {
+    pub fn directional(direction: Vec3, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Directional { direction },
+            color,
+            intensity,
+            shadows: None,
+        }
+    }
+
+    pub fn point(position: Vec3, range: f32, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Point { position, range },
+            color,
+            intensity,
+            shadows: None,
+        }
+    }
+
+    pub fn with_shadows(mut self, settings: ShadowSettings) -> Self {
+        self.shadows = Some(settings);
+        self
+    }
+
+    /// Every `Light` component in the world.
+    pub fn collect(world: &World) -> Vec<Light> {
+        world
+            .query::<&Light>()
+            .iter()
+            .map(|(_, light)| *light)
+            .collect()
+    }
+}
+
+/// A light as the shaders see it. Everything is a vec4 to keep the std140 layout trivial.
+#[derive(BufferContents, Clone, Copy, Default)]
+#[repr(C)]
+pub struct GpuLight {
+    /// xyz is the direction for directional lights and the position for point lights, w is 0 and
+    /// 1 respectively.
+    pub position: [f32; 4],
+    /// rgb is the color premultiplied by the intensity.
+    pub color: [f32; 4],
+    /// x is the range of point lights.
+    pub params: [f32; 4],
+}
+
+impl From<&Light> for GpuLight {
+    fn from(light: &Light) -> Self {
+        let [r, g, b]: [f32; 3] = light.color.into();
+        let color = [
+            r * light.intensity,
+            g * light.intensity,
+            b * light.intensity,
+            1.0,
+        ];
+
+        match light.kind {
+            LightKind::Directional { direction } => {
+                let d = direction.normalize_or_zero();
+                GpuLight {
+                    position: [d.x, d.y, d.z, 0.0],
+                    color,
+                    params: [0.0; 4],
+                }
+            }
+            LightKind::Point { position, range } => GpuLight {
+                position: [position.x, position.y, position.z, 1.0],
+                color,
+                params: [range, 0.0, 0.0, 0.0],
+            },
+        }
+    }
+}
+
+/// The uniform block bound by `PSOLit`.
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+pub struct LightsUniform {
+    pub camera_position: [f32; 4],
+    pub ambient: [f32; 4],
+    /// x is the number of lights in use.
+    pub count: [u32; 4],
+    /// Light space view-projection of the shadow map.
+    pub shadow_matrix: [[f32; 4]; 4],
+    /// x is the depth bias, y is 1 when a shadow map is used, z is the size of a shadow map
+    /// texel and w is the index of the light casting the shadow.
+    pub shadow_params: [f32; 4],
+    pub lights: [GpuLight; MAX_LIGHTS],
+}
+
+impl LightsUniform {
+    pub fn new(camera_position: Vec3, ambient: Color, lights: &[Light]) -> Self {
+        let mut uniform = LightsUniform {
+            camera_position: [camera_position.x, camera_position.y, camera_position.z, 1.0],
+            ambient: ambient.into(),
+            count: [lights.len().min(MAX_LIGHTS) as u32, 0, 0, 0],
+            shadow_matrix: Mat4::IDENTITY.to_cols_array_2d(),
+            shadow_params: [0.0; 4],
+            lights: [GpuLight::default(); MAX_LIGHTS],
+        };
+        for (dst, light) in uniform.lights.iter_mut().zip(lights) {
+            *dst = light.into();
+        }
+        uniform
+    }
+
+    /// Darkens the light at `light_index` where `shadow_map` is occluded. Only has an effect
+    /// when the shadow map is also passed to `PSOLit::draw`.
+    pub fn set_shadow(&mut self, light_index: usize, shadow_map: &ShadowMap) {
+        self.shadow_matrix = shadow_map.view_proj().to_cols_array_2d();
+        self.shadow_params = [
+            shadow_map.settings().bias,
+            1.0,
+            1.0 / shadow_map.settings().resolution as f32,
+            light_index as f32,
+        ];
+    }
+}
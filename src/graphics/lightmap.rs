@@ -0,0 +1,203 @@
+//! An offline CPU baker for the lightmaps `PSOLit` samples.
+//!
+//! Baking is slow, every texel casts a shadow ray against every triangle, so bake static scenes
+//! once, e.g. in a build step, and ship the result as a PNG.
+//!
+//! # Examples
+//! ```ignore
+//! let vertices = cube.vertices();
+//! let pixels = lightmap::bake(&vertices, cube.model(), &lights, &BakeSettings::default());
+//! let size = BakeSettings::default().resolution;
+//! let image = gfx.upload_rgba(pixels, [size, size, 1])?;
+//! cube.set_lightmap(Some(ImageView::new_default(image).unwrap()));
+//! ```
+
+use glam::{Mat4, Vec2, Vec3};
+
+use super::{
+    light::{Light, LightKind},
+    pipelines::lit::Vert,
+    texture::linear_to_srgb,
+    Color,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BakeSettings {
+    /// Width and height of the lightmap in texels.
+    pub resolution: u32,
+    /// Baked into every texel, replacing the ambient light of `LightsUniform`.
+    pub ambient: Color,
+    /// Whether the scene's triangles block light.
+    pub shadows: bool,
+    /// How far shadow rays start off the surface, against self-shadowing.
+    pub bias: f32,
+}
+
+impl Default for BakeSettings {
+    fn default() -> Self {
+        BakeSettings {
+            resolution: 128,
+            ambient: Color::grey(),
+            shadows: true,
+            bias: 0.001,
+        }
+    }
+}
+
+/// Bakes the diffuse light `lights` cast onto a triangle list with model matrix `model`, laid
+/// out by the vertices' `lightmap_uv`. Returns sRGB encoded RGBA8 pixels, ready for
+/// `GraphicsContext::upload_rgba`.
+pub fn bake(vertices: &[Vert], model: Mat4, lights: &[Light], settings: &BakeSettings) -> Vec<u8> {
+    let size = settings.resolution as usize;
+    let triangles: Vec<[Vec3; 3]> = vertices
+        .chunks_exact(3)
+        .map(|t| [0, 1, 2].map(|i| model.transform_point3(Vec3::from(t[i].position))))
+        .collect();
+    let ambient = Vec3::from(<[f32; 3]>::from(settings.ambient));
+
+    let mut texels: Vec<Option<Vec3>> = vec![None; size * size];
+    for (triangle, world) in vertices.chunks_exact(3).zip(&triangles) {
+        let uv = [0, 1, 2].map(|i| Vec2::from(triangle[i].lightmap_uv) * size as f32);
+        let normals = [0, 1, 2].map(|i| model.transform_vector3(Vec3::from(triangle[i].normal)));
+
+        let min = uv[0].min(uv[1]).min(uv[2]).floor().max(Vec2::ZERO);
+        let max = uv[0]
+            .max(uv[1])
+            .max(uv[2])
+            .ceil()
+            .min(Vec2::splat(size as f32));
+        for y in min.y as usize..max.y as usize {
+            for x in min.x as usize..max.x as usize {
+                let center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let Some(w) = barycentric(center, uv) else {
+                    continue;
+                };
+                let position = world[0] * w.x + world[1] * w.y + world[2] * w.z;
+                let normal =
+                    (normals[0] * w.x + normals[1] * w.y + normals[2] * w.z).normalize_or_zero();
+                let mut light = ambient;
+                for l in lights {
+                    light += direct_light(l, position, normal, &triangles, settings);
+                }
+                texels[y * size + x] = Some(light);
+            }
+        }
+    }
+
+    dilate(&mut texels, size);
+
+    texels
+        .iter()
+        .flat_map(|texel| {
+            let c = texel.unwrap_or(Vec3::ZERO);
+            [
+                linear_to_srgb(c.x),
+                linear_to_srgb(c.y),
+                linear_to_srgb(c.z),
+                255,
+            ]
+        })
+        .collect()
+}
+
+/// Diffuse light from `light` at a surface point, with the same falloff as `PSOLit`.
+fn direct_light(
+    light: &Light,
+    position: Vec3,
+    normal: Vec3,
+    triangles: &[[Vec3; 3]],
+    settings: &BakeSettings,
+) -> Vec3 {
+    let (to_light, distance, attenuation) = match light.kind {
+        LightKind::Directional { direction } => (-direction.normalize_or_zero(), f32::MAX, 1.0),
+        LightKind::Point {
+            position: light_position,
+            range,
+        } => {
+            let delta = light_position - position;
+            let distance = delta.length();
+            let falloff = (1.0 - distance / range.max(0.0001)).clamp(0.0, 1.0);
+            (delta / distance.max(0.0001), distance, falloff * falloff)
+        }
+    };
+
+    let diffuse = normal.dot(to_light).max(0.0) * attenuation;
+    if diffuse <= 0.0 {
+        return Vec3::ZERO;
+    }
+    if settings.shadows {
+        let origin = position + normal * settings.bias;
+        if triangles
+            .iter()
+            .any(|t| ray_hits_triangle(origin, to_light, t).is_some_and(|d| d < distance))
+        {
+            return Vec3::ZERO;
+        }
+    }
+    Vec3::from(<[f32; 3]>::from(light.color)) * light.intensity * diffuse
+}
+
+/// The barycentric weights of `p` in the 2D triangle `t`, if it is inside.
+fn barycentric(p: Vec2, t: [Vec2; 3]) -> Option<Vec3> {
+    let (e0, e1, e2) = (t[1] - t[0], t[2] - t[0], p - t[0]);
+    let det = e0.perp_dot(e1);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let v = e2.perp_dot(e1) / det;
+    let w = e0.perp_dot(e2) / det;
+    let u = 1.0 - v - w;
+    (u >= 0.0 && v >= 0.0 && w >= 0.0).then_some(Vec3::new(u, v, w))
+}
+
+/// Möller-Trumbore, the distance along `direction` to where the ray hits `t`.
+fn ray_hits_triangle(origin: Vec3, direction: Vec3, t: &[Vec3; 3]) -> Option<f32> {
+    let (e1, e2) = (t[1] - t[0], t[2] - t[0]);
+    let p = direction.cross(e2);
+    let det = e1.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    let inv = 1.0 / det;
+    let s = origin - t[0];
+    let u = s.dot(p) * inv;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = direction.dot(q) * inv;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = e2.dot(q) * inv;
+    (distance > 0.0).then_some(distance)
+}
+
+/// Fills empty texels bordering baked ones with their average, so filtering at the edge of a
+/// triangle doesn't blend in black.
+fn dilate(texels: &mut [Option<Vec3>], size: usize) {
+    let source = texels.to_vec();
+    for y in 0..size {
+        for x in 0..size {
+            if source[y * size + x].is_some() {
+                continue;
+            }
+            let mut sum = Vec3::ZERO;
+            let mut count = 0.0;
+            for (nx, ny) in [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ] {
+                if let Some(Some(c)) = (nx < size && ny < size).then(|| source[ny * size + nx]) {
+                    sum += c;
+                    count += 1.0;
+                }
+            }
+            if count > 0.0 {
+                texels[y * size + x] = Some(sum / count);
+            }
+        }
+    }
+}
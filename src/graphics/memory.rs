@@ -0,0 +1,158 @@
+//! How much GPU and host memory the context's allocator holds, to spot leaks like buffers created
+//! every frame and never freed.
+
+use std::{
+    fmt,
+    sync::{Arc, Weak},
+};
+
+use vulkano::{
+    device::DeviceOwned,
+    image::Image,
+    memory::{
+        allocator::{StandardMemoryAllocator, Suballocator},
+        MemoryPropertyFlags,
+    },
+    DeviceSize,
+};
+
+/// Usage of the memory types of one kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapUsage {
+    /// Device memory blocks the allocator holds.
+    pub blocks: usize,
+    /// Buffers and images suballocated from the blocks.
+    pub allocations: u64,
+    /// Size of all blocks, which is what the driver sees as allocated.
+    pub reserved_bytes: DeviceSize,
+    /// Part of the blocks in use by allocations.
+    pub used_bytes: DeviceSize,
+}
+
+impl HeapUsage {
+    fn add_block(&mut self, size: DeviceSize, free: DeviceSize, allocations: u64) {
+        self.blocks += 1;
+        self.allocations += allocations;
+        self.reserved_bytes += size;
+        self.used_bytes += size - free;
+    }
+}
+
+/// A snapshot of the allocator's state, from `GraphicsContext::memory_report`. Prints as a short
+/// human readable summary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Memory only the GPU can access, where images and vertex buffers belong.
+    pub device_local: HeapUsage,
+    /// Memory the host can map, for uploads, uniforms and per-frame buffers. Includes memory that
+    /// is also device local.
+    pub host_visible: HeapUsage,
+    /// Textures uploaded through the context that are still alive.
+    pub textures: usize,
+    /// Memory used by those textures.
+    pub texture_bytes: DeviceSize,
+}
+
+impl MemoryReport {
+    pub(crate) fn new(allocator: &StandardMemoryAllocator, textures: &TextureTracker) -> Self {
+        let mut report = MemoryReport::default();
+        let memory_types = &allocator
+            .device()
+            .physical_device()
+            .memory_properties()
+            .memory_types;
+        // The allocator has a pool per memory type, in the same order.
+        for (pool, memory_type) in allocator.pools().iter().zip(memory_types) {
+            let flags = memory_type.property_flags;
+            let heap = if flags.intersects(MemoryPropertyFlags::HOST_VISIBLE) {
+                &mut report.host_visible
+            } else if flags.intersects(MemoryPropertyFlags::DEVICE_LOCAL) {
+                &mut report.device_local
+            } else {
+                continue;
+            };
+            for block in pool.blocks() {
+                heap.add_block(
+                    block.device_memory().allocation_size(),
+                    block.suballocator().free_size(),
+                    block.allocation_count(),
+                );
+            }
+        }
+        (report.textures, report.texture_bytes) = textures.live();
+        report
+    }
+
+    /// Bytes the allocator holds in total.
+    pub fn reserved_bytes(&self) -> DeviceSize {
+        self.device_local.reserved_bytes + self.host_visible.reserved_bytes
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, heap) in [
+            ("device local", &self.device_local),
+            ("host visible", &self.host_visible),
+        ] {
+            writeln!(
+                f,
+                "{name}: {} used of {} in {} blocks, {} allocations",
+                Bytes(heap.used_bytes),
+                Bytes(heap.reserved_bytes),
+                heap.blocks,
+                heap.allocations
+            )?;
+        }
+        write!(
+            f,
+            "textures: {} using {}",
+            self.textures,
+            Bytes(self.texture_bytes)
+        )
+    }
+}
+
+struct Bytes(DeviceSize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MIB: DeviceSize = 1024 * 1024;
+        if self.0 >= MIB {
+            write!(f, "{:.1} MiB", self.0 as f64 / MIB as f64)
+        } else {
+            write!(f, "{:.1} KiB", self.0 as f64 / 1024.0)
+        }
+    }
+}
+
+/// Keeps track of the uploaded textures without keeping them alive.
+#[derive(Default)]
+pub(crate) struct TextureTracker {
+    textures: Vec<Weak<Image>>,
+}
+
+impl TextureTracker {
+    pub(crate) fn track(&mut self, image: &Arc<Image>) {
+        self.textures.retain(|texture| texture.strong_count() > 0);
+        self.textures.push(Arc::downgrade(image));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.textures.clear();
+    }
+
+    fn live(&self) -> (usize, DeviceSize) {
+        self.textures
+            .iter()
+            .filter_map(Weak::upgrade)
+            .fold((0, 0), |(count, bytes), image| {
+                let size = image
+                    .memory_requirements()
+                    .iter()
+                    .map(|requirements| requirements.layout.size())
+                    .sum::<DeviceSize>();
+                (count + 1, bytes + size)
+            })
+    }
+}
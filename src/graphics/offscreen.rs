@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::allocator::{
+        StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo,
+    },
+    descriptor_set::allocator::StandardDescriptorSetAllocator,
+    device::{Device, DeviceCreateInfo, Queue, QueueCreateInfo, QueueFlags},
+    format::Format,
+    image::Image,
+    instance::{Instance, InstanceCreateFlags, InstanceCreateInfo},
+    memory::allocator::StandardMemoryAllocator,
+    sync::{self, GpuFuture},
+    VulkanLibrary,
+};
+
+use super::{
+    adapter,
+    context::{Pipelines, RenderPasses},
+    readback,
+    render_target::RenderTarget,
+};
+
+/// A graphics context without a window or swapchain.
+///
+/// Frames are rendered into targets created with [`OffscreenContext::create_target`] using the
+/// same render passes and pipelines as [`GraphicsContext`](super::context::GraphicsContext), and
+/// can be read back with [`OffscreenContext::read_image`]. Useful for rendering tests in CI and
+/// for generating thumbnails on a server.
+///
+/// # Examples
+/// ```ignore
+/// let mut gfx = OffscreenContext::new(Format::R8G8B8A8_SRGB);
+/// let target = gfx.create_target([256, 256]);
+///
+/// let future = gfx.start_frame();
+/// let mut frame = gfx.render_passes.basic_msaa
+///     .frame([0.0, 0.0, 0.0, 1.0], future, target.color(), gfx.memory_allocator.clone())
+///     .unwrap();
+/// // ... draw passes as usual ...
+/// gfx.finish_frame(after_future);
+///
+/// let pixels = gfx.read_image(target.color());
+/// ```
+pub struct OffscreenContext {
+    _instance: Arc<Instance>,
+    pub device: Arc<Device>,
+    pub gfx_queue: Arc<Queue>,
+    pub format: Format,
+    pub pipelines: Pipelines,
+    pub render_passes: RenderPasses,
+    pub memory_allocator: Arc<StandardMemoryAllocator>,
+    pub cb_allocator: Arc<StandardCommandBufferAllocator>,
+    pub ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl OffscreenContext {
+    /// Creates a context whose render passes and pipelines target images of `format`.
+    pub fn new(format: Format) -> Self {
+        let library = VulkanLibrary::new().unwrap();
+
+        let _instance = Instance::new(
+            library,
+            InstanceCreateInfo {
+                flags: InstanceCreateFlags::ENUMERATE_PORTABILITY,
+                ..Default::default()
+            },
+        )
+        .expect("failed to create Vulkan instance");
+
+        let (physical_device, queue_family_index) =
+            adapter::pick_physical_device(&_instance, None, |p| {
+                p.queue_family_properties()
+                    .iter()
+                    .position(|q| q.queue_flags.intersects(QueueFlags::GRAPHICS))
+                    .map(|i| i as u32)
+            })
+            .expect("no suitable physical device found");
+
+        let (device, mut queues) = Device::new(
+            physical_device,
+            DeviceCreateInfo {
+                queue_create_infos: vec![QueueCreateInfo {
+                    queue_family_index,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let gfx_queue = queues.next().unwrap();
+
+        let memory_allocator = Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(),
+            StandardCommandBufferAllocatorCreateInfo {
+                secondary_buffer_count: 32,
+                ..Default::default()
+            },
+        ));
+
+        let ds_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+            device.clone(),
+            Default::default(),
+        ));
+
+        let render_passes = RenderPasses::new(gfx_queue.clone(), format);
+        let pipelines = Pipelines::new(
+            gfx_queue.clone(),
+            &render_passes,
+            cb_allocator.clone(),
+            ds_allocator.clone(),
+        );
+
+        Self {
+            _instance,
+            device,
+            gfx_queue,
+            format,
+            pipelines,
+            render_passes,
+            memory_allocator,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Creates a target whose color image can be passed as the final image of any render pass
+    /// `frame`.
+    pub fn create_target(&self, extent: [u32; 2]) -> RenderTarget {
+        RenderTarget::new(self.memory_allocator.clone(), self.format, extent)
+    }
+
+    pub fn start_frame(&self) -> Box<dyn GpuFuture> {
+        sync::now(self.device.clone()).boxed()
+    }
+
+    /// Submits the frame and blocks until the GPU is done with it.
+    pub fn finish_frame(&self, after_future: Box<dyn GpuFuture>) {
+        after_future
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    /// Copies the contents of `image` back to the host, tightly packed in the image's format.
+    pub fn read_image(&self, image: Arc<Image>) -> Vec<u8> {
+        let extent = image.extent();
+        self.read_image_region(image, [0, 0], [extent[0], extent[1]])
+    }
+
+    /// Copies part of `image` back to the host, see [`readback::read_image_region`].
+    pub fn read_image_region(
+        &self,
+        image: Arc<Image>,
+        offset: [u32; 2],
+        extent: [u32; 2],
+    ) -> Vec<u8> {
+        readback::read_image_region(
+            self.gfx_queue.clone(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            self.start_frame(),
+            image,
+            offset,
+            extent,
+        )
+    }
+
+    /// Copies `buffer` back to the host, e.g. the output of a compute kernel.
+    pub fn read_buffer<T>(&self, buffer: Subbuffer<[T]>) -> Vec<T>
+    where
+        T: BufferContents + Clone,
+    {
+        readback::read_buffer(
+            self.gfx_queue.clone(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            self.start_frame(),
+            buffer,
+        )
+    }
+}
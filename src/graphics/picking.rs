@@ -0,0 +1,428 @@
+//! Finding the entity under the mouse cursor.
+//!
+//! Rays from [`Camera::screen_to_ray`](super::camera::Camera::screen_to_ray) test against
+//! bounding boxes on the CPU, which is cheap but only as exact as the boxes. A [`PickingPass`]
+//! renders the ids of the meshes into an integer target and reads back the pixel under the
+//! cursor, which is exact but waits for the GPU. Either way the result can be spawned as a
+//! [`Picked`] event, which is despawned at the end of the next update like a
+//! [`FileDrop`](crate::file_drop::FileDrop).
+
+use std::sync::Arc;
+
+use glam::{Mat4, Vec3};
+use hecs::{Entity, World};
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, RecordingCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo,
+        SubpassContents, SubpassEndInfo,
+    },
+    device::Queue,
+    format::Format,
+    image::{view::ImageView, Image, ImageCreateInfo, ImageType, ImageUsage},
+    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    pipeline::{
+        graphics::{
+            color_blend::{ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{DepthState, DepthStencilState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::{Viewport, ViewportState},
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineLayout, PipelineShaderStageCreateInfo,
+    },
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
+    sync::GpuFuture,
+};
+
+use super::{debug, frustum::Aabb, pipelines::lit::Vert, readback, render_target::DEPTH_FORMAT};
+
+/// The entity under the cursor was picked, e.g. clicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Picked(pub Entity);
+
+/// A half-line in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Normalized.
+    pub direction: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// The point `distance` along the ray.
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    /// How far along the ray it enters `aabb`, 0 if it starts inside, or `None` if it misses.
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let inverse = self.direction.recip();
+        let t0 = (aabb.min - self.origin) * inverse;
+        let t1 = (aabb.max - self.origin) * inverse;
+        let near = t0.min(t1).max_element();
+        let far = t0.max(t1).min_element();
+        (near <= far && far >= 0.0).then_some(near.max(0.0))
+    }
+
+    /// The entity whose box the ray hits first.
+    pub fn closest_hit(&self, boxes: impl IntoIterator<Item = (Entity, Aabb)>) -> Option<Entity> {
+        boxes
+            .into_iter()
+            .filter_map(|(entity, aabb)| Some((entity, self.intersect_aabb(&aabb)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(entity, _)| entity)
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct PickingPush {
+    mvp: [[f32; 4]; 4],
+    id: u32,
+}
+
+/// Renders which entity covers each pixel, to pick exactly what is under the cursor.
+///
+/// # Examples
+/// ```ignore
+/// let mut picking = gfx.create_picking_pass();
+///
+/// // On click, with the meshes as they were drawn this frame:
+/// let future = picking.render(future, camera.mvp_mat(), &meshes);
+/// picking.emit_pick(&mut app.world, future, cursor_pixel);
+///
+/// for (_, Picked(entity)) in world.query::<&Picked>().iter() {
+///     // ... select `entity`
+/// }
+/// ```
+pub struct PickingPass {
+    gfx_queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    render_pass: Arc<RenderPass>,
+    pipeline: Arc<GraphicsPipeline>,
+    ids: Arc<Image>,
+    framebuffer: Arc<Framebuffer>,
+    /// The entity of each draw of the last render. A pixel holds the index plus one, and zero
+    /// where nothing was drawn.
+    entities: Vec<Entity>,
+}
+
+impl PickingPass {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        extent: [u32; 2],
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device().clone();
+
+        let render_pass = vulkano::single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                ids: {
+                    format: Format::R32_UINT,
+                    samples: 1,
+                    load_op: Clear,
+                    store_op: Store,
+                },
+                depth: {
+                    format: DEPTH_FORMAT,
+                    samples: 1,
+                    load_op: Clear,
+                    store_op: DontCare,
+                },
+            },
+            pass: {
+                color: [ids],
+                depth_stencil: {depth},
+            },
+        )
+        .unwrap();
+        debug::set_name(&*render_pass, "picking");
+
+        let vs = vs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let fs = fs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+        let stages = [
+            PipelineShaderStageCreateInfo::new(vs),
+            PipelineShaderStageCreateInfo::new(fs),
+        ];
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+                .into_pipeline_layout_create_info(device.clone())
+                .unwrap(),
+        )
+        .unwrap();
+
+        let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+        let pipeline = GraphicsPipeline::new(
+            device.clone(),
+            None,
+            GraphicsPipelineCreateInfo {
+                stages: stages.into_iter().collect(),
+                vertex_input_state: Some(vertex_input_state),
+                input_assembly_state: Some(InputAssemblyState::default()),
+                viewport_state: Some(ViewportState::default()),
+                rasterization_state: Some(RasterizationState::default()),
+                multisample_state: Some(MultisampleState::default()),
+                // Integer targets can't blend, the default attachment state doesn't.
+                color_blend_state: Some(ColorBlendState::with_attachment_states(
+                    1,
+                    ColorBlendAttachmentState::default(),
+                )),
+                depth_stencil_state: Some(DepthStencilState {
+                    depth: Some(DepthState::simple()),
+                    ..Default::default()
+                }),
+                dynamic_state: [DynamicState::Viewport].into_iter().collect(),
+                subpass: Some(subpass.into()),
+                ..GraphicsPipelineCreateInfo::layout(layout)
+            },
+        )
+        .unwrap();
+        debug::set_name(&*pipeline, "PSOPicking");
+
+        let (ids, framebuffer) = targets(&memory_allocator, &render_pass, extent);
+
+        Self {
+            gfx_queue,
+            memory_allocator,
+            cb_allocator,
+            render_pass,
+            pipeline,
+            ids,
+            framebuffer,
+            entities: Vec::new(),
+        }
+    }
+
+    pub fn extent(&self) -> [u32; 2] {
+        let [width, height, _] = self.ids.extent();
+        [width, height]
+    }
+
+    /// Recreates the targets at `extent`, e.g. after the window resized.
+    pub fn resize(&mut self, extent: [u32; 2]) {
+        if extent == self.extent() {
+            return;
+        }
+        (self.ids, self.framebuffer) = targets(&self.memory_allocator, &self.render_pass, extent);
+        self.entities.clear();
+    }
+
+    /// Renders the id of each mesh's entity, with `view_proj` the camera's and each mesh with its
+    /// model matrix. Meshes hide each other like they do on screen.
+    pub fn render(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        view_proj: Mat4,
+        meshes: &[(Entity, Subbuffer<[Vert]>, Mat4)],
+    ) -> Box<dyn GpuFuture> {
+        let [width, height] = self.extent();
+
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        debug::begin_region(&mut cb, "picking");
+
+        cb.begin_render_pass(
+            RenderPassBeginInfo {
+                clear_values: vec![Some([0u32; 4].into()), Some(1.0.into())],
+                ..RenderPassBeginInfo::framebuffer(self.framebuffer.clone())
+            },
+            SubpassBeginInfo {
+                contents: SubpassContents::Inline,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .set_viewport(
+            0,
+            [Viewport {
+                offset: [0.0, 0.0],
+                extent: [width as f32, height as f32],
+                depth_range: 0.0..=1.0,
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap()
+        .bind_pipeline_graphics(self.pipeline.clone())
+        .unwrap();
+
+        self.entities.clear();
+        for (entity, vertices, model) in meshes {
+            self.entities.push(*entity);
+            let push = PickingPush {
+                mvp: (view_proj * *model).to_cols_array_2d(),
+                id: self.entities.len() as u32,
+            };
+            cb.push_constants(self.pipeline.layout().clone(), 0, push)
+                .unwrap()
+                .bind_vertex_buffers(0, vertices.clone())
+                .unwrap();
+            unsafe {
+                cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+            }
+        }
+
+        cb.end_render_pass(SubpassEndInfo::default()).unwrap();
+        debug::end_region(&mut cb);
+
+        before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+
+    /// The entity at `pixel` in the last render, in physical pixels from the top left. Blocks
+    /// until `before_future` is done and the pixel is read back.
+    pub fn pick(&self, before_future: Box<dyn GpuFuture>, pixel: [u32; 2]) -> Option<Entity> {
+        let [width, height] = self.extent();
+        if pixel[0] >= width || pixel[1] >= height {
+            return None;
+        }
+        let bytes = readback::read_image_region(
+            self.gfx_queue.clone(),
+            self.memory_allocator.clone(),
+            self.cb_allocator.clone(),
+            before_future,
+            self.ids.clone(),
+            pixel,
+            [1, 1],
+        );
+        let id = u32::from_ne_bytes(bytes[..4].try_into().unwrap());
+        let index = id.checked_sub(1)?;
+        self.entities.get(index as usize).copied()
+    }
+
+    /// Like [`PickingPass::pick`], and spawns the entity found as a [`Picked`] event.
+    pub fn emit_pick(
+        &self,
+        world: &mut World,
+        before_future: Box<dyn GpuFuture>,
+        pixel: [u32; 2],
+    ) -> Option<Entity> {
+        let entity = self.pick(before_future, pixel)?;
+        world.spawn((Picked(entity),));
+        Some(entity)
+    }
+}
+
+fn targets(
+    memory_allocator: &Arc<StandardMemoryAllocator>,
+    render_pass: &Arc<RenderPass>,
+    extent: [u32; 2],
+) -> (Arc<Image>, Arc<Framebuffer>) {
+    let image = |format, usage| {
+        Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format,
+                extent: [extent[0], extent[1], 1],
+                usage,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap()
+    };
+    let ids = image(
+        Format::R32_UINT,
+        ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC,
+    );
+    let depth = image(DEPTH_FORMAT, ImageUsage::DEPTH_STENCIL_ATTACHMENT);
+
+    let framebuffer = Framebuffer::new(
+        render_pass.clone(),
+        FramebufferCreateInfo {
+            attachments: vec![
+                ImageView::new_default(ids.clone()).unwrap(),
+                ImageView::new_default(depth).unwrap(),
+            ],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    (ids, framebuffer)
+}
+
+pub(crate) fn clear(world: &mut World) {
+    let entities: Vec<_> = world
+        .query::<&Picked>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.despawn(entity).unwrap();
+    }
+}
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+
+            layout(push_constant) uniform Push {
+                mat4 mvp;
+                uint id;
+            } pc;
+
+            void main() {
+                gl_Position = pc.mvp * vec4(position, 1.0);
+            }
+        ",
+    }
+}
+
+mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) out uint f_id;
+
+            layout(push_constant) uniform Push {
+                mat4 mvp;
+                uint id;
+            } pc;
+
+            void main() {
+                f_id = pc.id;
+            }
+        ",
+    }
+}
@@ -15,15 +15,19 @@ use vulkano::{
             multisample::MultisampleState,
             rasterization::RasterizationState,
             vertex_input::{Vertex, VertexDefinition},
-            viewport::{Viewport, ViewportState},
+            viewport::ViewportState,
             GraphicsPipelineCreateInfo,
         },
         layout::PipelineDescriptorSetLayoutCreateInfo,
-        DynamicState, GraphicsPipeline, PipelineLayout, PipelineShaderStageCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineLayout, PipelineShaderStageCreateInfo,
     },
     render_pass::Subpass,
+    shader::EntryPoint,
 };
 
+use super::Projection2D;
+use crate::graphics::viewport::ViewportRegion;
+
 #[derive(BufferContents, Vertex)]
 #[repr(C)]
 pub struct Vert {
@@ -37,6 +41,7 @@ pub struct PSOBasic {
     gfx_queue: Arc<Queue>,
     subpass: Subpass,
     pub pipeline: Arc<GraphicsPipeline>,
+    projection: Projection2D,
     cb_allocator: Arc<StandardCommandBufferAllocator>,
 }
 
@@ -56,6 +61,19 @@ impl PSOBasic {
             .entry_point("main")
             .unwrap();
 
+        Self::from_shaders(gfx_queue, subpass, cb_allocator, vs, fs)
+    }
+
+    /// Builds the pipeline from caller-provided shaders, e.g. ones loaded through a
+    /// `ShaderLibrary`. The shaders must use the same vertex layout as [`Vert`].
+    pub fn from_shaders(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        vs: EntryPoint,
+        fs: EntryPoint,
+    ) -> Self {
+        let device = gfx_queue.device();
         let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
 
         let stages = [
@@ -95,7 +113,9 @@ impl PSOBasic {
                     },
                 )),
                 depth_stencil_state: None,
-                dynamic_state: [DynamicState::Viewport].into_iter().collect(),
+                dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                    .into_iter()
+                    .collect(),
                 subpass: Some(subpass.clone().into()),
                 ..GraphicsPipelineCreateInfo::layout(layout)
             },
@@ -106,14 +126,25 @@ impl PSOBasic {
             gfx_queue,
             subpass,
             pipeline,
+            projection: Projection2D::default(),
             cb_allocator,
         }
     }
 
+    /// How positions are mapped to the screen for the following draws. Defaults to
+    /// [`Projection2D::NDC`].
+    pub fn set_projection(&mut self, projection: Projection2D) {
+        self.projection = projection;
+    }
+
+    pub fn projection(&self) -> Projection2D {
+        self.projection
+    }
+
     /// Builds a secondary command buffer that draws the triangle on the current subpass.
     pub fn draw<V>(
         &self,
-        viewport_dimensions: [u32; 2],
+        viewport: impl Into<ViewportRegion>,
         vertices: Subbuffer<[V]>,
     ) -> Arc<CommandBuffer> {
         let mut builder = RecordingCommandBuffer::new(
@@ -131,23 +162,21 @@ impl PSOBasic {
         )
         .unwrap();
 
+        viewport.into().set(&mut builder);
+
         builder
-            .set_viewport(
-                0,
-                [Viewport {
-                    offset: [0.0, 0.0],
-                    extent: [viewport_dimensions[0] as f32, viewport_dimensions[1] as f32],
-                    depth_range: 0.0..=1.0,
-                }]
-                .into_iter()
-                .collect(),
-            )
-            .unwrap()
             .bind_pipeline_graphics(self.pipeline.clone())
             .unwrap()
             .bind_vertex_buffers(0, vertices.clone())
             .unwrap();
 
+        // Custom shaders may not take the projection.
+        if !self.pipeline.layout().push_constant_ranges().is_empty() {
+            builder
+                .push_constants(self.pipeline.layout().clone(), 0, self.projection)
+                .unwrap();
+        }
+
         unsafe {
             builder.draw(vertices.len() as u32, 1, 0, 0).unwrap();
         }
@@ -166,8 +195,12 @@ pub mod vs {
             layout(location = 1) in vec3 color;
             layout(location = 0) out vec3 v_color;
 
+            layout(push_constant) uniform Projection {
+                mat4 matrix;
+            } projection;
+
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0);
+                gl_Position = projection.matrix * vec4(position, 0.0, 1.0);
                 v_color = color;
             }
         ",
@@ -0,0 +1,290 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+        Image,
+    },
+    pipeline::{
+        graphics::{
+            color_blend::{AttachmentBlend, ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{CompareOp, DepthState, DepthStencilState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+};
+
+use crate::graphics::{camera::Camera, viewport::ViewportRegion};
+
+/// One camera-facing quad, read per instance.
+#[derive(BufferContents, Vertex, Clone, Copy)]
+#[repr(C)]
+pub struct Billboard {
+    /// The center of the quad in world space.
+    #[format(R32G32B32_SFLOAT)]
+    pub position: [f32; 3],
+    /// Width and height in world units.
+    #[format(R32G32_SFLOAT)]
+    pub size: [f32; 2],
+    /// Multiplied with the texture, e.g. to fade particles out.
+    #[format(R32G32B32A32_SFLOAT)]
+    pub color: [f32; 4],
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct BillboardCamera {
+    view_proj: [[f32; 4]; 4],
+    right: [f32; 4],
+    up: [f32; 4],
+}
+
+/// Textured quads that always face the camera, e.g. particles, markers and health bars. Each
+/// [`Billboard`] instance is expanded into a quad along the camera's right and up vectors in the
+/// vertex shader.
+///
+/// Billboards are depth tested without writing depth and blended with straight alpha, so draw
+/// them after the opaque meshes, sorted with a `TransparentQueue` if they overlap. Needs a
+/// subpass with a depth attachment, e.g. `RenderPassDepth`.
+pub struct PSOBillboard {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    pub pipeline: Arc<GraphicsPipeline>,
+    sampler: Arc<Sampler>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl PSOBillboard {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let pipeline = build_pipeline(&subpass);
+        let sampler = Sampler::new(
+            gfx_queue.device().clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        Self {
+            gfx_queue,
+            subpass,
+            pipeline,
+            sampler,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Builds a secondary command buffer that draws every billboard in `instances` with `image`,
+    /// facing `camera`.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        image: Arc<Image>,
+        instances: Subbuffer<[Billboard]>,
+        camera: &dyn Camera,
+    ) -> Arc<CommandBuffer> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(self.subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let texture = ImageView::new_default(image).unwrap();
+        let set = DescriptorSet::new(
+            self.ds_allocator.clone(),
+            self.pipeline.layout().set_layouts()[0].clone(),
+            [WriteDescriptorSet::image_view_sampler(
+                0,
+                texture,
+                self.sampler.clone(),
+            )],
+            [],
+        )
+        .unwrap();
+
+        let push = BillboardCamera {
+            view_proj: camera.mvp_mat().to_cols_array_2d(),
+            right: camera.right().extend(0.0).to_array(),
+            up: camera.up().extend(0.0).to_array(),
+        };
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(self.pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                0,
+                set,
+            )
+            .unwrap()
+            .push_constants(self.pipeline.layout().clone(), 0, push)
+            .unwrap()
+            .bind_vertex_buffers(0, instances.clone())
+            .unwrap();
+
+        unsafe {
+            // Two triangles per instance, the corners come from the vertex index.
+            cb.draw(6, instances.len() as u32, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+}
+
+fn build_pipeline(subpass: &Subpass) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vs = vs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let fs = fs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let vertex_input_state = Billboard::per_instance().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState {
+                    blend: Some(AttachmentBlend::alpha()),
+                    ..Default::default()
+                },
+            )),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(DepthState {
+                    write_enable: false,
+                    compare_op: CompareOp::Less,
+                }),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec2 size;
+            layout(location = 2) in vec4 color;
+            layout(location = 0) out vec2 v_uv;
+            layout(location = 1) out vec4 v_color;
+
+            layout(push_constant) uniform BillboardCamera {
+                mat4 view_proj;
+                vec4 right;
+                vec4 up;
+            } pc;
+
+            const vec2 CORNERS[6] = vec2[](
+                vec2(-0.5, -0.5), vec2(0.5, -0.5), vec2(0.5, 0.5),
+                vec2(-0.5, -0.5), vec2(0.5, 0.5), vec2(-0.5, 0.5)
+            );
+
+            void main() {
+                vec2 corner = CORNERS[gl_VertexIndex];
+                vec3 world = position
+                    + pc.right.xyz * corner.x * size.x
+                    + pc.up.xyz * corner.y * size.y;
+                v_uv = vec2(corner.x + 0.5, 0.5 - corner.y);
+                v_color = color;
+                gl_Position = pc.view_proj * vec4(world, 1.0);
+            }
+        ",
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 v_uv;
+            layout(location = 1) in vec4 v_color;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler2D tex;
+
+            void main() {
+                f_color = texture(tex, v_uv) * v_color;
+            }
+        ",
+    }
+}
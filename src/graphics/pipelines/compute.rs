@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::Subbuffer,
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferLevel, CommandBufferUsage, RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    image::{view::ImageView, Image},
+    pipeline::{
+        compute::ComputePipelineCreateInfo, layout::PipelineDescriptorSetLayoutCreateInfo,
+        ComputePipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    shader::EntryPoint,
+};
+
+/// Binds a buffer to a `buffer` block of the compute shader.
+pub fn storage_buffer<T: ?Sized>(binding: u32, buffer: Subbuffer<T>) -> WriteDescriptorSet {
+    WriteDescriptorSet::buffer(binding, buffer)
+}
+
+/// Binds an image to an `image2D` uniform of the compute shader. The image needs
+/// `ImageUsage::STORAGE`.
+pub fn storage_image(binding: u32, image: Arc<Image>) -> WriteDescriptorSet {
+    WriteDescriptorSet::image_view(binding, ImageView::new_default(image).unwrap())
+}
+
+pub struct PSOCompute {
+    gfx_queue: Arc<Queue>,
+    pub pipeline: Arc<ComputePipeline>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl PSOCompute {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        cs: EntryPoint,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device();
+        let stage = PipelineShaderStageCreateInfo::new(cs);
+
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages([&stage])
+                .into_pipeline_layout_create_info(device.clone())
+                .unwrap(),
+        )
+        .unwrap();
+
+        let pipeline = ComputePipeline::new(
+            device.clone(),
+            None,
+            ComputePipelineCreateInfo::stage_layout(stage, layout),
+        )
+        .unwrap();
+
+        Self {
+            gfx_queue,
+            pipeline,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Builds a primary command buffer that dispatches `group_counts` work groups with the given
+    /// resources bound to descriptor set 0, see [`storage_buffer`] and [`storage_image`].
+    ///
+    /// Submit it with `GraphicsContext::then_compute` inside a frame or
+    /// `GraphicsContext::submit_compute` outside of one.
+    pub fn dispatch(
+        &self,
+        group_counts: [u32; 3],
+        bindings: impl IntoIterator<Item = WriteDescriptorSet>,
+    ) -> Arc<CommandBuffer> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let layout = &self.pipeline.layout().set_layouts()[0];
+        let set =
+            DescriptorSet::new(self.ds_allocator.clone(), layout.clone(), bindings, []).unwrap();
+
+        cb.bind_pipeline_compute(self.pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                self.pipeline.layout().clone(),
+                0,
+                set,
+            )
+            .unwrap();
+
+        unsafe {
+            cb.dispatch(group_counts).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+}
@@ -0,0 +1,31 @@
+#version 450
+
+// `PSOLit`'s debug views, fed by the lit vertex shader. VIEW_NORMALS shows the world space
+// normals, VIEW_UVS the texture coordinates and VIEW_DEPTH the distance to the camera.
+
+layout(location = 0) in vec3 v_world;
+layout(location = 1) in vec3 v_normal;
+layout(location = 3) in vec2 v_uv;
+layout(location = 0) out vec4 f_color;
+
+#ifdef VIEW_DEPTH
+// Only the camera position of `PSOLit`'s lights block is read.
+layout(set = 0, binding = 0) uniform Lights {
+    vec4 camera_position;
+} u;
+#endif
+
+void main() {
+#if defined(VIEW_NORMALS)
+    f_color = vec4(normalize(v_normal) * 0.5 + 0.5, 1.0);
+#elif defined(VIEW_UVS)
+    // Red and green along u and v, with a checkerboard to show stretching.
+    vec2 cell = floor(v_uv * 8.0);
+    float checker = mod(cell.x + cell.y, 2.0);
+    f_color = vec4(fract(v_uv), checker * 0.5, 1.0);
+#elif defined(VIEW_DEPTH)
+    // White up close, fading to black with distance.
+    float distance = length(v_world - u.camera_position.xyz);
+    f_color = vec4(vec3(exp(-distance * 0.05)), 1.0);
+#endif
+}
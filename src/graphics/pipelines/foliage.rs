@@ -0,0 +1,312 @@
+use std::sync::Arc;
+
+use glam::Vec3;
+use vulkano::{
+    buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    pipeline::{
+        graphics::{
+            color_blend::{ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{DepthState, DepthStencilState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+};
+
+use super::lit::{self, Transform, Vert};
+use crate::graphics::{light::LightsUniform, viewport::ViewportRegion};
+
+/// How much a foliage vertex sways, in a second vertex buffer next to the [`Vert`]s. 0 keeps
+/// the vertex still, e.g. at the root of a plant, 1 sways it by the full amplitude.
+#[derive(BufferContents, Vertex, Clone, Copy)]
+#[repr(C)]
+pub struct SwayWeight {
+    #[format(R32_SFLOAT)]
+    pub weight: f32,
+}
+
+/// Wind blowing through foliage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Wind {
+    /// World space direction the wind pushes in.
+    pub direction: Vec3,
+    /// Furthest a vertex with weight 1 moves, in world units.
+    pub amplitude: f32,
+    /// Sways per second.
+    pub frequency: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind {
+            direction: Vec3::X,
+            amplitude: 0.1,
+            frequency: 0.5,
+        }
+    }
+}
+
+/// The uniform block bound by `PSOFoliage`.
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+pub struct WindUniform {
+    /// xyz is the direction, w the amplitude.
+    pub direction: [f32; 4],
+    /// x is the time in seconds, y the frequency.
+    pub params: [f32; 4],
+}
+
+impl WindUniform {
+    pub fn new(wind: &Wind, time: f32) -> Self {
+        let d = wind.direction.normalize_or_zero();
+        WindUniform {
+            direction: [d.x, d.y, d.z, wind.amplitude],
+            params: [time, wind.frequency, 0.0, 0.0],
+        }
+    }
+}
+
+/// Lit meshes swaying in the wind, e.g. grass and leaves. Each vertex is pushed along the wind
+/// by its [`SwayWeight`], phase shifted by its world position so neighbouring plants don't move
+/// in unison, then shaded like `PSOLit` without any maps.
+pub struct PSOFoliage {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    pub pipeline: Arc<GraphicsPipeline>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl PSOFoliage {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let pipeline = build_pipeline(&subpass);
+
+        Self {
+            gfx_queue,
+            subpass,
+            pipeline,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Uploads the wind for a frame. The buffer can be shared by every draw in that frame.
+    pub fn wind_buffer(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        wind: WindUniform,
+    ) -> Subbuffer<WindUniform> {
+        Buffer::from_data(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::UNIFORM_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            wind,
+        )
+        .unwrap()
+    }
+
+    /// Builds a secondary command buffer that draws the mesh, with one weight per vertex.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        vertices: Subbuffer<[Vert]>,
+        weights: Subbuffer<[SwayWeight]>,
+        transform: Transform,
+        lights: Subbuffer<LightsUniform>,
+        wind: Subbuffer<WindUniform>,
+    ) -> Arc<CommandBuffer> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(self.subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let set_layouts = self.pipeline.layout().set_layouts();
+        let sets = vec![
+            DescriptorSet::new(
+                self.ds_allocator.clone(),
+                set_layouts[0].clone(),
+                [WriteDescriptorSet::buffer(0, lights)],
+                [],
+            )
+            .unwrap(),
+            DescriptorSet::new(
+                self.ds_allocator.clone(),
+                set_layouts[1].clone(),
+                [WriteDescriptorSet::buffer(0, wind)],
+                [],
+            )
+            .unwrap(),
+        ];
+
+        let vertex_count = vertices.len() as u32;
+        viewport.into().set(&mut cb);
+        cb.bind_pipeline_graphics(self.pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                0,
+                sets,
+            )
+            .unwrap()
+            .push_constants(self.pipeline.layout().clone(), 0, transform)
+            .unwrap()
+            .bind_vertex_buffers(0, (vertices, weights))
+            .unwrap();
+
+        unsafe {
+            cb.draw(vertex_count, 1, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+}
+
+fn build_pipeline(subpass: &Subpass) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vs = vs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let fs = lit::fs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let vertex_input_state = [Vert::per_vertex(), SwayWeight::per_vertex()]
+        .definition(&vs)
+        .unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState::default(),
+            )),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(DepthState::simple()),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 normal;
+            layout(location = 2) in vec3 color;
+            layout(location = 3) in vec2 uv;
+            layout(location = 4) in vec4 tangent;
+            layout(location = 5) in vec2 lightmap_uv;
+            layout(location = 6) in float weight;
+            layout(location = 0) out vec3 v_world;
+            layout(location = 1) out vec3 v_normal;
+            layout(location = 2) out vec3 v_color;
+            layout(location = 3) out vec2 v_uv;
+            layout(location = 4) out vec4 v_tangent;
+            layout(location = 5) out vec2 v_lightmap_uv;
+
+            layout(push_constant) uniform Transform {
+                mat4 mvp;
+                mat4 model;
+            } pc;
+
+            // Set 1 of the lit fragment shader is unused without maps.
+            layout(set = 1, binding = 0) uniform Wind {
+                vec4 direction;
+                vec4 params;
+            } wind;
+
+            void main() {
+                vec3 rest = (pc.model * vec4(position, 1.0)).xyz;
+                float phase = dot(rest.xz, vec2(0.37, 0.73));
+                float sway = sin(wind.params.x * wind.params.y * 6.2831853 + phase)
+                    * wind.direction.w * weight;
+                // The wind blows in world space, move the vertex in model space so `mvp` applies.
+                vec3 offset = inverse(mat3(pc.model)) * (wind.direction.xyz * sway);
+                vec3 swayed = position + offset;
+
+                v_world = (pc.model * vec4(swayed, 1.0)).xyz;
+                v_normal = mat3(pc.model) * normal;
+                v_color = color;
+                v_uv = uv;
+                v_tangent = vec4(mat3(pc.model) * tangent.xyz, tangent.w);
+                v_lightmap_uv = lightmap_uv;
+                gl_Position = pc.mvp * vec4(swayed, 1.0);
+            }
+        ",
+    }
+}
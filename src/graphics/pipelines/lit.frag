@@ -0,0 +1,119 @@
+#version 450
+
+// Shared by every `PSOLit` fragment shader variant. SHADOWS enables sampling a shadow map,
+// NORMAL_MAP a tangent-space normal map and LIGHTMAP baked light, all from set 1.
+
+#define MAX_LIGHTS 8
+#define SHININESS 32.0
+
+layout(location = 0) in vec3 v_world;
+layout(location = 1) in vec3 v_normal;
+layout(location = 2) in vec3 v_color;
+layout(location = 3) in vec2 v_uv;
+layout(location = 4) in vec4 v_tangent;
+layout(location = 5) in vec2 v_lightmap_uv;
+layout(location = 0) out vec4 f_color;
+
+struct Light {
+    vec4 position;
+    vec4 color;
+    vec4 params;
+};
+
+layout(set = 0, binding = 0) uniform Lights {
+    vec4 camera_position;
+    vec4 ambient;
+    uvec4 count;
+    mat4 shadow_matrix;
+    // x: depth bias, y: 1 if a shadow map is in use, z: texel size, w: index of the light.
+    vec4 shadow_params;
+    Light lights[MAX_LIGHTS];
+} u;
+
+#ifdef SHADOWS
+layout(set = 1, binding = 0) uniform sampler2DShadow shadow_map;
+
+// 3x3 percentage-closer filtering.
+float shadow_factor() {
+    vec4 clip = u.shadow_matrix * vec4(v_world, 1.0);
+    vec3 ndc = clip.xyz / clip.w;
+    vec2 uv = ndc.xy * 0.5 + 0.5;
+    if (ndc.z > 1.0 || any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+        return 1.0;
+    }
+
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * u.shadow_params.z;
+            lit += texture(shadow_map, vec3(uv + offset, ndc.z - u.shadow_params.x));
+        }
+    }
+    return lit / 9.0;
+}
+#endif
+
+#ifdef NORMAL_MAP
+layout(set = 1, binding = 1) uniform sampler2D normal_map;
+
+vec3 surface_normal() {
+    vec3 n = normalize(v_normal);
+    // Gram-Schmidt, the interpolated tangent is no longer perpendicular to the normal.
+    vec3 t = normalize(v_tangent.xyz - n * dot(n, v_tangent.xyz));
+    vec3 b = cross(n, t) * v_tangent.w;
+    vec3 sampled = texture(normal_map, v_uv).xyz * 2.0 - 1.0;
+    return normalize(mat3(t, b, n) * sampled);
+}
+#else
+vec3 surface_normal() {
+    return normalize(v_normal);
+}
+#endif
+
+#ifdef LIGHTMAP
+layout(set = 1, binding = 2) uniform sampler2D lightmap;
+
+// The baked light includes the ambient light.
+vec3 indirect_light() {
+    return texture(lightmap, v_lightmap_uv).rgb;
+}
+#else
+vec3 indirect_light() {
+    return u.ambient.rgb;
+}
+#endif
+
+void main() {
+    vec3 n = surface_normal();
+    vec3 view_dir = normalize(u.camera_position.xyz - v_world);
+    vec3 result = indirect_light() * v_color;
+
+    for (uint i = 0; i < u.count.x; i++) {
+        Light light = u.lights[i];
+        vec3 to_light;
+        float attenuation = 1.0;
+
+        if (light.position.w == 0.0) {
+            to_light = -light.position.xyz;
+        } else {
+            vec3 delta = light.position.xyz - v_world;
+            float dist = length(delta);
+            to_light = delta / max(dist, 0.0001);
+            float falloff = clamp(1.0 - dist / max(light.params.x, 0.0001), 0.0, 1.0);
+            attenuation = falloff * falloff;
+        }
+
+#ifdef SHADOWS
+        if (u.shadow_params.y > 0.0 && i == uint(u.shadow_params.w)) {
+            attenuation *= shadow_factor();
+        }
+#endif
+
+        float diffuse = max(dot(n, to_light), 0.0);
+        vec3 half_dir = normalize(to_light + view_dir);
+        float specular = diffuse > 0.0 ? pow(max(dot(n, half_dir), 0.0), SHININESS) : 0.0;
+        result += (diffuse * v_color + specular) * light.color.rgb * attenuation;
+    }
+
+    f_color = vec4(result, 1.0);
+}
@@ -0,0 +1,871 @@
+use std::sync::Arc;
+
+use glam::{Mat4, Vec2, Vec3};
+use vulkano::{
+    buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage, DrawIndirectCommand,
+        RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+    },
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    pipeline::{
+        graphics::{
+            color_blend::{ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{CompareOp, DepthState, DepthStencilState},
+            input_assembly::{InputAssemblyState, PrimitiveTopology},
+            multisample::MultisampleState,
+            rasterization::{PolygonMode, RasterizationState},
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+    shader::EntryPoint,
+};
+
+use crate::graphics::{
+    debug_view::DebugView,
+    indirect::{IndirectDraws, MeshBatch},
+    light::LightsUniform,
+    shadow::ShadowMap,
+    viewport::ViewportRegion,
+};
+
+#[derive(BufferContents, Vertex)]
+#[repr(C)]
+pub struct Vert {
+    #[format(R32G32B32_SFLOAT)]
+    pub position: [f32; 3],
+    #[format(R32G32B32_SFLOAT)]
+    pub normal: [f32; 3],
+    #[format(R32G32B32_SFLOAT)]
+    pub color: [f32; 3],
+    #[format(R32G32_SFLOAT)]
+    pub uv: [f32; 2],
+    /// xyz points along +u in the normal map, w is 1 or -1 depending on whether +v is along
+    /// `normal x tangent` or against it. Fill it with [`compute_tangents`].
+    #[format(R32G32B32A32_SFLOAT)]
+    pub tangent: [f32; 4],
+    /// Where the vertex lies in the lightmap atlas. Unlike `uv` every surface needs its own
+    /// texels, so no two triangles may overlap.
+    #[format(R32G32_SFLOAT)]
+    pub lightmap_uv: [f32; 2],
+}
+
+/// Computes the tangent of every vertex of a triangle list from its positions and uvs. Each
+/// triangle gets a flat tangent, made perpendicular to the vertex normals.
+pub fn compute_tangents(vertices: &mut [Vert]) {
+    for triangle in vertices.chunks_exact_mut(3) {
+        let p: Vec<Vec3> = triangle.iter().map(|v| Vec3::from(v.position)).collect();
+        let uv: Vec<Vec2> = triangle.iter().map(|v| Vec2::from(v.uv)).collect();
+        let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+        let (d1, d2) = (uv[1] - uv[0], uv[2] - uv[0]);
+
+        let det = d1.x * d2.y - d2.x * d1.y;
+        let (tangent, bitangent) = if det.abs() < f32::EPSILON {
+            // Degenerate uvs, any tangent perpendicular to the normal will do.
+            (e1, e2)
+        } else {
+            let r = 1.0 / det;
+            ((e1 * d2.y - e2 * d1.y) * r, (e2 * d1.x - e1 * d2.x) * r)
+        };
+
+        for vertex in triangle.iter_mut() {
+            let n = Vec3::from(vertex.normal);
+            let t = (tangent - n * n.dot(tangent)).normalize_or_zero();
+            let w = if n.cross(t).dot(bitangent) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = [t.x, t.y, t.z, w];
+        }
+    }
+}
+
+/// Pushed once per draw call, or read from a storage buffer by indirect draws.
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+pub struct Transform {
+    /// Camera view-projection times `model`.
+    pub mvp: [[f32; 4]; 4],
+    /// Places the mesh in world space, where the lights live. Normals are transformed by its
+    /// upper 3x3, so keep scaling uniform.
+    pub model: [[f32; 4]; 4],
+}
+
+impl Transform {
+    pub fn new(view_proj: Mat4, model: Mat4) -> Self {
+        Transform {
+            mvp: (view_proj * model).to_cols_array_2d(),
+            model: model.to_cols_array_2d(),
+        }
+    }
+}
+
+/// The optional textures a lit mesh is drawn with, bound at set 1.
+#[derive(Default, Clone)]
+pub struct SurfaceMaps<'a> {
+    /// Shadows the light picked by `LightsUniform::set_shadow`.
+    pub shadow_map: Option<&'a ShadowMap>,
+    /// A tangent-space normal map in a UNORM format, see `GraphicsContext::upload_normal_map`.
+    /// Needs the tangents and uvs of the vertices filled in.
+    pub normal_map: Option<Arc<ImageView>>,
+    /// Baked light, e.g. from `lightmap::bake`, read with the vertices' `lightmap_uv`. It
+    /// replaces the ambient light, so leave the lights it was baked from out of the uniform.
+    pub lightmap: Option<Arc<ImageView>>,
+}
+
+impl SurfaceMaps<'_> {
+    /// Index into `PSOLit`'s variants, one bit per map.
+    fn variant(&self) -> usize {
+        self.shadow_map.is_some() as usize
+            | (self.normal_map.is_some() as usize) << 1
+            | (self.lightmap.is_some() as usize) << 2
+    }
+}
+
+/// Blinn-Phong shaded 3D meshes lit by up to `MAX_LIGHTS` directional and point lights, one of
+/// which can be shadowed by a `ShadowMap`, optionally with a tangent-space normal map and a
+/// baked lightmap. Needs a subpass with a depth attachment, e.g. `RenderPassDepth`.
+pub struct PSOLit {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    pub pipeline: Arc<GraphicsPipeline>,
+    /// A pipeline per combination of `SurfaceMaps`, indexed by `SurfaceMaps::variant`. Only
+    /// built for the built-in shaders, custom ones get `pipeline` alone.
+    variants: Vec<Arc<GraphicsPipeline>>,
+    /// Like `variants`, reading the transforms from a storage buffer for indirect draws.
+    indirect_variants: Vec<Arc<GraphicsPipeline>>,
+    /// The pipeline of each `DebugView` other than `Shaded` the device supports.
+    debug_pipelines: Vec<(DebugView, Arc<GraphicsPipeline>)>,
+    debug_view: DebugView,
+    normal_sampler: Arc<Sampler>,
+    lightmap_sampler: Arc<Sampler>,
+    /// Writes depth only, on `prepass_subpass`.
+    depth_only: Option<Arc<GraphicsPipeline>>,
+    prepass_subpass: Option<Subpass>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl PSOLit {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        Self::build(gfx_queue, None, subpass, cb_allocator, ds_allocator)
+    }
+
+    /// For a render pass with a depth prepass, e.g. `RenderPassDepth::with_prepass`. Meshes
+    /// are drawn into `prepass` with [`PSOLit::draw_depth`], then shaded in `subpass` where only
+    /// the closest surface passes the depth test.
+    pub fn with_prepass(
+        gfx_queue: Arc<Queue>,
+        prepass: Subpass,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        Self::build(
+            gfx_queue,
+            Some(prepass),
+            subpass,
+            cb_allocator,
+            ds_allocator,
+        )
+    }
+
+    fn build(
+        gfx_queue: Arc<Queue>,
+        prepass: Option<Subpass>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device();
+        let vs = vs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let vs_indirect = vs_indirect::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let fragment_shaders: Vec<_> = [
+            fs::load(device.clone()),
+            fs_shadowed::load(device.clone()),
+            fs_normal_mapped::load(device.clone()),
+            fs_normal_mapped_shadowed::load(device.clone()),
+            fs_lightmapped::load(device.clone()),
+            fs_lightmapped_shadowed::load(device.clone()),
+            fs_lightmapped_normal_mapped::load(device.clone()),
+            fs_lightmapped_normal_mapped_shadowed::load(device.clone()),
+        ]
+        .into_iter()
+        .map(|module| module.unwrap().entry_point("main").unwrap())
+        .collect();
+
+        let depth_equal = prepass.is_some();
+        let variants: Vec<_> = fragment_shaders
+            .iter()
+            .map(|fs| {
+                build_pipeline(
+                    &subpass,
+                    vs.clone(),
+                    fs.clone(),
+                    depth_equal,
+                    PolygonMode::Fill,
+                )
+            })
+            .collect();
+        // Indirect draws aren't part of the prepass, so they test depth as usual.
+        let indirect_variants = fragment_shaders
+            .into_iter()
+            .map(|fs| build_pipeline(&subpass, vs_indirect.clone(), fs, false, PolygonMode::Fill))
+            .collect();
+
+        let mut debug_pipelines = Vec::new();
+        // Lines need the `fill_mode_non_solid` feature.
+        if device.enabled_features().fill_mode_non_solid {
+            let fs = fs::load(device.clone())
+                .unwrap()
+                .entry_point("main")
+                .unwrap();
+            let pipeline = build_pipeline(&subpass, vs.clone(), fs, depth_equal, PolygonMode::Line);
+            debug_pipelines.push((DebugView::Wireframe, pipeline));
+        }
+        for (view, module) in [
+            (DebugView::Normals, fs_debug_normals::load(device.clone())),
+            (DebugView::Uvs, fs_debug_uvs::load(device.clone())),
+            (DebugView::Depth, fs_debug_depth::load(device.clone())),
+        ] {
+            let fs = module.unwrap().entry_point("main").unwrap();
+            let pipeline = build_pipeline(&subpass, vs.clone(), fs, depth_equal, PolygonMode::Fill);
+            debug_pipelines.push((view, pipeline));
+        }
+        let mut pso = Self::from_pipeline(
+            gfx_queue,
+            subpass,
+            variants[0].clone(),
+            cb_allocator,
+            ds_allocator,
+        );
+        pso.variants = variants;
+        pso.indirect_variants = indirect_variants;
+        pso.debug_pipelines = debug_pipelines;
+        pso.depth_only = prepass
+            .as_ref()
+            .map(|prepass| build_depth_pipeline(prepass, vs));
+        pso.prepass_subpass = prepass;
+        pso
+    }
+
+    /// Builds the pipeline from caller-provided shaders, e.g. ones loaded through a
+    /// `ShaderLibrary`. The shaders must use the same vertex layout, push constants and lights
+    /// block as the built-in ones.
+    pub fn from_shaders(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+        vs: EntryPoint,
+        fs: EntryPoint,
+    ) -> Self {
+        let pipeline = build_pipeline(&subpass, vs, fs, false, PolygonMode::Fill);
+        Self::from_pipeline(gfx_queue, subpass, pipeline, cb_allocator, ds_allocator)
+    }
+
+    fn from_pipeline(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        pipeline: Arc<GraphicsPipeline>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let normal_sampler = Sampler::new(
+            gfx_queue.device().clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::Repeat; 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let lightmap_sampler = Sampler::new(
+            gfx_queue.device().clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        Self {
+            gfx_queue,
+            subpass,
+            pipeline,
+            variants: Vec::new(),
+            indirect_variants: Vec::new(),
+            debug_pipelines: Vec::new(),
+            debug_view: DebugView::default(),
+            normal_sampler,
+            lightmap_sampler,
+            depth_only: None,
+            prepass_subpass: None,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Uploads the lights for a frame. The buffer can be shared by every draw in that frame.
+    pub fn lights_buffer(
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        lights: LightsUniform,
+    ) -> Subbuffer<LightsUniform> {
+        Buffer::from_data(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::UNIFORM_BUFFER,
+                ..Default::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..Default::default()
+            },
+            lights,
+        )
+        .unwrap()
+    }
+
+    /// Draws the following meshes with `view` instead of shading them, e.g. the
+    /// [`DebugView::get`] of the world every frame. Views the device or custom shaders don't
+    /// support are drawn shaded.
+    pub fn set_debug_view(&mut self, view: DebugView) {
+        self.debug_view = view;
+    }
+
+    pub fn debug_view(&self) -> DebugView {
+        self.debug_view
+    }
+
+    /// Builds a secondary command buffer that draws the mesh on the current subpass, with
+    /// whichever of `maps` are set.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        vertices: Subbuffer<[Vert]>,
+        transform: Transform,
+        lights: Subbuffer<LightsUniform>,
+        maps: SurfaceMaps,
+    ) -> Arc<CommandBuffer> {
+        // Custom shaders only get the plain pipeline.
+        let maps = if self.variants.is_empty() {
+            SurfaceMaps::default()
+        } else {
+            maps
+        };
+        let debug_pipeline = self
+            .debug_pipelines
+            .iter()
+            .find(|(view, _)| *view == self.debug_view)
+            .map(|(_, pipeline)| pipeline);
+        // Debug views ignore the maps.
+        let (pipeline, maps) = match debug_pipeline {
+            Some(pipeline) => (pipeline, SurfaceMaps::default()),
+            None => (
+                self.variants.get(maps.variant()).unwrap_or(&self.pipeline),
+                maps,
+            ),
+        };
+
+        let mut cb = self.secondary(&self.subpass);
+        let sets = self.descriptor_sets(pipeline, [WriteDescriptorSet::buffer(0, lights)], maps);
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(pipeline.clone()).unwrap();
+        if !sets.is_empty() {
+            cb.bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                sets,
+            )
+            .unwrap();
+        }
+        cb.push_constants(pipeline.layout().clone(), 0, transform)
+            .unwrap()
+            .bind_vertex_buffers(0, vertices.clone())
+            .unwrap();
+
+        unsafe {
+            cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+
+    /// Builds a secondary command buffer that draws every mesh instance in `draws` from
+    /// `batch` on the current subpass, with one indirect draw when the device supports
+    /// `multi_draw_indirect`. Not available with custom shaders.
+    pub fn draw_indirect(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        batch: &MeshBatch,
+        draws: &IndirectDraws,
+        lights: Subbuffer<LightsUniform>,
+        maps: SurfaceMaps,
+    ) -> Arc<CommandBuffer> {
+        let pipeline = self
+            .indirect_variants
+            .get(maps.variant())
+            .expect("PSOLit was built from custom shaders, which can't draw indirect");
+        let features = self.gfx_queue.device().enabled_features();
+        assert!(
+            features.draw_indirect_first_instance,
+            "indirect draws need the draw_indirect_first_instance feature"
+        );
+
+        let mut cb = self.secondary(&self.subpass);
+        let sets = self.descriptor_sets(
+            pipeline,
+            [
+                WriteDescriptorSet::buffer(0, lights),
+                WriteDescriptorSet::buffer(1, draws.transforms.clone()),
+            ],
+            maps,
+        );
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                sets,
+            )
+            .unwrap()
+            .bind_vertex_buffers(0, batch.vertices())
+            .unwrap();
+
+        let commands: Vec<Subbuffer<[DrawIndirectCommand]>> = if features.multi_draw_indirect {
+            vec![draws.commands.clone()]
+        } else {
+            (0..draws.len() as u64)
+                .map(|i| draws.commands.clone().slice(i..i + 1))
+                .collect()
+        };
+        for commands in commands {
+            unsafe {
+                cb.draw_indirect(commands).unwrap();
+            }
+        }
+
+        cb.end().unwrap()
+    }
+
+    /// Builds a secondary command buffer that draws the depth of the mesh in the prepass. Needs
+    /// a pipeline built [`with_prepass`](PSOLit::with_prepass).
+    pub fn draw_depth(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        vertices: Subbuffer<[Vert]>,
+        transform: Transform,
+    ) -> Arc<CommandBuffer> {
+        let pipeline = self
+            .depth_only
+            .as_ref()
+            .expect("PSOLit was built without a prepass");
+        let mut cb = self.secondary(self.prepass_subpass.as_ref().unwrap());
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(pipeline.clone())
+            .unwrap()
+            .push_constants(pipeline.layout().clone(), 0, transform)
+            .unwrap()
+            .bind_vertex_buffers(0, vertices.clone())
+            .unwrap();
+
+        unsafe {
+            cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+
+    /// Set 0 with `globals` and, if any maps are given, set 1 with them. None if the pipeline
+    /// uses no descriptors, like some debug views.
+    fn descriptor_sets(
+        &self,
+        pipeline: &GraphicsPipeline,
+        globals: impl IntoIterator<Item = WriteDescriptorSet>,
+        maps: SurfaceMaps,
+    ) -> Vec<Arc<DescriptorSet>> {
+        let set_layouts = pipeline.layout().set_layouts();
+        if set_layouts.is_empty() {
+            return Vec::new();
+        }
+        let mut sets = vec![DescriptorSet::new(
+            self.ds_allocator.clone(),
+            set_layouts[0].clone(),
+            globals,
+            [],
+        )
+        .unwrap()];
+        let mut writes = Vec::new();
+        if let Some(shadow_map) = maps.shadow_map {
+            writes.push(WriteDescriptorSet::image_view_sampler(
+                0,
+                shadow_map.view(),
+                shadow_map.sampler(),
+            ));
+        }
+        if let Some(normal_map) = maps.normal_map {
+            writes.push(WriteDescriptorSet::image_view_sampler(
+                1,
+                normal_map,
+                self.normal_sampler.clone(),
+            ));
+        }
+        if let Some(lightmap) = maps.lightmap {
+            writes.push(WriteDescriptorSet::image_view_sampler(
+                2,
+                lightmap,
+                self.lightmap_sampler.clone(),
+            ));
+        }
+        if !writes.is_empty() {
+            sets.push(
+                DescriptorSet::new(
+                    self.ds_allocator.clone(),
+                    set_layouts[1].clone(),
+                    writes,
+                    [],
+                )
+                .unwrap(),
+            );
+        }
+        sets
+    }
+
+    fn secondary(&self, subpass: &Subpass) -> RecordingCommandBuffer {
+        RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+}
+
+/// With `depth_equal`, only fragments at the depth laid down by a prepass are shaded.
+fn build_pipeline(
+    subpass: &Subpass,
+    vs: EntryPoint,
+    fs: EntryPoint,
+    depth_equal: bool,
+    polygon_mode: PolygonMode,
+) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState {
+                topology: PrimitiveTopology::TriangleList,
+                ..Default::default()
+            }),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState {
+                polygon_mode,
+                ..Default::default()
+            }),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState::default(),
+            )),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(if depth_equal {
+                    DepthState {
+                        write_enable: false,
+                        compare_op: CompareOp::Equal,
+                    }
+                } else {
+                    DepthState::simple()
+                }),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+/// The lit vertex shader without a fragment shader, so prepass depth matches the shaded pass
+/// exactly.
+fn build_depth_pipeline(subpass: &Subpass, vs: EntryPoint) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+    let stages = [PipelineShaderStageCreateInfo::new(vs)];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(DepthState::simple()),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 normal;
+            layout(location = 2) in vec3 color;
+            layout(location = 3) in vec2 uv;
+            layout(location = 4) in vec4 tangent;
+            layout(location = 5) in vec2 lightmap_uv;
+            layout(location = 0) out vec3 v_world;
+            layout(location = 1) out vec3 v_normal;
+            layout(location = 2) out vec3 v_color;
+            layout(location = 3) out vec2 v_uv;
+            layout(location = 4) out vec4 v_tangent;
+            layout(location = 5) out vec2 v_lightmap_uv;
+
+            layout(push_constant) uniform Transform {
+                mat4 mvp;
+                mat4 model;
+            } pc;
+
+            // The prepass runs this shader too, both must produce the same depth.
+            invariant gl_Position;
+
+            void main() {
+                v_world = (pc.model * vec4(position, 1.0)).xyz;
+                v_normal = mat3(pc.model) * normal;
+                v_color = color;
+                v_uv = uv;
+                v_tangent = vec4(mat3(pc.model) * tangent.xyz, tangent.w);
+                v_lightmap_uv = lightmap_uv;
+                gl_Position = pc.mvp * vec4(position, 1.0);
+            }
+        ",
+    }
+}
+
+/// `vs` for indirect draws: each instance reads its transform from a storage buffer, indexed by
+/// the `first_instance` of its draw command.
+pub mod vs_indirect {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 normal;
+            layout(location = 2) in vec3 color;
+            layout(location = 3) in vec2 uv;
+            layout(location = 4) in vec4 tangent;
+            layout(location = 5) in vec2 lightmap_uv;
+            layout(location = 0) out vec3 v_world;
+            layout(location = 1) out vec3 v_normal;
+            layout(location = 2) out vec3 v_color;
+            layout(location = 3) out vec2 v_uv;
+            layout(location = 4) out vec4 v_tangent;
+            layout(location = 5) out vec2 v_lightmap_uv;
+
+            struct Transform {
+                mat4 mvp;
+                mat4 model;
+            };
+
+            layout(set = 0, binding = 1) readonly buffer Transforms {
+                Transform transforms[];
+            };
+
+            void main() {
+                Transform t = transforms[gl_InstanceIndex];
+                v_world = (t.model * vec4(position, 1.0)).xyz;
+                v_normal = mat3(t.model) * normal;
+                v_color = color;
+                v_uv = uv;
+                v_tangent = vec4(mat3(t.model) * tangent.xyz, tangent.w);
+                v_lightmap_uv = lightmap_uv;
+                gl_Position = t.mvp * vec4(position, 1.0);
+            }
+        ",
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+    }
+}
+
+pub mod fs_shadowed {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("SHADOWS", "1")],
+    }
+}
+
+pub mod fs_normal_mapped {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("NORMAL_MAP", "1")],
+    }
+}
+
+pub mod fs_normal_mapped_shadowed {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("SHADOWS", "1"), ("NORMAL_MAP", "1")],
+    }
+}
+
+pub mod fs_lightmapped {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("LIGHTMAP", "1")],
+    }
+}
+
+pub mod fs_lightmapped_shadowed {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("LIGHTMAP", "1"), ("SHADOWS", "1")],
+    }
+}
+
+pub mod fs_lightmapped_normal_mapped {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("LIGHTMAP", "1"), ("NORMAL_MAP", "1")],
+    }
+}
+
+pub mod fs_lightmapped_normal_mapped_shadowed {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/lit.frag",
+        define: [("LIGHTMAP", "1"), ("SHADOWS", "1"), ("NORMAL_MAP", "1")],
+    }
+}
+
+pub mod fs_debug_normals {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/debug.frag",
+        define: [("VIEW_NORMALS", "1")],
+    }
+}
+
+pub mod fs_debug_uvs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/debug.frag",
+        define: [("VIEW_UVS", "1")],
+    }
+}
+
+pub mod fs_debug_depth {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/graphics/pipelines/debug.frag",
+        define: [("VIEW_DEPTH", "1")],
+    }
+}
@@ -1,2 +1,75 @@
 pub mod basic;
+pub mod billboard;
+pub mod compute;
+pub mod foliage;
+pub mod lit;
+pub mod outline;
+pub mod points;
 pub mod texture;
+pub mod vat;
+pub mod water;
+
+use glam::Mat4;
+use vulkano::{
+    buffer::BufferContents,
+    pipeline::graphics::color_blend::{AttachmentBlend, BlendFactor, BlendOp},
+};
+
+/// How the 2D pipelines blend a drawn pixel over what is already there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Straight alpha: the color is scaled by its alpha while blending.
+    #[default]
+    Alpha,
+    /// The color was already scaled by its alpha, e.g. uploaded with `UploadOptions::premultiply`.
+    /// Filtering such textures doesn't bleed the color of transparent texels into the edges.
+    Premultiplied,
+}
+
+impl From<BlendMode> for AttachmentBlend {
+    fn from(mode: BlendMode) -> AttachmentBlend {
+        match mode {
+            BlendMode::Alpha => AttachmentBlend::alpha(),
+            BlendMode::Premultiplied => AttachmentBlend {
+                src_color_blend_factor: BlendFactor::One,
+                dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                color_blend_op: BlendOp::Add,
+                src_alpha_blend_factor: BlendFactor::One,
+                dst_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                alpha_blend_op: BlendOp::Add,
+            },
+        }
+    }
+}
+
+/// Maps the positions given to the 2D pipelines to clip space. Pushed with every draw of
+/// `PSOBasic` and `PSOTexture`, see their `set_projection`.
+#[derive(BufferContents, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct Projection2D {
+    pub matrix: [[f32; 4]; 4],
+}
+
+impl Default for Projection2D {
+    fn default() -> Self {
+        Projection2D::NDC
+    }
+}
+
+impl Projection2D {
+    /// Positions are already in normalized device coordinates, -1 to 1 with y down.
+    pub const NDC: Projection2D = Projection2D {
+        matrix: Mat4::IDENTITY.to_cols_array_2d(),
+    };
+
+    /// Positions are in pixels of a `size` wide and high surface, with the origin at the top
+    /// left and y down. Pass the logical size, e.g. `GraphicsContext::logical_size`, so shapes
+    /// keep their size on high DPI screens.
+    pub fn pixels(size: [f32; 2]) -> Self {
+        let scale = Mat4::from_scale([2.0 / size[0], 2.0 / size[1], 1.0].into());
+        let offset = Mat4::from_translation([-1.0, -1.0, 0.0].into());
+        Projection2D {
+            matrix: (offset * scale).to_cols_array_2d(),
+        }
+    }
+}
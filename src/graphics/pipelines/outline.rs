@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use glam::Mat4;
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer,
+    },
+    device::Queue,
+    pipeline::{
+        graphics::{
+            color_blend::{ColorBlendAttachmentState, ColorBlendState, ColorComponents},
+            depth_stencil::{
+                CompareOp, DepthStencilState, StencilOp, StencilOpState, StencilOps, StencilState,
+            },
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineLayout, PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+};
+
+use super::lit::Vert;
+use crate::graphics::{viewport::ViewportRegion, Color};
+
+/// The stencil value written under outlined meshes.
+const OUTLINE_REFERENCE: u32 = 1;
+
+/// What an outline looks like.
+#[derive(Debug, Clone, Copy)]
+pub struct Outline {
+    pub color: Color,
+    /// How far the outline reaches past the mesh, in the mesh's local units.
+    pub width: f32,
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Outline {
+            color: Color::rgb(255, 200, 0),
+            width: 0.05,
+        }
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct OutlinePush {
+    mvp: [[f32; 4]; 4],
+    color: [f32; 4],
+    width: f32,
+}
+
+/// Outlines selected meshes with the stencil buffer. The mesh is first drawn into the stencil
+/// only, then drawn again pushed out along its normals, tinted where the stencil wasn't marked.
+/// The outline is drawn over everything, so selections stay visible behind other meshes. Meshes
+/// with flat normals, like `Cube`, get small gaps in the outline at their corners.
+///
+/// Needs a subpass with a depth and stencil attachment, e.g. `RenderPassDepth`. Execute the
+/// outline after the mesh itself was drawn.
+pub struct PSOOutline {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    /// Writes the stencil reference under the mesh, no color.
+    pub mark: Arc<GraphicsPipeline>,
+    /// Draws the extruded mesh in a flat color outside the marked stencil.
+    pub outline: Arc<GraphicsPipeline>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+}
+
+impl PSOOutline {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ) -> Self {
+        let mark = build_pipeline(&subpass, true);
+        let outline = build_pipeline(&subpass, false);
+
+        Self {
+            gfx_queue,
+            subpass,
+            mark,
+            outline,
+            cb_allocator,
+        }
+    }
+
+    /// Builds a secondary command buffer that outlines the mesh, with `mvp` the camera
+    /// view-projection times the mesh's model matrix.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        vertices: Subbuffer<[Vert]>,
+        mvp: Mat4,
+        outline: Outline,
+    ) -> Arc<CommandBuffer> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(self.subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_vertex_buffers(0, vertices.clone()).unwrap();
+
+        let push = |width| OutlinePush {
+            mvp: mvp.to_cols_array_2d(),
+            color: outline.color.into(),
+            width,
+        };
+        for (pipeline, width) in [(&self.mark, 0.0), (&self.outline, outline.width)] {
+            cb.bind_pipeline_graphics(pipeline.clone())
+                .unwrap()
+                .push_constants(pipeline.layout().clone(), 0, push(width))
+                .unwrap();
+            unsafe {
+                cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+            }
+        }
+
+        cb.end().unwrap()
+    }
+}
+
+/// With `mark`, the pipeline only writes the stencil. Otherwise it only draws where the stencil
+/// isn't marked.
+fn build_pipeline(subpass: &Subpass, mark: bool) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vs = vs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let fs = fs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    let stencil = StencilOpState {
+        ops: if mark {
+            StencilOps {
+                fail_op: StencilOp::Keep,
+                pass_op: StencilOp::Replace,
+                depth_fail_op: StencilOp::Keep,
+                compare_op: CompareOp::Always,
+            }
+        } else {
+            StencilOps {
+                fail_op: StencilOp::Keep,
+                pass_op: StencilOp::Keep,
+                depth_fail_op: StencilOp::Keep,
+                compare_op: CompareOp::NotEqual,
+            }
+        },
+        compare_mask: u32::MAX,
+        write_mask: if mark { u32::MAX } else { 0 },
+        reference: OUTLINE_REFERENCE,
+    };
+
+    let color_write_mask = if mark {
+        ColorComponents::empty()
+    } else {
+        ColorComponents::all()
+    };
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState {
+                    color_write_mask,
+                    ..Default::default()
+                },
+            )),
+            // No depth test, the outline shows through whatever is in front.
+            depth_stencil_state: Some(DepthStencilState {
+                stencil: Some(StencilState {
+                    front: stencil,
+                    back: stencil,
+                }),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 normal;
+
+            layout(push_constant) uniform Outline {
+                mat4 mvp;
+                vec4 color;
+                float width;
+            } pc;
+
+            void main() {
+                gl_Position = pc.mvp * vec4(position + normalize(normal) * pc.width, 1.0);
+            }
+        ",
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) out vec4 f_color;
+
+            layout(push_constant) uniform Outline {
+                mat4 mvp;
+                vec4 color;
+                float width;
+            } pc;
+
+            void main() {
+                f_color = pc.color;
+            }
+        ",
+    }
+}
@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use glam::Mat4;
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer,
+    },
+    device::Queue,
+    pipeline::{
+        graphics::{
+            color_blend::{AttachmentBlend, ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{DepthState, DepthStencilState},
+            input_assembly::{InputAssemblyState, PrimitiveTopology},
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineLayout, PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+    shader::EntryPoint,
+};
+
+use crate::graphics::viewport::ViewportRegion;
+
+/// One point of a cloud.
+#[derive(BufferContents, Vertex, Clone, Copy)]
+#[repr(C)]
+pub struct Point {
+    /// In world space.
+    #[format(R32G32B32_SFLOAT)]
+    pub position: [f32; 3],
+    #[format(R32G32B32A32_SFLOAT)]
+    pub color: [f32; 4],
+    /// Diameter in pixels, clamped to what the device can draw.
+    #[format(R32_SFLOAT)]
+    pub size: f32,
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct PointsPush {
+    view_proj: [[f32; 4]; 4],
+    max_size: f32,
+}
+
+/// How each point is filled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PointShape {
+    /// A screen aligned square, the cheapest.
+    #[default]
+    Square,
+    /// A disc, the corners of the square are discarded.
+    Round,
+}
+
+/// Point clouds, e.g. sensor data, particles or debug samples, with a color and a size in pixels
+/// per point. Points are depth tested and blended with straight alpha. Sizes above one pixel need
+/// the device's `large_points` feature, without it every point is one pixel. Needs a subpass with
+/// a depth attachment, e.g. `RenderPassDepth`.
+pub struct PSOPoints {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    pub pipeline: Arc<GraphicsPipeline>,
+    /// Same as `pipeline`, drawing [`PointShape::Round`].
+    pub round: Arc<GraphicsPipeline>,
+    max_size: f32,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+}
+
+impl PSOPoints {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device();
+        let vs = vs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let fs = fs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let fs_round = fs_round::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let pipeline = build_pipeline(&subpass, vs.clone(), fs);
+        let round = build_pipeline(&subpass, vs, fs_round);
+
+        let max_size = if device.enabled_features().large_points {
+            device.physical_device().properties().point_size_range[1]
+        } else {
+            1.0
+        };
+
+        Self {
+            gfx_queue,
+            subpass,
+            pipeline,
+            round,
+            max_size,
+            cb_allocator,
+        }
+    }
+
+    /// The largest point size the device draws, in pixels.
+    pub fn max_size(&self) -> f32 {
+        self.max_size
+    }
+
+    /// Builds a secondary command buffer that draws every point in `points`, seen through
+    /// `view_proj`.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        points: Subbuffer<[Point]>,
+        view_proj: Mat4,
+        shape: PointShape,
+    ) -> Arc<CommandBuffer> {
+        let pipeline = match shape {
+            PointShape::Square => &self.pipeline,
+            PointShape::Round => &self.round,
+        };
+
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(self.subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let push = PointsPush {
+            view_proj: view_proj.to_cols_array_2d(),
+            max_size: self.max_size,
+        };
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(pipeline.clone())
+            .unwrap()
+            .push_constants(pipeline.layout().clone(), 0, push)
+            .unwrap()
+            .bind_vertex_buffers(0, points.clone())
+            .unwrap();
+
+        unsafe {
+            cb.draw(points.len() as u32, 1, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+}
+
+fn build_pipeline(subpass: &Subpass, vs: EntryPoint, fs: EntryPoint) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vertex_input_state = Point::per_vertex().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState {
+                topology: PrimitiveTopology::PointList,
+                ..Default::default()
+            }),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState {
+                    blend: Some(AttachmentBlend::alpha()),
+                    ..Default::default()
+                },
+            )),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(DepthState::simple()),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec4 color;
+            layout(location = 2) in float size;
+            layout(location = 0) out vec4 v_color;
+
+            layout(push_constant) uniform PointsPush {
+                mat4 view_proj;
+                float max_size;
+            } pc;
+
+            void main() {
+                v_color = color;
+                gl_PointSize = clamp(size, 1.0, pc.max_size);
+                gl_Position = pc.view_proj * vec4(position, 1.0);
+            }
+        ",
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec4 v_color;
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                f_color = v_color;
+            }
+        ",
+    }
+}
+
+pub mod fs_round {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec4 v_color;
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                // gl_PointCoord runs from 0 to 1 across the square.
+                if (length(gl_PointCoord - 0.5) > 0.5) {
+                    discard;
+                }
+                f_color = v_color;
+            }
+        ",
+    }
+}
@@ -11,19 +11,15 @@ use vulkano::{
         allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
     },
     device::Queue,
-    image::{
-        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
-        view::ImageView,
-        Image,
-    },
+    image::{view::ImageView, Image},
     pipeline::{
         graphics::{
-            color_blend::{AttachmentBlend, ColorBlendAttachmentState, ColorBlendState},
+            color_blend::{ColorBlendAttachmentState, ColorBlendState},
             input_assembly::{InputAssemblyState, PrimitiveTopology},
             multisample::MultisampleState,
             rasterization::RasterizationState,
             vertex_input::{self, Vertex, VertexDefinition},
-            viewport::{Viewport, ViewportState},
+            viewport::ViewportState,
             GraphicsPipelineCreateInfo,
         },
         layout::PipelineDescriptorSetLayoutCreateInfo,
@@ -31,6 +27,13 @@ use vulkano::{
         PipelineShaderStageCreateInfo,
     },
     render_pass::Subpass,
+    shader::EntryPoint,
+};
+
+use super::{BlendMode, Projection2D};
+use crate::graphics::{
+    sampler::{SamplerCache, SamplerDesc},
+    viewport::ViewportRegion,
 };
 
 #[derive(BufferContents, vertex_input::Vertex)]
@@ -38,12 +41,21 @@ use vulkano::{
 pub struct Vert {
     #[format(R32G32_SFLOAT)]
     pub position: [f32; 2],
+    #[format(R32G32_SFLOAT)]
+    pub uv: [f32; 2],
+    /// Multiplied with the sampled color, white leaves the image as is.
+    #[format(R32G32B32A32_SFLOAT)]
+    pub tint: [f32; 4],
 }
 
 pub struct PSOTexture {
     gfx_queue: Arc<Queue>,
     subpass: Subpass,
     pub pipeline: Arc<GraphicsPipeline>,
+    /// Same as `pipeline`, blending with [`BlendMode::Premultiplied`].
+    pub premultiplied: Arc<GraphicsPipeline>,
+    projection: Projection2D,
+    samplers: SamplerCache,
     cb_allocator: Arc<StandardCommandBufferAllocator>,
     ds_allocator: Arc<StandardDescriptorSetAllocator>,
 }
@@ -65,97 +77,60 @@ impl PSOTexture {
             .entry_point("main")
             .unwrap();
 
-        let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
-
-        let stages = [
-            PipelineShaderStageCreateInfo::new(vs),
-            PipelineShaderStageCreateInfo::new(fs),
-        ];
-
-        let layout = PipelineLayout::new(
-            device.clone(),
-            // Since we only have one pipeline in this example, and thus one pipeline layout,
-            // we automatically generate the creation info for it from the resources used in the
-            // shaders. In a real application, you would specify this information manually so that
-            // you can re-use one layout in multiple pipelines.
-            PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
-                .into_pipeline_layout_create_info(device.clone())
-                .unwrap(),
-        )
-        .unwrap();
+        Self::from_shaders(gfx_queue, subpass, cb_allocator, ds_allocator, vs, fs)
+    }
 
-        let pipeline = GraphicsPipeline::new(
-            device.clone(),
-            None,
-            GraphicsPipelineCreateInfo {
-                stages: stages.into_iter().collect(),
-                // How vertex data is read from the vertex buffers into the vertex shader.
-                vertex_input_state: Some(vertex_input_state),
-                // How vertices are arranged into primitive shapes.
-                // The default primitive shape is a triangle.
-                input_assembly_state: Some(InputAssemblyState {
-                    topology: PrimitiveTopology::TriangleStrip,
-                    ..Default::default()
-                }),
-                // How primitives are transformed and clipped to fit the framebuffer.
-                // We use a resizable viewport, set to draw over the entire window.
-                viewport_state: Some(ViewportState::default()),
-                // How polygons are culled and converted into a raster of pixels.
-                // The default value does not perform any culling.
-                rasterization_state: Some(RasterizationState::default()),
-                // How multiple fragment shader samples are converted to a single pixel value.
-                // The default value does not perform any multisampling.
-                multisample_state: Some(MultisampleState {
-                    rasterization_samples: subpass.num_samples().unwrap(),
-                    ..Default::default()
-                }),
-                // How pixel values are combined with the values already present in the framebuffer.
-                // The default value overwrites the old value with the new one, without any
-                // blending.
-                color_blend_state: Some(ColorBlendState::with_attachment_states(
-                    subpass.num_color_attachments(),
-                    ColorBlendAttachmentState {
-                        blend: Some(AttachmentBlend::alpha()),
-                        ..Default::default()
-                    },
-                )),
-                depth_stencil_state: None,
-                // Dynamic states allows us to specify parts of the pipeline settings when
-                // recording the command buffer, before we perform drawing.
-                // Here, we specify that the viewport should be dynamic.
-                dynamic_state: [DynamicState::Viewport].into_iter().collect(),
-                subpass: Some(subpass.clone().into()),
-                ..GraphicsPipelineCreateInfo::layout(layout)
-            },
-        )
-        .unwrap();
+    /// Builds the pipeline from caller-provided shaders, e.g. ones loaded through a
+    /// `ShaderLibrary`. The shaders must use the same vertex layout and descriptor bindings as
+    /// the built-in ones, though they may ignore the tint.
+    pub fn from_shaders(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+        vs: EntryPoint,
+        fs: EntryPoint,
+    ) -> Self {
+        let pipeline = build_pipeline(&subpass, vs.clone(), fs.clone(), BlendMode::Alpha);
+        let premultiplied = build_pipeline(&subpass, vs, fs, BlendMode::Premultiplied);
 
         Self {
+            samplers: SamplerCache::new(gfx_queue.device().clone()),
             gfx_queue,
             subpass,
             pipeline,
+            premultiplied,
+            projection: Projection2D::default(),
             cb_allocator,
             ds_allocator,
         }
     }
 
+    /// How positions are mapped to the screen for the following draws. Defaults to
+    /// [`Projection2D::NDC`].
+    pub fn set_projection(&mut self, projection: Projection2D) {
+        self.projection = projection;
+    }
+
+    pub fn projection(&self) -> Projection2D {
+        self.projection
+    }
+
     /// Builds a secondary command buffer that draws the triangle on the current subpass.
+    /// Samplers are created once per distinct `sampler` and reused.
     pub fn draw<V>(
-        &self,
-        viewport_dimensions: [u32; 2],
+        &mut self,
+        viewport: impl Into<ViewportRegion>,
         image: Arc<Image>,
+        sampler: SamplerDesc,
+        blend: BlendMode,
         vertices: Subbuffer<[V]>,
     ) -> Arc<CommandBuffer> {
-        let sampler = Sampler::new(
-            self.gfx_queue.device().clone(),
-            SamplerCreateInfo {
-                mag_filter: Filter::Linear,
-                min_filter: Filter::Linear,
-                address_mode: [SamplerAddressMode::Repeat; 3],
-                ..Default::default()
-            },
-        )
-        .unwrap();
+        let pipeline = match blend {
+            BlendMode::Alpha => &self.pipeline,
+            BlendMode::Premultiplied => &self.premultiplied,
+        };
+        let sampler = self.samplers.get(sampler);
 
         let mut cb = RecordingCommandBuffer::new(
             self.cb_allocator.clone(),
@@ -174,7 +149,7 @@ impl PSOTexture {
 
         let texture = ImageView::new_default(image).unwrap();
 
-        let layout = &self.pipeline.layout().set_layouts()[0];
+        let layout = &pipeline.layout().set_layouts()[0];
         let set = DescriptorSet::new(
             self.ds_allocator.clone(),
             layout.clone(),
@@ -186,28 +161,25 @@ impl PSOTexture {
         )
         .unwrap();
 
-        cb.set_viewport(
-            0,
-            [Viewport {
-                offset: [0.0, 0.0],
-                extent: [viewport_dimensions[0] as f32, viewport_dimensions[1] as f32],
-                depth_range: 0.0..=1.0,
-            }]
-            .into_iter()
-            .collect(),
-        )
-        .unwrap()
-        .bind_pipeline_graphics(self.pipeline.clone())
-        .unwrap()
-        .bind_descriptor_sets(
-            PipelineBindPoint::Graphics,
-            self.pipeline.layout().clone(),
-            0,
-            set.clone(),
-        )
-        .unwrap()
-        .bind_vertex_buffers(0, vertices.clone())
-        .unwrap();
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                pipeline.layout().clone(),
+                0,
+                set.clone(),
+            )
+            .unwrap()
+            .bind_vertex_buffers(0, vertices.clone())
+            .unwrap();
+
+        // Custom shaders may not take the projection.
+        if !pipeline.layout().push_constant_ranges().is_empty() {
+            cb.push_constants(pipeline.layout().clone(), 0, self.projection)
+                .unwrap();
+        }
 
         unsafe {
             cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
@@ -217,6 +189,81 @@ impl PSOTexture {
     }
 }
 
+fn build_pipeline(
+    subpass: &Subpass,
+    vs: EntryPoint,
+    fs: EntryPoint,
+    blend: BlendMode,
+) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        // Since we only have one pipeline in this example, and thus one pipeline layout,
+        // we automatically generate the creation info for it from the resources used in the
+        // shaders. In a real application, you would specify this information manually so that
+        // you can re-use one layout in multiple pipelines.
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            // How vertex data is read from the vertex buffers into the vertex shader.
+            vertex_input_state: Some(vertex_input_state),
+            // How vertices are arranged into primitive shapes. Textured quads and nine-patches
+            // are sent as separate triangles.
+            input_assembly_state: Some(InputAssemblyState {
+                topology: PrimitiveTopology::TriangleList,
+                ..Default::default()
+            }),
+            // How primitives are transformed and clipped to fit the framebuffer.
+            // We use a resizable viewport, set to draw over the entire window.
+            viewport_state: Some(ViewportState::default()),
+            // How polygons are culled and converted into a raster of pixels.
+            // The default value does not perform any culling.
+            rasterization_state: Some(RasterizationState::default()),
+            // How multiple fragment shader samples are converted to a single pixel value.
+            // The default value does not perform any multisampling.
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            // How pixel values are combined with the values already present in the framebuffer.
+            // The default value overwrites the old value with the new one, without any
+            // blending.
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState {
+                    blend: Some(blend.into()),
+                    ..Default::default()
+                },
+            )),
+            depth_stencil_state: None,
+            // Dynamic states allows us to specify parts of the pipeline settings when
+            // recording the command buffer, before we perform drawing.
+            // Here, we specify that the viewport should be dynamic.
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
 pub mod vs {
     vulkano_shaders::shader! {
         ty: "vertex",
@@ -224,11 +271,19 @@ pub mod vs {
             #version 450
 
             layout(location = 0) in vec2 position;
+            layout(location = 1) in vec2 uv;
+            layout(location = 2) in vec4 tint;
             layout(location = 0) out vec2 tex_coords;
+            layout(location = 1) out vec4 v_tint;
+
+            layout(push_constant) uniform Projection {
+                mat4 matrix;
+            } projection;
 
             void main() {
-                gl_Position = vec4(position, 0.0, 1.0);
-                tex_coords = position + vec2(0.5);
+                gl_Position = projection.matrix * vec4(position, 0.0, 1.0);
+                tex_coords = uv;
+                v_tint = tint;
             }
         ",
     }
@@ -241,13 +296,14 @@ pub mod fs {
             #version 450
 
             layout(location = 0) in vec2 tex_coords;
+            layout(location = 1) in vec4 v_tint;
             layout(location = 0) out vec4 f_color;
 
             layout(set = 0, binding = 0) uniform sampler s;
             layout(set = 0, binding = 1) uniform texture2D tex;
 
             void main() {
-                f_color = texture(sampler2D(tex, s), tex_coords);
+                f_color = texture(sampler2D(tex, s), tex_coords) * v_tint;
             }
         ",
     }
@@ -0,0 +1,291 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    image::{
+        sampler::{Filter, Sampler, SamplerCreateInfo},
+        view::ImageView,
+    },
+    pipeline::{
+        graphics::{
+            color_blend::{ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{DepthState, DepthStencilState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+};
+
+use super::lit::{self, Transform, Vert};
+use crate::graphics::{light::LightsUniform, viewport::ViewportRegion};
+
+/// A baked vertex animation: the offset of every vertex from its rest position, one row of
+/// texels per frame and one column per vertex, in an `R32G32B32A32_SFLOAT` image. Upload it with
+/// `GraphicsContext::upload_vertex_animation`.
+#[derive(Clone)]
+pub struct VertexAnimation {
+    pub offsets: Arc<ImageView>,
+    pub frame_count: u32,
+    /// Frames per second the animation was baked at.
+    pub frame_rate: f32,
+}
+
+impl VertexAnimation {
+    /// The two frames around `time` seconds into the looping animation and how far between them
+    /// it is.
+    pub fn frames_at(&self, time: f32) -> (u32, u32, f32) {
+        let frame = (time * self.frame_rate).rem_euclid(self.frame_count as f32);
+        let current = (frame.floor() as u32).min(self.frame_count - 1);
+        let next = (current + 1) % self.frame_count;
+        (current, next, frame.fract())
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct VatPush {
+    mvp: [[f32; 4]; 4],
+    model: [[f32; 4]; 4],
+    /// x and y are the rows to blend, z the blend factor.
+    frames: [f32; 4],
+}
+
+/// Lit meshes animated by a [`VertexAnimation`], for crowds and foliage where skinning every
+/// instance is too costly. The vertex shader offsets each vertex by its texel in the current
+/// and next frame and shades the result like `PSOLit` without any maps.
+///
+/// Normals keep their rest pose, so animations that rotate surfaces far are lit slightly off.
+/// The mesh can't have more vertices than the device's largest image width.
+pub struct PSOVat {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    pub pipeline: Arc<GraphicsPipeline>,
+    sampler: Arc<Sampler>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl PSOVat {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let pipeline = build_pipeline(&subpass);
+        let sampler = Sampler::new(
+            gfx_queue.device().clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Nearest,
+                min_filter: Filter::Nearest,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        Self {
+            gfx_queue,
+            subpass,
+            pipeline,
+            sampler,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Builds a secondary command buffer that draws the mesh `time` seconds into `animation`.
+    /// `vertices` must be in the order the animation was baked in.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        vertices: Subbuffer<[Vert]>,
+        transform: Transform,
+        lights: Subbuffer<LightsUniform>,
+        animation: &VertexAnimation,
+        time: f32,
+    ) -> Arc<CommandBuffer> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(self.subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let set_layouts = self.pipeline.layout().set_layouts();
+        let sets = vec![
+            DescriptorSet::new(
+                self.ds_allocator.clone(),
+                set_layouts[0].clone(),
+                [WriteDescriptorSet::buffer(0, lights)],
+                [],
+            )
+            .unwrap(),
+            DescriptorSet::new(
+                self.ds_allocator.clone(),
+                set_layouts[1].clone(),
+                [WriteDescriptorSet::image_view_sampler(
+                    0,
+                    animation.offsets.clone(),
+                    self.sampler.clone(),
+                )],
+                [],
+            )
+            .unwrap(),
+        ];
+
+        let (current, next, blend) = animation.frames_at(time);
+        let push = VatPush {
+            mvp: transform.mvp,
+            model: transform.model,
+            frames: [current as f32, next as f32, blend, 0.0],
+        };
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(self.pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                0,
+                sets,
+            )
+            .unwrap()
+            .push_constants(self.pipeline.layout().clone(), 0, push)
+            .unwrap()
+            .bind_vertex_buffers(0, vertices.clone())
+            .unwrap();
+
+        unsafe {
+            cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+}
+
+fn build_pipeline(subpass: &Subpass) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vs = vs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let fs = lit::fs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState::default(),
+            )),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(DepthState::simple()),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 normal;
+            layout(location = 2) in vec3 color;
+            layout(location = 3) in vec2 uv;
+            layout(location = 4) in vec4 tangent;
+            layout(location = 5) in vec2 lightmap_uv;
+            layout(location = 0) out vec3 v_world;
+            layout(location = 1) out vec3 v_normal;
+            layout(location = 2) out vec3 v_color;
+            layout(location = 3) out vec2 v_uv;
+            layout(location = 4) out vec4 v_tangent;
+            layout(location = 5) out vec2 v_lightmap_uv;
+
+            layout(push_constant) uniform VatPush {
+                mat4 mvp;
+                mat4 model;
+                vec4 frames;
+            } pc;
+
+            // Set 1 of the lit fragment shader is unused without maps.
+            layout(set = 1, binding = 0) uniform sampler2D offsets;
+
+            void main() {
+                vec3 current = texelFetch(offsets, ivec2(gl_VertexIndex, int(pc.frames.x)), 0).xyz;
+                vec3 next = texelFetch(offsets, ivec2(gl_VertexIndex, int(pc.frames.y)), 0).xyz;
+                vec3 animated = position + mix(current, next, pc.frames.z);
+
+                v_world = (pc.model * vec4(animated, 1.0)).xyz;
+                v_normal = mat3(pc.model) * normal;
+                v_color = color;
+                v_uv = uv;
+                v_tangent = vec4(mat3(pc.model) * tangent.xyz, tangent.w);
+                v_lightmap_uv = lightmap_uv;
+                gl_Position = pc.mvp * vec4(animated, 1.0);
+            }
+        ",
+    }
+}
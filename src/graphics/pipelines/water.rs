@@ -0,0 +1,422 @@
+use std::sync::Arc;
+
+use glam::Vec3;
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBuffer, CommandBufferBeginInfo,
+        CommandBufferInheritanceInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    format::Format,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+    },
+    memory::allocator::StandardMemoryAllocator,
+    pipeline::{
+        graphics::{
+            color_blend::{ColorBlendAttachmentState, ColorBlendState},
+            depth_stencil::{DepthState, DepthStencilState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::ViewportState,
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    render_pass::Subpass,
+};
+
+use crate::graphics::{
+    camera::Camera, render_target::RenderTarget, viewport::ViewportRegion, Color,
+};
+
+#[derive(BufferContents, Vertex, Clone, Copy)]
+#[repr(C)]
+pub struct Vert {
+    /// World space position on the water surface.
+    #[format(R32G32B32_SFLOAT)]
+    pub position: [f32; 3],
+}
+
+impl Vert {
+    /// A square of water `size` wide centered on `center`, as a triangle list.
+    pub fn plane(center: Vec3, size: f32) -> [Vert; 6] {
+        let h = size / 2.0;
+        let corner = |x: f32, z: f32| Vert {
+            position: [center.x + x, center.y, center.z + z],
+        };
+        [
+            corner(-h, -h),
+            corner(h, -h),
+            corner(h, h),
+            corner(-h, -h),
+            corner(h, h),
+            corner(-h, h),
+        ]
+    }
+}
+
+/// How a water surface looks.
+#[derive(Clone)]
+pub struct Water {
+    /// Tangent space ripples tiled over the surface, see `GraphicsContext::upload_normal_map`.
+    pub normal_map: Arc<ImageView>,
+    /// Multiplied with what is seen through the water.
+    pub tint: Color,
+    /// Normal map repeats per world unit.
+    pub tiling: f32,
+    /// How fast the ripples scroll, in normal map tiles per second.
+    pub wave_speed: f32,
+    /// How far the ripples bend the reflection and refraction, in screen space.
+    pub distortion: f32,
+    /// Higher values keep the reflection to grazing angles.
+    pub fresnel_power: f32,
+}
+
+impl Water {
+    pub fn new(normal_map: Arc<ImageView>) -> Self {
+        Water {
+            normal_map,
+            tint: Color::from([0.6, 0.8, 0.85, 1.0]),
+            tiling: 0.25,
+            wave_speed: 0.03,
+            distortion: 0.02,
+            fresnel_power: 5.0,
+        }
+    }
+}
+
+/// What the water reflects and what is seen through it, rendered each frame before the water is
+/// drawn. Both have to be the size of the frame the water is drawn into.
+pub struct WaterTargets {
+    /// The scene as seen by `PerspectiveCamera::reflected` at the water's height.
+    pub reflection: RenderTarget,
+    /// The scene as seen by the camera, without the water.
+    pub scene_color: RenderTarget,
+}
+
+impl WaterTargets {
+    pub fn new(
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        format: Format,
+        extent: [u32; 2],
+    ) -> Self {
+        WaterTargets {
+            reflection: RenderTarget::new(memory_allocator.clone(), format, extent),
+            scene_color: RenderTarget::new(memory_allocator, format, extent),
+        }
+    }
+
+    pub fn resize(&mut self, memory_allocator: Arc<StandardMemoryAllocator>, extent: [u32; 2]) {
+        self.reflection.resize(memory_allocator.clone(), extent);
+        self.scene_color.resize(memory_allocator, extent);
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct WaterPush {
+    mvp: [[f32; 4]; 4],
+    /// xyz is the camera position, w the time in seconds.
+    camera: [f32; 4],
+    tint: [f32; 4],
+    /// Tiling, wave speed, distortion and fresnel power.
+    params: [f32; 4],
+}
+
+/// A water surface that blends a planar reflection with the scene seen through it.
+///
+/// The reflection and the scene behind the water come from [`WaterTargets`], rendered before the
+/// pass the water is drawn in. Ripples from the normal map distort both, and a fresnel term
+/// favours the reflection at grazing angles. Needs a subpass with a depth attachment, e.g.
+/// `RenderPassDepth`.
+///
+/// Geometry below the water shows up in the reflection too, keep it out with the reflected
+/// camera's render layers.
+///
+/// # Examples
+/// ```ignore
+/// let mut targets = gfx.create_water_targets(gfx.window.inner_size().into());
+/// let reflected = camera.reflected(water_height);
+/// // ... render the scene with `reflected` into `targets.reflection.color()` and with `camera`
+/// // into `targets.scene_color.color()` ...
+/// draw_pass.execute(gfx.pipelines.water.draw(
+///     draw_pass.viewport_dimensions(),
+///     surface,
+///     &camera,
+///     &water,
+///     &targets,
+///     time,
+/// ));
+/// ```
+pub struct PSOWater {
+    gfx_queue: Arc<Queue>,
+    subpass: Subpass,
+    pub pipeline: Arc<GraphicsPipeline>,
+    /// Repeats the normal map across the surface.
+    repeat_sampler: Arc<Sampler>,
+    /// Keeps distorted screen space lookups from wrapping around the targets.
+    clamp_sampler: Arc<Sampler>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+}
+
+impl PSOWater {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        subpass: Subpass,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let pipeline = build_pipeline(&subpass);
+        let sampler = |address_mode| {
+            Sampler::new(
+                gfx_queue.device().clone(),
+                SamplerCreateInfo {
+                    mag_filter: Filter::Linear,
+                    min_filter: Filter::Linear,
+                    address_mode: [address_mode; 3],
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let repeat_sampler = sampler(SamplerAddressMode::Repeat);
+        let clamp_sampler = sampler(SamplerAddressMode::ClampToEdge);
+
+        Self {
+            gfx_queue,
+            subpass,
+            pipeline,
+            repeat_sampler,
+            clamp_sampler,
+            cb_allocator,
+            ds_allocator,
+        }
+    }
+
+    /// Builds a secondary command buffer that draws the water surface `time` seconds in.
+    pub fn draw(
+        &self,
+        viewport: impl Into<ViewportRegion>,
+        vertices: Subbuffer<[Vert]>,
+        camera: &dyn Camera,
+        water: &Water,
+        targets: &WaterTargets,
+        time: f32,
+    ) -> Arc<CommandBuffer> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Secondary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::MultipleSubmit,
+                inheritance_info: Some(CommandBufferInheritanceInfo {
+                    render_pass: Some(self.subpass.clone().into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let set = DescriptorSet::new(
+            self.ds_allocator.clone(),
+            self.pipeline.layout().set_layouts()[0].clone(),
+            [
+                WriteDescriptorSet::image_view_sampler(
+                    0,
+                    water.normal_map.clone(),
+                    self.repeat_sampler.clone(),
+                ),
+                WriteDescriptorSet::image_view_sampler(
+                    1,
+                    ImageView::new_default(targets.reflection.color()).unwrap(),
+                    self.clamp_sampler.clone(),
+                ),
+                WriteDescriptorSet::image_view_sampler(
+                    2,
+                    ImageView::new_default(targets.scene_color.color()).unwrap(),
+                    self.clamp_sampler.clone(),
+                ),
+            ],
+            [],
+        )
+        .unwrap();
+
+        let position = camera.position();
+        let push = WaterPush {
+            mvp: camera.mvp_mat().to_cols_array_2d(),
+            camera: [position.x, position.y, position.z, time],
+            tint: water.tint.into(),
+            params: [
+                water.tiling,
+                water.wave_speed,
+                water.distortion,
+                water.fresnel_power,
+            ],
+        };
+
+        viewport.into().set(&mut cb);
+
+        cb.bind_pipeline_graphics(self.pipeline.clone())
+            .unwrap()
+            .bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                self.pipeline.layout().clone(),
+                0,
+                set,
+            )
+            .unwrap()
+            .push_constants(self.pipeline.layout().clone(), 0, push)
+            .unwrap()
+            .bind_vertex_buffers(0, vertices.clone())
+            .unwrap();
+
+        unsafe {
+            cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+        }
+
+        cb.end().unwrap()
+    }
+}
+
+fn build_pipeline(subpass: &Subpass) -> Arc<GraphicsPipeline> {
+    let device = subpass.render_pass().device();
+    let vs = vs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let fs = fs::load(device.clone())
+        .unwrap()
+        .entry_point("main")
+        .unwrap();
+    let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(vertex_input_state),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState {
+                rasterization_samples: subpass.num_samples().unwrap(),
+                ..Default::default()
+            }),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState::default(),
+            )),
+            depth_stencil_state: Some(DepthStencilState {
+                depth: Some(DepthState::simple()),
+                ..Default::default()
+            }),
+            dynamic_state: [DynamicState::Viewport, DynamicState::Scissor]
+                .into_iter()
+                .collect(),
+            subpass: Some(subpass.clone().into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+pub mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+            layout(location = 0) out vec3 v_world;
+
+            layout(push_constant) uniform WaterPush {
+                mat4 mvp;
+                vec4 camera;
+                vec4 tint;
+                vec4 params;
+            } pc;
+
+            void main() {
+                v_world = position;
+                gl_Position = pc.mvp * vec4(position, 1.0);
+            }
+        ",
+    }
+}
+
+pub mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 v_world;
+            layout(location = 0) out vec4 f_color;
+
+            layout(push_constant) uniform WaterPush {
+                mat4 mvp;
+                vec4 camera;
+                vec4 tint;
+                vec4 params;
+            } pc;
+
+            layout(set = 0, binding = 0) uniform sampler2D normal_map;
+            layout(set = 0, binding = 1) uniform sampler2D reflection;
+            layout(set = 0, binding = 2) uniform sampler2D scene_color;
+
+            // Reflectance of water looking straight down.
+            const float F0 = 0.02;
+
+            void main() {
+                float time = pc.camera.w;
+                vec2 uv = v_world.xz * pc.params.x;
+                vec2 scroll = vec2(pc.params.y * time);
+
+                // Two layers scrolling against each other, so the ripples don't just slide.
+                vec3 ripple = texture(normal_map, uv + scroll).xyz
+                    + texture(normal_map, uv * 0.7 - scroll.yx).xyz;
+                ripple = ripple - 1.0;
+                // The surface faces up, so tangent space z is world y.
+                vec3 normal = normalize(vec3(ripple.x, ripple.z, ripple.y));
+
+                vec2 screen = gl_FragCoord.xy / vec2(textureSize(scene_color, 0));
+                vec2 offset = ripple.xy * pc.params.z;
+                vec3 reflected = texture(reflection, screen + offset).rgb;
+                vec3 refracted = texture(scene_color, screen + offset).rgb * pc.tint.rgb;
+
+                vec3 view = normalize(pc.camera.xyz - v_world);
+                float cos_theta = clamp(dot(normal, view), 0.0, 1.0);
+                float fresnel = F0 + (1.0 - F0) * pow(1.0 - cos_theta, pc.params.w);
+
+                f_color = vec4(mix(refracted, reflected, fresnel), 1.0);
+            }
+        ",
+    }
+}
@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::RecordingCommandBuffer,
+    device::Queue,
+    image::Image,
+    memory::allocator::StandardMemoryAllocator,
+    pipeline::{
+        graphics::color_blend::{AttachmentBlend, BlendFactor, BlendOp},
+        GraphicsPipeline,
+    },
+    render_pass::Subpass,
+    shader::{EntryPoint, ShaderModule},
+};
+
+use super::{fullscreen_pipeline, fullscreen_render_pass, PassRecorder, HDR_FORMAT};
+use crate::graphics::render_target::RenderTarget;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BloomSettings {
+    /// Brightness above which pixels start to bloom.
+    pub threshold: f32,
+    /// How strongly the blurred highlights are added back onto the frame.
+    pub intensity: f32,
+    /// Number of half-resolution blur steps. Only applied when the bloom is created or resized.
+    pub levels: u32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        BloomSettings {
+            threshold: 1.0,
+            intensity: 0.3,
+            levels: 5,
+        }
+    }
+}
+
+/// Bright-pass extraction followed by a downsample/upsample blur chain, composited back onto the
+/// frame. Enabled through `PostProcess::enable_bloom`.
+pub struct Bloom {
+    pub settings: BloomSettings,
+    bright: Arc<GraphicsPipeline>,
+    downsample: Arc<GraphicsPipeline>,
+    upsample: Arc<GraphicsPipeline>,
+    composite: Arc<GraphicsPipeline>,
+    mips: Vec<RenderTarget>,
+}
+
+impl Bloom {
+    pub(super) fn new(
+        gfx_queue: Arc<Queue>,
+        vs: EntryPoint,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+        settings: BloomSettings,
+    ) -> Self {
+        let device = gfx_queue.device().clone();
+        let clear_pass = Subpass::from(
+            fullscreen_render_pass(gfx_queue.clone(), HDR_FORMAT, false),
+            0,
+        )
+        .unwrap();
+        // The upsample chain adds onto the mip that is already there.
+        let load_pass = Subpass::from(
+            fullscreen_render_pass(gfx_queue.clone(), HDR_FORMAT, true),
+            0,
+        )
+        .unwrap();
+        let additive = AttachmentBlend {
+            src_color_blend_factor: BlendFactor::One,
+            dst_color_blend_factor: BlendFactor::One,
+            color_blend_op: BlendOp::Add,
+            src_alpha_blend_factor: BlendFactor::One,
+            dst_alpha_blend_factor: BlendFactor::One,
+            alpha_blend_op: BlendOp::Add,
+        };
+
+        let load = |module: Arc<ShaderModule>| module.entry_point("main").unwrap();
+        let bright = fullscreen_pipeline(
+            gfx_queue.clone(),
+            vs.clone(),
+            load(bright::load(device.clone()).unwrap()),
+            clear_pass.clone(),
+            None,
+        );
+        let downsample = fullscreen_pipeline(
+            gfx_queue.clone(),
+            vs.clone(),
+            load(downsample::load(device.clone()).unwrap()),
+            clear_pass.clone(),
+            None,
+        );
+        let upsample = fullscreen_pipeline(
+            gfx_queue.clone(),
+            vs.clone(),
+            load(upsample::load(device.clone()).unwrap()),
+            load_pass,
+            Some(additive),
+        );
+        let composite = fullscreen_pipeline(
+            gfx_queue,
+            vs,
+            load(composite::load(device).unwrap()),
+            clear_pass,
+            None,
+        );
+
+        Self {
+            settings,
+            bright,
+            downsample,
+            upsample,
+            composite,
+            mips: mip_chain(memory_allocator, extent, settings.levels),
+        }
+    }
+
+    pub(super) fn resize(
+        &mut self,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        extent: [u32; 2],
+    ) {
+        self.mips = mip_chain(memory_allocator, extent, self.settings.levels);
+    }
+
+    pub(super) fn record(
+        &self,
+        recorder: &PassRecorder,
+        cb: &mut RecordingCommandBuffer,
+        src: Arc<Image>,
+        dst: Arc<Image>,
+    ) {
+        let levels = self.mips.len();
+
+        recorder.record(
+            cb,
+            &self.bright,
+            &[src.clone()],
+            self.mips[0].color(),
+            [self.settings.threshold, 0.0, 0.0, 0.0],
+        );
+
+        for i in 0..levels - 1 {
+            recorder.record(
+                cb,
+                &self.downsample,
+                &[self.mips[i].color()],
+                self.mips[i + 1].color(),
+                [0.0; 4],
+            );
+        }
+
+        for i in (0..levels - 1).rev() {
+            recorder.record(
+                cb,
+                &self.upsample,
+                &[self.mips[i + 1].color()],
+                self.mips[i].color(),
+                [0.0; 4],
+            );
+        }
+
+        recorder.record(
+            cb,
+            &self.composite,
+            &[src, self.mips[0].color()],
+            dst,
+            [self.settings.intensity, 0.0, 0.0, 0.0],
+        );
+    }
+}
+
+fn mip_chain(
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    extent: [u32; 2],
+    levels: u32,
+) -> Vec<RenderTarget> {
+    (1..=levels.max(1))
+        .map(|level| {
+            let mip_extent = [(extent[0] >> level).max(1), (extent[1] >> level).max(1)];
+            RenderTarget::new(memory_allocator.clone(), HDR_FORMAT, mip_extent)
+        })
+        .collect()
+}
+
+mod bright {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            void main() {
+                vec3 color = texture(sampler2D(src, s), uv).rgb;
+                float brightness = max(color.r, max(color.g, color.b));
+                float contribution = max(brightness - pc.params.x, 0.0) / max(brightness, 0.0001);
+                f_color = vec4(color * contribution, 1.0);
+            }
+        ",
+    }
+}
+
+mod downsample {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            void main() {
+                vec2 t = pc.texel_size;
+                vec3 color = texture(sampler2D(src, s), uv + vec2(-t.x, -t.y)).rgb;
+                color += texture(sampler2D(src, s), uv + vec2(t.x, -t.y)).rgb;
+                color += texture(sampler2D(src, s), uv + vec2(-t.x, t.y)).rgb;
+                color += texture(sampler2D(src, s), uv + vec2(t.x, t.y)).rgb;
+                f_color = vec4(color * 0.25, 1.0);
+            }
+        ",
+    }
+}
+
+mod upsample {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            // 3x3 tent filter.
+            void main() {
+                vec2 t = pc.texel_size;
+                vec3 color = texture(sampler2D(src, s), uv).rgb * 4.0;
+                color += texture(sampler2D(src, s), uv + vec2(-t.x, 0.0)).rgb * 2.0;
+                color += texture(sampler2D(src, s), uv + vec2(t.x, 0.0)).rgb * 2.0;
+                color += texture(sampler2D(src, s), uv + vec2(0.0, -t.y)).rgb * 2.0;
+                color += texture(sampler2D(src, s), uv + vec2(0.0, t.y)).rgb * 2.0;
+                color += texture(sampler2D(src, s), uv + vec2(-t.x, -t.y)).rgb;
+                color += texture(sampler2D(src, s), uv + vec2(t.x, -t.y)).rgb;
+                color += texture(sampler2D(src, s), uv + vec2(-t.x, t.y)).rgb;
+                color += texture(sampler2D(src, s), uv + vec2(t.x, t.y)).rgb;
+                f_color = vec4(color / 16.0, 1.0);
+            }
+        ",
+    }
+}
+
+mod composite {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+            layout(set = 0, binding = 2) uniform texture2D bloom;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            void main() {
+                vec4 scene = texture(sampler2D(src, s), uv);
+                vec3 glow = texture(sampler2D(bloom, s), uv).rgb;
+                f_color = vec4(scene.rgb + glow * pc.params.x, scene.a);
+            }
+        ",
+    }
+}
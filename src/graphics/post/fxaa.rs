@@ -0,0 +1,197 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, RecordingCommandBuffer,
+    },
+    descriptor_set::allocator::StandardDescriptorSetAllocator,
+    device::Queue,
+    format::Format,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        Image,
+    },
+    memory::allocator::StandardMemoryAllocator,
+    pipeline::GraphicsPipeline,
+    render_pass::Subpass,
+    sync::GpuFuture,
+};
+
+use super::{fullscreen_pipeline, fullscreen_render_pass, vs, PassRecorder};
+use crate::graphics::{debug, render_target::RenderTarget};
+
+/// Fast approximate anti-aliasing as a single fullscreen pass.
+///
+/// The scene is rendered without multisampling into [`Fxaa::scene`] and filtered into the final
+/// image by [`Fxaa::run`]. Costs one extra single-sampled target instead of the 4x or 8x
+/// attachments MSAA needs, at the price of slightly blurrier edges and textures.
+pub struct Fxaa {
+    gfx_queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    recorder: PassRecorder,
+    pipeline: Arc<GraphicsPipeline>,
+    target: RenderTarget,
+}
+
+impl Fxaa {
+    /// Creates the pass for a final image of `format` and `extent`. The scene target uses the
+    /// same format, so the regular render passes and pipelines for that format can draw into it.
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        format: Format,
+        extent: [u32; 2],
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device();
+
+        let vs = vs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let fs = fxaa::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let pipeline = fullscreen_pipeline(
+            gfx_queue.clone(),
+            vs,
+            fs,
+            Subpass::from(fullscreen_render_pass(gfx_queue.clone(), format, false), 0).unwrap(),
+            None,
+        );
+
+        let sampler = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let target = RenderTarget::new(memory_allocator.clone(), format, extent);
+
+        Self {
+            gfx_queue,
+            memory_allocator,
+            cb_allocator,
+            recorder: PassRecorder {
+                ds_allocator,
+                sampler,
+            },
+            pipeline,
+            target,
+        }
+    }
+
+    /// The image the scene should be rendered into.
+    pub fn scene(&self) -> Arc<Image> {
+        self.target.color()
+    }
+
+    pub fn resize(&mut self, extent: [u32; 2]) {
+        self.target.resize(self.memory_allocator.clone(), extent);
+    }
+
+    /// Filters the scene into `final_image`.
+    pub fn run(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        final_image: Arc<Image>,
+    ) -> Box<dyn GpuFuture> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        debug::begin_region(&mut cb, "fxaa");
+        self.recorder.record(
+            &mut cb,
+            &self.pipeline,
+            &[self.target.color()],
+            final_image,
+            [0.0; 4],
+        );
+        debug::end_region(&mut cb);
+
+        before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+}
+
+mod fxaa {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            #define EDGE_MIN (1.0 / 128.0)
+            #define EDGE_MUL (1.0 / 8.0)
+            #define SPAN_MAX 8.0
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            float luma(vec3 color) {
+                return dot(color, vec3(0.299, 0.587, 0.114));
+            }
+
+            vec3 fetch(vec2 offset) {
+                return texture(sampler2D(src, s), uv + offset).rgb;
+            }
+
+            void main() {
+                vec2 t = pc.texel_size;
+                vec4 center = texture(sampler2D(src, s), uv);
+
+                float luma_nw = luma(fetch(vec2(-t.x, -t.y)));
+                float luma_ne = luma(fetch(vec2(t.x, -t.y)));
+                float luma_sw = luma(fetch(vec2(-t.x, t.y)));
+                float luma_se = luma(fetch(vec2(t.x, t.y)));
+                float luma_m = luma(center.rgb);
+
+                float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+                float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+                // Blur along the edge, perpendicular to the luma gradient.
+                vec2 dir = vec2(
+                    -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+                    (luma_nw + luma_sw) - (luma_ne + luma_se)
+                );
+                float reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * EDGE_MUL, EDGE_MIN);
+                float rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+                dir = clamp(dir * rcp_dir_min, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * t;
+
+                vec3 rgb_a = 0.5 * (fetch(dir * (1.0 / 3.0 - 0.5)) + fetch(dir * (2.0 / 3.0 - 0.5)));
+                vec3 rgb_b = rgb_a * 0.5 + 0.25 * (fetch(dir * -0.5) + fetch(dir * 0.5));
+
+                // The wider sample crossed into another edge, fall back to the narrow one.
+                float luma_b = luma(rgb_b);
+                vec3 rgb = (luma_b < luma_min || luma_b > luma_max) ? rgb_a : rgb_b;
+                f_color = vec4(rgb, center.a);
+            }
+        ",
+    }
+}
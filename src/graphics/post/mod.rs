@@ -0,0 +1,794 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::BufferContents,
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, RecordingCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo,
+        SubpassContents, SubpassEndInfo,
+    },
+    descriptor_set::{
+        allocator::StandardDescriptorSetAllocator, DescriptorSet, WriteDescriptorSet,
+    },
+    device::Queue,
+    format::Format,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+        Image,
+    },
+    memory::allocator::StandardMemoryAllocator,
+    pipeline::{
+        graphics::{
+            color_blend::{AttachmentBlend, ColorBlendAttachmentState, ColorBlendState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::VertexInputState,
+            viewport::{Viewport, ViewportState},
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineBindPoint, PipelineLayout,
+        PipelineShaderStageCreateInfo,
+    },
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
+    shader::EntryPoint,
+    sync::GpuFuture,
+};
+
+use super::{
+    context::{Pipelines, RenderPasses},
+    debug,
+    render_target::RenderTarget,
+};
+use crate::accessibility::ColorFilter;
+
+pub mod bloom;
+pub mod fxaa;
+
+pub use bloom::{Bloom, BloomSettings};
+pub use fxaa::Fxaa;
+
+/// Format of the offscreen targets the scene and the effects render into.
+pub const HDR_FORMAT: Format = Format::R16G16B16A16_SFLOAT;
+
+/// Push constants available to effect shaders.
+///
+/// An effect that uses them has to declare the whole block:
+/// ```glsl
+/// layout(push_constant) uniform Params {
+///     vec4 params;
+///     vec2 texel_size;
+/// } pc;
+/// ```
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct EffectParams {
+    params: [f32; 4],
+    texel_size: [f32; 2],
+}
+
+/// A fullscreen fragment shader pass registered with [`PostProcess`].
+pub struct PostEffect {
+    pipeline: Arc<GraphicsPipeline>,
+    /// Sampled at bindings 2.., after the previous result.
+    inputs: Vec<Arc<Image>>,
+    /// Passed to the shader as `pc.params`, the meaning is up to the effect.
+    pub params: [f32; 4],
+    pub enabled: bool,
+}
+
+/// Runs a chain of fullscreen passes over an HDR offscreen copy of the scene before resolving it
+/// to the final image.
+///
+/// Render the scene with [`PostProcess::render_passes`] and [`PostProcess::pipelines`] into
+/// [`PostProcess::scene`] instead of the swapchain image, then call [`PostProcess::run`] with the
+/// swapchain image. Effects are fragment shaders that read the previous result from
+/// `layout(set = 0, binding = 0) uniform sampler s` and
+/// `layout(set = 0, binding = 1) uniform texture2D src` at `layout(location = 0) in vec2 uv`, and
+/// the images they were added with from binding 2 on.
+///
+/// # Examples
+/// ```ignore
+/// let mut post = gfx.create_post_process();
+/// post.add_vignette(0.4);
+/// post.add_color_grading(gfx.upload_lut(include_bytes!("warm.png"))?, 1.0);
+/// post.add_gamma(2.2);
+///
+/// let future = gfx.start_frame().unwrap();
+/// let mut frame = post.render_passes.basic_msaa
+///     .frame(clear, future, post.scene(), gfx.memory_allocator.clone())
+///     .unwrap();
+/// // ... draw with post.pipelines ...
+/// let after = post.run(after_scene, gfx.final_images[gfx.image_index as usize].clone());
+/// gfx.finish_frame(after);
+/// ```
+pub struct PostProcess {
+    gfx_queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    hdr_pass: Arc<RenderPass>,
+    vs: EntryPoint,
+    sampler: Arc<Sampler>,
+    resolve: Arc<GraphicsPipeline>,
+    targets: [RenderTarget; 2],
+    effects: Vec<PostEffect>,
+    /// Index of the color filter effect, once one was set.
+    color_filter: Option<usize>,
+    bloom: Option<Bloom>,
+    pub render_passes: RenderPasses,
+    pub pipelines: Pipelines,
+}
+
+impl PostProcess {
+    /// Creates the chain for an `output_format` final image of `extent` pixels.
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        output_format: Format,
+        extent: [u32; 2],
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    ) -> Self {
+        let device = gfx_queue.device();
+
+        let hdr_pass = fullscreen_render_pass(gfx_queue.clone(), HDR_FORMAT, false);
+        let output_pass = fullscreen_render_pass(gfx_queue.clone(), output_format, false);
+
+        let vs = vs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let resolve_fs = resolve::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let resolve = fullscreen_pipeline(
+            gfx_queue.clone(),
+            vs.clone(),
+            resolve_fs,
+            Subpass::from(output_pass, 0).unwrap(),
+            None,
+        );
+
+        let sampler = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let targets = [
+            RenderTarget::new(memory_allocator.clone(), HDR_FORMAT, extent),
+            RenderTarget::new(memory_allocator.clone(), HDR_FORMAT, extent),
+        ];
+
+        let render_passes = RenderPasses::new(gfx_queue.clone(), HDR_FORMAT);
+        let pipelines = Pipelines::new(
+            gfx_queue.clone(),
+            &render_passes,
+            cb_allocator.clone(),
+            ds_allocator.clone(),
+        );
+
+        Self {
+            gfx_queue,
+            memory_allocator,
+            cb_allocator,
+            ds_allocator,
+            hdr_pass,
+            vs,
+            sampler,
+            resolve,
+            targets,
+            effects: Vec::new(),
+            color_filter: None,
+            bloom: None,
+            render_passes,
+            pipelines,
+        }
+    }
+
+    /// The HDR image the scene should be rendered into.
+    pub fn scene(&self) -> Arc<Image> {
+        self.targets[0].color()
+    }
+
+    pub fn resize(&mut self, extent: [u32; 2]) {
+        for target in self.targets.iter_mut() {
+            target.resize(self.memory_allocator.clone(), extent);
+        }
+        if let Some(bloom) = self.bloom.as_mut() {
+            bloom.resize(self.memory_allocator.clone(), extent);
+        }
+    }
+
+    /// Appends an effect to the end of the chain and returns its index.
+    pub fn add_effect(&mut self, fs: EntryPoint, params: [f32; 4]) -> usize {
+        self.add_effect_with_inputs(fs, params, Vec::new())
+    }
+
+    /// Like [`PostProcess::add_effect`], for shaders that also sample `inputs`, at bindings 2..
+    /// in order.
+    pub fn add_effect_with_inputs(
+        &mut self,
+        fs: EntryPoint,
+        params: [f32; 4],
+        inputs: Vec<Arc<Image>>,
+    ) -> usize {
+        let pipeline = fullscreen_pipeline(
+            self.gfx_queue.clone(),
+            self.vs.clone(),
+            fs,
+            Subpass::from(self.hdr_pass.clone(), 0).unwrap(),
+            None,
+        );
+
+        self.effects.push(PostEffect {
+            pipeline,
+            inputs,
+            params,
+            enabled: true,
+        });
+        self.effects.len() - 1
+    }
+
+    /// Darkens the corners of the frame; `strength` of 0 disables it.
+    pub fn add_vignette(&mut self, strength: f32) -> usize {
+        let fs = vignette::load(self.gfx_queue.device().clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        self.add_effect(fs, [strength, 0.0, 0.0, 0.0])
+    }
+
+    /// Applies `pow(color, 1 / gamma)`, for targets that are not sRGB.
+    pub fn add_gamma(&mut self, gamma: f32) -> usize {
+        let fs = gamma::load(self.gfx_queue.device().clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        self.add_effect(fs, [gamma, 0.0, 0.0, 0.0])
+    }
+
+    /// Maps colors through `lut`, blended with the original by `strength`.
+    ///
+    /// The LUT is a strip of `size` slices of `size` by `size` texels, e.g. 256x16 for a size of
+    /// 16: red increases to the right within a slice, green down the rows and blue from slice to
+    /// slice, see [`neutral_lut`]. Upload it with `GraphicsContext::upload_lut`. Colors are
+    /// looked up sRGB encoded, as they look on screen, so add it before [`PostProcess::add_gamma`].
+    pub fn add_color_grading(&mut self, lut: Arc<Image>, strength: f32) -> usize {
+        let [width, size, _] = lut.extent();
+        assert!(
+            width == size * size,
+            "a color grading LUT of size {size} has to be {} texels wide, not {width}",
+            size * size
+        );
+        let fs = color_grading::load(self.gfx_queue.device().clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        self.add_effect_with_inputs(fs, [strength, size as f32, 0.0, 0.0], vec![lut])
+    }
+
+    /// Sets or clears the filter for color vision deficiencies, e.g. from
+    /// `Accessibility::color_filter`. The filter is added to the end of the chain the first time,
+    /// later calls reuse it.
+    pub fn set_color_filter(&mut self, filter: Option<ColorFilter>) {
+        let index = match (self.color_filter, filter) {
+            (Some(index), _) => index,
+            (None, None) => return,
+            (None, Some(_)) => {
+                let fs = color_filter::load(self.gfx_queue.device().clone())
+                    .unwrap()
+                    .entry_point("main")
+                    .unwrap();
+                let index = self.add_effect(fs, [0.0; 4]);
+                self.color_filter = Some(index);
+                index
+            }
+        };
+
+        let effect = &mut self.effects[index];
+        effect.enabled = filter.is_some();
+        if let Some(filter) = filter {
+            effect.params = filter.params();
+        }
+    }
+
+    pub fn effect_mut(&mut self, index: usize) -> &mut PostEffect {
+        &mut self.effects[index]
+    }
+
+    /// Enables bloom, which runs before the registered effects.
+    pub fn enable_bloom(&mut self, settings: BloomSettings) {
+        let extent = self.targets[0].extent();
+        self.bloom = Some(Bloom::new(
+            self.gfx_queue.clone(),
+            self.vs.clone(),
+            self.memory_allocator.clone(),
+            extent,
+            settings,
+        ));
+    }
+
+    pub fn disable_bloom(&mut self) {
+        self.bloom = None;
+    }
+
+    pub fn bloom_mut(&mut self) -> Option<&mut Bloom> {
+        self.bloom.as_mut()
+    }
+
+    /// Runs every enabled effect over the scene and resolves the result into `final_image`.
+    pub fn run(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        final_image: Arc<Image>,
+    ) -> Box<dyn GpuFuture> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        debug::begin_region(&mut cb, "post_process");
+        let recorder = PassRecorder {
+            ds_allocator: self.ds_allocator.clone(),
+            sampler: self.sampler.clone(),
+        };
+
+        // Ping-pong between the two targets, the scene starts out in the first one.
+        let mut src = 0;
+        if let Some(bloom) = self.bloom.as_ref() {
+            let dst = 1 - src;
+            bloom.record(
+                &recorder,
+                &mut cb,
+                self.targets[src].color(),
+                self.targets[dst].color(),
+            );
+            src = dst;
+        }
+
+        for effect in self.effects.iter().filter(|e| e.enabled) {
+            let dst = 1 - src;
+            let mut sources = vec![self.targets[src].color()];
+            sources.extend(effect.inputs.iter().cloned());
+            recorder.record(
+                &mut cb,
+                &effect.pipeline,
+                &sources,
+                self.targets[dst].color(),
+                effect.params,
+            );
+            src = dst;
+        }
+
+        recorder.record(
+            &mut cb,
+            &self.resolve,
+            &[self.targets[src].color()],
+            final_image,
+            [0.0; 4],
+        );
+
+        // Whatever was last written has to be back in the first target for the next frame's
+        // scene, so swap instead of copying.
+        if src == 1 {
+            self.targets.swap(0, 1);
+        }
+        debug::end_region(&mut cb);
+
+        before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+}
+
+/// The RGBA pixels of a color grading LUT that leaves colors unchanged, `size * size` texels wide
+/// and `size` high. Save it as a PNG, grade it in an image editor along with a screenshot, and
+/// pass the result to [`PostProcess::add_color_grading`].
+pub fn neutral_lut(size: u32) -> Vec<u8> {
+    let level = |i: u32| (i * 255 / (size - 1).max(1)) as u8;
+    let mut pixels = Vec::with_capacity((size * size * size * 4) as usize);
+    for green in 0..size {
+        for blue in 0..size {
+            for red in 0..size {
+                pixels.extend([level(red), level(green), level(blue), 255]);
+            }
+        }
+    }
+    pixels
+}
+
+/// Records fullscreen passes that sample `sources` at bindings 1.. with a shared sampler at
+/// binding 0.
+struct PassRecorder {
+    ds_allocator: Arc<StandardDescriptorSetAllocator>,
+    sampler: Arc<Sampler>,
+}
+
+impl PassRecorder {
+    fn record(
+        &self,
+        cb: &mut RecordingCommandBuffer,
+        pipeline: &Arc<GraphicsPipeline>,
+        sources: &[Arc<Image>],
+        dst: Arc<Image>,
+        params: [f32; 4],
+    ) {
+        let src_extent = sources[0].extent();
+        let extent = dst.extent();
+        let framebuffer = Framebuffer::new(
+            pipeline.subpass().render_pass().clone(),
+            FramebufferCreateInfo {
+                attachments: vec![ImageView::new_default(dst).unwrap()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let layout = &pipeline.layout().set_layouts()[0];
+        let writes = [WriteDescriptorSet::sampler(0, self.sampler.clone())]
+            .into_iter()
+            .chain(sources.iter().enumerate().map(|(i, src)| {
+                WriteDescriptorSet::image_view(
+                    i as u32 + 1,
+                    ImageView::new_default(src.clone()).unwrap(),
+                )
+            }));
+        let set =
+            DescriptorSet::new(self.ds_allocator.clone(), layout.clone(), writes, []).unwrap();
+
+        cb.begin_render_pass(
+            RenderPassBeginInfo {
+                clear_values: vec![None],
+                ..RenderPassBeginInfo::framebuffer(framebuffer)
+            },
+            SubpassBeginInfo {
+                contents: SubpassContents::Inline,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .set_viewport(
+            0,
+            [Viewport {
+                offset: [0.0, 0.0],
+                extent: [extent[0] as f32, extent[1] as f32],
+                depth_range: 0.0..=1.0,
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap()
+        .bind_pipeline_graphics(pipeline.clone())
+        .unwrap()
+        .bind_descriptor_sets(
+            PipelineBindPoint::Graphics,
+            pipeline.layout().clone(),
+            0,
+            set,
+        )
+        .unwrap();
+
+        if !pipeline.layout().push_constant_ranges().is_empty() {
+            let params = EffectParams {
+                params,
+                texel_size: [1.0 / src_extent[0] as f32, 1.0 / src_extent[1] as f32],
+            };
+            cb.push_constants(pipeline.layout().clone(), 0, params)
+                .unwrap();
+        }
+
+        unsafe {
+            cb.draw(3, 1, 0, 0).unwrap();
+        }
+
+        cb.end_render_pass(SubpassEndInfo::default()).unwrap();
+    }
+}
+
+/// A single color attachment pass. With `load` the previous contents are kept so that passes can
+/// blend onto them, otherwise they are discarded.
+fn fullscreen_render_pass(gfx_queue: Arc<Queue>, format: Format, load: bool) -> Arc<RenderPass> {
+    let device = gfx_queue.device().clone();
+    if load {
+        vulkano::single_pass_renderpass!(
+            device,
+            attachments: {
+                color: {
+                    format: format,
+                    samples: 1,
+                    load_op: Load,
+                    store_op: Store,
+                },
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {},
+            },
+        )
+        .unwrap()
+    } else {
+        vulkano::single_pass_renderpass!(
+            device,
+            attachments: {
+                color: {
+                    format: format,
+                    samples: 1,
+                    load_op: DontCare,
+                    store_op: Store,
+                },
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {},
+            },
+        )
+        .unwrap()
+    }
+}
+
+/// Builds a pipeline that draws a single fullscreen triangle without vertex buffers.
+fn fullscreen_pipeline(
+    gfx_queue: Arc<Queue>,
+    vs: EntryPoint,
+    fs: EntryPoint,
+    subpass: Subpass,
+    blend: Option<AttachmentBlend>,
+) -> Arc<GraphicsPipeline> {
+    let device = gfx_queue.device();
+    let stages = [
+        PipelineShaderStageCreateInfo::new(vs),
+        PipelineShaderStageCreateInfo::new(fs),
+    ];
+
+    let layout = PipelineLayout::new(
+        device.clone(),
+        PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+            .into_pipeline_layout_create_info(device.clone())
+            .unwrap(),
+    )
+    .unwrap();
+
+    GraphicsPipeline::new(
+        device.clone(),
+        None,
+        GraphicsPipelineCreateInfo {
+            stages: stages.into_iter().collect(),
+            vertex_input_state: Some(VertexInputState::default()),
+            input_assembly_state: Some(InputAssemblyState::default()),
+            viewport_state: Some(ViewportState::default()),
+            rasterization_state: Some(RasterizationState::default()),
+            multisample_state: Some(MultisampleState::default()),
+            color_blend_state: Some(ColorBlendState::with_attachment_states(
+                subpass.num_color_attachments(),
+                ColorBlendAttachmentState {
+                    blend,
+                    ..Default::default()
+                },
+            )),
+            dynamic_state: [DynamicState::Viewport].into_iter().collect(),
+            subpass: Some(subpass.into()),
+            ..GraphicsPipelineCreateInfo::layout(layout)
+        },
+    )
+    .unwrap()
+}
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) out vec2 uv;
+
+            void main() {
+                uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+                gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+            }
+        ",
+    }
+}
+
+mod resolve {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            void main() {
+                f_color = clamp(texture(sampler2D(src, s), uv), 0.0, 1.0);
+            }
+        ",
+    }
+}
+
+mod vignette {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            void main() {
+                vec4 color = texture(sampler2D(src, s), uv);
+                float dist = distance(uv, vec2(0.5));
+                float falloff = smoothstep(0.8, 0.2, dist * (1.0 + pc.params.x));
+                f_color = vec4(color.rgb * mix(1.0, falloff, pc.params.x), color.a);
+            }
+        ",
+    }
+}
+
+mod gamma {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            void main() {
+                vec4 color = texture(sampler2D(src, s), uv);
+                f_color = vec4(pow(color.rgb, vec3(1.0 / pc.params.x)), color.a);
+            }
+        ",
+    }
+}
+
+mod color_filter {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+
+            // x: 0 protanopia, 1 deuteranopia, 2 tritanopia. y: 1 to correct instead of
+            // simulate. z: strength.
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            vec3 to_lms(vec3 c) {
+                return vec3(
+                    dot(vec3(17.8824, 43.5161, 4.11935), c),
+                    dot(vec3(3.45565, 27.1554, 3.86714), c),
+                    dot(vec3(0.0299566, 0.184309, 1.46709), c)
+                );
+            }
+
+            vec3 to_rgb(vec3 lms) {
+                return vec3(
+                    dot(vec3(0.0809444479, -0.130504409, 0.116721066), lms),
+                    dot(vec3(-0.0102485335, 0.0540193266, -0.113614708), lms),
+                    dot(vec3(-0.000365296938, -0.00412161469, 0.693511405), lms)
+                );
+            }
+
+            // Collapses the missing cone's response onto the other two.
+            vec3 simulate(vec3 c, int kind) {
+                vec3 lms = to_lms(c);
+                if (kind == 0) {
+                    lms.x = 2.02344 * lms.y - 2.52581 * lms.z;
+                } else if (kind == 1) {
+                    lms.y = 0.494207 * lms.x + 1.24827 * lms.z;
+                } else {
+                    lms.z = -0.395913 * lms.x + 0.801109 * lms.y;
+                }
+                return to_rgb(lms);
+            }
+
+            void main() {
+                vec4 color = texture(sampler2D(src, s), uv);
+                vec3 simulated = simulate(color.rgb, int(pc.params.x));
+
+                vec3 filtered = simulated;
+                if (pc.params.y > 0.5) {
+                    // Daltonize: move the lost difference into channels that are still seen.
+                    vec3 error = color.rgb - simulated;
+                    vec3 shift = vec3(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+                    filtered = color.rgb + shift;
+                }
+
+                f_color = vec4(mix(color.rgb, max(filtered, 0.0), pc.params.z), color.a);
+            }
+        ",
+    }
+}
+
+mod color_grading {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec2 uv;
+            layout(location = 0) out vec4 f_color;
+
+            layout(set = 0, binding = 0) uniform sampler s;
+            layout(set = 0, binding = 1) uniform texture2D src;
+            layout(set = 0, binding = 2) uniform texture2D lut;
+
+            // x: strength. y: size of the LUT.
+            layout(push_constant) uniform Params {
+                vec4 params;
+                vec2 texel_size;
+            } pc;
+
+            vec3 to_srgb(vec3 c) {
+                return mix(12.92 * c, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+            }
+
+            vec3 to_linear(vec3 c) {
+                return mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+            }
+
+            // Samples the two blue slices around the color and blends them, the sampler filters
+            // red and green within a slice.
+            vec3 lookup(vec3 c) {
+                float size = pc.params.y;
+                vec3 scaled = c * (size - 1.0);
+                float slice = floor(scaled.b);
+                float next = min(slice + 1.0, size - 1.0);
+                vec2 texel = scaled.rg + 0.5;
+                vec2 strip = vec2(size * size, size);
+                vec3 a = texture(sampler2D(lut, s), vec2(texel.x + slice * size, texel.y) / strip).rgb;
+                vec3 b = texture(sampler2D(lut, s), vec2(texel.x + next * size, texel.y) / strip).rgb;
+                return mix(a, b, scaled.b - slice);
+            }
+
+            void main() {
+                vec4 color = texture(sampler2D(src, s), uv);
+                vec3 graded = to_linear(lookup(to_srgb(clamp(color.rgb, 0.0, 1.0))));
+                f_color = vec4(mix(color.rgb, graded, pc.params.x), color.a);
+            }
+        ",
+    }
+}
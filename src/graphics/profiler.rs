@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, RecordingCommandBuffer,
+    },
+    device::Queue,
+    query::{QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType},
+    sync::{GpuFuture, PipelineStage},
+};
+
+/// Number of frames whose queries can be in flight at once. Results are read this many frames
+/// after they were recorded, when the GPU is done with them.
+const FRAMES: usize = 3;
+
+/// How long one scope took on the GPU in a frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PassTiming {
+    pub name: &'static str,
+    pub ms: f32,
+}
+
+/// A scope started with [`GpuProfiler::begin`], to pass to [`GpuProfiler::end`].
+#[must_use]
+pub struct ProfileScope {
+    index: u32,
+}
+
+struct FrameQueries {
+    pool: Arc<QueryPool>,
+    names: Vec<&'static str>,
+}
+
+/// Measures how long render passes take on the GPU with timestamp queries.
+///
+/// Call [`GpuProfiler::begin_frame`] once per frame, then wrap passes in [`GpuProfiler::time`],
+/// or write the markers into a command buffer being recorded with [`GpuProfiler::begin`] and
+/// [`GpuProfiler::end`]. The timings of a frame are available from [`GpuProfiler::timings`] a
+/// few frames later, once the GPU finished it.
+///
+/// # Examples
+/// ```ignore
+/// let mut profiler = gfx.create_gpu_profiler(16).expect("no timestamp support");
+///
+/// let future = profiler.begin_frame(gfx.start_frame()?);
+/// let future = profiler.time("scene", future, |future| draw_scene(&mut gfx, future));
+/// let future = profiler.time("post", future, |future| post.run(future, final_image));
+/// gfx.finish_frame(future)?;
+///
+/// for timing in profiler.timings() {
+///     println!("{}: {:.2} ms", timing.name, timing.ms);
+/// }
+/// ```
+pub struct GpuProfiler {
+    gfx_queue: Arc<Queue>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    frames: Vec<FrameQueries>,
+    current: usize,
+    max_scopes: u32,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// Mask of the bits of a timestamp that are valid.
+    valid_mask: u64,
+    timings: Vec<PassTiming>,
+}
+
+impl GpuProfiler {
+    /// Creates a profiler for up to `max_scopes` scopes per frame, or `None` if the queue can't
+    /// write timestamps.
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        max_scopes: u32,
+    ) -> Option<Self> {
+        let device = gfx_queue.device();
+        let physical_device = device.physical_device();
+        let valid_bits = physical_device.queue_family_properties()
+            [gfx_queue.queue_family_index() as usize]
+            .timestamp_valid_bits?;
+        let valid_mask = if valid_bits >= 64 {
+            u64::MAX
+        } else {
+            (1 << valid_bits) - 1
+        };
+
+        let frames = (0..FRAMES)
+            .map(|_| FrameQueries {
+                pool: QueryPool::new(
+                    device.clone(),
+                    QueryPoolCreateInfo {
+                        query_count: max_scopes * 2,
+                        ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+                    },
+                )
+                .unwrap(),
+                names: Vec::new(),
+            })
+            .collect();
+
+        Some(Self {
+            gfx_queue,
+            cb_allocator,
+            frames,
+            current: 0,
+            max_scopes,
+            period: physical_device.properties().timestamp_period,
+            valid_mask,
+            timings: Vec::new(),
+        })
+    }
+
+    /// The timings of the most recent finished frame, in the order the scopes began.
+    pub fn timings(&self) -> &[PassTiming] {
+        &self.timings
+    }
+
+    /// How long the scope called `name` took in the most recent finished frame.
+    pub fn timing(&self, name: &str) -> Option<f32> {
+        self.timings
+            .iter()
+            .find(|timing| timing.name == name)
+            .map(|timing| timing.ms)
+    }
+
+    /// The sum of all scopes of the most recent finished frame.
+    pub fn total_ms(&self) -> f32 {
+        self.timings.iter().map(|timing| timing.ms).sum()
+    }
+
+    /// Starts a new frame: collects the results of the frame that last used this frame's
+    /// queries, and resets them after `before_future`.
+    pub fn begin_frame(&mut self, before_future: Box<dyn GpuFuture>) -> Box<dyn GpuFuture> {
+        self.current = (self.current + 1) % FRAMES;
+        self.collect();
+
+        let frame = &mut self.frames[self.current];
+        frame.names.clear();
+        let pool = frame.pool.clone();
+
+        let mut cb = self.primary();
+        unsafe {
+            cb.reset_query_pool(pool, 0..self.max_scopes * 2).unwrap();
+        }
+        before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+
+    /// Writes the start marker of a scope called `name` into `cb`, outside of a render pass.
+    /// Scopes past `max_scopes` in a frame aren't measured.
+    pub fn begin(&mut self, cb: &mut RecordingCommandBuffer, name: &'static str) -> ProfileScope {
+        let frame = &mut self.frames[self.current];
+        let index = frame.names.len() as u32;
+        if index < self.max_scopes {
+            frame.names.push(name);
+            unsafe {
+                cb.write_timestamp(frame.pool.clone(), index * 2, PipelineStage::TopOfPipe)
+                    .unwrap();
+            }
+        }
+        ProfileScope { index }
+    }
+
+    /// Writes the end marker of `scope` into `cb`, outside of a render pass.
+    pub fn end(&mut self, cb: &mut RecordingCommandBuffer, scope: ProfileScope) {
+        if scope.index < self.max_scopes {
+            let frame = &self.frames[self.current];
+            unsafe {
+                cb.write_timestamp(
+                    frame.pool.clone(),
+                    scope.index * 2 + 1,
+                    PipelineStage::BottomOfPipe,
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    /// Measures the work `f` submits after `before_future`, e.g. a render pass `frame` or a
+    /// post-processing chain.
+    pub fn time<F>(
+        &mut self,
+        name: &'static str,
+        before_future: Box<dyn GpuFuture>,
+        f: F,
+    ) -> Box<dyn GpuFuture>
+    where
+        F: FnOnce(Box<dyn GpuFuture>) -> Box<dyn GpuFuture>,
+    {
+        let mut cb = self.primary();
+        let scope = self.begin(&mut cb, name);
+        let future = before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed();
+
+        let future = f(future);
+
+        let mut cb = self.primary();
+        self.end(&mut cb, scope);
+        future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+
+    /// Replaces the timings with the results of the current frame's queries, if the GPU has
+    /// written all of them.
+    fn collect(&mut self) {
+        let frame = &self.frames[self.current];
+        if frame.names.is_empty() {
+            return;
+        }
+
+        let mut ticks = vec![0u64; frame.names.len() * 2];
+        let ready = frame
+            .pool
+            .get_results(0..ticks.len() as u32, &mut ticks, QueryResultFlags::empty())
+            .unwrap_or(false);
+        if !ready {
+            return;
+        }
+
+        self.timings = frame
+            .names
+            .iter()
+            .zip(ticks.chunks_exact(2))
+            .map(|(&name, pair)| {
+                let elapsed = pair[1].wrapping_sub(pair[0]) & self.valid_mask;
+                PassTiming {
+                    name,
+                    ms: elapsed as f32 * self.period / 1_000_000.0,
+                }
+            })
+            .collect();
+    }
+
+    fn primary(&self) -> RecordingCommandBuffer {
+        RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+}
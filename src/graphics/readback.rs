@@ -0,0 +1,123 @@
+//! Copies GPU buffers and images back to the host, e.g. to inspect compute results in tests.
+//!
+//! Each read records a copy after `before_future`, submits it and blocks until the GPU is done.
+//! vulkano's synchronization inserts the barriers between earlier writes and the copy. Sources
+//! need `TRANSFER_SRC` usage.
+
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer},
+    command_buffer::{
+        allocator::CommandBufferAllocator, BufferImageCopy, CommandBufferBeginInfo,
+        CommandBufferLevel, CommandBufferUsage, CopyBufferInfo, CopyImageToBufferInfo,
+        RecordingCommandBuffer,
+    },
+    device::Queue,
+    image::Image,
+    memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
+    sync::GpuFuture,
+    DeviceSize,
+};
+
+/// Copies `buffer` to the host.
+pub fn read_buffer<T>(
+    queue: Arc<Queue>,
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    cb_allocator: Arc<dyn CommandBufferAllocator>,
+    before_future: Box<dyn GpuFuture>,
+    buffer: Subbuffer<[T]>,
+) -> Vec<T>
+where
+    T: BufferContents + Clone,
+{
+    let host = host_buffer::<T>(memory_allocator, buffer.len());
+    let mut cb = primary(&queue, cb_allocator);
+    cb.copy_buffer(CopyBufferInfo::buffers(buffer, host.clone()))
+        .unwrap();
+    submit_and_wait(queue, before_future, cb);
+
+    let data = host.read().unwrap().to_vec();
+    data
+}
+
+/// Copies the `extent` pixels of `image` starting at `offset` to the host, tightly packed in the
+/// image's format. Only the first layer and mip level are read.
+pub fn read_image_region(
+    queue: Arc<Queue>,
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    cb_allocator: Arc<dyn CommandBufferAllocator>,
+    before_future: Box<dyn GpuFuture>,
+    image: Arc<Image>,
+    offset: [u32; 2],
+    extent: [u32; 2],
+) -> Vec<u8> {
+    let size = extent[0] as DeviceSize * extent[1] as DeviceSize * image.format().block_size();
+    let host = host_buffer::<u8>(memory_allocator, size);
+
+    let mut subresource = image.subresource_layers();
+    subresource.array_layers = 0..1;
+    let mut cb = primary(&queue, cb_allocator);
+    cb.copy_image_to_buffer(CopyImageToBufferInfo {
+        regions: [BufferImageCopy {
+            image_subresource: subresource,
+            image_offset: [offset[0], offset[1], 0],
+            image_extent: [extent[0], extent[1], 1],
+            ..Default::default()
+        }]
+        .into(),
+        ..CopyImageToBufferInfo::image_buffer(image, host.clone())
+    })
+    .unwrap();
+    submit_and_wait(queue, before_future, cb);
+
+    let pixels = host.read().unwrap().to_vec();
+    pixels
+}
+
+fn host_buffer<T: BufferContents>(
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    len: DeviceSize,
+) -> Subbuffer<[T]> {
+    Buffer::new_slice::<T>(
+        memory_allocator,
+        BufferCreateInfo {
+            usage: BufferUsage::TRANSFER_DST,
+            ..Default::default()
+        },
+        AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_HOST
+                | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+            ..Default::default()
+        },
+        len,
+    )
+    .unwrap()
+}
+
+fn primary(queue: &Queue, cb_allocator: Arc<dyn CommandBufferAllocator>) -> RecordingCommandBuffer {
+    RecordingCommandBuffer::new(
+        cb_allocator,
+        queue.queue_family_index(),
+        CommandBufferLevel::Primary,
+        CommandBufferBeginInfo {
+            usage: CommandBufferUsage::OneTimeSubmit,
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+fn submit_and_wait(
+    queue: Arc<Queue>,
+    before_future: Box<dyn GpuFuture>,
+    cb: RecordingCommandBuffer,
+) {
+    before_future
+        .then_execute(queue, cb.end().unwrap())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+}
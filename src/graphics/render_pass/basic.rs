@@ -16,6 +16,11 @@ use vulkano::{
     Validated, ValidationError, VulkanError,
 };
 
+use crate::graphics::{
+    debug,
+    viewport::{SplitScreen, ViewportRegion},
+};
+
 pub struct RenderPassBasic {
     pub gfx_queue: Arc<Queue>,
     pub render_pass: Arc<RenderPass>,
@@ -94,6 +99,7 @@ impl RenderPassBasic {
                 ..Default::default()
             },
         )?;
+        debug::begin_region(&mut command_buffer, "basic");
         command_buffer.begin_render_pass(
             RenderPassBeginInfo {
                 clear_values: vec![Some(clear_color.into()), Some(clear_color.into())],
@@ -146,6 +152,7 @@ impl<'a> BasicFrame<'a> {
                         .as_mut()
                         .unwrap()
                         .end_render_pass(SubpassEndInfo::default())?;
+                    debug::end_region(self.command_buffer.as_mut().unwrap());
                     let command_buffer = self.command_buffer.take().unwrap().end().unwrap();
 
                     let after_main_cb = self
@@ -178,6 +185,11 @@ impl<'f, 's: 'f> BasicDrawPass<'f, 's> {
         self.frame.framebuffer.extent()
     }
 
+    /// The regions of `players` sharing this frame, see [`SplitScreen::regions`].
+    pub fn split_screen(&self, layout: SplitScreen, players: u32) -> Vec<ViewportRegion> {
+        layout.regions(players, self.viewport_dimensions())
+    }
+
     /// Appends a command that executes a secondary command buffer that performs drawing.
     #[inline]
     pub fn execute(
@@ -271,6 +283,7 @@ impl RenderPassBasicMSAA {
                 ..Default::default()
             },
         )?;
+        debug::begin_region(&mut command_buffer, "basic_msaa");
         command_buffer.begin_render_pass(
             RenderPassBeginInfo {
                 clear_values: vec![Some(clear_color.into()), Some(clear_color.into())],
@@ -325,6 +338,7 @@ impl<'a> BasicMSAAFrame<'a> {
                         .as_mut()
                         .unwrap()
                         .end_render_pass(SubpassEndInfo::default())?;
+                    debug::end_region(self.command_buffer.as_mut().unwrap());
                     let command_buffer = self.command_buffer.take().unwrap().end().unwrap();
 
                     let after_main_cb = self
@@ -357,6 +371,11 @@ impl<'f, 's: 'f> BasicMSAADrawPass<'f, 's> {
         self.frame.framebuffer.extent()
     }
 
+    /// The regions of `players` sharing this frame, see [`SplitScreen::regions`].
+    pub fn split_screen(&self, layout: SplitScreen, players: u32) -> Vec<ViewportRegion> {
+        layout.regions(players, self.viewport_dimensions())
+    }
+
     /// Appends a command that executes a secondary command buffer that performs drawing.
     #[inline]
     pub fn execute(
@@ -378,22 +397,21 @@ fn framebuffer_setup(
     memory_allocator: Arc<StandardMemoryAllocator>,
 ) -> Arc<Framebuffer> {
     let extent = image.extent();
-    let intermediary = ImageView::new_default(
-        Image::new(
-            memory_allocator.clone(),
-            ImageCreateInfo {
-                image_type: ImageType::Dim2d,
-                format: image.format(),
-                extent: [extent[0], extent[1], 1],
-                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSIENT_ATTACHMENT,
-                samples: SampleCount::Sample4,
-                ..Default::default()
-            },
-            AllocationCreateInfo::default(),
-        )
-        .unwrap(),
+    let intermediary = Image::new(
+        memory_allocator.clone(),
+        ImageCreateInfo {
+            image_type: ImageType::Dim2d,
+            format: image.format(),
+            extent: [extent[0], extent[1], 1],
+            usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSIENT_ATTACHMENT,
+            samples: SampleCount::Sample4,
+            ..Default::default()
+        },
+        AllocationCreateInfo::default(),
     )
     .unwrap();
+    debug::set_name(&*intermediary, "msaa_intermediary");
+    let intermediary = ImageView::new_default(intermediary).unwrap();
 
     let view = ImageView::new_default(image.clone()).unwrap();
     Framebuffer::new(
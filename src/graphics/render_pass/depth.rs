@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{
+        allocator::{CommandBufferAllocator, StandardCommandBufferAllocator},
+        CommandBuffer, CommandBufferBeginInfo, CommandBufferLevel, CommandBufferUsage,
+        RecordingCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo, SubpassContents,
+        SubpassEndInfo,
+    },
+    device::Queue,
+    format::Format,
+    image::{view::ImageView, Image, ImageCreateInfo, ImageType, ImageUsage, SampleCount},
+    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
+    sync::GpuFuture,
+    Validated, ValidationError, VulkanError,
+};
+
+use crate::graphics::{
+    debug,
+    render_target::DEPTH_STENCIL_FORMAT,
+    viewport::{SplitScreen, ViewportRegion},
+};
+
+/// A multisampled pass with a depth and stencil buffer, for 3D scenes where draw order shouldn't
+/// decide what is visible.
+///
+/// Built [`with_prepass`](RenderPassDepth::with_prepass), the frame starts with a depth-only
+/// subpass. Meshes drawn into it first let the shaded subpass test for equal depth, so each pixel
+/// runs the expensive fragment shader once.
+pub struct RenderPassDepth {
+    pub gfx_queue: Arc<Queue>,
+    pub render_pass: Arc<RenderPass>,
+    pub cb_allocator: Arc<dyn CommandBufferAllocator>,
+    prepass: bool,
+}
+
+impl RenderPassDepth {
+    pub fn new(gfx_queue: Arc<Queue>, format: Format) -> Result<Self, Validated<VulkanError>> {
+        let device = gfx_queue.device().clone();
+        let render_pass = vulkano::single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                intermediary: {
+                    format: format,
+                    // This has to match the image definition.
+                    samples: 4,
+                    load_op: Clear,
+                    store_op: DontCare,
+                },
+                depth: {
+                    format: DEPTH_STENCIL_FORMAT,
+                    samples: 4,
+                    load_op: Clear,
+                    store_op: DontCare,
+                },
+                color: {
+                    format: format,
+                    samples: 1,
+                    load_op: Clear,
+                    store_op: Store,
+                },
+            },
+            pass: {
+                color: [intermediary],
+                color_resolve: [color],
+                depth_stencil: {depth},
+            },
+        )?;
+
+        Ok(Self::from_render_pass(gfx_queue, render_pass, false))
+    }
+
+    /// Like [`RenderPassDepth::new`], with a depth prepass before the shaded subpass.
+    pub fn with_prepass(
+        gfx_queue: Arc<Queue>,
+        format: Format,
+    ) -> Result<Self, Validated<VulkanError>> {
+        let device = gfx_queue.device().clone();
+        let render_pass = vulkano::ordered_passes_renderpass!(
+            device.clone(),
+            attachments: {
+                intermediary: {
+                    format: format,
+                    samples: 4,
+                    load_op: Clear,
+                    store_op: DontCare,
+                },
+                depth: {
+                    format: DEPTH_STENCIL_FORMAT,
+                    samples: 4,
+                    load_op: Clear,
+                    store_op: DontCare,
+                },
+                color: {
+                    format: format,
+                    samples: 1,
+                    load_op: Clear,
+                    store_op: Store,
+                },
+            },
+            passes: [
+                {
+                    color: [],
+                    depth_stencil: {depth},
+                    input: [],
+                },
+                {
+                    color: [intermediary],
+                    color_resolve: [color],
+                    depth_stencil: {depth},
+                    input: [],
+                },
+            ],
+        )?;
+
+        Ok(Self::from_render_pass(gfx_queue, render_pass, true))
+    }
+
+    fn from_render_pass(
+        gfx_queue: Arc<Queue>,
+        render_pass: Arc<RenderPass>,
+        prepass: bool,
+    ) -> Self {
+        let cb_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            gfx_queue.device().clone(),
+            Default::default(),
+        ));
+
+        Self {
+            gfx_queue,
+            render_pass,
+            cb_allocator,
+            prepass,
+        }
+    }
+
+    pub fn has_prepass(&self) -> bool {
+        self.prepass
+    }
+
+    pub fn cb_allocator(&self) -> Arc<dyn CommandBufferAllocator> {
+        self.cb_allocator.clone()
+    }
+
+    pub fn queue(&self) -> Arc<Queue> {
+        self.gfx_queue.clone()
+    }
+
+    pub fn frame<F>(
+        &mut self,
+        clear_color: [f32; 4],
+        before_future: F,
+        final_image: Arc<Image>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+    ) -> Result<DepthFrame, Validated<VulkanError>>
+    where
+        F: GpuFuture + 'static,
+    {
+        let framebuffer = framebuffer_setup(
+            final_image.clone(),
+            self.render_pass.clone(),
+            memory_allocator.clone(),
+        );
+
+        let mut command_buffer = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )?;
+        let region = if self.prepass {
+            "depth_prepass"
+        } else {
+            "depth"
+        };
+        debug::begin_region(&mut command_buffer, region);
+        command_buffer.begin_render_pass(
+            RenderPassBeginInfo {
+                clear_values: vec![
+                    Some(clear_color.into()),
+                    Some((1.0, 0).into()),
+                    Some(clear_color.into()),
+                ],
+
+                ..RenderPassBeginInfo::framebuffer(framebuffer.clone())
+            },
+            SubpassBeginInfo {
+                contents: SubpassContents::SecondaryCommandBuffers,
+                ..Default::default()
+            },
+        )?;
+        Ok(DepthFrame {
+            system: self,
+            num_pass: 0,
+            framebuffer,
+            before_main_cb_future: Some(before_future.boxed()),
+            command_buffer: Some(command_buffer),
+        })
+    }
+
+    /// The shaded subpass. With a prepass, pipelines drawn here should test for equal depth
+    /// without writing it.
+    pub fn draw_pass(&self) -> Subpass {
+        let index = if self.prepass { 1 } else { 0 };
+        Subpass::from(self.render_pass.clone(), index).unwrap()
+    }
+
+    /// The depth-only subpass, if there is one.
+    pub fn prepass(&self) -> Option<Subpass> {
+        self.prepass
+            .then(|| Subpass::from(self.render_pass.clone(), 0).unwrap())
+    }
+}
+
+pub struct DepthFrame<'a> {
+    system: &'a mut RenderPassDepth,
+    num_pass: u8,
+    framebuffer: Arc<Framebuffer>,
+    before_main_cb_future: Option<Box<dyn GpuFuture>>,
+    command_buffer: Option<RecordingCommandBuffer>,
+}
+
+impl<'a> DepthFrame<'a> {
+    pub fn next_pass<'f>(&'f mut self) -> Result<Option<DepthPass<'f, 'a>>, Box<ValidationError>> {
+        Ok(
+            match {
+                let current_pass = self.num_pass;
+                self.num_pass += 1;
+                current_pass
+            } {
+                0 if self.system.prepass => Some(DepthPass::Prepass(DepthDrawPass { frame: self })),
+                1 if self.system.prepass => {
+                    self.command_buffer.as_mut().unwrap().next_subpass(
+                        SubpassEndInfo::default(),
+                        SubpassBeginInfo {
+                            contents: SubpassContents::SecondaryCommandBuffers,
+                            ..Default::default()
+                        },
+                    )?;
+                    Some(DepthPass::Draw(DepthDrawPass { frame: self }))
+                }
+                0 => Some(DepthPass::Draw(DepthDrawPass { frame: self })),
+                n if n == 1 + self.system.prepass as u8 => {
+                    self.command_buffer
+                        .as_mut()
+                        .unwrap()
+                        .end_render_pass(SubpassEndInfo::default())?;
+                    debug::end_region(self.command_buffer.as_mut().unwrap());
+                    let command_buffer = self.command_buffer.take().unwrap().end().unwrap();
+
+                    let after_main_cb = self
+                        .before_main_cb_future
+                        .take()
+                        .unwrap()
+                        .then_execute(self.system.gfx_queue.clone(), command_buffer)
+                        .unwrap(); // TODO convert back to error type
+                    Some(DepthPass::Finished(after_main_cb.boxed()))
+                }
+                _ => None,
+            },
+        )
+    }
+}
+
+/// Struct provided to the user that allows them to customize or handle the pass.
+pub enum DepthPass<'f, 's: 'f> {
+    /// Only with a prepass. Draw depth only, e.g. with `PSOLit::draw_depth`.
+    Prepass(DepthDrawPass<'f, 's>),
+    Draw(DepthDrawPass<'f, 's>),
+    Finished(Box<dyn GpuFuture>),
+}
+
+/// Allows the user to draw objects on the scene.
+pub struct DepthDrawPass<'f, 's: 'f> {
+    frame: &'f mut DepthFrame<'s>,
+}
+
+impl<'f, 's: 'f> DepthDrawPass<'f, 's> {
+    pub fn viewport_dimensions(&self) -> [u32; 2] {
+        self.frame.framebuffer.extent()
+    }
+
+    /// The regions of `players` sharing this frame, see [`SplitScreen::regions`].
+    pub fn split_screen(&self, layout: SplitScreen, players: u32) -> Vec<ViewportRegion> {
+        layout.regions(players, self.viewport_dimensions())
+    }
+
+    /// Appends a command that executes a secondary command buffer that performs drawing.
+    #[inline]
+    pub fn execute(
+        &mut self,
+        command_buffer: Arc<CommandBuffer>,
+    ) -> Result<(), Box<ValidationError>> {
+        self.frame
+            .command_buffer
+            .as_mut()
+            .unwrap()
+            .execute_commands(command_buffer)?;
+        Ok(())
+    }
+}
+
+fn framebuffer_setup(
+    image: Arc<Image>,
+    render_pass: Arc<RenderPass>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+) -> Arc<Framebuffer> {
+    let extent = image.extent();
+    let transient = |format: Format, usage: ImageUsage, name: &str| {
+        let image = Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format,
+                extent: [extent[0], extent[1], 1],
+                usage: usage | ImageUsage::TRANSIENT_ATTACHMENT,
+                samples: SampleCount::Sample4,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap();
+        debug::set_name(&*image, name);
+        ImageView::new_default(image).unwrap()
+    };
+
+    let intermediary = transient(
+        image.format(),
+        ImageUsage::COLOR_ATTACHMENT,
+        "msaa_intermediary",
+    );
+    let depth = transient(
+        DEPTH_STENCIL_FORMAT,
+        ImageUsage::DEPTH_STENCIL_ATTACHMENT,
+        "msaa_depth",
+    );
+
+    let view = ImageView::new_default(image.clone()).unwrap();
+    Framebuffer::new(
+        render_pass.clone(),
+        FramebufferCreateInfo {
+            attachments: vec![intermediary, depth, view],
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
@@ -15,6 +15,11 @@ use vulkano::{
     sync::GpuFuture,
     Validated, ValidationError, VulkanError,
 };
+
+use crate::graphics::{
+    debug,
+    viewport::{SplitScreen, ViewportRegion},
+};
 use vulkano::{image::view::ImageView, render_pass::FramebufferCreateInfo};
 
 pub struct RenderPassOverlay {
@@ -82,6 +87,7 @@ impl RenderPassOverlay {
                 ..Default::default()
             },
         )?;
+        debug::begin_region(&mut command_buffer, "overlay");
         command_buffer.begin_render_pass(
             RenderPassBeginInfo {
                 clear_values: vec![None],
@@ -136,6 +142,7 @@ impl<'a> OverlayFrame<'a> {
                         .as_mut()
                         .unwrap()
                         .end_render_pass(SubpassEndInfo::default())?;
+                    debug::end_region(self.command_buffer.as_mut().unwrap());
                     let command_buffer = self.command_buffer.take().unwrap().end().unwrap();
 
                     let after_main_cb = self
@@ -168,6 +175,11 @@ impl<'f, 's: 'f> OverlayDrawPass<'f, 's> {
         self.frame.framebuffer.extent()
     }
 
+    /// The regions of `players` sharing this frame, see [`SplitScreen::regions`].
+    pub fn split_screen(&self, layout: SplitScreen, players: u32) -> Vec<ViewportRegion> {
+        layout.regions(players, self.viewport_dimensions())
+    }
+
     /// Appends a command that executes a secondary command buffer that performs drawing.
     #[inline]
     pub fn execute(
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use vulkano::{
+    format::Format,
+    image::{Image, ImageCreateInfo, ImageType, ImageUsage},
+    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+};
+
+pub const DEPTH_FORMAT: Format = Format::D32_SFLOAT;
+
+/// Depth with a stencil, for the 3D pass where stencil effects like outlines need it.
+pub const DEPTH_STENCIL_FORMAT: Format = Format::D32_SFLOAT_S8_UINT;
+
+/// An image that can be rendered into and then sampled, e.g. for minimaps, mirrors or
+/// post-processing.
+///
+/// Pass [`RenderTarget::color`] as the final image of a render pass `frame`, then draw it with
+/// `PSOTexture` like any uploaded image. The color format has to match the format the render pass
+/// was created with; `GraphicsContext::create_render_target` takes care of that.
+pub struct RenderTarget {
+    format: Format,
+    color: Arc<Image>,
+    depth: Option<Arc<Image>>,
+}
+
+impl RenderTarget {
+    pub fn new(
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        format: Format,
+        extent: [u32; 2],
+    ) -> Self {
+        RenderTarget {
+            format,
+            color: color_image(memory_allocator, format, extent),
+            depth: None,
+        }
+    }
+
+    /// Like [`RenderTarget::new`] but also owns a sampled depth image of [`DEPTH_FORMAT`].
+    pub fn with_depth(
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        format: Format,
+        extent: [u32; 2],
+    ) -> Self {
+        RenderTarget {
+            format,
+            color: color_image(memory_allocator.clone(), format, extent),
+            depth: Some(depth_image(memory_allocator, extent)),
+        }
+    }
+
+    pub fn color(&self) -> Arc<Image> {
+        self.color.clone()
+    }
+
+    pub fn depth(&self) -> Option<Arc<Image>> {
+        self.depth.clone()
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn extent(&self) -> [u32; 2] {
+        let extent = self.color.extent();
+        [extent[0], extent[1]]
+    }
+
+    /// Recreates the images at the new size. Previously returned images are left untouched.
+    pub fn resize(&mut self, memory_allocator: Arc<StandardMemoryAllocator>, extent: [u32; 2]) {
+        if self.extent() == extent {
+            return;
+        }
+
+        self.color = color_image(memory_allocator.clone(), self.format, extent);
+        if self.depth.is_some() {
+            self.depth = Some(depth_image(memory_allocator, extent));
+        }
+    }
+}
+
+fn color_image(
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    format: Format,
+    extent: [u32; 2],
+) -> Arc<Image> {
+    Image::new(
+        memory_allocator,
+        ImageCreateInfo {
+            image_type: ImageType::Dim2d,
+            format,
+            extent: [extent[0], extent[1], 1],
+            usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED | ImageUsage::TRANSFER_SRC,
+            ..Default::default()
+        },
+        AllocationCreateInfo::default(),
+    )
+    .unwrap()
+}
+
+fn depth_image(memory_allocator: Arc<StandardMemoryAllocator>, extent: [u32; 2]) -> Arc<Image> {
+    Image::new(
+        memory_allocator,
+        ImageCreateInfo {
+            image_type: ImageType::Dim2d,
+            format: DEPTH_FORMAT,
+            extent: [extent[0], extent[1], 1],
+            usage: ImageUsage::DEPTH_STENCIL_ATTACHMENT | ImageUsage::SAMPLED,
+            ..Default::default()
+        },
+        AllocationCreateInfo::default(),
+    )
+    .unwrap()
+}
@@ -0,0 +1,33 @@
+//! Recovery from losing the GPU or the window surface, as events systems can query.
+//!
+//! When a frame fails with a lost device or surface, [`GraphicsContext`] rebuilds what was lost
+//! instead of failing every frame after. Pass the resets to the world with
+//! [`GraphicsContext::emit_resets`]; each is spawned as an entity with a [`GraphicsReset`]
+//! component and despawned at the end of the next update, like a
+//! [`FileDrop`](crate::file_drop::FileDrop).
+//!
+//! [`GraphicsContext`]: super::context::GraphicsContext
+//! [`GraphicsContext::emit_resets`]: super::context::GraphicsContext::emit_resets
+
+use hecs::World;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsReset {
+    /// The device was recreated. Everything the app created with the old one is unusable and
+    /// has to be created again: textures, buffers, render targets, post-processing chains and
+    /// the context's `previous_frame_end`.
+    DeviceLost,
+    /// The surface and swapchain were recreated. Only the swapchain images changed.
+    SurfaceLost,
+}
+
+pub(crate) fn clear(world: &mut World) {
+    let entities: Vec<_> = world
+        .query::<&GraphicsReset>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.despawn(entity).unwrap();
+    }
+}
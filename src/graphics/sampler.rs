@@ -0,0 +1,199 @@
+//! Sampler configuration and a cache so equal configurations share one `Sampler`.
+//!
+//! ```ignore
+//! let tiles = Texture::new(0.5).with_sampler(SamplerDesc::nearest());
+//! let ground = Texture::new(1.0)
+//!     .with_tiling([8.0, 8.0], WrapMode::Repeat)
+//!     .with_sampler(SamplerDesc::default().with_anisotropy(16.0));
+//! ```
+
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use vulkano::{
+    device::Device,
+    image::sampler::{
+        Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode, LOD_CLAMP_NONE,
+    },
+    pipeline::graphics::depth_stencil::CompareOp,
+};
+
+/// How an image is filtered and addressed when sampled. Defaults to linear filtering and
+/// repeating in every direction.
+#[derive(Debug, Clone, Copy)]
+pub struct SamplerDesc {
+    pub mag_filter: Filter,
+    pub min_filter: Filter,
+    pub mipmap_mode: SamplerMipmapMode,
+    /// For the u, v and w coordinates.
+    pub address_mode: [SamplerAddressMode; 3],
+    /// Maximum anisotropy, clamped to the device's limit. `None` or without device support
+    /// the sampler isn't anisotropic.
+    pub anisotropy: Option<f32>,
+    /// Added to the mip level the hardware picks, clamped to the device's limit.
+    pub mip_lod_bias: f32,
+    /// Compares samples against a reference instead of returning them, for shadow maps.
+    pub compare: Option<CompareOp>,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        SamplerDesc {
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_mode: SamplerMipmapMode::Linear,
+            address_mode: [SamplerAddressMode::Repeat; 3],
+            anisotropy: None,
+            mip_lod_bias: 0.0,
+            compare: None,
+        }
+    }
+}
+
+/// The fields of a [`SamplerDesc`] with floats as bits.
+type DescKey = (
+    Filter,
+    Filter,
+    SamplerMipmapMode,
+    [SamplerAddressMode; 3],
+    Option<u32>,
+    u32,
+    Option<CompareOp>,
+);
+
+// Floats compare by their bits, so a desc can key the cache.
+impl PartialEq for SamplerDesc {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SamplerDesc {}
+
+impl Hash for SamplerDesc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl SamplerDesc {
+    fn key(&self) -> DescKey {
+        (
+            self.mag_filter,
+            self.min_filter,
+            self.mipmap_mode,
+            self.address_mode,
+            self.anisotropy.map(f32::to_bits),
+            self.mip_lod_bias.to_bits(),
+            self.compare,
+        )
+    }
+
+    /// Nearest filtering, for pixel art.
+    pub fn nearest() -> Self {
+        SamplerDesc {
+            mag_filter: Filter::Nearest,
+            min_filter: Filter::Nearest,
+            mipmap_mode: SamplerMipmapMode::Nearest,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the magnification and minification filter.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.mag_filter = filter;
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn with_mipmap_mode(mut self, mipmap_mode: SamplerMipmapMode) -> Self {
+        self.mipmap_mode = mipmap_mode;
+        self
+    }
+
+    /// Sets the address mode of every coordinate.
+    pub fn with_address_mode(mut self, address_mode: SamplerAddressMode) -> Self {
+        self.address_mode = [address_mode; 3];
+        self
+    }
+
+    /// Up to `max` samples along the direction the surface slopes away, e.g. 16 for ground
+    /// seen at a flat angle.
+    pub fn with_anisotropy(mut self, max: f32) -> Self {
+        self.anisotropy = Some(max);
+        self
+    }
+
+    /// Positive values pick blurrier mip levels, negative ones sharper.
+    pub fn with_mip_lod_bias(mut self, bias: f32) -> Self {
+        self.mip_lod_bias = bias;
+        self
+    }
+
+    pub fn with_compare(mut self, compare: CompareOp) -> Self {
+        self.compare = Some(compare);
+        self
+    }
+}
+
+/// Creates samplers on first use and hands out the same one for equal descs afterwards.
+pub struct SamplerCache {
+    device: Arc<Device>,
+    samplers: HashMap<SamplerDesc, Arc<Sampler>>,
+}
+
+impl SamplerCache {
+    pub fn new(device: Arc<Device>) -> Self {
+        SamplerCache {
+            device,
+            samplers: HashMap::new(),
+        }
+    }
+
+    /// The sampler for `desc`, fitted to what the device supports.
+    pub fn get(&mut self, desc: SamplerDesc) -> Arc<Sampler> {
+        let device = &self.device;
+        self.samplers
+            .entry(desc)
+            .or_insert_with(|| {
+                let properties = device.physical_device().properties();
+                // Anisotropy needs the `sampler_anisotropy` feature, which `GraphicsContext`
+                // enables when the device has it.
+                let anisotropy = desc
+                    .anisotropy
+                    .filter(|_| device.enabled_features().sampler_anisotropy)
+                    .map(|max| max.clamp(1.0, properties.max_sampler_anisotropy))
+                    .filter(|&max| max > 1.0);
+                let max_bias = properties.max_sampler_lod_bias;
+                Sampler::new(
+                    device.clone(),
+                    SamplerCreateInfo {
+                        mag_filter: desc.mag_filter,
+                        min_filter: desc.min_filter,
+                        mipmap_mode: desc.mipmap_mode,
+                        address_mode: desc.address_mode,
+                        mip_lod_bias: desc.mip_lod_bias.clamp(-max_bias, max_bias),
+                        anisotropy,
+                        compare: desc.compare,
+                        // Every mip level the image has, otherwise only the first is sampled.
+                        lod: 0.0..=LOD_CLAMP_NONE,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+            })
+            .clone()
+    }
+
+    /// How many distinct samplers were created.
+    pub fn len(&self) -> usize {
+        self.samplers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samplers.is_empty()
+    }
+}
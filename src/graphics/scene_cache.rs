@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, CopyImageInfo, RecordingCommandBuffer,
+    },
+    device::Queue,
+    format::Format,
+    image::Image,
+    memory::allocator::StandardMemoryAllocator,
+    sync::GpuFuture,
+};
+
+use super::{debug, render_target::RenderTarget};
+
+/// Keeps a rendering of a scene that rarely changes, so frames only copy it to the final image
+/// instead of drawing everything again, e.g. for editors and visualizations.
+///
+/// The cached image is independent of the swapchain: it survives swapchain recreation and is
+/// only redrawn after [`SceneCache::invalidate`] or a resize. Dynamic content like cursors,
+/// selection highlights or UI is drawn over the composited image with an overlay pass every
+/// frame. Create it with `GraphicsContext::create_scene_cache`, and again after a
+/// `GraphicsReset::DeviceLost`.
+///
+/// # Examples
+/// ```ignore
+/// let mut cache = gfx.create_scene_cache();
+///
+/// let future = gfx.start_frame()?;
+/// let future = cache.render(future, |future, image| {
+///     let mut frame = gfx.render_passes.basic_msaa
+///         .frame(clear, future, image, gfx.memory_allocator.clone())
+///         .unwrap();
+///     let mut after = None;
+///     while let Some(pass) = frame.next_pass().unwrap() {
+///         match pass {
+///             BasicMSAAPass::Draw(mut draw_pass) => { /* ... draw the static scene ... */ }
+///             BasicMSAAPass::Finished(af) => after = Some(af),
+///         }
+///     }
+///     after.unwrap()
+/// });
+/// let future = cache.composite(future, gfx.scene_image());
+/// // ... draw the overlays with gfx.render_passes.overlay ...
+/// gfx.finish_frame(future)?;
+///
+/// // Once the scene changed:
+/// cache.invalidate();
+/// ```
+pub struct SceneCache {
+    gfx_queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    target: RenderTarget,
+    valid: bool,
+}
+
+impl SceneCache {
+    /// Creates an empty cache of `format` and `extent`. The format has to match the final image
+    /// it is composited into.
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        format: Format,
+        extent: [u32; 2],
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+    ) -> Self {
+        let target = RenderTarget::new(memory_allocator.clone(), format, extent);
+
+        Self {
+            gfx_queue,
+            memory_allocator,
+            cb_allocator,
+            target,
+            valid: false,
+        }
+    }
+
+    /// The cached image, to render the scene into.
+    pub fn image(&self) -> Arc<Image> {
+        self.target.color()
+    }
+
+    /// Whether the image holds the current scene. While it does, [`SceneCache::render`] skips
+    /// drawing.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Marks the scene as changed, so it is drawn again on the next [`SceneCache::render`].
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Recreates the image at the new size, which invalidates it. Does nothing if the size is the
+    /// same.
+    pub fn resize(&mut self, extent: [u32; 2]) {
+        if self.target.extent() != extent {
+            self.target.resize(self.memory_allocator.clone(), extent);
+            self.valid = false;
+        }
+    }
+
+    /// Calls `draw` with the future to render after and the cached image if the cache is
+    /// invalid, and returns the future `draw` returned. Otherwise returns `before_future`
+    /// untouched.
+    pub fn render<F>(&mut self, before_future: Box<dyn GpuFuture>, draw: F) -> Box<dyn GpuFuture>
+    where
+        F: FnOnce(Box<dyn GpuFuture>, Arc<Image>) -> Box<dyn GpuFuture>,
+    {
+        if self.valid {
+            return before_future;
+        }
+
+        let after = draw(before_future, self.image());
+        self.valid = true;
+        after
+    }
+
+    /// Copies the cached image into `final_image`. If their sizes differ only the overlapping
+    /// top left part is copied, so resize the cache along with the window.
+    pub fn composite(
+        &self,
+        before_future: Box<dyn GpuFuture>,
+        final_image: Arc<Image>,
+    ) -> Box<dyn GpuFuture> {
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        debug::begin_region(&mut cb, "scene_cache");
+        cb.copy_image(CopyImageInfo::images(self.image(), final_image))
+            .unwrap();
+        debug::end_region(&mut cb);
+
+        before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc,
+    },
+};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use shaderc::{Compiler, ShaderKind};
+use vulkano::{
+    device::Device,
+    shader::{EntryPoint, ShaderModule, ShaderModuleCreateInfo},
+    Validated, VulkanError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+impl From<ShaderStage> for ShaderKind {
+    fn from(stage: ShaderStage) -> ShaderKind {
+        match stage {
+            ShaderStage::Vertex => ShaderKind::Vertex,
+            ShaderStage::Fragment => ShaderKind::Fragment,
+            ShaderStage::Compute => ShaderKind::Compute,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Io(PathBuf, std::io::Error),
+    /// shaderc couldn't be initialized.
+    Compiler,
+    Compile(PathBuf, shaderc::Error),
+    /// The shader compiled but has no `main` entry point.
+    NoEntryPoint(PathBuf),
+    Vulkan(Validated<VulkanError>),
+    /// The file watcher couldn't be created or couldn't watch a shader directory.
+    Watch(notify::Error),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::Io(path, e) => write!(f, "failed to read {}: {e}", path.display()),
+            ShaderError::Compiler => write!(f, "failed to initialize shaderc"),
+            ShaderError::Compile(path, e) => {
+                write!(f, "failed to compile {}: {e}", path.display())
+            }
+            ShaderError::NoEntryPoint(path) => {
+                write!(f, "{} has no main entry point", path.display())
+            }
+            ShaderError::Vulkan(e) => write!(f, "failed to create shader module: {e}"),
+            ShaderError::Watch(e) => write!(f, "failed to watch shaders: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+struct LoadedShader {
+    stage: ShaderStage,
+    module: Arc<ShaderModule>,
+}
+
+/// Compiles GLSL from disk at runtime and recompiles it whenever the file changes.
+///
+/// The built-in pipelines are compiled at build time through `vulkano_shaders`. This is for
+/// shader iteration: load the sources through the library, build the pipeline with
+/// [`ReloadablePipeline`], and call [`ShaderLibrary::poll`] once per frame.
+pub struct ShaderLibrary {
+    device: Arc<Device>,
+    compiler: Compiler,
+    shaders: HashMap<PathBuf, LoadedShader>,
+    watcher: RecommendedWatcher,
+    watched_dirs: HashSet<PathBuf>,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl ShaderLibrary {
+    pub fn new(device: Arc<Device>) -> Result<Self, ShaderError> {
+        let (tx, events) = channel();
+        let watcher = notify::recommended_watcher(tx).map_err(ShaderError::Watch)?;
+
+        Ok(Self {
+            device,
+            compiler: Compiler::new().ok_or(ShaderError::Compiler)?,
+            shaders: HashMap::new(),
+            watcher,
+            watched_dirs: HashSet::new(),
+            events,
+        })
+    }
+
+    /// Compiles the shader at `path` and starts watching it for changes.
+    pub fn load(
+        &mut self,
+        path: impl AsRef<Path>,
+        stage: ShaderStage,
+    ) -> Result<Arc<ShaderModule>, ShaderError> {
+        let path = canonical(path.as_ref())?;
+        let module = self.compile(&path, stage)?;
+
+        // Editors commonly save by replacing the file, which drops watches on the file itself,
+        // so the parent directory is watched instead.
+        if let Some(dir) = path.parent() {
+            if !self.watched_dirs.contains(dir) {
+                self.watcher
+                    .watch(dir, RecursiveMode::NonRecursive)
+                    .map_err(ShaderError::Watch)?;
+                self.watched_dirs.insert(dir.to_path_buf());
+            }
+        }
+
+        self.shaders.insert(
+            path,
+            LoadedShader {
+                stage,
+                module: module.clone(),
+            },
+        );
+
+        Ok(module)
+    }
+
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<Arc<ShaderModule>> {
+        let path = canonical(path.as_ref()).ok()?;
+        self.shaders.get(&path).map(|s| s.module.clone())
+    }
+
+    /// Recompiles every loaded shader whose file changed since the last call and returns their
+    /// paths. A shader that fails to compile keeps its previous module and the error is printed.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = HashSet::new();
+        for event in self.events.try_iter().flatten() {
+            if event.kind.is_modify() || event.kind.is_create() {
+                changed.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .filter(|p| self.shaders.contains_key(p)),
+                );
+            }
+        }
+
+        let mut reloaded = Vec::new();
+        for path in changed {
+            let stage = self.shaders[&path].stage;
+            match self.compile(&path, stage) {
+                Ok(module) => {
+                    self.shaders.get_mut(&path).unwrap().module = module;
+                    reloaded.push(path);
+                }
+                Err(e) => tracing::warn!(target: "graphics", "shader reload failed: {e}"),
+            }
+        }
+
+        reloaded
+    }
+
+    fn compile(&self, path: &Path, stage: ShaderStage) -> Result<Arc<ShaderModule>, ShaderError> {
+        let source = fs::read_to_string(path).map_err(|e| ShaderError::Io(path.into(), e))?;
+
+        let artifact = self
+            .compiler
+            .compile_into_spirv(&source, stage.into(), &path.to_string_lossy(), "main", None)
+            .map_err(|e| ShaderError::Compile(path.into(), e))?;
+
+        let module = unsafe {
+            ShaderModule::new(
+                self.device.clone(),
+                ShaderModuleCreateInfo::new(artifact.as_binary()),
+            )
+        }
+        .map_err(ShaderError::Vulkan)?;
+
+        // Checked here so a reload without one keeps the previous module.
+        entry_point(path, module.clone())?;
+        Ok(module)
+    }
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, ShaderError> {
+    path.canonicalize()
+        .map_err(|e| ShaderError::Io(path.to_path_buf(), e))
+}
+
+fn entry_point(path: &Path, module: Arc<ShaderModule>) -> Result<EntryPoint, ShaderError> {
+    module
+        .entry_point("main")
+        .ok_or_else(|| ShaderError::NoEntryPoint(path.into()))
+}
+
+/// A pipeline built from a vertex and fragment shader owned by a [`ShaderLibrary`], rebuilt
+/// whenever either of them is reloaded.
+///
+/// # Examples
+/// ```ignore
+/// let mut shaders = ShaderLibrary::new(gfx.device.clone()).unwrap();
+/// let queue = gfx.gfx_queue.clone();
+/// let subpass = gfx.render_passes.basic.draw_pass();
+/// let cb_allocator = gfx.cb_allocator.clone();
+/// let mut pso = ReloadablePipeline::new(
+///     &mut shaders,
+///     "shaders/basic.vert",
+///     "shaders/basic.frag",
+///     move |vs, fs| PSOBasic::from_shaders(queue.clone(), subpass.clone(), cb_allocator.clone(), vs, fs),
+/// )
+/// .unwrap();
+///
+/// // Every frame:
+/// let changed = shaders.poll();
+/// pso.refresh(&shaders, &changed);
+/// ```
+pub struct ReloadablePipeline<P> {
+    vs: PathBuf,
+    fs: PathBuf,
+    build: Box<dyn Fn(EntryPoint, EntryPoint) -> P>,
+    pub pipeline: P,
+}
+
+impl<P> ReloadablePipeline<P> {
+    pub fn new<F>(
+        library: &mut ShaderLibrary,
+        vs: impl AsRef<Path>,
+        fs: impl AsRef<Path>,
+        build: F,
+    ) -> Result<Self, ShaderError>
+    where
+        F: Fn(EntryPoint, EntryPoint) -> P + 'static,
+    {
+        let vs = canonical(vs.as_ref())?;
+        let fs = canonical(fs.as_ref())?;
+        let pipeline = build(
+            entry_point(&vs, library.load(&vs, ShaderStage::Vertex)?)?,
+            entry_point(&fs, library.load(&fs, ShaderStage::Fragment)?)?,
+        );
+
+        Ok(Self {
+            vs,
+            fs,
+            build: Box::new(build),
+            pipeline,
+        })
+    }
+
+    /// Rebuilds the pipeline if one of its shaders is in `changed`, as returned by
+    /// [`ShaderLibrary::poll`]. Returns whether a rebuild happened.
+    pub fn refresh(&mut self, library: &ShaderLibrary, changed: &[PathBuf]) -> bool {
+        if !changed.iter().any(|p| *p == self.vs || *p == self.fs) {
+            return false;
+        }
+
+        let (Some(vs), Some(fs)) = (library.get(&self.vs), library.get(&self.fs)) else {
+            return false;
+        };
+        let (Ok(vs), Ok(fs)) = (entry_point(&self.vs, vs), entry_point(&self.fs, fs)) else {
+            return false;
+        };
+
+        self.pipeline = (self.build)(vs, fs);
+        true
+    }
+}
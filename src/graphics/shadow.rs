@@ -0,0 +1,324 @@
+use std::sync::Arc;
+
+use glam::{Mat4, Vec3};
+use vulkano::{
+    buffer::{BufferContents, Subbuffer},
+    command_buffer::{
+        allocator::StandardCommandBufferAllocator, CommandBufferBeginInfo, CommandBufferLevel,
+        CommandBufferUsage, RecordingCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo,
+        SubpassContents, SubpassEndInfo,
+    },
+    device::Queue,
+    image::{
+        sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo},
+        view::ImageView,
+        Image, ImageCreateInfo, ImageType, ImageUsage,
+    },
+    memory::allocator::{AllocationCreateInfo, StandardMemoryAllocator},
+    pipeline::{
+        graphics::{
+            depth_stencil::{CompareOp, DepthState, DepthStencilState},
+            input_assembly::InputAssemblyState,
+            multisample::MultisampleState,
+            rasterization::RasterizationState,
+            vertex_input::{Vertex, VertexDefinition},
+            viewport::{Viewport, ViewportState},
+            GraphicsPipelineCreateInfo,
+        },
+        layout::PipelineDescriptorSetLayoutCreateInfo,
+        DynamicState, GraphicsPipeline, Pipeline, PipelineLayout, PipelineShaderStageCreateInfo,
+    },
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
+    sync::GpuFuture,
+};
+
+use super::{
+    debug,
+    light::{Light, LightKind, ShadowSettings},
+    pipelines::lit::Vert,
+    render_target::DEPTH_FORMAT,
+};
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct ShadowPush {
+    mvp: [[f32; 4]; 4],
+}
+
+/// Orthographic view-projection looking along `direction` at a square of `extent` half-width
+/// around `focus`.
+pub fn directional_view_proj(direction: Vec3, focus: Vec3, extent: f32) -> Mat4 {
+    let direction = direction.normalize_or_zero();
+    let up = if direction.cross(Vec3::Y).length_squared() < 1e-6 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    // Back far enough that everything inside the extent is in front of the light.
+    let eye = focus - direction * extent * 2.0;
+    let view = Mat4::look_at_rh(eye, focus, up);
+    let proj = Mat4::orthographic_rh(-extent, extent, -extent, extent, 0.0, extent * 4.0);
+    proj * view
+}
+
+/// Depth of the scene as seen from a directional light, sampled by `PSOLit` to shadow that
+/// light.
+///
+/// # Examples
+/// ```ignore
+/// let sun = Light::directional(Vec3::new(-1.0, -2.0, 1.0), Color::white(), 1.0)
+///     .with_shadows(ShadowSettings::default());
+/// let mut shadow_map = ShadowMap::new(gfx.gfx_queue.clone(), gfx.memory_allocator.clone(),
+///     gfx.cb_allocator.clone(), sun.shadows.unwrap());
+///
+/// let future = shadow_map.render(future, &sun, Vec3::ZERO, &[(cube_vertices, cube.model())]);
+/// let mut lights = LightsUniform::new(camera.position(), Color::grey(), &[sun]);
+/// lights.set_shadow(0, &shadow_map);
+/// // ... draw with `gfx.pipelines.lit.draw(..., Some(&shadow_map))`
+/// ```
+pub struct ShadowMap {
+    gfx_queue: Arc<Queue>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cb_allocator: Arc<StandardCommandBufferAllocator>,
+    render_pass: Arc<RenderPass>,
+    pipeline: Arc<GraphicsPipeline>,
+    sampler: Arc<Sampler>,
+    settings: ShadowSettings,
+    view: Arc<ImageView>,
+    view_proj: Mat4,
+}
+
+impl ShadowMap {
+    pub fn new(
+        gfx_queue: Arc<Queue>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        cb_allocator: Arc<StandardCommandBufferAllocator>,
+        settings: ShadowSettings,
+    ) -> Self {
+        let device = gfx_queue.device().clone();
+
+        let render_pass = vulkano::single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                depth: {
+                    format: DEPTH_FORMAT,
+                    samples: 1,
+                    load_op: Clear,
+                    store_op: Store,
+                },
+            },
+            pass: {
+                color: [],
+                depth_stencil: {depth},
+            },
+        )
+        .unwrap();
+
+        let vs = vs::load(device.clone())
+            .unwrap()
+            .entry_point("main")
+            .unwrap();
+        let vertex_input_state = Vert::per_vertex().definition(&vs).unwrap();
+        let stages = [PipelineShaderStageCreateInfo::new(vs)];
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+                .into_pipeline_layout_create_info(device.clone())
+                .unwrap(),
+        )
+        .unwrap();
+
+        let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+        let pipeline = GraphicsPipeline::new(
+            device.clone(),
+            None,
+            GraphicsPipelineCreateInfo {
+                stages: stages.into_iter().collect(),
+                vertex_input_state: Some(vertex_input_state),
+                input_assembly_state: Some(InputAssemblyState::default()),
+                viewport_state: Some(ViewportState::default()),
+                rasterization_state: Some(RasterizationState::default()),
+                multisample_state: Some(MultisampleState::default()),
+                depth_stencil_state: Some(DepthStencilState {
+                    depth: Some(DepthState::simple()),
+                    ..Default::default()
+                }),
+                dynamic_state: [DynamicState::Viewport].into_iter().collect(),
+                subpass: Some(subpass.into()),
+                ..GraphicsPipelineCreateInfo::layout(layout)
+            },
+        )
+        .unwrap();
+
+        let sampler = Sampler::new(
+            device,
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                compare: Some(CompareOp::LessOrEqual),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let view = shadow_image(memory_allocator.clone(), settings.resolution);
+
+        Self {
+            gfx_queue,
+            memory_allocator,
+            cb_allocator,
+            render_pass,
+            pipeline,
+            sampler,
+            settings,
+            view,
+            view_proj: Mat4::IDENTITY,
+        }
+    }
+
+    pub fn settings(&self) -> ShadowSettings {
+        self.settings
+    }
+
+    /// The light space view-projection of the last [`ShadowMap::render`].
+    pub fn view_proj(&self) -> Mat4 {
+        self.view_proj
+    }
+
+    pub fn view(&self) -> Arc<ImageView> {
+        self.view.clone()
+    }
+
+    /// A depth-comparing sampler for `sampler2DShadow`.
+    pub fn sampler(&self) -> Arc<Sampler> {
+        self.sampler.clone()
+    }
+
+    /// Renders the depth of `meshes`, each with its model matrix, as seen by `light` around
+    /// `focus`. Picks up changes to the light's [`ShadowSettings`]. Lights that are not
+    /// directional or have no shadow settings leave the map untouched.
+    pub fn render(
+        &mut self,
+        before_future: Box<dyn GpuFuture>,
+        light: &Light,
+        focus: Vec3,
+        meshes: &[(Subbuffer<[Vert]>, Mat4)],
+    ) -> Box<dyn GpuFuture> {
+        let (LightKind::Directional { direction }, Some(settings)) = (light.kind, light.shadows)
+        else {
+            return before_future;
+        };
+
+        if settings.resolution != self.settings.resolution {
+            self.view = shadow_image(self.memory_allocator.clone(), settings.resolution);
+        }
+        self.settings = settings;
+        self.view_proj = directional_view_proj(direction, focus, settings.extent);
+
+        let framebuffer = Framebuffer::new(
+            self.render_pass.clone(),
+            FramebufferCreateInfo {
+                attachments: vec![self.view.clone()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let mut cb = RecordingCommandBuffer::new(
+            self.cb_allocator.clone(),
+            self.gfx_queue.queue_family_index(),
+            CommandBufferLevel::Primary,
+            CommandBufferBeginInfo {
+                usage: CommandBufferUsage::OneTimeSubmit,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        debug::begin_region(&mut cb, "shadow_map");
+
+        let resolution = settings.resolution as f32;
+        cb.begin_render_pass(
+            RenderPassBeginInfo {
+                clear_values: vec![Some(1.0.into())],
+                ..RenderPassBeginInfo::framebuffer(framebuffer)
+            },
+            SubpassBeginInfo {
+                contents: SubpassContents::Inline,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .set_viewport(
+            0,
+            [Viewport {
+                offset: [0.0, 0.0],
+                extent: [resolution, resolution],
+                depth_range: 0.0..=1.0,
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap()
+        .bind_pipeline_graphics(self.pipeline.clone())
+        .unwrap();
+
+        for (vertices, model) in meshes {
+            let push = ShadowPush {
+                mvp: (self.view_proj * *model).to_cols_array_2d(),
+            };
+            cb.push_constants(self.pipeline.layout().clone(), 0, push)
+                .unwrap()
+                .bind_vertex_buffers(0, vertices.clone())
+                .unwrap();
+            unsafe {
+                cb.draw(vertices.len() as u32, 1, 0, 0).unwrap();
+            }
+        }
+
+        cb.end_render_pass(SubpassEndInfo::default()).unwrap();
+        debug::end_region(&mut cb);
+
+        before_future
+            .then_execute(self.gfx_queue.clone(), cb.end().unwrap())
+            .unwrap()
+            .boxed()
+    }
+}
+
+fn shadow_image(memory_allocator: Arc<StandardMemoryAllocator>, resolution: u32) -> Arc<ImageView> {
+    ImageView::new_default(
+        Image::new(
+            memory_allocator,
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: DEPTH_FORMAT,
+                extent: [resolution, resolution, 1],
+                usage: ImageUsage::DEPTH_STENCIL_ATTACHMENT | ImageUsage::SAMPLED,
+                ..Default::default()
+            },
+            AllocationCreateInfo::default(),
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: r"
+            #version 450
+
+            layout(location = 0) in vec3 position;
+
+            layout(push_constant) uniform Push {
+                mat4 mvp;
+            } pc;
+
+            void main() {
+                gl_Position = pc.mvp * vec4(position, 1.0);
+            }
+        ",
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{f32::consts::TAU, sync::Arc};
 
 use vulkano::{
     buffer::{Buffer, BufferCreateInfo, BufferUsage},
@@ -7,66 +7,278 @@ use vulkano::{
 };
 
 use super::pipelines::basic::{PSOBasic, Vert};
+use super::viewport::ViewportRegion;
 use super::Color;
 
+/// Rows and columns gradients are split into. Colors are interpolated linearly between vertices,
+/// so curved or clamped gradients need the extra vertices to look smooth.
+const GRADIENT_SUBDIVISIONS: u32 = 16;
+
+/// How a shape is colored. Positions are relative to the shape's bounding box, `[0.0, 0.0]` at
+/// its top left and `[1.0, 1.0]` at its bottom right. `PSOBasic` has no vertex alpha, so the
+/// colors' alpha is ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fill {
+    Solid(Color),
+    /// The colors at the top left, top right, bottom right and bottom left corners, blended in
+    /// between.
+    Corners([Color; 4]),
+    /// Blends from `start` at `from` to `end` at `to`, and keeps the end colors beyond them.
+    Linear {
+        from: [f32; 2],
+        to: [f32; 2],
+        start: Color,
+        end: Color,
+    },
+    /// Blends from `inner` at `center` to `outer` at `radius`, and keeps `outer` beyond it.
+    Radial {
+        center: [f32; 2],
+        radius: f32,
+        inner: Color,
+        outer: Color,
+    },
+}
+
+impl From<Color> for Fill {
+    fn from(color: Color) -> Fill {
+        Fill::Solid(color)
+    }
+}
+
+impl Fill {
+    /// A gradient from `start` at the top to `end` at the bottom.
+    pub fn vertical(start: Color, end: Color) -> Fill {
+        Fill::Linear {
+            from: [0.5, 0.0],
+            to: [0.5, 1.0],
+            start,
+            end,
+        }
+    }
+
+    /// A gradient from `start` on the left to `end` on the right.
+    pub fn horizontal(start: Color, end: Color) -> Fill {
+        Fill::Linear {
+            from: [0.0, 0.5],
+            to: [1.0, 0.5],
+            start,
+            end,
+        }
+    }
+
+    /// The color at `uv` in the bounding box.
+    pub fn color_at(&self, uv: [f32; 2]) -> Color {
+        match *self {
+            Fill::Solid(color) => color,
+            Fill::Corners([top_left, top_right, bottom_right, bottom_left]) => {
+                let top = top_left.lerp(top_right, uv[0]);
+                let bottom = bottom_left.lerp(bottom_right, uv[0]);
+                top.lerp(bottom, uv[1])
+            }
+            Fill::Linear {
+                from,
+                to,
+                start,
+                end,
+            } => {
+                let direction = [to[0] - from[0], to[1] - from[1]];
+                let length_squared = direction[0] * direction[0] + direction[1] * direction[1];
+                if length_squared == 0.0 {
+                    return start;
+                }
+                let along = ((uv[0] - from[0]) * direction[0] + (uv[1] - from[1]) * direction[1])
+                    / length_squared;
+                start.lerp(end, along.clamp(0.0, 1.0))
+            }
+            Fill::Radial {
+                center,
+                radius,
+                inner,
+                outer,
+            } => {
+                let distance = (uv[0] - center[0]).hypot(uv[1] - center[1]);
+                if radius <= 0.0 {
+                    return outer;
+                }
+                inner.lerp(outer, (distance / radius).min(1.0))
+            }
+        }
+    }
+
+    /// How many rows and columns of vertices the fill needs to look right.
+    fn subdivisions(&self) -> u32 {
+        match self {
+            Fill::Solid(_) | Fill::Corners(_) => 1,
+            Fill::Linear { .. } | Fill::Radial { .. } => GRADIENT_SUBDIVISIONS,
+        }
+    }
+}
+
 pub struct Square {
     size: f32,
-    color: Color,
+    fill: Fill,
 }
 
 impl Square {
-    pub fn new(size: f32, color: Color) -> Self {
-        Square { size, color }
+    /// A square reaching `size` from the center in every direction, filled with a color or a
+    /// [`Fill`].
+    pub fn new(size: f32, fill: impl Into<Fill>) -> Self {
+        Square {
+            size,
+            fill: fill.into(),
+        }
     }
 
     pub fn draw(
         &self,
         memory_allocator: Arc<dyn MemoryAllocator>,
         pipeline: &mut PSOBasic,
-        viewport: [u32; 2],
+        viewport: impl Into<ViewportRegion>,
     ) -> Arc<CommandBuffer> {
-        let vertices = [
-            Vert {
-                position: [-self.size, -self.size],
-                color: self.color.into(),
-            },
-            Vert {
-                position: [self.size, self.size],
-                color: self.color.into(),
-            },
-            Vert {
-                position: [-self.size, self.size],
-                color: self.color.into(),
-            },
-            Vert {
-                position: [-self.size, -self.size],
-                color: self.color.into(),
-            },
+        let rect = Rect::new([-self.size, -self.size], [2.0 * self.size; 2], self.fill);
+        rect.draw(memory_allocator, pipeline, viewport)
+    }
+}
+
+/// An axis aligned rectangle.
+pub struct Rect {
+    position: [f32; 2],
+    size: [f32; 2],
+    fill: Fill,
+}
+
+impl Rect {
+    /// A rectangle with its top left corner at `position`.
+    pub fn new(position: [f32; 2], size: [f32; 2], fill: impl Into<Fill>) -> Self {
+        Rect {
+            position,
+            size,
+            fill: fill.into(),
+        }
+    }
+
+    pub fn draw(
+        &self,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        pipeline: &mut PSOBasic,
+        viewport: impl Into<ViewportRegion>,
+    ) -> Arc<CommandBuffer> {
+        let n = self.fill.subdivisions();
+        let vertex = |column: u32, row: u32| {
+            let uv = [column as f32 / n as f32, row as f32 / n as f32];
             Vert {
-                position: [self.size, -self.size],
-                color: self.color.into(),
-            },
+                position: [
+                    self.position[0] + uv[0] * self.size[0],
+                    self.position[1] + uv[1] * self.size[1],
+                ],
+                color: self.fill.color_at(uv).into(),
+            }
+        };
+
+        let mut vertices = Vec::with_capacity((n * n * 6) as usize);
+        for row in 0..n {
+            for column in 0..n {
+                vertices.extend([
+                    vertex(column, row),
+                    vertex(column + 1, row + 1),
+                    vertex(column, row + 1),
+                    vertex(column, row),
+                    vertex(column + 1, row),
+                    vertex(column + 1, row + 1),
+                ]);
+            }
+        }
+
+        draw_vertices(memory_allocator, pipeline, viewport, vertices)
+    }
+}
+
+pub struct Circle {
+    center: [f32; 2],
+    radius: f32,
+    fill: Fill,
+    segments: u32,
+}
+
+impl Circle {
+    pub fn new(center: [f32; 2], radius: f32, fill: impl Into<Fill>) -> Self {
+        Circle {
+            center,
+            radius,
+            fill: fill.into(),
+            segments: 48,
+        }
+    }
+
+    /// How many straight edges approximate the circle, 48 by default.
+    pub fn segments(mut self, segments: u32) -> Self {
+        self.segments = segments.max(3);
+        self
+    }
+
+    pub fn draw(
+        &self,
+        memory_allocator: Arc<dyn MemoryAllocator>,
+        pipeline: &mut PSOBasic,
+        viewport: impl Into<ViewportRegion>,
+    ) -> Arc<CommandBuffer> {
+        let rings = self.fill.subdivisions().div_ceil(2);
+        // `ring` 0 is the center, `rings` the edge.
+        let vertex = |ring: u32, segment: u32| {
+            let angle = segment as f32 / self.segments as f32 * TAU;
+            let distance = ring as f32 / rings as f32;
+            let offset = [angle.cos() * distance, angle.sin() * distance];
             Vert {
-                position: [self.size, self.size],
-                color: self.color.into(),
-            },
-        ];
-
-        let vb = Buffer::from_iter(
-            memory_allocator.clone(),
-            BufferCreateInfo {
-                usage: BufferUsage::VERTEX_BUFFER,
-                ..Default::default()
-            },
-            AllocationCreateInfo {
-                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
-                    | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
-                ..Default::default()
-            },
-            vertices,
-        )
-        .unwrap();
-
-        pipeline.draw(viewport, vb)
+                position: [
+                    self.center[0] + offset[0] * self.radius,
+                    self.center[1] + offset[1] * self.radius,
+                ],
+                color: self
+                    .fill
+                    .color_at([0.5 + offset[0] * 0.5, 0.5 + offset[1] * 0.5])
+                    .into(),
+            }
+        };
+
+        let mut vertices = Vec::new();
+        for segment in 0..self.segments {
+            vertices.extend([vertex(0, 0), vertex(1, segment), vertex(1, segment + 1)]);
+            for ring in 1..rings {
+                vertices.extend([
+                    vertex(ring, segment),
+                    vertex(ring + 1, segment),
+                    vertex(ring + 1, segment + 1),
+                    vertex(ring, segment),
+                    vertex(ring + 1, segment + 1),
+                    vertex(ring, segment + 1),
+                ]);
+            }
+        }
+
+        draw_vertices(memory_allocator, pipeline, viewport, vertices)
     }
 }
+
+fn draw_vertices(
+    memory_allocator: Arc<dyn MemoryAllocator>,
+    pipeline: &mut PSOBasic,
+    viewport: impl Into<ViewportRegion>,
+    vertices: Vec<Vert>,
+) -> Arc<CommandBuffer> {
+    let vb = Buffer::from_iter(
+        memory_allocator,
+        BufferCreateInfo {
+            usage: BufferUsage::VERTEX_BUFFER,
+            ..Default::default()
+        },
+        AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE
+                | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+            ..Default::default()
+        },
+        vertices,
+    )
+    .unwrap();
+
+    pipeline.draw(viewport, vb)
+}
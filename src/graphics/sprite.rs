@@ -0,0 +1,194 @@
+//! Animated sprites, playing frames of a sprite sheet or atlas.
+//!
+//! Spawn a [`SpriteAnimation`] together with the [`UvRect`] it drives, and run [`animate`] every
+//! update, or add [`system`] to the fixed stage. Draw the entity with
+//! `Texture::with_uv_rect(*uv_rect)`.
+//!
+//! ```ignore
+//! let walk = SpriteAnimation::from_grid(8, 4, 8..16, 12.0).with_mode(PlaybackMode::PingPong);
+//! app.world.spawn((walk.clone(), walk.uv_rect()));
+//! app.add_named_system(FIXED_STAGE, "sprites", sprite::system(step));
+//! ```
+
+use std::{ops::Range, time::Duration};
+
+use hecs::World;
+
+use super::texture::UvRect;
+use crate::app::System;
+
+/// What happens after the last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlaybackMode {
+    /// Stops on the last frame.
+    Once,
+    /// Starts over from the first frame.
+    #[default]
+    Loop,
+    /// Plays backwards to the first frame, then forwards again.
+    PingPong,
+}
+
+/// One frame of an animation: where it is in the image and how long it shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpriteFrame {
+    pub uv_rect: UvRect,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    frames: Vec<SpriteFrame>,
+    mode: PlaybackMode,
+    speed: f32,
+    frame: usize,
+    /// Time spent on the current frame.
+    elapsed: Duration,
+    forward: bool,
+    playing: bool,
+}
+
+impl SpriteAnimation {
+    /// Plays `frames` in order. Panics if there are none.
+    pub fn new(frames: Vec<SpriteFrame>) -> Self {
+        assert!(!frames.is_empty(), "a sprite animation needs frames");
+        SpriteAnimation {
+            frames,
+            mode: PlaybackMode::default(),
+            speed: 1.0,
+            frame: 0,
+            elapsed: Duration::ZERO,
+            forward: true,
+            playing: true,
+        }
+    }
+
+    /// Plays the `cells` of a sheet of `columns` by `rows` cells at `fps` frames per second,
+    /// counted like [`UvRect::grid`].
+    pub fn from_grid(columns: u32, rows: u32, cells: Range<u32>, fps: f32) -> Self {
+        let duration = Duration::from_secs_f32(1.0 / fps);
+        let frames = cells
+            .map(|index| SpriteFrame {
+                uv_rect: UvRect::grid(columns, rows, index),
+                duration,
+            })
+            .collect();
+        SpriteAnimation::new(frames)
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Plays `speed` times as fast, e.g. 0.5 for half speed. Defaults to 1.
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Jumps back to the first frame and plays.
+    pub fn restart(&mut self) {
+        self.frame = 0;
+        self.elapsed = Duration::ZERO;
+        self.forward = true;
+        self.playing = true;
+    }
+
+    /// False when paused, or after the last frame with [`PlaybackMode::Once`].
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    /// The part of the image to show now.
+    pub fn uv_rect(&self) -> UvRect {
+        self.frames[self.frame].uv_rect
+    }
+
+    /// Moves the animation `elapsed` forward, scaled by its speed. Returns whether the frame
+    /// changed.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let start = self.frame;
+        self.elapsed += elapsed.mul_f32(self.speed);
+        let mut stepped = false;
+        loop {
+            let duration = self.frames[self.frame].duration;
+            // Zero length frames would loop forever otherwise, they last one update instead.
+            if self.elapsed < duration || (duration.is_zero() && stepped) {
+                break;
+            }
+            self.elapsed -= duration;
+            if !self.step() {
+                self.elapsed = Duration::ZERO;
+                self.playing = false;
+                break;
+            }
+            stepped = true;
+        }
+        self.frame != start
+    }
+
+    /// Moves to the next frame. Returns false if the animation ended instead.
+    fn step(&mut self) -> bool {
+        let last = self.frames.len() - 1;
+        match self.mode {
+            PlaybackMode::Once => {
+                if self.frame == last {
+                    return false;
+                }
+                self.frame += 1;
+            }
+            PlaybackMode::Loop => self.frame = (self.frame + 1) % self.frames.len(),
+            PlaybackMode::PingPong => {
+                if last == 0 {
+                    return true;
+                }
+                if (self.forward && self.frame == last) || (!self.forward && self.frame == 0) {
+                    self.forward = !self.forward;
+                }
+                if self.forward {
+                    self.frame += 1;
+                } else {
+                    self.frame -= 1;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Advances every [`SpriteAnimation`] by `elapsed` and writes its current frame to the entity's
+/// [`UvRect`].
+pub fn animate(world: &mut World, elapsed: Duration) {
+    for (_, (animation, uv_rect)) in world.query_mut::<(&mut SpriteAnimation, &mut UvRect)>() {
+        animation.advance(elapsed);
+        *uv_rect = animation.uv_rect();
+    }
+}
+
+/// A system running [`animate`] with `step` each time, for the fixed stage with the tick length.
+pub fn system(step: Duration) -> System {
+    Box::new(move |world| {
+        animate(world, step);
+        Ok(())
+    })
+}
@@ -3,20 +3,278 @@ use std::sync::Arc;
 use vulkano::{
     buffer::{Buffer, BufferCreateInfo, BufferUsage},
     command_buffer::CommandBuffer,
-    image::Image,
+    image::{sampler::SamplerAddressMode, Image},
     memory::allocator::{AllocationCreateInfo, MemoryAllocator, MemoryTypeFilter},
 };
 
 use super::pipelines::texture::PSOTexture;
 use super::pipelines::texture::Vert;
+use super::pipelines::BlendMode;
+use super::sampler::SamplerDesc;
+use super::viewport::ViewportRegion;
+use super::Color;
 
+/// How texture coordinates outside of the image are sampled, see [`Texture::with_tiling`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WrapMode {
+    #[default]
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+impl From<WrapMode> for SamplerAddressMode {
+    fn from(wrap: WrapMode) -> SamplerAddressMode {
+        match wrap {
+            WrapMode::Repeat => SamplerAddressMode::Repeat,
+            WrapMode::MirroredRepeat => SamplerAddressMode::MirroredRepeat,
+            WrapMode::ClampToEdge => SamplerAddressMode::ClampToEdge,
+        }
+    }
+}
+
+/// Pixel conversions applied to RGBA8 images before they are uploaded, see
+/// `GraphicsContext::upload_png_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadOptions {
+    /// Pixels of exactly this color become fully transparent, e.g. `[255, 0, 255]` for sprites
+    /// drawn on a magenta background.
+    pub color_key: Option<[u8; 3]>,
+    /// Scales the color by the alpha. Draw the result with [`BlendMode::Premultiplied`].
+    pub premultiply: bool,
+}
+
+impl UploadOptions {
+    /// Converts `rgba` in place. The color channels are taken to be sRGB encoded, so
+    /// premultiplying happens in linear space.
+    pub fn apply(&self, rgba: &mut [u8]) {
+        for pixel in rgba.chunks_exact_mut(4) {
+            if self.color_key == Some([pixel[0], pixel[1], pixel[2]]) {
+                pixel.copy_from_slice(&[0, 0, 0, 0]);
+                continue;
+            }
+            if self.premultiply && pixel[3] != 255 {
+                let alpha = pixel[3] as f32 / 255.0;
+                for channel in pixel[..3].iter_mut() {
+                    *channel = linear_to_srgb(srgb_to_linear(*channel) * alpha);
+                }
+            }
+        }
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn linear_to_srgb(value: f32) -> u8 {
+    let c = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+/// Splits an image into a 3x3 grid whose corners keep their size while the edges and center
+/// stretch, e.g. for UI panels and buttons that scale without blurring their frame. See
+/// [`Texture::with_nine_patch`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NinePatch {
+    /// Left, right, top and bottom margins of the image that aren't stretched, as fractions of
+    /// its width and height.
+    pub margins: [f32; 4],
+    /// Left, right, top and bottom size of those margins on the quad, in the units of
+    /// [`Texture::new`].
+    pub borders: [f32; 4],
+}
+
+impl NinePatch {
+    /// The same margin on every side.
+    pub fn uniform(margin: f32, border: f32) -> Self {
+        NinePatch {
+            margins: [margin; 4],
+            borders: [border; 4],
+        }
+    }
+}
+
+/// The part of an image a [`Texture`] shows, e.g. one sprite of a sprite sheet or atlas. In
+/// texture coordinates, `[0.0, 0.0]` at the top left of the image and `[1.0, 1.0]` at the bottom
+/// right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub offset: [f32; 2],
+    pub size: [f32; 2],
+}
+
+impl Default for UvRect {
+    fn default() -> Self {
+        UvRect::FULL
+    }
+}
+
+impl UvRect {
+    /// The whole image.
+    pub const FULL: UvRect = UvRect {
+        offset: [0.0, 0.0],
+        size: [1.0, 1.0],
+    };
+
+    pub fn new(offset: [f32; 2], size: [f32; 2]) -> Self {
+        UvRect { offset, size }
+    }
+
+    /// The rectangle of `size` pixels at `offset` pixels in an image of `image_size` pixels.
+    pub fn from_pixels(offset: [u32; 2], size: [u32; 2], image_size: [u32; 2]) -> Self {
+        let [w, h] = [image_size[0] as f32, image_size[1] as f32];
+        UvRect {
+            offset: [offset[0] as f32 / w, offset[1] as f32 / h],
+            size: [size[0] as f32 / w, size[1] as f32 / h],
+        }
+    }
+
+    /// Cell `index` of a sheet of `columns` by `rows` equally sized cells, counted left to right
+    /// and then top to bottom.
+    pub fn grid(columns: u32, rows: u32, index: u32) -> Self {
+        let size = [1.0 / columns as f32, 1.0 / rows as f32];
+        let [column, row] = [index % columns, index / columns];
+        UvRect {
+            offset: [column as f32 * size[0], row as f32 * size[1]],
+            size,
+        }
+    }
+
+    /// Maps `uv` in the whole image to the same place in this rectangle.
+    fn map(&self, uv: [f32; 2]) -> [f32; 2] {
+        [
+            self.offset[0] + uv[0] * self.size[0],
+            self.offset[1] + uv[1] * self.size[1],
+        ]
+    }
+}
+
+/// A textured quad. Its corners are computed on the CPU when it is drawn: the quad is scaled and
+/// rotated around its anchor, which is then moved to its position.
 pub struct Texture {
-    size: f32,
+    extent: [f32; 2],
+    flip_x: bool,
+    flip_y: bool,
+    tiling: [f32; 2],
+    sampler: SamplerDesc,
+    blend: BlendMode,
+    nine_patch: Option<NinePatch>,
+    position: [f32; 2],
+    rotation: f32,
+    scale: [f32; 2],
+    anchor: [f32; 2],
+    uv_rect: UvRect,
+    tint: Color,
 }
 
 impl Texture {
     pub fn new(size: f32) -> Self {
-        Texture { size }
+        Texture {
+            extent: [size, size],
+            flip_x: false,
+            flip_y: false,
+            tiling: [1.0, 1.0],
+            sampler: SamplerDesc::default(),
+            blend: BlendMode::default(),
+            nine_patch: None,
+            position: [0.0, 0.0],
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            anchor: [0.5, 0.5],
+            uv_rect: UvRect::FULL,
+            tint: Color::white(),
+        }
+    }
+
+    /// Where the anchor ends up, in the units of the pipeline's projection. Defaults to the
+    /// origin.
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Rotates the quad around its anchor by `radians`, clockwise on screen since y points down.
+    pub fn with_rotation(mut self, radians: f32) -> Self {
+        self.rotation = radians;
+        self
+    }
+
+    /// Scales the quad around its anchor.
+    pub fn with_scale(mut self, scale: [f32; 2]) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The point of the quad that is placed at the position and rotated and scaled around, from
+    /// `[0.0, 0.0]` at the top left to `[1.0, 1.0]` at the bottom right. Defaults to the center.
+    pub fn with_anchor(mut self, anchor: [f32; 2]) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Shows only `uv_rect` of the image, e.g. one sprite of a sheet. Tiling still repeats the
+    /// whole image, so don't combine the two.
+    pub fn with_uv_rect(mut self, uv_rect: UvRect) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    /// Multiplies the image with `tint`, e.g. to flash a sprite red or fade it out through the
+    /// alpha. Use a premultiplied tint with [`BlendMode::Premultiplied`].
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Makes the quad `extent[0]` wide and `extent[1]` high instead of square, measured from
+    /// the center like the size given to [`Texture::new`].
+    pub fn with_extent(mut self, extent: [f32; 2]) -> Self {
+        self.extent = extent;
+        self
+    }
+
+    /// Draws the image as a nine-patch. Tiling is ignored, flipping mirrors the whole grid.
+    pub fn with_nine_patch(mut self, nine_patch: NinePatch) -> Self {
+        self.nine_patch = Some(nine_patch);
+        self
+    }
+
+    /// Mirrors the image horizontally and/or vertically, so one asset can face both ways.
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Repeats the image `tiling` times across the quad on each axis, sampled with `wrap`.
+    /// Overrides the address mode set with [`Texture::with_sampler`].
+    pub fn with_tiling(mut self, tiling: [f32; 2], wrap: WrapMode) -> Self {
+        self.tiling = tiling;
+        self.sampler = self.sampler.with_address_mode(wrap.into());
+        self
+    }
+
+    /// How the image is filtered and addressed, e.g. [`SamplerDesc::nearest`] for pixel art or
+    /// with anisotropy for tiled ground. Defaults to linear filtering, repeating.
+    pub fn with_sampler(mut self, sampler: SamplerDesc) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Use [`BlendMode::Premultiplied`] for images uploaded with `UploadOptions::premultiply`.
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
     }
 
     pub fn draw(
@@ -24,28 +282,16 @@ impl Texture {
         memory_allocator: Arc<dyn MemoryAllocator>,
         pipeline: &mut PSOTexture,
         image: Arc<Image>,
-        viewport: [u32; 2],
+        viewport: impl Into<ViewportRegion>,
     ) -> Arc<CommandBuffer> {
-        let vertices = [
-            Vert {
-                position: [-self.size, -self.size],
-            },
-            Vert {
-                position: [self.size, self.size],
-            },
-            Vert {
-                position: [-self.size, self.size],
-            },
-            Vert {
-                position: [-self.size, -self.size],
-            },
-            Vert {
-                position: [self.size, -self.size],
-            },
-            Vert {
-                position: [self.size, self.size],
-            },
-        ];
+        let mut vertices = match self.nine_patch {
+            Some(patch) => self.nine_patch_vertices(patch),
+            None => self.quad_vertices(),
+        };
+        for vertex in &mut vertices {
+            vertex.position = self.transform(vertex.position);
+            vertex.uv = self.uv_rect.map(vertex.uv);
+        }
 
         let vb = Buffer::from_iter(
             memory_allocator,
@@ -62,6 +308,106 @@ impl Texture {
         )
         .unwrap();
 
-        pipeline.draw(viewport, image, vb)
+        pipeline.draw(viewport, image, self.sampler, self.blend, vb)
+    }
+
+    /// Moves a corner of the centered, untransformed quad to where it is drawn.
+    fn transform(&self, corner: [f32; 2]) -> [f32; 2] {
+        let [x, y] = self.extent;
+        let anchor = [
+            x * (2.0 * self.anchor[0] - 1.0),
+            y * (2.0 * self.anchor[1] - 1.0),
+        ];
+        let local = [
+            (corner[0] - anchor[0]) * self.scale[0],
+            (corner[1] - anchor[1]) * self.scale[1],
+        ];
+        let (sin, cos) = self.rotation.sin_cos();
+        [
+            self.position[0] + local[0] * cos - local[1] * sin,
+            self.position[1] + local[0] * sin + local[1] * cos,
+        ]
+    }
+
+    fn quad_vertices(&self) -> Vec<Vert> {
+        let [x, y] = self.extent;
+        let (u0, u1) = if self.flip_x {
+            (self.tiling[0], 0.0)
+        } else {
+            (0.0, self.tiling[0])
+        };
+        let (v0, v1) = if self.flip_y {
+            (self.tiling[1], 0.0)
+        } else {
+            (0.0, self.tiling[1])
+        };
+
+        let tint = self.tint.into();
+        vec![
+            Vert {
+                position: [-x, -y],
+                uv: [u0, v0],
+                tint,
+            },
+            Vert {
+                position: [x, y],
+                uv: [u1, v1],
+                tint,
+            },
+            Vert {
+                position: [-x, y],
+                uv: [u0, v1],
+                tint,
+            },
+            Vert {
+                position: [-x, -y],
+                uv: [u0, v0],
+                tint,
+            },
+            Vert {
+                position: [x, -y],
+                uv: [u1, v0],
+                tint,
+            },
+            Vert {
+                position: [x, y],
+                uv: [u1, v1],
+                tint,
+            },
+        ]
+    }
+
+    /// Two triangles for each of the nine cells.
+    fn nine_patch_vertices(&self, patch: NinePatch) -> Vec<Vert> {
+        let [x, y] = self.extent;
+        let [left, right, top, bottom] = patch.borders;
+        let xs = [-x, -x + left, x - right, x];
+        let ys = [-y, -y + top, y - bottom, y];
+
+        let [left, right, top, bottom] = patch.margins;
+        let flip = |t: f32, flip: bool| if flip { 1.0 - t } else { t };
+        let us = [0.0, left, 1.0 - right, 1.0].map(|u| flip(u, self.flip_x));
+        let vs = [0.0, top, 1.0 - bottom, 1.0].map(|v| flip(v, self.flip_y));
+
+        let tint = self.tint.into();
+        let vert = |i: usize, j: usize| Vert {
+            position: [xs[i], ys[j]],
+            uv: [us[i], vs[j]],
+            tint,
+        };
+        let mut vertices = Vec::with_capacity(54);
+        for j in 0..3 {
+            for i in 0..3 {
+                vertices.extend([
+                    vert(i, j),
+                    vert(i + 1, j + 1),
+                    vert(i, j + 1),
+                    vert(i, j),
+                    vert(i + 1, j),
+                    vert(i + 1, j + 1),
+                ]);
+            }
+        }
+        vertices
     }
 }
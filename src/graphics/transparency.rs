@@ -0,0 +1,65 @@
+use glam::Vec3;
+
+use super::camera::Camera;
+
+/// Collects the transparent meshes of a frame so they can be drawn back to front.
+///
+/// Alpha blending only looks right when what is behind was drawn first, and the depth buffer
+/// can't sort blended meshes. Draw the opaque meshes, then everything from
+/// [`sorted`](TransparentQueue::sorted) with a pipeline that tests depth without writing it.
+/// Meshes are ordered by their center, so intersecting or very large meshes can still blend in
+/// the wrong order.
+///
+/// # Examples
+/// ```ignore
+/// let mut queue = TransparentQueue::new();
+/// for cube in glass.iter() {
+///     queue.push(cube.aabb().center(), cube);
+/// }
+/// for cube in queue.sorted(&camera) {
+///     draw_pass.execute(cube.draw(...));
+/// }
+/// ```
+pub struct TransparentQueue<T> {
+    items: Vec<(f32, Vec3, T)>,
+}
+
+impl<T> Default for TransparentQueue<T> {
+    fn default() -> Self {
+        TransparentQueue { items: Vec::new() }
+    }
+}
+
+impl<T> TransparentQueue<T> {
+    pub fn new() -> Self {
+        TransparentQueue::default()
+    }
+
+    /// Queues `item`, with `center` its world space position.
+    pub fn push(&mut self, center: Vec3, item: T) {
+        self.items.push((0.0, center, item));
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Empties the queue, farthest item from `camera` first.
+    pub fn sorted(&mut self, camera: &dyn Camera) -> Vec<T> {
+        self.sorted_from(camera.position())
+    }
+
+    /// Like [`TransparentQueue::sorted`], for a camera at `eye`.
+    pub fn sorted_from(&mut self, eye: Vec3) -> Vec<T> {
+        for (distance, center, _) in self.items.iter_mut() {
+            *distance = center.distance_squared(eye);
+        }
+        // Stable, so items at the same distance keep the order they were pushed in.
+        self.items.sort_by(|a, b| b.0.total_cmp(&a.0));
+        self.items.drain(..).map(|(_, _, item)| item).collect()
+    }
+}
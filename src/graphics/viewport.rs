@@ -0,0 +1,118 @@
+use vulkano::{
+    command_buffer::RecordingCommandBuffer,
+    pipeline::graphics::viewport::{Scissor, Viewport},
+};
+
+/// The part of the render target a draw lands in, in pixels from the top left. Every pipeline
+/// takes one, or a plain `[width, height]` for the whole target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewportRegion {
+    pub offset: [f32; 2],
+    pub extent: [f32; 2],
+}
+
+impl ViewportRegion {
+    pub fn new(offset: [f32; 2], extent: [f32; 2]) -> Self {
+        ViewportRegion { offset, extent }
+    }
+
+    /// Width over height, e.g. for `PerspectiveCamera::set_aspect_ratio`.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.extent[0] / self.extent[1]
+    }
+
+    /// The pixel at `ndc`, normalized device coordinates from -1 at the top left to 1 at the
+    /// bottom right of the region.
+    pub fn ndc_to_pixel(&self, ndc: [f32; 2]) -> [f32; 2] {
+        [0, 1].map(|i| self.offset[i] + (ndc[i] + 1.0) * 0.5 * self.extent[i])
+    }
+
+    /// The inverse of [`ViewportRegion::ndc_to_pixel`].
+    pub fn pixel_to_ndc(&self, pixel: [f32; 2]) -> [f32; 2] {
+        [0, 1].map(|i| (pixel[i] - self.offset[i]) / self.extent[i] * 2.0 - 1.0)
+    }
+
+    /// Whether `pixel` lies in the region, e.g. to find which split screen view was clicked.
+    pub fn contains(&self, pixel: [f32; 2]) -> bool {
+        (0..2).all(|i| pixel[i] >= self.offset[i] && pixel[i] < self.offset[i] + self.extent[i])
+    }
+
+    /// Sets the viewport and a scissor of the same rectangle, so nothing is drawn outside it.
+    pub(crate) fn set(self, cb: &mut RecordingCommandBuffer) {
+        cb.set_viewport(
+            0,
+            [Viewport {
+                offset: self.offset,
+                extent: self.extent,
+                depth_range: 0.0..=1.0,
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap()
+        .set_scissor(
+            0,
+            [Scissor {
+                offset: [self.offset[0] as u32, self.offset[1] as u32],
+                extent: [self.extent[0] as u32, self.extent[1] as u32],
+            }]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+    }
+}
+
+impl From<[u32; 2]> for ViewportRegion {
+    fn from(dimensions: [u32; 2]) -> Self {
+        ViewportRegion::new([0.0, 0.0], [dimensions[0] as f32, dimensions[1] as f32])
+    }
+}
+
+/// How two players share the screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitScreen {
+    /// Side by side.
+    #[default]
+    Vertical,
+    /// One above the other.
+    Horizontal,
+}
+
+impl SplitScreen {
+    /// The region of each of `players` on a target of `dimensions`. Two players split the
+    /// screen in half, three or four get a quarter each, with the bottom right left empty for
+    /// three.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let regions = SplitScreen::Vertical.regions(2, draw_pass.viewport_dimensions());
+    /// for (camera, region) in cameras.iter().zip(regions) {
+    ///     draw_pass.execute(cube.draw(..., region, camera, ...));
+    /// }
+    /// ```
+    pub fn regions(self, players: u32, dimensions: [u32; 2]) -> Vec<ViewportRegion> {
+        let [w, h] = [dimensions[0] as f32, dimensions[1] as f32];
+        match players {
+            0 => Vec::new(),
+            1 => vec![ViewportRegion::new([0.0, 0.0], [w, h])],
+            2 => match self {
+                SplitScreen::Vertical => vec![
+                    ViewportRegion::new([0.0, 0.0], [w / 2.0, h]),
+                    ViewportRegion::new([w / 2.0, 0.0], [w / 2.0, h]),
+                ],
+                SplitScreen::Horizontal => vec![
+                    ViewportRegion::new([0.0, 0.0], [w, h / 2.0]),
+                    ViewportRegion::new([0.0, h / 2.0], [w, h / 2.0]),
+                ],
+            },
+            3 | 4 => (0..players)
+                .map(|i| {
+                    let [x, y] = [(i % 2) as f32, (i / 2) as f32];
+                    ViewportRegion::new([x * w / 2.0, y * h / 2.0], [w / 2.0, h / 2.0])
+                })
+                .collect(),
+            _ => panic!("split screen supports up to 4 players, got {players}"),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Parent and child entities, e.g. a sword held by a player or the wheels of a car.
+//!
+//! A child's [`Transform`] is relative to its parent. After each update the
+//! [`App`](crate::app::App) writes the [`GlobalTransform`] of every entity with a
+//! [`RenderTransform`], which places it in the world.
+//!
+//! ```ignore
+//! let car = app.world.spawn((Transform::from_translation(Vec3::new(5.0, 0.0, 0.0)),));
+//! let wheel = app.world.spawn((Transform::from_translation(Vec3::new(1.0, -0.5, 0.0)),));
+//! hierarchy::set_parent(&mut app.world, wheel, car)?;
+//!
+//! // Despawns the wheel too.
+//! hierarchy::despawn_recursive(&mut app.world, car);
+//! ```
+
+use hecs::{Entity, NoSuchEntity, World};
+
+use crate::transform::{RenderTransform, Transform};
+
+mod tests;
+
+/// The entity this one is a child of. Set with [`set_parent`] so the parent's [`Children`]
+/// stay in sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+/// The children of an entity, in the order they were added. Kept by [`set_parent`] and
+/// [`remove_parent`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+}
+
+/// Where an entity is in the world: its [`RenderTransform`] combined with those of its
+/// ancestors. Added and updated by `App`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GlobalTransform(pub Transform);
+
+/// Makes `child` a child of `parent`, moving it from its old parent if it had one.
+///
+/// Panics if `parent` is `child` or one of its descendants.
+pub fn set_parent(world: &mut World, child: Entity, parent: Entity) -> Result<(), NoSuchEntity> {
+    if !world.contains(child) || !world.contains(parent) {
+        return Err(NoSuchEntity);
+    }
+    assert!(
+        !is_ancestor(world, child, parent),
+        "an entity can't be its own ancestor"
+    );
+    remove_parent(world, child)?;
+
+    world.insert_one(child, Parent(parent))?;
+    if let Ok(mut children) = world.get::<&mut Children>(parent) {
+        children.0.push(child);
+        return Ok(());
+    }
+    world.insert_one(parent, Children(vec![child]))
+}
+
+/// Makes `child` a root entity again. Does nothing if it has no parent.
+pub fn remove_parent(world: &mut World, child: Entity) -> Result<(), NoSuchEntity> {
+    let Ok(Parent(parent)) = world.remove_one::<Parent>(child) else {
+        return if world.contains(child) {
+            Ok(())
+        } else {
+            Err(NoSuchEntity)
+        };
+    };
+    let now_empty = match world.get::<&mut Children>(parent) {
+        Ok(mut children) => {
+            children.0.retain(|&c| c != child);
+            children.0.is_empty()
+        }
+        Err(_) => false,
+    };
+    if now_empty {
+        let _ = world.remove_one::<Children>(parent);
+    }
+    Ok(())
+}
+
+/// Whether `ancestor` is `entity` or one of its ancestors.
+pub fn is_ancestor(world: &World, ancestor: Entity, entity: Entity) -> bool {
+    let mut current = Some(entity);
+    while let Some(entity) = current {
+        if entity == ancestor {
+            return true;
+        }
+        current = world.get::<&Parent>(entity).ok().map(|parent| parent.0);
+    }
+    false
+}
+
+/// Despawns `entity` and all its descendants, and removes it from its parent's children.
+pub fn despawn_recursive(world: &mut World, entity: Entity) -> Result<(), NoSuchEntity> {
+    remove_parent(world, entity)?;
+    let mut stack = vec![entity];
+    while let Some(entity) = stack.pop() {
+        if let Ok(children) = world.get::<&Children>(entity) {
+            stack.extend(children.iter());
+        }
+        let _ = world.despawn(entity);
+    }
+    Ok(())
+}
+
+/// Writes the [`GlobalTransform`] of every entity with a [`RenderTransform`], going down from
+/// the roots. Children without a `RenderTransform` pass their parent's on to their own children.
+pub fn propagate_transforms(world: &mut World) {
+    // Entities whose parent was despawned without `despawn_recursive` count as roots.
+    let roots: Vec<_> = world
+        .query::<(Option<&RenderTransform>, Option<&Parent>)>()
+        .iter()
+        .filter(|(_, (_, parent))| parent.is_none_or(|parent| !world.contains(parent.0)))
+        .map(|(entity, (render, _))| (entity, render.map_or(Transform::IDENTITY, |r| r.0)))
+        .collect();
+
+    let mut globals = Vec::new();
+    let mut stack = roots;
+    while let Some((entity, global)) = stack.pop() {
+        if world.get::<&RenderTransform>(entity).is_ok() {
+            globals.push((entity, global));
+        }
+        let Ok(children) = world.get::<&Children>(entity) else {
+            continue;
+        };
+        for child in children.iter() {
+            let local = world
+                .get::<&RenderTransform>(child)
+                .map_or(Transform::IDENTITY, |render| render.0);
+            stack.push((child, global.mul_transform(&local)));
+        }
+    }
+
+    for (entity, global) in globals {
+        if let Ok(mut current) = world.get::<&mut GlobalTransform>(entity) {
+            current.0 = global;
+            continue;
+        }
+        world.insert_one(entity, GlobalTransform(global)).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use crate::hierarchy::{self, Children, GlobalTransform, Parent};
+    use crate::transform::{RenderTransform, Transform};
+    use glam::Vec3;
+    use hecs::{Entity, World};
+
+    fn children(world: &World, parent: Entity) -> Vec<Entity> {
+        world
+            .get::<&Children>(parent)
+            .map_or(Vec::new(), |children| children.iter().collect())
+    }
+
+    #[test]
+    fn test_reparent_keeps_children_in_sync() {
+        let mut world = World::new();
+        let (car, trailer, wheel) = (world.spawn(()), world.spawn(()), world.spawn(()));
+        hierarchy::set_parent(&mut world, wheel, car).unwrap();
+        assert_eq!(vec![wheel], children(&world, car));
+
+        hierarchy::set_parent(&mut world, wheel, trailer).unwrap();
+        assert_eq!(Parent(trailer), *world.get::<&Parent>(wheel).unwrap());
+        assert_eq!(vec![wheel], children(&world, trailer));
+        // Emptied children are removed.
+        assert!(world.get::<&Children>(car).is_err());
+
+        hierarchy::remove_parent(&mut world, wheel).unwrap();
+        assert!(world.get::<&Parent>(wheel).is_err());
+        assert!(children(&world, trailer).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cycle_panics() {
+        let mut world = World::new();
+        let (car, wheel) = (world.spawn(()), world.spawn(()));
+        hierarchy::set_parent(&mut world, wheel, car).unwrap();
+        let _ = hierarchy::set_parent(&mut world, car, wheel);
+    }
+
+    #[test]
+    fn test_is_ancestor() {
+        let mut world = World::new();
+        let (car, wheel, bolt, other) = (
+            world.spawn(()),
+            world.spawn(()),
+            world.spawn(()),
+            world.spawn(()),
+        );
+        hierarchy::set_parent(&mut world, wheel, car).unwrap();
+        hierarchy::set_parent(&mut world, bolt, wheel).unwrap();
+        assert!(hierarchy::is_ancestor(&world, car, bolt));
+        assert!(hierarchy::is_ancestor(&world, bolt, bolt));
+        assert!(!hierarchy::is_ancestor(&world, bolt, car));
+        assert!(!hierarchy::is_ancestor(&world, other, bolt));
+    }
+
+    #[test]
+    fn test_despawn_recursive() {
+        let mut world = World::new();
+        let (garage, car, wheel, bolt) = (
+            world.spawn(()),
+            world.spawn(()),
+            world.spawn(()),
+            world.spawn(()),
+        );
+        hierarchy::set_parent(&mut world, car, garage).unwrap();
+        hierarchy::set_parent(&mut world, wheel, car).unwrap();
+        hierarchy::set_parent(&mut world, bolt, wheel).unwrap();
+
+        hierarchy::despawn_recursive(&mut world, car).unwrap();
+        assert!(world.contains(garage));
+        assert!(!world.contains(car) && !world.contains(wheel) && !world.contains(bolt));
+        assert!(children(&world, garage).is_empty());
+        assert!(hierarchy::despawn_recursive(&mut world, car).is_err());
+    }
+
+    #[test]
+    fn test_propagate_transforms() {
+        let mut world = World::new();
+        let at = |x| RenderTransform(Transform::from_translation(Vec3::new(x, 0.0, 0.0)));
+        let car = world.spawn((at(5.0),));
+        // Without a transform of its own, the wheel's children are placed relative to the car.
+        let axle = world.spawn(());
+        let wheel = world.spawn((at(1.0),));
+        hierarchy::set_parent(&mut world, axle, car).unwrap();
+        hierarchy::set_parent(&mut world, wheel, axle).unwrap();
+
+        hierarchy::propagate_transforms(&mut world);
+        let global = |entity| world.get::<&GlobalTransform>(entity).unwrap().0.translation;
+        assert_eq!(Vec3::new(5.0, 0.0, 0.0), global(car));
+        assert_eq!(Vec3::new(6.0, 0.0, 0.0), global(wheel));
+        assert!(world.get::<&GlobalTransform>(axle).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+//! Keyboard and mouse state for systems, and routing input to the local players of a session,
+//! e.g. two players sharing a keyboard in split-screen.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use hecs::World;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{DeviceEvent, ElementState, Ime, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::{netcode::PlayerId, settings::Settings};
+
+mod tests;
+
+/// Logical pixels per line, to turn touchpad scrolling into lines like a mouse wheel's.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// The actions a local player has triggered, kept on the player's entity next to its
+/// [`PlayerId`]. `A` is the game's action type, e.g. an enum of jump, fire and the movement
+/// directions.
+///
+/// Read it in systems like any other component. For networked sessions, it is the local input to
+/// hand to the netcode for that player.
+#[derive(Debug, Clone)]
+pub struct PlayerInput<A> {
+    pressed: HashSet<A>,
+    just_pressed: HashSet<A>,
+    just_released: HashSet<A>,
+}
+
+impl<A> Default for PlayerInput<A> {
+    fn default() -> Self {
+        PlayerInput {
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+}
+
+impl<A: Copy + Eq + Hash> PlayerInput<A> {
+    pub fn new() -> Self {
+        PlayerInput::default()
+    }
+
+    /// Whether the action is held.
+    pub fn pressed(&self, action: A) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Whether the action started since the last [`InputRouter::end_frame`].
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether the action stopped since the last [`InputRouter::end_frame`].
+    pub fn just_released(&self, action: A) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    pub fn press(&mut self, action: A) {
+        if self.pressed.insert(action) {
+            self.just_pressed.insert(action);
+        }
+    }
+
+    pub fn release(&mut self, action: A) {
+        if self.pressed.remove(&action) {
+            self.just_released.insert(action);
+        }
+    }
+
+    fn clear_transitions(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+/// Maps keys to the local player and action they belong to, and applies key events to the
+/// matching [`PlayerInput`].
+///
+/// Keys are matched by their physical position, so a split like WASD and the arrow keys stays
+/// the same on every keyboard layout.
+///
+/// # Examples
+/// ```ignore
+/// let mut router = InputRouter::new();
+/// router
+///     .bind(p1, KeyCode::KeyW, Action::Up)
+///     .bind(p2, KeyCode::ArrowUp, Action::Up);
+/// app.world.spawn((p1, PlayerInput::<Action>::new()));
+/// app.world.spawn((p2, PlayerInput::<Action>::new()));
+///
+/// // In the event loop:
+/// router.handle_window_event(&mut app.world, &event);
+/// // After the update:
+/// router.end_frame(&mut app.world);
+/// ```
+#[derive(Debug, Clone)]
+pub struct InputRouter<A> {
+    bindings: HashMap<KeyCode, (PlayerId, A)>,
+}
+
+impl<A> Default for InputRouter<A> {
+    fn default() -> Self {
+        InputRouter {
+            bindings: HashMap::new(),
+        }
+    }
+}
+
+impl<A: Copy + Eq + Hash + Send + Sync + 'static> InputRouter<A> {
+    pub fn new() -> Self {
+        InputRouter::default()
+    }
+
+    /// Routes `key` to `action` of `player`. A key belongs to one player, binding it again moves
+    /// it.
+    pub fn bind(&mut self, player: PlayerId, key: KeyCode, action: A) -> &mut Self {
+        self.bindings.insert(key, (player, action));
+        self
+    }
+
+    /// Binds the keys of the player's settings, turning each action name into an action with
+    /// `action`. Names it returns `None` for are skipped.
+    pub fn bind_settings(
+        &mut self,
+        player: PlayerId,
+        settings: &Settings,
+        action: impl Fn(&str) -> Option<A>,
+    ) -> &mut Self {
+        for (name, key) in &settings.key_bindings {
+            if let Some(action) = action(name) {
+                self.bind(player, *key, action);
+            }
+        }
+        self
+    }
+
+    pub fn unbind(&mut self, key: KeyCode) {
+        self.bindings.remove(&key);
+    }
+
+    /// Removes every binding of `player`, e.g. when they leave the session.
+    pub fn unbind_player(&mut self, player: PlayerId) {
+        self.bindings.retain(|_, (p, _)| *p != player);
+    }
+
+    /// The player and action `key` is bound to.
+    pub fn binding(&self, key: KeyCode) -> Option<(PlayerId, A)> {
+        self.bindings.get(&key).copied()
+    }
+
+    /// Applies keyboard events to the bound players' input. Key repeats and other events are
+    /// ignored.
+    pub fn handle_window_event(&self, world: &mut World, event: &WindowEvent) {
+        if let WindowEvent::KeyboardInput { event, .. } = event {
+            if event.repeat {
+                return;
+            }
+            if let PhysicalKey::Code(key) = event.physical_key {
+                self.route(world, key, event.state == ElementState::Pressed);
+            }
+        }
+    }
+
+    /// Presses or releases the action bound to `key` on every entity of its player.
+    pub fn route(&self, world: &mut World, key: KeyCode, pressed: bool) {
+        let Some((player, action)) = self.binding(key) else {
+            return;
+        };
+        for (_, (id, input)) in world.query_mut::<(&PlayerId, &mut PlayerInput<A>)>() {
+            if *id != player {
+                continue;
+            }
+            if pressed {
+                input.press(action);
+            } else {
+                input.release(action);
+            }
+        }
+    }
+
+    /// Clears the just pressed and just released actions of every player. Call it once the
+    /// frame's systems have run.
+    pub fn end_frame(&self, world: &mut World) {
+        for (_, input) in world.query_mut::<&mut PlayerInput<A>>() {
+            input.clear_transitions();
+        }
+    }
+}
+
+/// The keyboard and mouse as of this update, kept on a single entity of the world. The
+/// [`Engine`](crate::engine::Engine) feeds it every window event, with a custom event loop call
+/// [`Input::update`]. The just pressed and just released keys, the scroll and the text are
+/// cleared at the end of each [`App::update`](crate::app::App::update).
+///
+/// ```ignore
+/// let input = Input::get(&world).unwrap();
+/// if input.just_pressed(KeyCode::Space) {
+///     jump(world);
+/// }
+/// if let (true, Some(ndc)) = (input.button_pressed(MouseButton::Left), input.cursor_ndc()) {
+///     aim_at(world, ndc);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Input {
+    keys: PlayerInput<KeyCode>,
+    buttons: PlayerInput<MouseButton>,
+    /// In physical pixels, `None` outside the window.
+    cursor: Option<[f32; 2]>,
+    window_size: [f32; 2],
+    scale_factor: f64,
+    scroll: [f32; 2],
+    mouse_motion: [f32; 2],
+    focused: bool,
+    text: String,
+}
+
+impl Input {
+    /// State for a window of `size` physical pixels, with nothing pressed.
+    pub fn new(size: PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Input {
+            keys: PlayerInput::new(),
+            buttons: PlayerInput::new(),
+            cursor: None,
+            window_size: [size.width as f32, size.height as f32],
+            scale_factor,
+            scroll: [0.0; 2],
+            mouse_motion: [0.0; 2],
+            focused: true,
+            text: String::new(),
+        }
+    }
+
+    /// The input of `world`, if it has any yet.
+    pub fn get(world: &World) -> Option<hecs::Ref<'_, Input>> {
+        let (entity, _) = world.query::<&Input>().iter().next()?;
+        world.get::<&Input>(entity).ok()
+    }
+
+    /// Applies `event` to the input of `world`. Without one it adds one, which doesn't know the
+    /// window's size before it is resized.
+    pub fn update(world: &mut World, event: &WindowEvent) {
+        if let Some((_, input)) = world.query_mut::<&mut Input>().into_iter().next() {
+            input.handle_window_event(event);
+            return;
+        }
+        let mut input = Input::new(PhysicalSize::new(1, 1), 1.0);
+        input.handle_window_event(event);
+        world.spawn((input,));
+    }
+
+    /// Applies the raw mouse motion of `event` to the input of `world`, if it has one.
+    pub fn update_device(world: &mut World, event: &DeviceEvent) {
+        for (_, input) in world.query_mut::<&mut Input>() {
+            input.handle_device_event(event);
+        }
+    }
+
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                let pressed = event.state == ElementState::Pressed;
+                if let (PhysicalKey::Code(key), false) = (event.physical_key, event.repeat) {
+                    self.set_key(key, pressed);
+                }
+                // Repeats type the character again.
+                if let (Some(text), true) = (&event.text, pressed) {
+                    self.text.push_str(text);
+                }
+            }
+            WindowEvent::Ime(Ime::Commit(text)) => self.text.push_str(text),
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.set_button(*button, *state == ElementState::Pressed);
+            }
+            WindowEvent::CursorMoved { position, .. } => self.set_cursor(Some(*position)),
+            WindowEvent::CursorLeft { .. } => self.set_cursor(None),
+            WindowEvent::MouseWheel { delta, .. } => self.add_scroll(*delta),
+            WindowEvent::Resized(size) => {
+                self.window_size = [size.width as f32, size.height as f32];
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor;
+            }
+            WindowEvent::Focused(focused) => {
+                self.focused = *focused;
+                // Releases come to the focused window, so they'd be missed.
+                if !focused {
+                    self.release_all();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Device events come in whether the window is focused or not, only motion while it is
+    /// counts.
+    pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+        if let (DeviceEvent::MouseMotion { delta: (x, y) }, true) = (event, self.focused) {
+            self.add_mouse_motion([*x as f32, *y as f32]);
+        }
+    }
+
+    /// Whether `key` is held.
+    pub fn pressed(&self, key: KeyCode) -> bool {
+        self.keys.pressed(key)
+    }
+
+    /// Whether `key` went down this update.
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.keys.just_pressed(key)
+    }
+
+    /// Whether `key` went up this update.
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.keys.just_released(key)
+    }
+
+    pub fn button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons.pressed(button)
+    }
+
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons.just_pressed(button)
+    }
+
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons.just_released(button)
+    }
+
+    /// The cursor in logical pixels from the top left of the window, `None` outside it.
+    pub fn cursor(&self) -> Option<[f32; 2]> {
+        let scale = self.scale_factor as f32;
+        self.cursor.map(|[x, y]| [x / scale, y / scale])
+    }
+
+    /// The cursor in normalized device coordinates of the window, from -1 at the top left to 1
+    /// at the bottom right.
+    pub fn cursor_ndc(&self) -> Option<[f32; 2]> {
+        self.cursor
+            .map(|cursor| [0, 1].map(|i| cursor[i] / self.window_size[i] * 2.0 - 1.0))
+    }
+
+    /// How far the wheel turned this update, in lines. Positive `y` scrolls up, positive `x`
+    /// right.
+    pub fn scroll(&self) -> [f32; 2] {
+        self.scroll
+    }
+
+    /// How far the mouse moved this update, unaccelerated and in device units, right and down
+    /// positive. Unlike the cursor it keeps moving at the edges of the screen and while the
+    /// cursor is locked, e.g. for mouse look.
+    pub fn mouse_motion(&self) -> [f32; 2] {
+        self.mouse_motion
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// The text typed this update, e.g. for a text field.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_key(&mut self, key: KeyCode, pressed: bool) {
+        if pressed {
+            self.keys.press(key);
+        } else {
+            self.keys.release(key);
+        }
+    }
+
+    pub fn set_button(&mut self, button: MouseButton, pressed: bool) {
+        if pressed {
+            self.buttons.press(button);
+        } else {
+            self.buttons.release(button);
+        }
+    }
+
+    pub fn set_cursor(&mut self, position: Option<PhysicalPosition<f64>>) {
+        self.cursor = position.map(|p| [p.x as f32, p.y as f32]);
+    }
+
+    pub fn add_scroll(&mut self, delta: MouseScrollDelta) {
+        let [x, y] = match delta {
+            MouseScrollDelta::LineDelta(x, y) => [x, y],
+            MouseScrollDelta::PixelDelta(p) => {
+                let logical = p.to_logical::<f32>(self.scale_factor);
+                [logical.x / PIXELS_PER_LINE, logical.y / PIXELS_PER_LINE]
+            }
+        };
+        self.scroll[0] += x;
+        self.scroll[1] += y;
+    }
+
+    pub fn add_mouse_motion(&mut self, delta: [f32; 2]) {
+        self.mouse_motion[0] += delta[0];
+        self.mouse_motion[1] += delta[1];
+    }
+
+    /// Releases every key and button, e.g. when the window loses focus.
+    pub fn release_all(&mut self) {
+        for key in self.keys.pressed.clone() {
+            self.keys.release(key);
+        }
+        for button in self.buttons.pressed.clone() {
+            self.buttons.release(button);
+        }
+    }
+
+    /// Clears what happened this update, keeping what is held.
+    pub fn end_frame(&mut self) {
+        self.keys.clear_transitions();
+        self.buttons.clear_transitions();
+        self.scroll = [0.0; 2];
+        self.mouse_motion = [0.0; 2];
+        self.text.clear();
+    }
+}
+
+pub(crate) fn end_frame(world: &mut World) {
+    for (_, input) in world.query_mut::<&mut Input>() {
+        input.end_frame();
+    }
+}
@@ -0,0 +1,148 @@
+#[cfg(test)]
+mod tests {
+    use crate::input::{Input, InputRouter, PlayerInput};
+    use crate::netcode::PlayerId;
+    use crate::settings::Settings;
+    use hecs::World;
+    use winit::{
+        dpi::{PhysicalPosition, PhysicalSize},
+        event::{DeviceEvent, MouseButton, MouseScrollDelta, WindowEvent},
+        keyboard::KeyCode,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Action {
+        Up,
+        Fire,
+    }
+
+    fn split_keyboard() -> (World, InputRouter<Action>, hecs::Entity, hecs::Entity) {
+        let p1 = PlayerId::new(1).unwrap();
+        let p2 = PlayerId::new(2).unwrap();
+        let mut router = InputRouter::new();
+        router
+            .bind(p1, KeyCode::KeyW, Action::Up)
+            .bind(p1, KeyCode::Space, Action::Fire)
+            .bind(p2, KeyCode::ArrowUp, Action::Up);
+
+        let mut world = World::new();
+        let e1 = world.spawn((p1, PlayerInput::<Action>::new()));
+        let e2 = world.spawn((p2, PlayerInput::<Action>::new()));
+        (world, router, e1, e2)
+    }
+
+    #[test]
+    fn test_routes_keys_to_their_player() {
+        let (mut world, router, e1, e2) = split_keyboard();
+        router.route(&mut world, KeyCode::ArrowUp, true);
+
+        let input1 = world.get::<&PlayerInput<Action>>(e1).unwrap();
+        let input2 = world.get::<&PlayerInput<Action>>(e2).unwrap();
+        assert!(!input1.pressed(Action::Up));
+        assert!(input2.pressed(Action::Up));
+        assert!(input2.just_pressed(Action::Up));
+    }
+
+    #[test]
+    fn test_end_frame_clears_transitions() {
+        let (mut world, router, e1, _) = split_keyboard();
+        router.route(&mut world, KeyCode::Space, true);
+        router.end_frame(&mut world);
+        {
+            let input = world.get::<&PlayerInput<Action>>(e1).unwrap();
+            assert!(input.pressed(Action::Fire));
+            assert!(!input.just_pressed(Action::Fire));
+        }
+
+        router.route(&mut world, KeyCode::Space, false);
+        let input = world.get::<&PlayerInput<Action>>(e1).unwrap();
+        assert!(!input.pressed(Action::Fire));
+        assert!(input.just_released(Action::Fire));
+    }
+
+    #[test]
+    fn test_unbind_player() {
+        let (mut world, mut router, e1, _) = split_keyboard();
+        router.unbind_player(PlayerId::new(1).unwrap());
+        router.route(&mut world, KeyCode::KeyW, true);
+
+        assert!(router.binding(KeyCode::ArrowUp).is_some());
+        let input = world.get::<&PlayerInput<Action>>(e1).unwrap();
+        assert!(!input.pressed(Action::Up));
+    }
+
+    #[test]
+    fn test_bind_settings() {
+        let mut settings = Settings::default();
+        settings
+            .bind_key("up", KeyCode::KeyW)
+            .bind_key("dance", KeyCode::KeyX);
+        let player = PlayerId::new(1).unwrap();
+
+        let mut router = InputRouter::new();
+        router.bind_settings(player, &settings, |name| match name {
+            "up" => Some(Action::Up),
+            _ => None,
+        });
+        assert_eq!(Some((player, Action::Up)), router.binding(KeyCode::KeyW));
+        assert_eq!(None, router.binding(KeyCode::KeyX));
+    }
+
+    #[test]
+    fn test_input_key_transitions() {
+        let mut input = Input::new(PhysicalSize::new(800, 600), 1.0);
+        input.set_key(KeyCode::Space, true);
+        input.set_button(MouseButton::Left, true);
+        assert!(input.pressed(KeyCode::Space));
+        assert!(input.just_pressed(KeyCode::Space));
+        assert!(input.button_just_pressed(MouseButton::Left));
+
+        input.end_frame();
+        assert!(input.pressed(KeyCode::Space));
+        assert!(!input.just_pressed(KeyCode::Space));
+
+        input.release_all();
+        assert!(!input.pressed(KeyCode::Space));
+        assert!(input.just_released(KeyCode::Space));
+        assert!(input.button_just_released(MouseButton::Left));
+    }
+
+    #[test]
+    fn test_input_cursor() {
+        let mut input = Input::new(PhysicalSize::new(800, 600), 2.0);
+        assert_eq!(None, input.cursor_ndc());
+
+        input.set_cursor(Some(PhysicalPosition::new(200.0, 450.0)));
+        assert_eq!(Some([100.0, 225.0]), input.cursor());
+        assert_eq!(Some([-0.5, 0.5]), input.cursor_ndc());
+
+        input.set_cursor(None);
+        assert_eq!(None, input.cursor());
+    }
+
+    #[test]
+    fn test_input_scroll_lasts_one_frame() {
+        let mut input = Input::new(PhysicalSize::new(800, 600), 2.0);
+        input.add_scroll(MouseScrollDelta::LineDelta(0.0, 1.0));
+        input.add_scroll(MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+            80.0, 0.0,
+        )));
+        assert_eq!([2.0, 1.0], input.scroll());
+
+        input.end_frame();
+        assert_eq!([0.0, 0.0], input.scroll());
+    }
+
+    #[test]
+    fn test_input_mouse_motion_needs_focus() {
+        let mut input = Input::new(PhysicalSize::new(800, 600), 1.0);
+        input.handle_device_event(&DeviceEvent::MouseMotion { delta: (3.0, -2.0) });
+        input.handle_device_event(&DeviceEvent::MouseMotion { delta: (1.0, 0.0) });
+        assert_eq!([4.0, -2.0], input.mouse_motion());
+
+        input.end_frame();
+        input.handle_window_event(&WindowEvent::Focused(false));
+        input.handle_device_event(&DeviceEvent::MouseMotion { delta: (3.0, -2.0) });
+        assert_eq!([0.0, 0.0], input.mouse_motion());
+    }
+}
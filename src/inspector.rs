@@ -0,0 +1,191 @@
+//! Names for entities, and a listing of the world to find out what it holds while debugging.
+//!
+//! ```ignore
+//! let player = inspector::spawn_named(&mut app.world, "player", (Transform::IDENTITY,));
+//! assert_eq!(inspector::find_by_name(&app.world, "player"), Some(player));
+//!
+//! // Lists the entities and resources once a second.
+//! app.add_system(inspector::log_every(Duration::from_secs(1)));
+//! ```
+//!
+//! hecs only knows components by `TypeId`, so the listing names the types registered with
+//! [`ComponentNames::register`] and shows the others as unknown. The engine's own components
+//! are registered already. The listing ends with the app's systems and whether they run, see
+//! [`App::set_system_enabled`](crate::app::App::set_system_enabled).
+
+use std::{
+    any::{type_name, TypeId},
+    cell::Cell,
+    collections::HashMap,
+    fmt::{self, Write},
+    time::Duration,
+};
+
+use hecs::{Component, DynamicBundle, Entity, World};
+
+use crate::{
+    app::SystemStates,
+    commands::CommandQueue,
+    hierarchy::{Children, GlobalTransform, Parent},
+    time::{Time, Timer, TimerMode},
+    transform::{PreviousTransform, RenderTransform, Transform},
+};
+
+/// A name to tell entities apart in the inspector and to look them up with [`find_by_name`].
+/// Names don't have to be unique.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Name(String);
+
+impl Name {
+    pub fn new(name: impl Into<String>) -> Self {
+        Name(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Spawns an entity with `components` and a [`Name`].
+pub fn spawn_named(
+    world: &mut World,
+    name: impl Into<String>,
+    components: impl DynamicBundle,
+) -> Entity {
+    let entity = world.spawn(components);
+    world.insert_one(entity, Name::new(name)).unwrap();
+    entity
+}
+
+/// The first entity called `name`, if any.
+pub fn find_by_name(world: &World, name: &str) -> Option<Entity> {
+    world
+        .query::<&Name>()
+        .iter()
+        .find(|(_, n)| n.as_str() == name)
+        .map(|(entity, _)| entity)
+}
+
+/// The type names of components, for listing them. Kept on a single entity of the world.
+pub struct ComponentNames {
+    names: HashMap<TypeId, &'static str>,
+}
+
+impl Default for ComponentNames {
+    fn default() -> Self {
+        let mut names = ComponentNames {
+            names: HashMap::new(),
+        };
+        names
+            .register::<Name>()
+            .register::<Transform>()
+            .register::<PreviousTransform>()
+            .register::<RenderTransform>()
+            .register::<GlobalTransform>()
+            .register::<Parent>()
+            .register::<Children>()
+            .register::<Time>()
+            .register::<CommandQueue>()
+            .register::<SystemStates>();
+        names
+    }
+}
+
+impl ComponentNames {
+    /// Names `T` in the listing, by its type name without the module path.
+    pub fn register<T: Component>(&mut self) -> &mut Self {
+        let full = type_name::<T>();
+        // Keeps generic arguments whole, e.g. `State<game::Screen>`.
+        let path_end = full.find('<').unwrap_or(full.len());
+        let start = full[..path_end].rfind("::").map_or(0, |i| i + 2);
+        self.names.insert(TypeId::of::<T>(), &full[start..]);
+        self
+    }
+
+    /// The name of the component type `id`, `None` if it wasn't registered.
+    pub fn get(&self, id: TypeId) -> Option<&'static str> {
+        self.names.get(&id).copied()
+    }
+
+    /// Like [`ComponentNames::register`] on the names in `world`, adding them if there are
+    /// none.
+    pub fn register_in<T: Component>(world: &mut World) {
+        if let Some((_, names)) = world.query_mut::<&mut ComponentNames>().into_iter().next() {
+            names.register::<T>();
+            return;
+        }
+        let mut names = ComponentNames::default();
+        names.register::<T>();
+        world.spawn((names,));
+    }
+}
+
+/// Lists the entities of `world` with their names and component types, one per line, followed
+/// by the resources and the systems. Resources are entities with a single unnamed component,
+/// which is how the engine keeps them.
+pub fn inspect(world: &World) -> String {
+    let default_names = ComponentNames::default();
+    let mut registered = world.query::<&ComponentNames>();
+    let names = registered
+        .iter()
+        .next()
+        .map_or(&default_names, |(_, names)| names);
+    let name_of = |id: TypeId| names.get(id).unwrap_or("unknown");
+
+    let mut entities = String::new();
+    let mut resources = Vec::new();
+    let mut count = 0;
+    for entity in world.iter() {
+        let types: Vec<_> = entity.component_types().collect();
+        let name = entity.get::<&Name>();
+        if types.len() == 1 && name.is_none() {
+            resources.push(name_of(types[0]));
+            continue;
+        }
+        count += 1;
+        let _ = write!(entities, "{:?}", entity.entity());
+        if let Some(name) = name {
+            let _ = write!(entities, " \"{name}\"");
+        }
+        let mut types: Vec<_> = types.into_iter().map(name_of).collect();
+        types.sort_unstable();
+        let _ = writeln!(entities, ": {}", types.join(", "));
+    }
+
+    resources.sort_unstable();
+    let mut listing = format!("{count} entities\n{entities}");
+    let _ = writeln!(listing, "resources: {}", resources.join(", "));
+
+    let mut systems = world.query::<&SystemStates>();
+    if let Some((_, SystemStates(states))) = systems.iter().next() {
+        let _ = writeln!(listing, "{} systems", states.len());
+        for state in states {
+            let toggle = match (state.enabled, state.runs) {
+                (true, true) => "enabled",
+                (true, false) => "stage disabled",
+                (false, _) => "disabled",
+            };
+            let _ = writeln!(listing, "{} {}: {toggle}", state.stage, state.name);
+        }
+    }
+    listing
+}
+
+/// A system that logs [`inspect`] every `interval` of real time, also while the game is paused.
+pub fn log_every(interval: Duration) -> impl Fn(&mut World) + 'static {
+    let timer = Cell::new(Timer::new(interval, TimerMode::Repeating));
+    move |world| {
+        let mut ticked = timer.get();
+        if ticked.tick(Time::get(world).raw_delta()).just_finished() {
+            tracing::debug!(target: "inspector", "\n{}", inspect(world));
+        }
+        timer.set(ticked);
+    }
+}
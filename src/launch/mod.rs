@@ -0,0 +1,163 @@
+//! Command line options shared by every onion executable, so demos and the dedicated server start
+//! the same way.
+
+use std::{fmt, path::PathBuf};
+
+#[cfg(feature = "graphics")]
+use crate::{graphics::context::DebugLevel, settings::Settings};
+
+mod tests;
+
+/// Printed for `--help`.
+pub const USAGE: &str = "\
+options:
+    --headless          run without a window
+    --width <pixels>    window width, overriding the settings file
+    --height <pixels>   window height, overriding the settings file
+    --no-validation     disable the Vulkan validation layers
+    --connect <addr>    join the session at addr
+    --replay <file>     play back a recorded session
+    --help              print this message";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchError {
+    /// The option needs a value and none followed it.
+    MissingValue(String),
+    /// The value of the option doesn't parse.
+    InvalidValue { option: String, value: String },
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::MissingValue(option) => write!(f, "{option} needs a value"),
+            LaunchError::InvalidValue { option, value } => {
+                write!(f, "invalid value {value:?} for {option}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+/// The parsed command line.
+///
+/// Options can be given as `--width 1280` or `--width=1280`. Anything that isn't one of the
+/// options in [`USAGE`] ends up in [`LaunchOptions::extra`] for the game to parse.
+///
+/// # Examples
+/// ```ignore
+/// let options = LaunchOptions::from_env_or_exit();
+/// let settings = SettingsFile::load("settings.toml")?;
+/// let mut startup = settings.get().clone();
+/// options.apply(&mut startup);
+///
+/// if !options.headless {
+///     let gfx = GraphicsContext::builder()
+///         .settings(&startup)
+///         .debug(options.debug_level())
+///         .build(&event_loop)?;
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub headless: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub no_validation: bool,
+    /// The address of the session to join.
+    pub connect: Option<String>,
+    /// The recorded session to play back.
+    pub replay: Option<PathBuf>,
+    pub help: bool,
+    /// Arguments that aren't engine options, in order.
+    pub extra: Vec<String>,
+}
+
+impl LaunchOptions {
+    /// Parses the arguments the process was started with.
+    pub fn from_env() -> Result<Self, LaunchError> {
+        LaunchOptions::parse(std::env::args().skip(1))
+    }
+
+    /// Like [`LaunchOptions::from_env`], but prints [`USAGE`] and exits for `--help` or invalid
+    /// options, as every onion executable does.
+    pub fn from_env_or_exit() -> Self {
+        match LaunchOptions::from_env() {
+            Ok(options) if options.help => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("{e}\n\n{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    /// Parses `args`, without the executable name.
+    pub fn parse<I, S>(args: I) -> Result<Self, LaunchError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut options = LaunchOptions::default();
+        let mut args = args.into_iter().map(Into::into);
+        while let Some(arg) = args.next() {
+            let (option, inline) = match arg.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => {
+                    (option.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| LaunchError::MissingValue(option.clone()))
+            };
+
+            match option.as_str() {
+                "--headless" => options.headless = true,
+                "--no-validation" => options.no_validation = true,
+                "--help" => options.help = true,
+                "--width" => options.width = Some(parse_value(&option, value()?)?),
+                "--height" => options.height = Some(parse_value(&option, value()?)?),
+                "--connect" => options.connect = Some(value()?),
+                "--replay" => options.replay = Some(PathBuf::from(value()?)),
+                _ => options.extra.push(arg),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Overrides `settings` with the options given, e.g. the window size. Meant for the settings
+    /// of this launch, so the overrides aren't saved to the settings file.
+    #[cfg(feature = "graphics")]
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(width) = self.width {
+            settings.window_size[0] = width;
+        }
+        if let Some(height) = self.height {
+            settings.window_size[1] = height;
+        }
+    }
+
+    /// [`DebugLevel::Off`] with `--no-validation`, otherwise the default for the build.
+    #[cfg(feature = "graphics")]
+    pub fn debug_level(&self) -> DebugLevel {
+        if self.no_validation {
+            DebugLevel::Off
+        } else {
+            DebugLevel::default()
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(option: &str, value: String) -> Result<T, LaunchError> {
+    value.parse().map_err(|_| LaunchError::InvalidValue {
+        option: option.to_owned(),
+        value,
+    })
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use crate::launch::{LaunchError, LaunchOptions};
+    #[cfg(feature = "graphics")]
+    use crate::settings::Settings;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse() {
+        let options = LaunchOptions::parse([
+            "--headless",
+            "--width",
+            "800",
+            "--replay=match.rpl",
+            "--connect",
+            "127.0.0.1:7777",
+            "--level",
+            "3",
+        ])
+        .unwrap();
+
+        assert!(options.headless);
+        assert_eq!(Some(800), options.width);
+        assert_eq!(None, options.height);
+        assert_eq!(Some(PathBuf::from("match.rpl")), options.replay);
+        assert_eq!(Some("127.0.0.1:7777".to_owned()), options.connect);
+        assert_eq!(vec!["--level".to_owned(), "3".to_owned()], options.extra);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(LaunchError::MissingValue("--connect".to_owned())),
+            LaunchOptions::parse(["--connect"])
+        );
+        assert_eq!(
+            Err(LaunchError::InvalidValue {
+                option: "--height".to_owned(),
+                value: "tall".to_owned(),
+            }),
+            LaunchOptions::parse(["--height=tall"])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "graphics")]
+    fn test_apply_overrides_window_size() {
+        let mut settings = Settings::default();
+        LaunchOptions::parse(["--height", "600"])
+            .unwrap()
+            .apply(&mut settings);
+        assert_eq!(
+            [Settings::default().window_size[0], 600],
+            settings.window_size
+        );
+    }
+}
@@ -1,3 +1,42 @@
+#[cfg(feature = "graphics")]
+pub mod accessibility;
 pub mod app;
+pub mod bundle;
+pub mod commands;
+pub mod crash;
+pub mod diagnostics;
+#[cfg(feature = "graphics")]
+pub mod engine;
+pub mod event;
+#[cfg(feature = "graphics")]
+pub mod file_drop;
+#[cfg(feature = "graphics")]
 pub mod graphics;
+pub mod hierarchy;
+#[cfg(feature = "graphics")]
+pub mod input;
+pub mod inspector;
+pub mod launch;
+pub mod logging;
 pub mod netcode;
+pub mod pool;
+pub mod prelude;
+pub mod redraw;
+pub mod removal;
+pub mod resource;
+pub mod save;
+#[cfg(feature = "serde")]
+pub mod scene;
+pub mod schedule;
+#[cfg(feature = "graphics")]
+pub mod settings;
+pub mod state;
+pub mod streaming;
+pub mod system;
+pub mod time;
+pub mod transform;
+
+pub use onion_macros::{Bundle, IdType};
+
+// The derives name `::onion`, which has to resolve inside this crate too.
+extern crate self as onion;
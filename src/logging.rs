@@ -0,0 +1,31 @@
+//! The engine reports what it does through `tracing`, under these targets:
+//!
+//! - `graphics`: device selection, shader reloads and camera matrices.
+//! - `graphics::vulkan`: validation layer messages, see `DebugLevel`.
+//! - `ecs`: a span per update and per system, and dropped simulation time.
+//! - `netcode`: rollbacks and commits.
+//!
+//! Nothing is printed unless the app installs a subscriber. Apps with their own subscriber can
+//! filter on the targets, the rest can enable the `subscriber` feature and call [`init`].
+
+/// Default filter of [`init`], when `RUST_LOG` isn't set.
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Prints the engine's events to stderr, filtered by `RUST_LOG`, e.g.
+/// `RUST_LOG=graphics=debug,ecs=trace`. Does nothing if a subscriber is already installed.
+#[cfg(feature = "subscriber")]
+pub fn init() {
+    init_with_filter(DEFAULT_FILTER);
+}
+
+/// Like [`init`], with `filter` instead of [`DEFAULT_FILTER`] when `RUST_LOG` isn't set.
+#[cfg(feature = "subscriber")]
+pub fn init_with_filter(filter: &str) {
+    use tracing_subscriber::EnvFilter;
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(filter));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
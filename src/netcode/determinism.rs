@@ -0,0 +1,193 @@
+//! Catches simulations that give different results on different machines, which desyncs networked
+//! games.
+//!
+//! A scripted run feeds fixed inputs through a [`Replayable`] and checksums the state of every
+//! frame. Tests compare the checksums against golden values recorded once, so a change in float
+//! results or iteration order shows up as the first frame that diverged. Checksums hash floats by
+//! their bits, so `0.1 + 0.2` computed two ways is a divergence even if it prints the same.
+//!
+//! hecs iterates archetypes in the order they were created, and entities in the order they were
+//! spawned into them. [`checksum_world`] sorts by entity instead, so it only depends on which
+//! entities exist and their components.
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use hecs::{Component, World};
+
+use super::replay::Replayable;
+
+/// A 64 bit FNV-1a hasher. Unlike `DefaultHasher` its output is the same on every platform and
+/// Rust version, so checksums can be stored.
+#[derive(Debug, Clone)]
+pub struct StateHasher(u64);
+
+impl StateHasher {
+    pub fn new() -> Self {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        StateHasher::new()
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    // The defaults write native endian bytes.
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+}
+
+/// Checksum of a value without floats.
+pub fn checksum<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StateHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Checksum of every `C` in `world`, in entity order. `hash` writes one component, which lets
+/// components with floats use [`StateHasher::write_f32`].
+pub fn checksum_world<C: Component>(world: &World, hash: impl Fn(&C, &mut StateHasher)) -> u64 {
+    let mut components: Vec<_> = world
+        .query::<&C>()
+        .iter()
+        .map(|(entity, component)| {
+            let mut hasher = StateHasher::new();
+            hash(component, &mut hasher);
+            (entity.to_bits(), hasher.finish())
+        })
+        .collect();
+    components.sort_unstable();
+
+    let mut hasher = StateHasher::new();
+    components.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Runs `next` from `seed` over `inputs` the way a client does, and returns the checksum of
+/// [`Replayable::current`] on every frame: the seed's, then one per input advanced. Frames are
+/// committed every `commit_every` frames, like a client confirming server frames, so the run goes
+/// through the same recomputation as a networked game. Zero never commits.
+pub fn run_script<Input: Clone, State: Clone>(
+    next: fn(&Input, &State) -> State,
+    seed: State,
+    inputs: &[Input],
+    commit_every: u64,
+    checksum: impl Fn(&State) -> u64,
+) -> Vec<u64> {
+    let Some((first, rest)) = inputs.split_first() else {
+        return Vec::new();
+    };
+    let mut replay = Replayable::new(next, seed, first.clone());
+    let mut checksums = vec![checksum(replay.current())];
+    for (frame, input) in (2..).zip(rest) {
+        replay.advance(input.clone());
+        if commit_every > 0 && frame % commit_every == 0 {
+            replay.commit(frame);
+        }
+        checksums.push(checksum(replay.current()));
+    }
+    checksums
+}
+
+/// The first frame where a run differed from the golden values. Frames count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub frame: u64,
+    pub expected: Option<u64>,
+    pub actual: Option<u64>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |checksum: Option<u64>| match checksum {
+            Some(checksum) => format!("{checksum:#018x}"),
+            None => "nothing".to_owned(),
+        };
+        write!(
+            f,
+            "simulation diverged on frame {}: expected {}, got {}",
+            self.frame,
+            show(self.expected),
+            show(self.actual)
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Compares a run against golden checksums, e.g. `assert_eq!(Ok(()), verify(&run, GOLDEN))`.
+pub fn verify(actual: &[u64], golden: &[u64]) -> Result<(), Divergence> {
+    let frames = actual.len().max(golden.len());
+    for index in 0..frames {
+        let (expected, actual) = (golden.get(index).copied(), actual.get(index).copied());
+        if expected != actual {
+            return Err(Divergence {
+                frame: index as u64 + 1,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+/// Lets one-shot events raised by the simulation, like sound triggers, through exactly once even
+/// though rollbacks re-simulate the frames that raised them.
+///
+/// Systems [`emit`](EventGate::emit) events tagged with the frame being simulated, every time the
+/// frame is simulated. Before re-simulating from a frame, [`rewind`](EventGate::rewind) forgets
+/// what was emitted from it on, so events a misprediction raised are dropped. Once frames are
+/// confirmed, [`confirm`](EventGate::confirm) hands out their events, each once.
+pub struct EventGate<E> {
+    pending: BTreeMap<u64, Vec<E>>,
+    // Frames up to and including this one have been handed out.
+    confirmed: Option<u64>,
+}
+
+impl<E> Default for EventGate<E> {
+    fn default() -> Self {
+        EventGate {
+            pending: BTreeMap::new(),
+            confirmed: None,
+        }
+    }
+}
+
+impl<E: PartialEq> EventGate<E> {
+    pub fn new() -> Self {
+        EventGate::default()
+    }
+
+    /// Records `event` raised while simulating `frame`. Raising the same event on the same frame
+    /// again, e.g. while re-simulating it, has no effect. Events for confirmed frames are ignored.
+    pub fn emit(&mut self, frame: u64, event: E) {
+        if self.confirmed.is_some_and(|confirmed| frame <= confirmed) {
+            return;
+        }
+        let events = self.pending.entry(frame).or_default();
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+
+    /// Forgets the events of `frame` and later, before they are simulated again.
+    pub fn rewind(&mut self, frame: u64) {
+        tracing::trace!(target: "netcode", frame, "rewinding gated events");
+        self.pending.split_off(&frame);
+    }
+
+    /// Marks every frame up to and including `frame` as confirmed and returns their events that
+    /// weren't returned before, oldest frame first. The same event raised on two different frames
+    /// is returned twice.
+    pub fn confirm(&mut self, frame: u64) -> Vec<E> {
+        let later = self.pending.split_off(&(frame + 1));
+        let ready = std::mem::replace(&mut self.pending, later);
+        self.confirmed = Some(self.confirmed.map_or(frame, |c| c.max(frame)));
+        ready.into_values().flatten().collect()
+    }
+
+    /// Events of frames that aren't confirmed yet, with their frame.
+    pub fn pending(&self) -> impl Iterator<Item = (u64, &E)> {
+        self.pending
+            .iter()
+            .flat_map(|(frame, events)| events.iter().map(move |e| (*frame, e)))
+    }
+}
@@ -1,3 +1,13 @@
+pub mod determinism;
+pub mod gate;
 pub mod replay;
 mod tests;
-pub mod net;
\ No newline at end of file
+pub mod net;
+
+use std::num::NonZeroU32;
+
+use crate::IdType;
+
+/// Identifies a player in a networked session.
+#[derive(IdType, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PlayerId(NonZeroU32);
@@ -0,0 +1,184 @@
+//! Moves messages between peers. The netcode only needs to send and poll opaque messages, so any
+//! reliable, ordered channel works as a [`Transport`].
+
+use std::{
+    fmt,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetError {
+    /// Connecting to the address failed, with the reason given by the platform.
+    Connect(String),
+    /// The connection isn't open yet.
+    NotConnected,
+    /// The other side closed the connection, or it was lost.
+    Closed,
+    /// Sending failed, with the reason given by the platform.
+    Send(String),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Connect(reason) => write!(f, "failed to connect: {reason}"),
+            NetError::NotConnected => write!(f, "not connected yet"),
+            NetError::Closed => write!(f, "connection closed"),
+            NetError::Send(reason) => write!(f, "failed to send: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+/// A reliable, ordered connection to one peer.
+pub trait Transport {
+    /// Queues `message` to be sent.
+    fn send(&mut self, message: &[u8]) -> Result<(), NetError>;
+
+    /// The next message received, or `None` if there is none right now. Never blocks, so it can
+    /// be polled once per tick.
+    fn recv(&mut self) -> Result<Option<Vec<u8>>, NetError>;
+}
+
+/// Both ends of an in-process connection, for tests and for running a host and a client in the
+/// same process.
+pub struct Loopback {
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+}
+
+impl Loopback {
+    /// Two connected ends. Dropping one closes the other.
+    pub fn pair() -> (Loopback, Loopback) {
+        let (a_tx, b_rx) = mpsc::channel();
+        let (b_tx, a_rx) = mpsc::channel();
+        (
+            Loopback { tx: a_tx, rx: a_rx },
+            Loopback { tx: b_tx, rx: b_rx },
+        )
+    }
+}
+
+impl Transport for Loopback {
+    fn send(&mut self, message: &[u8]) -> Result<(), NetError> {
+        self.tx.send(message.to_vec()).map_err(|_| NetError::Closed)
+    }
+
+    fn recv(&mut self) -> Result<Option<Vec<u8>>, NetError> {
+        match self.rx.try_recv() {
+            Ok(message) => Ok(Some(message)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(NetError::Closed),
+        }
+    }
+}
+
+#[cfg(all(feature = "websocket", target_arch = "wasm32"))]
+pub use websocket::WebSocketTransport;
+
+#[cfg(all(feature = "websocket", target_arch = "wasm32"))]
+mod websocket {
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+    use wasm_bindgen::{closure::Closure, JsCast};
+    use web_sys::{BinaryType, CloseEvent, MessageEvent, WebSocket};
+
+    use super::{NetError, Transport};
+
+    #[derive(Default)]
+    struct Inbox {
+        messages: VecDeque<Vec<u8>>,
+        closed: bool,
+    }
+
+    /// A connection through the browser's WebSocket API, for clients running in a browser.
+    ///
+    /// Messages are sent as binary frames. The socket connects in the background, sending fails
+    /// with [`NetError::NotConnected`] until it is open, see [`WebSocketTransport::is_open`].
+    pub struct WebSocketTransport {
+        socket: WebSocket,
+        inbox: Rc<RefCell<Inbox>>,
+        _on_message: Closure<dyn FnMut(MessageEvent)>,
+        _on_close: Closure<dyn FnMut(CloseEvent)>,
+    }
+
+    impl WebSocketTransport {
+        /// Starts connecting to `url`, e.g. `wss://example.com/session`.
+        pub fn connect(url: &str) -> Result<Self, NetError> {
+            let socket = WebSocket::new(url).map_err(|e| NetError::Connect(format!("{e:?}")))?;
+            socket.set_binary_type(BinaryType::Arraybuffer);
+
+            let inbox = Rc::new(RefCell::new(Inbox::default()));
+
+            let on_message = {
+                let inbox = inbox.clone();
+                Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                    // Text frames aren't part of the protocol and are dropped.
+                    if let Ok(buffer) = event.data().dyn_into::<js_sys::ArrayBuffer>() {
+                        let message = js_sys::Uint8Array::new(&buffer).to_vec();
+                        inbox.borrow_mut().messages.push_back(message);
+                    }
+                })
+            };
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+            let on_close = {
+                let inbox = inbox.clone();
+                Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+                    tracing::debug!(
+                        target: "netcode",
+                        code = event.code(),
+                        reason = %event.reason(),
+                        "websocket closed"
+                    );
+                    inbox.borrow_mut().closed = true;
+                })
+            };
+            socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+            Ok(WebSocketTransport {
+                socket,
+                inbox,
+                _on_message: on_message,
+                _on_close: on_close,
+            })
+        }
+
+        pub fn is_open(&self) -> bool {
+            self.socket.ready_state() == WebSocket::OPEN
+        }
+    }
+
+    impl Transport for WebSocketTransport {
+        fn send(&mut self, message: &[u8]) -> Result<(), NetError> {
+            if self.inbox.borrow().closed {
+                return Err(NetError::Closed);
+            }
+            if !self.is_open() {
+                return Err(NetError::NotConnected);
+            }
+            self.socket
+                .send_with_u8_array(message)
+                .map_err(|e| NetError::Send(format!("{e:?}")))
+        }
+
+        fn recv(&mut self) -> Result<Option<Vec<u8>>, NetError> {
+            let mut inbox = self.inbox.borrow_mut();
+            match inbox.messages.pop_front() {
+                Some(message) => Ok(Some(message)),
+                None if inbox.closed => Err(NetError::Closed),
+                None => Ok(None),
+            }
+        }
+    }
+
+    impl Drop for WebSocketTransport {
+        fn drop(&mut self) {
+            // The handlers are freed with the transport, the socket must not call them after.
+            self.socket.set_onmessage(None);
+            self.socket.set_onclose(None);
+            let _ = self.socket.close();
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::LinkedList;
 
 pub fn net() {
-    println!("test")
+    tracing::debug!(target: "netcode", "test")
 }
 
 pub struct Replayable<Input, State> {
@@ -95,6 +95,7 @@ impl <Input: Clone, State: Clone> Replayable<Input, State> {
 
     // Commits all frames before the given id, clearing them from the buffer
     pub fn commit(&mut self, id: u64) {
+        tracing::trace!(target: "netcode", frame = id, "committing frames");
         let missing = id - self.frame;
         for _i in 0..missing {
             self.advance(self.history.back().unwrap().clone())
@@ -1,7 +1,16 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::netcode::replay;
+    use std::hash::Hasher;
+
+    use hecs::World;
+
+    use crate::netcode::{
+        determinism::{self, StateHasher},
+        gate,
+        net::{Loopback, NetError, Transport},
+        replay,
+    };
     #[test]
     fn test_simple_current() {
         let mut r = replay::Replayable::new(|input: &i8, state: &i8| -> i8 {
@@ -51,4 +60,139 @@ mod tests {
         assert_eq!(384, *r.current());
     }
 
+    #[test]
+    fn test_gate_resimulated_event_plays_once() {
+        let mut g = gate::EventGate::new();
+        g.emit(3, "jump");
+        g.rewind(2);
+        g.emit(3, "jump");
+        g.emit(3, "jump");
+        assert_eq!(vec!["jump"], g.confirm(3));
+        assert!(g.confirm(4).is_empty());
+
+        g.emit(3, "jump");
+        assert!(g.confirm(5).is_empty());
+    }
+
+    #[test]
+    fn test_gate_drops_mispredicted_event() {
+        let mut g = gate::EventGate::new();
+        g.emit(1, "step");
+        g.emit(2, "hit");
+        g.rewind(2);
+        g.emit(2, "miss");
+        assert_eq!(vec!["step", "miss"], g.confirm(2));
+    }
+
+    #[test]
+    fn test_gate_keeps_unconfirmed_events() {
+        let mut g = gate::EventGate::new();
+        g.emit(1, "step");
+        g.emit(4, "step");
+        assert_eq!(vec!["step"], g.confirm(2));
+        assert_eq!(vec![(4, &"step")], g.pending().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_loopback_delivers_in_order() {
+        let (mut a, mut b) = Loopback::pair();
+        a.send(b"hello").unwrap();
+        a.send(b"world").unwrap();
+        assert_eq!(Some(b"hello".to_vec()), b.recv().unwrap());
+        assert_eq!(Some(b"world".to_vec()), b.recv().unwrap());
+        assert_eq!(None, b.recv().unwrap());
+
+        drop(a);
+        assert_eq!(Err(NetError::Closed), b.recv());
+        assert_eq!(Err(NetError::Closed), b.send(b"anyone?"));
+    }
+
+    #[derive(Clone)]
+    struct Ship {
+        x: f32,
+        v: f32,
+    }
+
+    fn thrust(input: &i8, ship: &Ship) -> Ship {
+        let v = (ship.v + *input as f32 * 0.1) * 0.99;
+        Ship { x: ship.x + v / 60.0, v }
+    }
+
+    fn ship_checksum(ship: &Ship) -> u64 {
+        let mut h = StateHasher::new();
+        h.write_f32(ship.x);
+        h.write_f32(ship.v);
+        h.finish()
+    }
+
+    fn ship_inputs() -> Vec<i8> {
+        (0..12).map(|i| [1, 1, 0, -1][i % 4]).collect()
+    }
+
+    // Recorded on x86_64. Only uses IEEE basic arithmetic, which every platform has to round the
+    // same way.
+    const SHIP_GOLDEN: &[u64] = &[
+        0xa8c7f832281a39c5,
+        0x7fcd3311875945c6,
+        0x682b9d8b5eb3f75e,
+        0xe447899bd5173218,
+        0x30fb444a9fbfff28,
+        0xa745356b1bc00536,
+        0x5673bf97204f66b5,
+        0xa236036b3ace0d9c,
+        0xe23ec573a3a68b86,
+        0xc4e04fbcb9a0bd1e,
+        0x673c172dd4346c1e,
+        0x74b78408412c516e,
+    ];
+
+    #[test]
+    fn test_script_matches_golden() {
+        let seed = Ship { x: 0.0, v: 0.0 };
+        let run = determinism::run_script(thrust, seed, &ship_inputs(), 0, ship_checksum);
+        assert_eq!(Ok(()), determinism::verify(&run, SHIP_GOLDEN));
+    }
+
+    #[test]
+    fn test_script_commits_dont_change_state() {
+        let seed = Ship { x: 0.0, v: 0.0 };
+        let run = determinism::run_script(thrust, seed, &ship_inputs(), 3, ship_checksum);
+        assert_eq!(Ok(()), determinism::verify(&run, SHIP_GOLDEN));
+    }
+
+    #[test]
+    fn test_verify_reports_first_divergence() {
+        let mut run = SHIP_GOLDEN[..8].to_vec();
+        run[6] ^= 1;
+        let divergence = determinism::verify(&run, SHIP_GOLDEN).unwrap_err();
+        assert_eq!(7, divergence.frame);
+        assert_eq!(Some(SHIP_GOLDEN[6]), divergence.expected);
+    }
+
+    #[test]
+    fn test_world_checksum_ignores_archetype_order() {
+        let hash = |x: &f32, h: &mut StateHasher| h.write_f32(*x);
+
+        let mut a = World::new();
+        let a1 = a.spawn((1.0f32,));
+        a.spawn((2.0f32, "tag"));
+
+        let mut b = World::new();
+        let b1 = b.spawn((1.0f32, "tag"));
+        let b2 = b.spawn((2.0f32,));
+        b.remove_one::<&str>(b1).unwrap();
+        b.insert_one(b2, "tag").unwrap();
+
+        assert_eq!(a1, b1);
+        assert_eq!(
+            determinism::checksum_world(&a, hash),
+            determinism::checksum_world(&b, hash)
+        );
+
+        *b.get::<&mut f32>(b1).unwrap() = 1.5;
+        assert_ne!(
+            determinism::checksum_world(&a, hash),
+            determinism::checksum_world(&b, hash)
+        );
+    }
 }
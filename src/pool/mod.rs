@@ -0,0 +1,178 @@
+//! Fixed-capacity pools for objects and entities that are created and destroyed often, such as
+//! bullets and particles.
+
+use hecs::{DynamicBundle, Entity, World};
+
+mod tests;
+
+/// A fixed number of preallocated slots that are handed out and returned instead of allocated
+/// and dropped.
+pub struct Pool<T> {
+    slots: Vec<T>,
+    active: Vec<bool>,
+    free: Vec<usize>,
+    reset: Box<dyn FnMut(&mut T)>,
+}
+
+impl<T> Pool<T> {
+    /// Fills `capacity` slots with `create`.
+    pub fn new(capacity: usize, mut create: impl FnMut() -> T) -> Self {
+        Self {
+            slots: (0..capacity).map(|_| create()).collect(),
+            active: vec![false; capacity],
+            // Reversed so slots are handed out from the front.
+            free: (0..capacity).rev().collect(),
+            reset: Box::new(|_| ()),
+        }
+    }
+
+    /// Runs `reset` on every slot as it is released.
+    pub fn with_reset(mut self, reset: impl FnMut(&mut T) + 'static) -> Self {
+        self.reset = Box::new(reset);
+        self
+    }
+
+    /// Marks a free slot as in use and returns its index, or `None` if the pool is exhausted.
+    pub fn acquire(&mut self) -> Option<usize> {
+        let index = self.free.pop()?;
+        self.active[index] = true;
+        Some(index)
+    }
+
+    /// Resets the slot and makes it available again. Releasing a free slot, or an index past
+    /// [`Pool::capacity`], does nothing.
+    pub fn release(&mut self, index: usize) {
+        if !self.is_active(index) {
+            return;
+        }
+        (self.reset)(&mut self.slots[index]);
+        self.active[index] = false;
+        self.free.push(index);
+    }
+
+    /// The slot at `index` if it is in use.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.is_active(index).then(|| &self.slots[index])
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if self.is_active(index) {
+            Some(&mut self.slots[index])
+        } else {
+            None
+        }
+    }
+
+    /// Slots in use with their indices.
+    pub fn iter_active(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.active[*i])
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    fn is_active(&self, index: usize) -> bool {
+        self.active.get(index).copied().unwrap_or(false)
+    }
+}
+
+/// Marks an entity owned by an [`EntityPool`]. Systems over pooled entities should skip the ones
+/// that are not `active`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pooled {
+    pub active: bool,
+}
+
+type Reset = Box<dyn FnMut(&mut World, Entity)>;
+
+/// Entities spawned up front with the same components and recycled instead of despawned.
+///
+/// Recycling only flips [`Pooled::active`], so the entities never move between archetypes.
+///
+/// # Examples
+/// ```ignore
+/// let mut bullets = EntityPool::new(&mut world, 256, || (Position::default(), Velocity::default()))
+///     .with_reset(|world, entity| *world.get::<&mut Velocity>(entity).unwrap() = Velocity::default());
+///
+/// if let Some(bullet) = bullets.spawn(&mut world) {
+///     *world.get::<&mut Position>(bullet).unwrap() = muzzle;
+/// }
+/// ```
+pub struct EntityPool {
+    entities: Vec<Entity>,
+    free: Vec<Entity>,
+    reset: Reset,
+}
+
+impl EntityPool {
+    /// Spawns `capacity` inactive entities with the components from `bundle`.
+    pub fn new<B: DynamicBundle>(
+        world: &mut World,
+        capacity: usize,
+        mut bundle: impl FnMut() -> B,
+    ) -> Self {
+        let entities: Vec<Entity> = (0..capacity)
+            .map(|_| {
+                let entity = world.spawn(bundle());
+                world.insert_one(entity, Pooled { active: false }).unwrap();
+                entity
+            })
+            .collect();
+
+        Self {
+            free: entities.iter().rev().copied().collect(),
+            entities,
+            reset: Box::new(|_, _| ()),
+        }
+    }
+
+    /// Runs `reset` on every entity as it is despawned.
+    pub fn with_reset(mut self, reset: impl FnMut(&mut World, Entity) + 'static) -> Self {
+        self.reset = Box::new(reset);
+        self
+    }
+
+    /// Activates a pooled entity, or returns `None` if all of them are in use.
+    pub fn spawn(&mut self, world: &mut World) -> Option<Entity> {
+        let entity = self.free.pop()?;
+        world.get::<&mut Pooled>(entity).unwrap().active = true;
+        Some(entity)
+    }
+
+    /// Resets the entity and returns it to the pool. Entities that are not active members of
+    /// this pool are ignored.
+    pub fn despawn(&mut self, world: &mut World, entity: Entity) {
+        if !self.entities.contains(&entity) {
+            return;
+        }
+        match world.get::<&mut Pooled>(entity) {
+            Ok(mut pooled) if pooled.active => pooled.active = false,
+            _ => return,
+        }
+        (self.reset)(world, entity);
+        self.free.push(entity);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.entities.len() - self.free.len()
+    }
+
+    /// Despawns every entity of the pool from `world`.
+    pub fn clear(self, world: &mut World) {
+        for entity in self.entities {
+            let _ = world.despawn(entity);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use crate::pool::{EntityPool, Pool, Pooled};
+    use hecs::World;
+
+    #[test]
+    fn test_pool_exhausts() {
+        let mut pool = Pool::new(2, || 0);
+        assert!(pool.acquire().is_some());
+        assert!(pool.acquire().is_some());
+        assert!(pool.acquire().is_none());
+        assert_eq!(2, pool.active_count());
+    }
+
+    #[test]
+    fn test_pool_out_of_range() {
+        let mut pool = Pool::new(2, || 0);
+        pool.acquire().unwrap();
+        assert!(pool.get(2).is_none());
+        assert!(pool.get_mut(usize::MAX).is_none());
+        pool.release(2);
+        assert_eq!(1, pool.active_count());
+    }
+
+    #[test]
+    fn test_pool_release_resets() {
+        let mut pool = Pool::new(1, || 0).with_reset(|i| *i = 0);
+        let index = pool.acquire().unwrap();
+        *pool.get_mut(index).unwrap() = 5;
+        pool.release(index);
+        assert!(pool.get(index).is_none());
+
+        let index = pool.acquire().unwrap();
+        assert_eq!(Some(&0), pool.get(index));
+    }
+
+    #[test]
+    fn test_entity_pool_recycles() {
+        let mut world = World::new();
+        let mut pool = EntityPool::new(&mut world, 1, || (0u32,))
+            .with_reset(|world, entity| *world.get::<&mut u32>(entity).unwrap() = 0);
+
+        let entity = pool.spawn(&mut world).unwrap();
+        *world.get::<&mut u32>(entity).unwrap() = 7;
+        assert!(pool.spawn(&mut world).is_none());
+
+        pool.despawn(&mut world, entity);
+        assert!(!world.get::<&Pooled>(entity).unwrap().active);
+        assert_eq!(entity, pool.spawn(&mut world).unwrap());
+        assert_eq!(0, *world.get::<&u32>(entity).unwrap());
+    }
+}
@@ -0,0 +1,61 @@
+//! The commonly used types, gathered in one place.
+//!
+//! Most applications can start with:
+//!
+//! ```ignore
+//! use onion::prelude::*;
+//! ```
+//!
+//! instead of reaching into `graphics::context`, `graphics::camera` and friends one by one.
+
+// ECS: the entity world, the app that drives systems over it, entity pools and transforms.
+pub use crate::app::{App, System};
+pub use crate::bundle::SpawnBundle;
+pub use crate::commands::Commands;
+pub use crate::diagnostics::Diagnostics;
+#[cfg(feature = "graphics")]
+pub use crate::engine::{Engine, RenderSystem, WindowInput};
+pub use crate::event::{Event, EventReader, EventWriter, Events, Trigger};
+#[cfg(feature = "graphics")]
+pub use crate::file_drop::FileDrop;
+pub use crate::hierarchy::{Children, GlobalTransform, Parent};
+#[cfg(feature = "graphics")]
+pub use crate::input::Input;
+pub use crate::inspector::Name;
+pub use crate::pool::{EntityPool, Pool, Pooled};
+pub use crate::redraw::{RedrawMode, RedrawRequested};
+pub use crate::removal::RemovedComponents;
+pub use crate::resource::FromWorld;
+#[cfg(feature = "graphics")]
+pub use crate::settings::{Settings, SettingsChanged};
+pub use crate::state::{in_state, State, States};
+pub use crate::system::{IntoSystem, Query, Res, ResMut};
+pub use crate::time::{Stopwatch, Time, Timer, TimerMode};
+pub use crate::transform::{RenderTransform, Transform};
+pub use hecs::World;
+
+// Graphics: the context owning the device and window, cameras, lights, colors, and drawable
+// shapes.
+#[cfg(feature = "graphics")]
+pub use crate::graphics::{
+    camera::{Camera, PerspectiveCamera},
+    context::{DebugLevel, GraphicsContext, GraphicsContextBuilder, WindowMode},
+    cube::Cube,
+    cursor::Cursor,
+    debug_view::DebugView,
+    error::GraphicsError,
+    layers::RenderLayers,
+    light::{Light, ShadowSettings},
+    picking::Picked,
+    reset::GraphicsReset,
+    sampler::SamplerDesc,
+    shadow::ShadowMap,
+    shape::{Circle, Fill, Rect, Square},
+    sprite::{PlaybackMode, SpriteAnimation},
+    texture::{NinePatch, Texture, UvRect},
+    viewport::{SplitScreen, ViewportRegion},
+    Color,
+};
+
+// Netcode: rollback-friendly state history.
+pub use crate::netcode::replay::Replayable;
@@ -0,0 +1,85 @@
+//! Rendering only when something changed, to save power in tools and editors.
+//!
+//! With [`RedrawMode::OnDemand`] the event loop sleeps until there is input, and a frame is only
+//! rendered after input or when a system asks for one by spawning [`RedrawRequested`], e.g. every
+//! update while an animation plays. The loop asks the [`App`](crate::app::App) what to do:
+//!
+//! ```ignore
+//! app.set_redraw_mode(RedrawMode::OnDemand);
+//!
+//! event_loop.run(move |event, elwt| {
+//!     elwt.set_control_flow(app.control_flow());
+//!     match event {
+//!         Event::WindowEvent { event: WindowEvent::RedrawRequested, .. } => render(&mut gfx),
+//!         Event::WindowEvent { event, .. } => app.handle_window_event(&event),
+//!         Event::AboutToWait => {
+//!             app.update();
+//!             if app.take_redraw() {
+//!                 gfx.window.request_redraw();
+//!             }
+//!         }
+//!         _ => (),
+//!     }
+//! })
+//! ```
+
+use hecs::World;
+#[cfg(feature = "graphics")]
+use winit::event::WindowEvent;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedrawMode {
+    /// Render as often as possible, like games do.
+    #[default]
+    Continuous,
+    /// Wait for input and only render after input or a [`RedrawRequested`].
+    OnDemand,
+}
+
+/// Spawned by systems that need another frame, e.g. while animating. Despawned by the next
+/// update, which marks a redraw as needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedrawRequested;
+
+/// Asks for a frame after this update.
+pub fn request(world: &mut World) {
+    world.spawn((RedrawRequested,));
+}
+
+/// Whether `event` changes what the window should show: input, resizes, focus and dropped files.
+#[cfg(feature = "graphics")]
+pub fn is_input(event: &WindowEvent) -> bool {
+    matches!(
+        event,
+        WindowEvent::KeyboardInput { .. }
+            | WindowEvent::ModifiersChanged(_)
+            | WindowEvent::Ime(_)
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::CursorEntered { .. }
+            | WindowEvent::CursorLeft { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::Resized(_)
+            | WindowEvent::ScaleFactorChanged { .. }
+            | WindowEvent::Focused(_)
+            | WindowEvent::Occluded(false)
+            | WindowEvent::ThemeChanged(_)
+            | WindowEvent::HoveredFile(_)
+            | WindowEvent::DroppedFile(_)
+            | WindowEvent::HoveredFileCancelled
+    )
+}
+
+/// Despawns the requests and returns whether there were any.
+pub(crate) fn take(world: &mut World) -> bool {
+    let entities: Vec<_> = world
+        .query::<&RedrawRequested>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for &entity in &entities {
+        world.despawn(entity).unwrap();
+    }
+    !entities.is_empty()
+}
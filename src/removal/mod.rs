@@ -0,0 +1,141 @@
+//! Finding out which entities lost a component, e.g. to release the GPU buffer or close the
+//! socket that belonged to it.
+//!
+//! hecs doesn't report removals, so the [`App`](crate::app::App) compares the entities with a
+//! tracked component at the end of each update with those at the end of the last one. An entity
+//! counts as having lost the component whether it was removed or the entity despawned. Two
+//! things follow from that:
+//!
+//! - A component removed and added again within one update isn't reported, the entity has it at
+//!   the end of both.
+//! - Each tracked type costs a pass over the entities with the component per update, so track
+//!   the ones whose removal matters rather than every type.
+//!
+//! ```ignore
+//! app.on_remove::<Connection>(|world, entity| {
+//!     // `entity` may be despawned already.
+//!     sockets.close(entity);
+//! });
+//!
+//! // Or read them in a system of the next update.
+//! fn release_meshes(removed: Res<RemovedComponents<Mesh>>, mut gpu: ResMut<GpuMeshes>) {
+//!     for entity in removed.iter() {
+//!         gpu.release(entity);
+//!     }
+//! }
+//! app.track_removals::<Mesh>().add_system(release_meshes);
+//! ```
+
+use std::{any::Any, collections::HashSet, marker::PhantomData};
+
+use hecs::{Component, Entity, World};
+
+use crate::resource;
+
+mod tests;
+
+/// The entities that lost their `T` component during the last update, kept on a single entity
+/// of the world by [`App::track_removals`](crate::app::App::track_removals).
+pub struct RemovedComponents<T: Component> {
+    removed: Vec<Entity>,
+    /// The entities with a `T` at the end of the last update.
+    present: HashSet<Entity>,
+    _component: PhantomData<fn() -> T>,
+}
+
+impl<T: Component> Default for RemovedComponents<T> {
+    fn default() -> Self {
+        RemovedComponents {
+            removed: Vec::new(),
+            present: HashSet::new(),
+            _component: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> RemovedComponents<T> {
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.removed.iter().copied()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.removed.contains(&entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.removed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+pub(crate) type Hook = Box<dyn Fn(&mut World, Entity)>;
+
+/// The removal hooks of one component type.
+pub(crate) struct RemovalTracker<T: Component> {
+    hooks: Vec<Hook>,
+    /// The entities with a `T` at the end of the last update, once `apply` replaced them in the
+    /// [`RemovedComponents`]. Kept to reuse the allocation.
+    previous: HashSet<Entity>,
+    _component: PhantomData<fn() -> T>,
+}
+
+impl<T: Component> RemovalTracker<T> {
+    pub(crate) fn new() -> Self {
+        RemovalTracker {
+            hooks: Vec::new(),
+            previous: HashSet::new(),
+            _component: PhantomData,
+        }
+    }
+
+    pub(crate) fn add_hook(&mut self, hook: Hook) {
+        self.hooks.push(hook);
+    }
+}
+
+/// A [`RemovalTracker`] of any component type.
+pub(crate) trait Removals {
+    /// Updates the [`RemovedComponents`] and runs the hooks for each removal.
+    fn apply(&mut self, world: &mut World);
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Component> Removals for RemovalTracker<T> {
+    fn apply(&mut self, world: &mut World) {
+        let removed = {
+            let Some(mut tracked) = resource::get_mut::<RemovedComponents<T>>(world) else {
+                return;
+            };
+            let tracked = &mut *tracked;
+            let mut present = std::mem::take(&mut self.previous);
+            present.clear();
+            present.extend(world.query::<&T>().iter().map(|(entity, _)| entity));
+            self.previous = std::mem::replace(&mut tracked.present, present);
+            tracked.removed.clear();
+            tracked.removed.extend(
+                self.previous
+                    .iter()
+                    .filter(|entity| !tracked.present.contains(entity)),
+            );
+            // In a fixed order, so hooks run the same way on every machine.
+            tracked.removed.sort_unstable();
+            if self.hooks.is_empty() {
+                return;
+            }
+            tracked.removed.clone()
+        };
+        for entity in removed {
+            for hook in &self.hooks {
+                hook(world, entity);
+            }
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::app::App;
+    use crate::removal::RemovedComponents;
+    use crate::resource;
+    use hecs::{Entity, World};
+    use std::sync::{Arc, Mutex};
+
+    struct Mesh;
+
+    fn removed(app: &App) -> Vec<Entity> {
+        let removed = resource::get::<RemovedComponents<Mesh>>(&app.world).unwrap();
+        removed.iter().collect()
+    }
+
+    #[test]
+    fn test_remove_and_despawn() {
+        let mut app = App::new();
+        app.track_removals::<Mesh>();
+        let (kept, removed_from, despawned) = (
+            app.world.spawn((Mesh,)),
+            app.world.spawn((Mesh,)),
+            app.world.spawn((Mesh,)),
+        );
+        app.step();
+        assert!(removed(&app).is_empty());
+
+        app.world.remove_one::<Mesh>(removed_from).unwrap();
+        app.world.despawn(despawned).unwrap();
+        app.step();
+        let mut expected = vec![removed_from, despawned];
+        expected.sort_unstable();
+        assert_eq!(expected, removed(&app));
+        assert!(!removed(&app).contains(&kept));
+
+        // Reported for one update only.
+        app.step();
+        assert!(removed(&app).is_empty());
+    }
+
+    #[test]
+    fn test_reinsert() {
+        let mut app = App::new();
+        app.track_removals::<Mesh>();
+        let entity = app.world.spawn((Mesh,));
+        app.step();
+
+        // Removed and added again within an update: the entity has it at the end of both.
+        app.world.remove_one::<Mesh>(entity).unwrap();
+        app.world.insert_one(entity, Mesh).unwrap();
+        app.step();
+        assert!(removed(&app).is_empty());
+
+        app.world.remove_one::<Mesh>(entity).unwrap();
+        app.step();
+        assert_eq!(vec![entity], removed(&app));
+        app.world.insert_one(entity, Mesh).unwrap();
+        app.step();
+        app.world.remove_one::<Mesh>(entity).unwrap();
+        app.step();
+        assert_eq!(vec![entity], removed(&app));
+    }
+
+    #[test]
+    fn test_hooks() {
+        let mut app = App::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hooked = seen.clone();
+        app.on_remove::<Mesh>(move |world: &mut World, entity| {
+            hooked
+                .lock()
+                .unwrap()
+                .push((entity, world.contains(entity)));
+        });
+        let entity = app.world.spawn((Mesh,));
+        app.step();
+        app.world.despawn(entity).unwrap();
+        app.step();
+        app.step();
+        assert_eq!(vec![(entity, false)], *seen.lock().unwrap());
+    }
+}
@@ -0,0 +1,114 @@
+//! Resources: values the world holds once, like [`Time`](crate::time::Time), kept as the
+//! component of a single entity.
+//!
+//! ```ignore
+//! #[derive(Default)]
+//! struct Score(u32);
+//!
+//! struct SpawnTable {
+//!     rate: f32,
+//! }
+//!
+//! impl FromWorld for SpawnTable {
+//!     fn from_world(world: &mut World) -> Self {
+//!         let difficulty = resource::get::<Difficulty>(world).map_or(1.0, |d| d.0);
+//!         SpawnTable { rate: 2.0 * difficulty }
+//!     }
+//! }
+//!
+//! app.init_resource::<Score>().init_resource::<SpawnTable>();
+//! ```
+
+use hecs::{Component, Entity, Ref, RefMut, World};
+
+mod tests;
+
+/// Resources made from the world, e.g. from other resources. Types with a [`Default`] get it
+/// from that.
+pub trait FromWorld {
+    fn from_world(world: &mut World) -> Self;
+}
+
+impl<T: Default> FromWorld for T {
+    fn from_world(_: &mut World) -> Self {
+        T::default()
+    }
+}
+
+/// The entity holding the resource of type `T`, if any.
+pub fn entity<T: Component>(world: &World) -> Option<Entity> {
+    world.query::<&T>().iter().next().map(|(entity, _)| entity)
+}
+
+/// The resource of type `T`, `None` if the world has none or it is borrowed mutably.
+pub fn get<T: Component>(world: &World) -> Option<Ref<'_, T>> {
+    world.get::<&T>(entity::<T>(world)?).ok()
+}
+
+/// The resource of type `T` to change, `None` if the world has none or it is borrowed.
+pub fn get_mut<T: Component>(world: &World) -> Option<RefMut<'_, T>> {
+    world.get::<&mut T>(entity::<T>(world)?).ok()
+}
+
+/// Sets the resource of type `T`, replacing the one the world has.
+pub fn insert<T: Component>(world: &mut World, value: T) {
+    if let Some((_, current)) = world.query_mut::<&mut T>().into_iter().next() {
+        *current = value;
+        return;
+    }
+    world.spawn((value,));
+}
+
+/// Adds the resource of type `T` made with [`FromWorld`], unless the world has one already.
+pub fn init<T: Component + FromWorld>(world: &mut World) {
+    if entity::<T>(world).is_none() {
+        let value = T::from_world(world);
+        insert(world, value);
+    }
+}
+
+/// The resource of type `T`, added with `f` if the world has none.
+///
+/// Panics if the resource is borrowed.
+pub fn get_or_insert_with<T: Component>(world: &mut World, f: impl FnOnce() -> T) -> RefMut<'_, T> {
+    let entity = match entity::<T>(world) {
+        Some(entity) => entity,
+        None => world.spawn((f(),)),
+    };
+    world
+        .get::<&mut T>(entity)
+        .expect("the resource is borrowed")
+}
+
+/// Runs `f` with the resource of type `T` and the world, for changing both at once. The resource
+/// is taken out of the world while `f` runs, so `f` doesn't find it there. Returns `None`
+/// without running `f` if the world has none.
+pub fn scope<T: Component, R>(
+    world: &mut World,
+    f: impl FnOnce(&mut World, &mut T) -> R,
+) -> Option<R> {
+    let entity = entity::<T>(world)?;
+    let mut value = world.remove_one::<T>(entity).ok()?;
+    let result = f(world, &mut value);
+    if world.contains(entity) {
+        world.insert_one(entity, value).unwrap();
+    } else {
+        // `f` despawned the entity.
+        insert(world, value);
+    }
+    Some(result)
+}
+
+/// Takes the resource of type `T` out of the world, despawning its entity if nothing else is
+/// on it.
+pub fn remove<T: Component>(world: &mut World) -> Option<T> {
+    let entity = entity::<T>(world)?;
+    let value = world.remove_one::<T>(entity).ok()?;
+    if world
+        .entity(entity)
+        .is_ok_and(|e| e.component_types().next().is_none())
+    {
+        let _ = world.despawn(entity);
+    }
+    Some(value)
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests {
+    use crate::resource;
+    use hecs::World;
+
+    #[derive(Debug, PartialEq)]
+    struct Score(u32);
+
+    #[test]
+    fn test_scope_restores_value() {
+        let mut world = World::new();
+        resource::insert(&mut world, Score(1));
+        let entity = resource::entity::<Score>(&world);
+        let found = resource::scope(&mut world, |world, score: &mut Score| {
+            score.0 += 1;
+            resource::get::<Score>(world).is_some()
+        });
+        assert_eq!(Some(false), found);
+        assert_eq!(Score(2), *resource::get::<Score>(&world).unwrap());
+        assert_eq!(entity, resource::entity::<Score>(&world));
+    }
+
+    #[test]
+    fn test_scope_after_despawn() {
+        let mut world = World::new();
+        resource::insert(&mut world, Score(1));
+        let entity = resource::entity::<Score>(&world).unwrap();
+        resource::scope(&mut world, |world, score: &mut Score| {
+            world.despawn(entity).unwrap();
+            score.0 += 1;
+        });
+        assert_eq!(Score(2), *resource::get::<Score>(&world).unwrap());
+        assert_eq!(
+            None,
+            resource::scope(&mut World::new(), |_, _: &mut Score| ())
+        );
+    }
+}
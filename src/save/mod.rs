@@ -0,0 +1,221 @@
+//! Named save slots on disk with a version header, so saves written by older builds can be
+//! migrated forward when the saved data changes shape.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+mod tests;
+
+const MAGIC: &[u8; 4] = b"ONSV";
+const EXTENSION: &str = "sav";
+const FLAG_COMPRESSED: u8 = 1;
+/// Magic, version and flags.
+const HEADER_LEN: usize = 4 + 4 + 1;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// Slot names may only contain ASCII letters, digits, `-` and `_`.
+    InvalidSlot(String),
+    /// The file is not a save or is truncated.
+    Corrupt,
+    /// The save was written by a newer build.
+    TooNew(u32),
+    /// No migration is registered from this version.
+    MissingMigration(u32),
+    /// A migration rejected the data.
+    Migration(u32, String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "save file i/o failed: {e}"),
+            SaveError::InvalidSlot(slot) => write!(f, "invalid save slot name {slot:?}"),
+            SaveError::Corrupt => write!(f, "save file is corrupt"),
+            SaveError::TooNew(version) => {
+                write!(f, "save version {version} is newer than this build")
+            }
+            SaveError::MissingMigration(version) => {
+                write!(f, "no migration registered from save version {version}")
+            }
+            SaveError::Migration(version, e) => {
+                write!(f, "migrating save from version {version} failed: {e}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+type Migration = Box<dyn Fn(Vec<u8>) -> Result<Vec<u8>, String>>;
+
+/// Persists player progress into named slots under a directory.
+///
+/// The game decides what goes into a save; this only stores bytes. Every save records
+/// [`SaveGame::version`], and loading an older save runs the registered migrations one version
+/// at a time until it matches.
+///
+/// # Examples
+/// ```ignore
+/// let mut saves = SaveGame::new("saves", 2);
+/// // Version 2 added a trailing byte for the difficulty.
+/// saves.register_migration(1, |mut data| {
+///     data.push(0);
+///     Ok(data)
+/// });
+///
+/// saves.save("slot1", &progress.to_bytes())?;
+/// let progress = Progress::from_bytes(&saves.load("slot1")?);
+/// ```
+pub struct SaveGame {
+    dir: PathBuf,
+    version: u32,
+    compress: bool,
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl SaveGame {
+    /// Stores slots in `dir`, created on the first save. `version` is the format this build
+    /// writes.
+    pub fn new(dir: impl Into<PathBuf>, version: u32) -> Self {
+        Self {
+            dir: dir.into(),
+            version,
+            compress: true,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// Saves are deflate compressed unless disabled here, e.g. to inspect them by hand.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Registers the upgrade of a save from version `from` to `from + 1`.
+    pub fn register_migration(
+        &mut self,
+        from: u32,
+        migration: impl Fn(Vec<u8>) -> Result<Vec<u8>, String> + 'static,
+    ) -> &mut Self {
+        self.migrations.insert(from, Box::new(migration));
+        self
+    }
+
+    /// Writes `data` to `slot`, replacing the previous save only once the new one is complete.
+    pub fn save(&self, slot: &str, data: &[u8]) -> Result<(), SaveError> {
+        let path = self.slot_path(slot)?;
+        fs::create_dir_all(&self.dir)?;
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + data.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        if self.compress {
+            bytes.push(FLAG_COMPRESSED);
+            let mut encoder = DeflateEncoder::new(bytes, Compression::default());
+            encoder.write_all(data)?;
+            bytes = encoder.finish()?;
+        } else {
+            bytes.push(0);
+            bytes.extend_from_slice(data);
+        }
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Reads `slot` and migrates it to the current version.
+    pub fn load(&self, slot: &str) -> Result<Vec<u8>, SaveError> {
+        let bytes = fs::read(self.slot_path(slot)?)?;
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            return Err(SaveError::Corrupt);
+        }
+
+        let mut version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        if version > self.version {
+            return Err(SaveError::TooNew(version));
+        }
+
+        let body = &bytes[HEADER_LEN..];
+        let mut data = if bytes[8] & FLAG_COMPRESSED != 0 {
+            let mut data = Vec::new();
+            DeflateDecoder::new(body)
+                .read_to_end(&mut data)
+                .map_err(|_| SaveError::Corrupt)?;
+            data
+        } else {
+            body.to_vec()
+        };
+
+        while version < self.version {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or(SaveError::MissingMigration(version))?;
+            data = migration(data).map_err(|e| SaveError::Migration(version, e))?;
+            version += 1;
+        }
+
+        Ok(data)
+    }
+
+    pub fn exists(&self, slot: &str) -> bool {
+        self.slot_path(slot).is_ok_and(|path| path.is_file())
+    }
+
+    pub fn delete(&self, slot: &str) -> Result<(), SaveError> {
+        fs::remove_file(self.slot_path(slot)?)?;
+        Ok(())
+    }
+
+    /// Names of every slot in the directory, sorted.
+    pub fn slots(&self) -> Result<Vec<String>, SaveError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut slots = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == EXTENSION) {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    slots.push(stem.to_owned());
+                }
+            }
+        }
+        slots.sort();
+        Ok(slots)
+    }
+
+    fn slot_path(&self, slot: &str) -> Result<PathBuf, SaveError> {
+        let valid = !slot.is_empty()
+            && slot
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(SaveError::InvalidSlot(slot.to_owned()));
+        }
+        Ok(self.dir.join(slot).with_extension(EXTENSION))
+    }
+}
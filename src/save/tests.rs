@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use crate::save::{SaveError, SaveGame};
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("onion-save-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = temp_dir("round-trip");
+        let saves = SaveGame::new(&dir, 1);
+        saves.save("slot1", b"progress").unwrap();
+        assert_eq!(b"progress".to_vec(), saves.load("slot1").unwrap());
+        assert_eq!(vec!["slot1".to_owned()], saves.slots().unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrates_old_saves() {
+        let dir = temp_dir("migrate");
+        SaveGame::new(&dir, 1).save("slot1", &[1]).unwrap();
+
+        let mut saves = SaveGame::new(&dir, 3);
+        saves
+            .register_migration(1, |mut data| {
+                data.push(2);
+                Ok(data)
+            })
+            .register_migration(2, |mut data| {
+                data.push(3);
+                Ok(data)
+            });
+        assert_eq!(vec![1, 2, 3], saves.load("slot1").unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_newer_saves() {
+        let dir = temp_dir("too-new");
+        SaveGame::new(&dir, 2).save("slot1", &[]).unwrap();
+        assert!(matches!(
+            SaveGame::new(&dir, 1).load("slot1"),
+            Err(SaveError::TooNew(2))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_path_slots() {
+        let saves = SaveGame::new(temp_dir("invalid"), 1);
+        assert!(matches!(
+            saves.save("../escape", &[]),
+            Err(SaveError::InvalidSlot(_))
+        ));
+    }
+}
@@ -0,0 +1,291 @@
+//! Entities and resources saved to and loaded from TOML files, for save games and scenes
+//! written in an editor. Needs the `serde` feature.
+//!
+//! Only the component and resource types registered with the [`SceneRegistry`] are saved, under
+//! the name they were registered with:
+//!
+//! ```toml
+//! [resources.score]
+//! points = 120
+//!
+//! [[entities]]
+//! name = "player"
+//! health = 80.0
+//!
+//! [entities.transform]
+//! translation = [0.0, 1.0, 0.0]
+//! rotation = [0.0, 0.0, 0.0, 1.0]
+//! scale = [1.0, 1.0, 1.0]
+//! ```
+//!
+//! ```ignore
+//! let mut scenes = SceneRegistry::new();
+//! scenes
+//!     .register_component::<Health>("health")
+//!     .register_resource::<Score>("score");
+//!
+//! scenes.save(&app.world, "saves/level1.toml")?;
+//! let spawned = scenes.load(&mut app.world, "saves/level1.toml")?;
+//! ```
+//!
+//! Entity ids change between runs, so components holding entities, like
+//! [`Parent`](crate::hierarchy::Parent), can't be saved this way.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+use hecs::{Component, Entity, World};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{inspector::Name, transform::Transform};
+
+pub mod prefab;
+mod tests;
+
+pub use prefab::{PrefabInstance, Prefabs};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    /// The file isn't valid TOML or isn't a scene.
+    Parse(String),
+    /// The scene has a component or resource that wasn't registered under this name.
+    UnknownType(String),
+    /// No prefab has this name.
+    UnknownPrefab(String),
+    /// A component or resource couldn't be converted to or from TOML.
+    Value(String, String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "scene file i/o failed: {e}"),
+            SceneError::Parse(e) => write!(f, "invalid scene file: {e}"),
+            SceneError::UnknownType(name) => write!(f, "no scene type registered as {name:?}"),
+            SceneError::UnknownPrefab(name) => write!(f, "no prefab called {name:?}"),
+            SceneError::Value(name, e) => write!(f, "invalid {name} in scene: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+/// The file layout.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SceneFile {
+    resources: toml::Table,
+    entities: Vec<toml::Table>,
+}
+
+/// The first entity with a registered type.
+type FindFn = fn(&World) -> Option<Entity>;
+/// Reads a registered type from an entity, `None` if the entity doesn't have it.
+type SaveFn = fn(&World, Entity) -> Option<Result<toml::Value, String>>;
+/// Adds a registered type read from TOML to an entity.
+type LoadFn = fn(&mut World, Entity, toml::Value) -> Result<(), String>;
+
+#[derive(Clone, Copy)]
+struct Registration {
+    find: FindFn,
+    save: SaveFn,
+    load: LoadFn,
+}
+
+impl Registration {
+    fn of<T: Component + Serialize + DeserializeOwned>() -> Self {
+        Registration {
+            find: |world| world.query::<&T>().iter().next().map(|(entity, _)| entity),
+            save: |world, entity| {
+                let component = world.get::<&T>(entity).ok()?;
+                Some(toml::Value::try_from(&*component).map_err(|e| e.to_string()))
+            },
+            load: |world, entity, value| {
+                let component: T = value.try_into().map_err(|e| e.to_string())?;
+                world
+                    .insert_one(entity, component)
+                    .map_err(|e| e.to_string())
+            },
+        }
+    }
+}
+
+/// The component and resource types that go into scenes, by the name they are saved under.
+#[derive(Clone)]
+pub struct SceneRegistry {
+    components: BTreeMap<String, Registration>,
+    resources: BTreeMap<String, Registration>,
+}
+
+impl Default for SceneRegistry {
+    fn default() -> Self {
+        let mut registry = SceneRegistry {
+            components: BTreeMap::new(),
+            resources: BTreeMap::new(),
+        };
+        registry
+            .register_component::<Name>("name")
+            .register_component::<Transform>("transform");
+        registry
+    }
+}
+
+impl SceneRegistry {
+    /// A registry with the engine's [`Name`] and [`Transform`] registered.
+    pub fn new() -> Self {
+        SceneRegistry::default()
+    }
+
+    /// Saves components of type `T` as `name`.
+    ///
+    /// Panics if `name` is taken.
+    pub fn register_component<T: Component + Serialize + DeserializeOwned>(
+        &mut self,
+        name: &str,
+    ) -> &mut Self {
+        let previous = self
+            .components
+            .insert(name.to_owned(), Registration::of::<T>());
+        assert!(previous.is_none(), "{name} is already registered");
+        self
+    }
+
+    /// Saves the resource of type `T`, the component of a single entity like
+    /// [`Time`](crate::time::Time), as `name`.
+    ///
+    /// Panics if `name` is taken.
+    pub fn register_resource<T: Component + Serialize + DeserializeOwned>(
+        &mut self,
+        name: &str,
+    ) -> &mut Self {
+        let previous = self
+            .resources
+            .insert(name.to_owned(), Registration::of::<T>());
+        assert!(previous.is_none(), "{name} is already registered");
+        self
+    }
+
+    /// The registered resources and the entities with registered components of `world`, as
+    /// TOML.
+    pub fn to_toml(&self, world: &World) -> Result<String, SceneError> {
+        let save = |name: &str, registration: &Registration, entity| {
+            (registration.save)(world, entity)
+                .map(|value| value.map_err(|e| SceneError::Value(name.to_owned(), e)))
+        };
+
+        let mut scene = SceneFile::default();
+        let mut resource_entities = Vec::new();
+        for (name, registration) in &self.resources {
+            let Some(entity) = (registration.find)(world) else {
+                continue;
+            };
+            if let Some(value) = save(name, registration, entity) {
+                scene.resources.insert(name.clone(), value?);
+                resource_entities.push(entity);
+            }
+        }
+        for entity in world.iter().map(|entity| entity.entity()) {
+            if resource_entities.contains(&entity) {
+                continue;
+            }
+            let mut components = toml::Table::new();
+            for (name, registration) in &self.components {
+                if let Some(value) = save(name, registration, entity) {
+                    components.insert(name.clone(), value?);
+                }
+            }
+            if !components.is_empty() {
+                scene.entities.push(components);
+            }
+        }
+        Ok(toml::to_string_pretty(&scene).expect("scenes are valid TOML"))
+    }
+
+    /// Spawns the entities of the scene `text` into `world` and sets its resources, replacing
+    /// those `world` has. Returns the spawned entities in the order of the scene.
+    ///
+    /// Nothing is spawned or set if the scene has an unregistered type.
+    pub fn load_toml(&self, world: &mut World, text: &str) -> Result<Vec<Entity>, SceneError> {
+        let scene: SceneFile =
+            toml::from_str(text).map_err(|e| SceneError::Parse(e.to_string()))?;
+        for name in scene.resources.keys() {
+            self.resource(name)?;
+        }
+        for components in &scene.entities {
+            self.check_components(components)?;
+        }
+
+        for (name, value) in scene.resources {
+            let registration = self.resource(&name)?;
+            let entity = (registration.find)(world).unwrap_or_else(|| world.spawn(()));
+            (registration.load)(world, entity, value).map_err(|e| SceneError::Value(name, e))?;
+        }
+        let mut spawned = Vec::with_capacity(scene.entities.len());
+        for components in scene.entities {
+            spawned.push(self.spawn(world, components)?);
+        }
+        Ok(spawned)
+    }
+
+    /// Spawns an entity with `components`, a table of registered component names like an
+    /// `[[entities]]` entry of a scene.
+    ///
+    /// Nothing is spawned if a component wasn't registered.
+    pub fn spawn(&self, world: &mut World, components: toml::Table) -> Result<Entity, SceneError> {
+        self.check_components(&components)?;
+        let entity = world.spawn(());
+        for (name, value) in components {
+            let registration = self.components[&name];
+            (registration.load)(world, entity, value).map_err(|e| SceneError::Value(name, e))?;
+        }
+        Ok(entity)
+    }
+
+    /// Fails if `components` has a component that wasn't registered.
+    pub fn check_components(&self, components: &toml::Table) -> Result<(), SceneError> {
+        match components
+            .keys()
+            .find(|name| !self.components.contains_key(*name))
+        {
+            Some(name) => Err(SceneError::UnknownType(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn resource(&self, name: &str) -> Result<Registration, SceneError> {
+        self.resources
+            .get(name)
+            .copied()
+            .ok_or_else(|| SceneError::UnknownType(name.to_owned()))
+    }
+
+    /// Writes [`SceneRegistry::to_toml`] to `path`, replacing the file only once the new one is
+    /// complete.
+    pub fn save(&self, world: &World, path: impl AsRef<Path>) -> Result<(), SceneError> {
+        let path = path.as_ref();
+        let text = self.to_toml(world)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Loads the scene at `path` with [`SceneRegistry::load_toml`].
+    pub fn load(
+        &self,
+        world: &mut World,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<Entity>, SceneError> {
+        let text = fs::read_to_string(path)?;
+        self.load_toml(world, &text)
+    }
+}
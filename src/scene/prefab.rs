@@ -0,0 +1,203 @@
+//! Prefabs: entities described once in a TOML file and spawned many times.
+//!
+//! A prefab file is a table of registered components, like an `[[entities]]` entry of a scene,
+//! named after the file:
+//!
+//! ```toml
+//! # prefabs/enemy.toml
+//! name = "enemy"
+//! health = 100.0
+//!
+//! [transform]
+//! translation = [0.0, 0.0, 0.0]
+//! rotation = [0.0, 0.0, 0.0, 1.0]
+//! scale = [1.0, 1.0, 1.0]
+//! ```
+//!
+//! ```ignore
+//! let mut prefabs = Prefabs::load_dir("prefabs", scenes)?;
+//!
+//! // Overrides replace single fields, the rest comes from the prefab.
+//! let overrides = toml::toml! {
+//!     health = 250.0
+//!     [transform]
+//!     translation = [4.0, 0.0, 2.0]
+//! };
+//! let boss = prefabs.spawn(&mut app.world, "enemy", overrides)?;
+//!
+//! // During development, once per frame.
+//! prefabs.reload();
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use hecs::{Entity, World};
+
+use crate::scene::{SceneError, SceneRegistry};
+
+const EXTENSION: &str = "toml";
+
+/// Which prefab an entity was spawned from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefabInstance(pub String);
+
+struct Prefab {
+    path: Option<PathBuf>,
+    components: toml::Table,
+}
+
+/// Prefabs by name, spawned through a [`SceneRegistry`].
+pub struct Prefabs {
+    registry: SceneRegistry,
+    dir: Option<PathBuf>,
+    prefabs: BTreeMap<String, Prefab>,
+    /// When each file was last read, also if reading it failed.
+    modified: BTreeMap<PathBuf, SystemTime>,
+}
+
+impl Prefabs {
+    /// No prefabs, add them with [`Prefabs::insert`].
+    pub fn new(registry: SceneRegistry) -> Self {
+        Prefabs {
+            registry,
+            dir: None,
+            prefabs: BTreeMap::new(),
+            modified: BTreeMap::new(),
+        }
+    }
+
+    /// The prefabs in the `.toml` files of `dir`, named after the files.
+    pub fn load_dir(dir: impl Into<PathBuf>, registry: SceneRegistry) -> Result<Self, SceneError> {
+        let mut prefabs = Prefabs::new(registry);
+        let dir = dir.into();
+        for (name, path) in prefab_files(&dir)? {
+            prefabs.load_file(name, path)?;
+        }
+        prefabs.dir = Some(dir);
+        Ok(prefabs)
+    }
+
+    /// Adds the prefab `name` with `components`, replacing the one with the same name.
+    pub fn insert(&mut self, name: &str, components: toml::Table) -> Result<(), SceneError> {
+        self.registry.check_components(&components)?;
+        let prefab = Prefab {
+            path: None,
+            components,
+        };
+        self.prefabs.insert(name.to_owned(), prefab);
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.prefabs.keys().map(String::as_str)
+    }
+
+    /// Spawns the prefab `name` with a [`PrefabInstance`]. The fields of `overrides` replace
+    /// those of the prefab, and its components are added.
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        name: &str,
+        overrides: toml::Table,
+    ) -> Result<Entity, SceneError> {
+        let prefab = self
+            .prefabs
+            .get(name)
+            .ok_or_else(|| SceneError::UnknownPrefab(name.to_owned()))?;
+        let mut components = prefab.components.clone();
+        merge(&mut components, overrides);
+        let entity = self.registry.spawn(world, components)?;
+        world
+            .insert_one(entity, PrefabInstance(name.to_owned()))
+            .unwrap();
+        Ok(entity)
+    }
+
+    /// Rereads the prefab files changed since they were read, and reads new files in the
+    /// directory. Returns the names of the prefabs that changed. A file that fails to load keeps
+    /// its previous prefab and the error is logged.
+    ///
+    /// Entities spawned before keep their components, only later spawns see the change.
+    pub fn reload(&mut self) -> Vec<String> {
+        let mut files: Vec<_> = self
+            .prefabs
+            .iter()
+            .filter_map(|(name, prefab)| Some((name.clone(), prefab.path.clone()?)))
+            .collect();
+        if let Some(dir) = &self.dir {
+            match prefab_files(dir) {
+                Ok(found) => {
+                    files.extend(found.into_iter().filter(|(name, _)| !self.contains(name)))
+                }
+                Err(e) => tracing::warn!(target: "scene", "failed to list prefabs: {e}"),
+            }
+        }
+
+        let mut changed = Vec::new();
+        for (name, path) in files {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            if modified.is_some() && modified.as_ref() == self.modified.get(&path) {
+                continue;
+            }
+            match self.load_file(name.clone(), path) {
+                Ok(()) => changed.push(name),
+                Err(e) => tracing::warn!(target: "scene", "failed to reload prefab {name}: {e}"),
+            }
+        }
+        changed
+    }
+
+    fn load_file(&mut self, name: String, path: PathBuf) -> Result<(), SceneError> {
+        if let Ok(modified) = fs::metadata(&path).and_then(|m| m.modified()) {
+            self.modified.insert(path.clone(), modified);
+        }
+        let text = fs::read_to_string(&path)?;
+        let components: toml::Table =
+            toml::from_str(&text).map_err(|e| SceneError::Parse(e.to_string()))?;
+        self.registry.check_components(&components)?;
+        let prefab = Prefab {
+            path: Some(path),
+            components,
+        };
+        self.prefabs.insert(name, prefab);
+        Ok(())
+    }
+}
+
+/// The names and paths of the prefab files in `dir`.
+fn prefab_files(dir: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == EXTENSION) {
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                files.push((name.to_owned(), path.clone()));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Replaces the fields of `base` with those of `overrides`, going into tables present in both.
+fn merge(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge(base, overrides)
+            }
+            (Some(field), value) => *field = value,
+            (None, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::inspector::Name;
+    use crate::scene::{PrefabInstance, Prefabs, SceneError, SceneRegistry};
+    use crate::transform::Transform;
+    use glam::Vec3;
+    use hecs::World;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    struct Health(f32);
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    struct Score {
+        points: u32,
+    }
+
+    fn registry() -> SceneRegistry {
+        let mut registry = SceneRegistry::new();
+        registry
+            .register_component::<Health>("health")
+            .register_resource::<Score>("score");
+        registry
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let registry = registry();
+        let mut world = World::new();
+        let transform = Transform::from_translation(Vec3::new(1.0, 2.0, 3.0));
+        world.spawn((Name::new("player"), transform, Health(80.0)));
+        world.spawn((Score { points: 120 },));
+        // Unregistered, so not saved.
+        world.spawn((1u8,));
+        let text = registry.to_toml(&world).unwrap();
+
+        let mut loaded = World::new();
+        let spawned = registry.load_toml(&mut loaded, &text).unwrap();
+        assert_eq!(1, spawned.len());
+        let player = spawned[0];
+        assert_eq!("player", loaded.get::<&Name>(player).unwrap().as_str());
+        assert_eq!(transform, *loaded.get::<&Transform>(player).unwrap());
+        assert_eq!(Health(80.0), *loaded.get::<&Health>(player).unwrap());
+        let scores: Vec<_> = loaded.query::<&Score>().iter().map(|(_, s)| *s).collect();
+        assert_eq!(vec![Score { points: 120 }], scores);
+        assert_eq!(2, loaded.len());
+    }
+
+    #[test]
+    fn test_load_replaces_resources() {
+        let registry = registry();
+        let mut world = World::new();
+        world.spawn((Score { points: 5 },));
+        registry
+            .load_toml(&mut world, "[resources.score]\npoints = 9\n")
+            .unwrap();
+        let scores: Vec<_> = world.query::<&Score>().iter().map(|(_, s)| *s).collect();
+        assert_eq!(vec![Score { points: 9 }], scores);
+    }
+
+    #[test]
+    fn test_unknown_type_loads_nothing() {
+        let registry = registry();
+        let mut world = World::new();
+        let text = "[[entities]]\nhealth = 1.0\n\n[[entities]]\nmana = 3.0\n";
+        assert!(matches!(
+            registry.load_toml(&mut world, text),
+            Err(SceneError::UnknownType(name)) if name == "mana"
+        ));
+        assert_eq!(0, world.len());
+    }
+
+    #[test]
+    fn test_prefab_overrides() {
+        let mut prefabs = Prefabs::new(registry());
+        let enemy = toml::from_str("health = 100.0\nname = \"enemy\"").unwrap();
+        prefabs.insert("enemy", enemy).unwrap();
+
+        let mut world = World::new();
+        let overrides = toml::from_str("health = 250.0").unwrap();
+        let boss = prefabs.spawn(&mut world, "enemy", overrides).unwrap();
+        assert_eq!(Health(250.0), *world.get::<&Health>(boss).unwrap());
+        assert_eq!("enemy", world.get::<&Name>(boss).unwrap().as_str());
+        assert_eq!(
+            PrefabInstance("enemy".to_owned()),
+            *world.get::<&PrefabInstance>(boss).unwrap()
+        );
+        assert!(matches!(
+            prefabs.spawn(&mut world, "boss", toml::Table::new()),
+            Err(SceneError::UnknownPrefab(_))
+        ));
+    }
+
+    #[test]
+    fn test_prefab_reload() {
+        let dir = std::env::temp_dir().join(format!("onion-prefabs-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("enemy.toml"), "health = 100.0\n").unwrap();
+
+        let mut prefabs = Prefabs::load_dir(&dir, registry()).unwrap();
+        assert!(prefabs.contains("enemy"));
+        assert!(prefabs.reload().is_empty());
+
+        std::fs::write(dir.join("ally.toml"), "health = 50.0\n").unwrap();
+        assert_eq!(vec!["ally".to_owned()], prefabs.reload());
+        let mut world = World::new();
+        let ally = prefabs
+            .spawn(&mut world, "ally", toml::Table::new())
+            .unwrap();
+        assert_eq!(Health(50.0), *world.get::<&Health>(ally).unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
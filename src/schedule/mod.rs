@@ -0,0 +1,145 @@
+//! The order systems run in within a stage.
+//!
+//! Systems run in the order they were added unless
+//! [`App::run_before`](crate::app::App::run_before) says otherwise. Orderings name systems or sets
+//! of them, and only order systems of the same stage, so a set can span stages. Stages are
+//! plain names, add a system to any stage and order whole stages with
+//! [`App::run_stage_before`](crate::app::App::run_stage_before):
+//!
+//! ```ignore
+//! app.add_named_system(FIXED_STAGE, "gravity", gravity)
+//!     .add_named_system(FIXED_STAGE, "collide", collide)
+//!     .add_named_system(FIXED_STAGE, "move_players", move_players)
+//!     .add_to_set("gravity", "physics")
+//!     .add_to_set("collide", "physics")
+//!     .run_before("move_players", "physics");
+//!
+//! app.add_named_system("late_update", "camera_follow", camera_follow)
+//!     .run_stage_before(DEFAULT_STAGE, "late_update");
+//! ```
+
+use std::{cmp::Reverse, collections::BinaryHeap, error::Error, fmt};
+
+mod tests;
+
+/// Why systems couldn't be put in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    /// An ordering names neither a system nor a set.
+    UnknownName(String),
+    /// A stage ordering names a stage without systems.
+    UnknownStage(String),
+    /// The orderings contradict each other. Holds the systems that are part of or wait on the
+    /// cycle, in the order they were added.
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::UnknownName(name) => write!(f, "no system or set called {name}"),
+            OrderError::UnknownStage(stage) => write!(f, "no system in stage {stage}"),
+            OrderError::Cycle(names) => {
+                write!(f, "systems are ordered in a cycle: {}", names.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for OrderError {}
+
+/// What the ordering needs to know about a system.
+pub(crate) struct Node<'a> {
+    pub name: &'a str,
+    pub stage: &'a str,
+    pub sets: &'a [String],
+}
+
+impl Node<'_> {
+    fn matches(&self, name: &str) -> bool {
+        self.name == name || self.sets.iter().any(|set| set == name)
+    }
+}
+
+/// The indices of `nodes` in the order to run them. Each `(first, then)` of `orderings` runs
+/// the systems matching `first` before those matching `then` in the same stage, and each of
+/// `stage_orderings` the systems of stage `first` before those of `then`. Otherwise the order of
+/// `nodes` is kept.
+pub(crate) fn run_order(
+    nodes: &[Node],
+    orderings: &[(String, String)],
+    stage_orderings: &[(String, String)],
+) -> Result<Vec<usize>, OrderError> {
+    let matching = |name: &str| -> Result<Vec<usize>, OrderError> {
+        let indices: Vec<_> = (0..nodes.len())
+            .filter(|&i| nodes[i].matches(name))
+            .collect();
+        if indices.is_empty() {
+            return Err(OrderError::UnknownName(name.to_owned()));
+        }
+        Ok(indices)
+    };
+
+    let in_stage = |stage: &str| -> Result<Vec<usize>, OrderError> {
+        let indices: Vec<_> = (0..nodes.len())
+            .filter(|&i| nodes[i].stage == stage)
+            .collect();
+        if indices.is_empty() {
+            return Err(OrderError::UnknownStage(stage.to_owned()));
+        }
+        Ok(indices)
+    };
+
+    let mut edges = vec![Vec::new(); nodes.len()];
+    let mut waiting_on = vec![0; nodes.len()];
+    let mut add_edge = |i: usize, j: usize| {
+        edges[i].push(j);
+        waiting_on[j] += 1;
+    };
+    for (first, then) in orderings {
+        let thens = matching(then)?;
+        for i in matching(first)? {
+            for &j in &thens {
+                if i != j && nodes[i].stage == nodes[j].stage {
+                    add_edge(i, j);
+                }
+            }
+        }
+    }
+    for (first, then) in stage_orderings {
+        let thens = in_stage(then)?;
+        for i in in_stage(first)? {
+            for &j in &thens {
+                if i != j {
+                    add_edge(i, j);
+                }
+            }
+        }
+    }
+
+    // Always the earliest added of the systems that are ready, so unordered systems keep their
+    // order.
+    let mut ready: BinaryHeap<_> = (0..nodes.len())
+        .filter(|&i| waiting_on[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+        for &j in &edges[i] {
+            waiting_on[j] -= 1;
+            if waiting_on[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    if order.len() < nodes.len() {
+        let stuck = (0..nodes.len())
+            .filter(|&i| waiting_on[i] > 0)
+            .map(|i| nodes[i].name.to_owned())
+            .collect();
+        return Err(OrderError::Cycle(stuck));
+    }
+    Ok(order)
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use crate::schedule::{run_order, Node, OrderError};
+
+    fn node<'a>(name: &'a str, stage: &'a str, sets: &'a [String]) -> Node<'a> {
+        Node { name, stage, sets }
+    }
+
+    fn orderings(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|&(first, then)| (first.to_owned(), then.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unordered_keep_insertion_order() {
+        let nodes = [
+            node("a", "update", &[]),
+            node("b", "update", &[]),
+            node("c", "update", &[]),
+        ];
+        assert_eq!(vec![0, 1, 2], run_order(&nodes, &[], &[]).unwrap());
+
+        // `a` waits for `c`, `b` and `c` keep their order.
+        let order = run_order(&nodes, &orderings(&[("c", "a")]), &[]).unwrap();
+        assert_eq!(vec![1, 2, 0], order);
+    }
+
+    #[test]
+    fn test_set_expands_to_members() {
+        let physics = ["physics".to_owned()];
+        let nodes = [
+            node("move_players", "fixed", &[]),
+            node("gravity", "fixed", &physics),
+            node("collide", "fixed", &physics),
+        ];
+        let order = run_order(&nodes, &orderings(&[("physics", "move_players")]), &[]).unwrap();
+        assert_eq!(vec![1, 2, 0], order);
+    }
+
+    #[test]
+    fn test_ordering_across_stages_is_ignored() {
+        let nodes = [node("a", "update", &[]), node("b", "fixed", &[])];
+        let order = run_order(&nodes, &orderings(&[("b", "a")]), &[]).unwrap();
+        assert_eq!(vec![0, 1], order);
+    }
+
+    #[test]
+    fn test_stage_ordering() {
+        let nodes = [node("a", "late", &[]), node("b", "update", &[])];
+        let order = run_order(&nodes, &[], &orderings(&[("update", "late")])).unwrap();
+        assert_eq!(vec![1, 0], order);
+    }
+
+    #[test]
+    fn test_unknown_names() {
+        let nodes = [node("a", "update", &[])];
+        assert_eq!(
+            Err(OrderError::UnknownName("b".to_owned())),
+            run_order(&nodes, &orderings(&[("a", "b")]), &[])
+        );
+        assert_eq!(
+            Err(OrderError::UnknownStage("late".to_owned())),
+            run_order(&nodes, &[], &orderings(&[("update", "late")]))
+        );
+    }
+
+    #[test]
+    fn test_cycle_lists_stuck_systems() {
+        let nodes = [
+            node("a", "update", &[]),
+            node("b", "update", &[]),
+            node("c", "update", &[]),
+            node("d", "update", &[]),
+        ];
+        // `c` waits on the cycle of `a` and `b`, `d` is free to run.
+        let order = run_order(
+            &nodes,
+            &orderings(&[("a", "b"), ("b", "a"), ("b", "c")]),
+            &[],
+        );
+        assert_eq!(
+            Err(OrderError::Cycle(vec![
+                "a".to_owned(),
+                "b".to_owned(),
+                "c".to_owned()
+            ])),
+            order
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Engine settings the player changes, e.g. in an options menu, kept in a TOML file.
+//!
+//! Loading and saving the file needs the `serde` feature. Changes made through
+//! [`SettingsFile::modify`] are saved right away and spawned into the world as a
+//! [`SettingsChanged`] entity, which lives until the end of the next update like a
+//! [`FileDrop`](crate::file_drop::FileDrop).
+
+use std::collections::BTreeMap;
+
+use hecs::World;
+use winit::keyboard::KeyCode;
+
+use crate::graphics::context::{AntiAliasing, PresentMode};
+
+#[cfg(feature = "serde")]
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+mod tests;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct Settings {
+    /// Size of the window's contents in physical pixels, when windowed.
+    pub window_size: [u32; 2],
+    pub vsync: bool,
+    pub anti_aliasing: AntiAliasing,
+    /// Master volume from 0 to 1.
+    pub volume: f32,
+    /// Keys by action name, e.g. `jump = "Space"`.
+    pub key_bindings: BTreeMap<String, KeyCode>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            window_size: [1280, 720],
+            vsync: true,
+            anti_aliasing: AntiAliasing::Msaa,
+            volume: 1.0,
+            key_bindings: BTreeMap::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Fifo with vsync, otherwise Mailbox, which falls back to Immediate.
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::Mailbox
+        }
+    }
+
+    /// The key bound to `action`.
+    pub fn key(&self, action: &str) -> Option<KeyCode> {
+        self.key_bindings.get(action).copied()
+    }
+
+    pub fn bind_key(&mut self, action: impl Into<String>, key: KeyCode) -> &mut Self {
+        self.key_bindings.insert(action.into(), key);
+        self
+    }
+}
+
+/// Spawned when [`SettingsFile::modify`] changed the settings, with the new settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SettingsChanged(pub Settings);
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    /// The file isn't valid TOML or has a setting of the wrong type.
+    Parse(String),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Io(e) => write!(f, "settings file i/o failed: {e}"),
+            SettingsError::Parse(e) => write!(f, "invalid settings file: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for SettingsError {}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for SettingsError {
+    fn from(e: io::Error) -> Self {
+        SettingsError::Io(e)
+    }
+}
+
+/// [`Settings`] backed by a TOML file.
+///
+/// # Examples
+/// ```ignore
+/// let mut settings = SettingsFile::load("settings.toml")?;
+/// let mut gfx = GraphicsContext::builder()
+///     .settings(settings.get())
+///     .build(&event_loop)?;
+///
+/// // In the options menu:
+/// settings.modify(&mut app.world, |s| s.vsync = false)?;
+///
+/// // In a system:
+/// for (_, SettingsChanged(settings)) in world.query::<&SettingsChanged>().iter() {
+///     gfx.apply_settings(settings);
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub struct SettingsFile {
+    path: PathBuf,
+    settings: Settings,
+}
+
+#[cfg(feature = "serde")]
+impl SettingsFile {
+    /// Reads the settings at `path`. A missing file gives the defaults and settings missing from
+    /// the file keep their default, so older files still load.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, SettingsError> {
+        let path = path.into();
+        let settings = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| SettingsError::Parse(e.to_string()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(SettingsFile { path, settings })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Writes the settings, replacing the file only once the new one is complete.
+    pub fn save(&self) -> Result<(), SettingsError> {
+        let text = toml::to_string_pretty(&self.settings).expect("settings are valid TOML");
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Changes the settings with `f`. If anything changed, saves them and spawns a
+    /// [`SettingsChanged`]. Returns whether anything changed.
+    pub fn modify(
+        &mut self,
+        world: &mut World,
+        f: impl FnOnce(&mut Settings),
+    ) -> Result<bool, SettingsError> {
+        let previous = self.settings.clone();
+        f(&mut self.settings);
+        if self.settings == previous {
+            return Ok(false);
+        }
+        self.save()?;
+        world.spawn((SettingsChanged(self.settings.clone()),));
+        Ok(true)
+    }
+}
+
+pub(crate) fn clear(world: &mut World) {
+    let entities: Vec<_> = world
+        .query::<&SettingsChanged>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.despawn(entity).unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use crate::settings::{clear, Settings, SettingsChanged};
+    use hecs::World;
+    use winit::keyboard::KeyCode;
+
+    #[test]
+    fn test_key_bindings() {
+        let mut settings = Settings::default();
+        settings.bind_key("jump", KeyCode::Space);
+        assert_eq!(Some(KeyCode::Space), settings.key("jump"));
+        assert_eq!(None, settings.key("fire"));
+    }
+
+    #[test]
+    fn test_clear_despawns_changes() {
+        let mut world = World::new();
+        world.spawn((SettingsChanged(Settings::default()),));
+        clear(&mut world);
+        assert_eq!(0, world.query::<&SettingsChanged>().iter().count());
+    }
+
+    #[cfg(feature = "serde")]
+    mod file {
+        use crate::graphics::context::AntiAliasing;
+        use crate::settings::{Settings, SettingsChanged, SettingsFile};
+        use hecs::World;
+        use std::path::PathBuf;
+        use winit::keyboard::KeyCode;
+
+        fn temp_path(name: &str) -> PathBuf {
+            let path = std::env::temp_dir()
+                .join(format!("onion-settings-{name}-{}.toml", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+            path
+        }
+
+        #[test]
+        fn test_missing_file_gives_defaults() {
+            let settings = SettingsFile::load(temp_path("missing")).unwrap();
+            assert_eq!(&Settings::default(), settings.get());
+        }
+
+        #[test]
+        fn test_missing_settings_keep_defaults() {
+            let path = temp_path("partial");
+            std::fs::write(&path, "vsync = false\n[key_bindings]\njump = \"Space\"\n").unwrap();
+
+            let settings = SettingsFile::load(&path).unwrap();
+            assert!(!settings.get().vsync);
+            assert_eq!(Some(KeyCode::Space), settings.get().key("jump"));
+            assert_eq!(Settings::default().window_size, settings.get().window_size);
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn test_anti_aliasing_round_trips() {
+            let path = temp_path("anti-aliasing");
+            let mut world = World::new();
+            let mut settings = SettingsFile::load(&path).unwrap();
+            assert_eq!(AntiAliasing::Msaa, settings.get().anti_aliasing);
+
+            settings
+                .modify(&mut world, |s| s.anti_aliasing = AntiAliasing::Fxaa)
+                .unwrap();
+            let loaded = SettingsFile::load(&path).unwrap();
+            assert_eq!(AntiAliasing::Fxaa, loaded.get().anti_aliasing);
+            std::fs::remove_file(path).unwrap();
+        }
+
+        #[test]
+        fn test_modify_saves_and_notifies() {
+            let path = temp_path("modify");
+            let mut world = World::new();
+            let mut settings = SettingsFile::load(&path).unwrap();
+
+            assert!(!settings.modify(&mut world, |s| s.vsync = true).unwrap());
+            assert!(!path.exists());
+
+            assert!(settings
+                .modify(&mut world, |s| {
+                    s.volume = 0.5;
+                    s.bind_key("fire", KeyCode::KeyF);
+                })
+                .unwrap());
+            assert_eq!(settings.get(), SettingsFile::load(&path).unwrap().get());
+            let changes: Vec<f32> = world
+                .query::<&SettingsChanged>()
+                .iter()
+                .map(|(_, SettingsChanged(s))| s.volume)
+                .collect();
+            assert_eq!(vec![0.5], changes);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Game states like a menu, gameplay and a pause screen, with systems that run on entering and
+//! leaving them.
+//!
+//! The current state is a [`State`] resource. Systems request a change with [`State::set`],
+//! which the next update applies before any system runs: it runs the old state's exit systems,
+//! the transition systems and the new state's enter systems.
+//!
+//! ```ignore
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! enum Screen {
+//!     Menu,
+//!     Playing,
+//!     Paused,
+//! }
+//!
+//! app.add_state(Screen::Menu)
+//!     .on_enter(Screen::Playing, spawn_level)
+//!     .on_exit(Screen::Playing, despawn_level)
+//!     .add_named_system(DEFAULT_STAGE, "move_players", move_players)
+//!     .run_if("move_players", in_state(Screen::Playing));
+//!
+//! fn pause(input: Res<Input>, mut screen: ResMut<State<Screen>>) {
+//!     if input.just_pressed(KeyCode::Escape) {
+//!         screen.set(Screen::Paused);
+//!     }
+//! }
+//! ```
+
+use std::{any::Any, fmt::Debug};
+
+use hecs::World;
+
+use crate::app::System;
+
+mod tests;
+
+/// Values a [`State`] can take, usually a fieldless enum.
+pub trait States: Debug + Clone + PartialEq + Send + Sync + 'static {}
+
+impl<S: Debug + Clone + PartialEq + Send + Sync + 'static> States for S {}
+
+/// The current state of type `S`, kept on a single entity of the world by
+/// [`App::add_state`](crate::app::App::add_state).
+#[derive(Debug, Clone, PartialEq)]
+pub struct State<S: States> {
+    current: S,
+    next: Option<S>,
+}
+
+impl<S: States> State<S> {
+    pub(crate) fn new(initial: S) -> Self {
+        State {
+            current: initial,
+            next: None,
+        }
+    }
+
+    pub fn current(&self) -> &S {
+        &self.current
+    }
+
+    /// Changes to `next` at the start of the next update. The last request of an update wins.
+    /// Setting the current state does nothing. Requests from enter, exit and transition systems
+    /// wait for the update after.
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+
+    /// The state the next update changes to, if any.
+    pub fn next(&self) -> Option<&S> {
+        self.next.as_ref()
+    }
+
+    /// The current state of `world`, `None` if the state wasn't added.
+    pub fn get(world: &World) -> Option<S> {
+        world
+            .query::<&State<S>>()
+            .iter()
+            .next()
+            .map(|(_, state)| state.current.clone())
+    }
+
+    /// Like [`State::set`], for systems taking the world. Returns false if the state wasn't
+    /// added.
+    pub fn request(world: &mut World, next: S) -> bool {
+        match world.query_mut::<&mut State<S>>().into_iter().next() {
+            Some((_, state)) => {
+                state.set(next);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A run condition for [`App::run_if`](crate::app::App::run_if): whether the current state is
+/// `state`.
+pub fn in_state<S: States>(state: S) -> impl Fn(&World) -> bool + 'static {
+    move |world| State::<S>::get(world).as_ref() == Some(&state)
+}
+
+/// The enter, exit and transition systems of one state type.
+pub(crate) struct StateSchedule<S: States> {
+    entered: bool,
+    on_enter: Vec<(S, System)>,
+    on_exit: Vec<(S, System)>,
+    on_transition: Vec<(S, S, System)>,
+}
+
+impl<S: States> StateSchedule<S> {
+    pub(crate) fn new() -> Self {
+        StateSchedule {
+            entered: false,
+            on_enter: Vec::new(),
+            on_exit: Vec::new(),
+            on_transition: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_on_enter(&mut self, state: S, system: System) {
+        self.on_enter.push((state, system));
+    }
+
+    pub(crate) fn add_on_exit(&mut self, state: S, system: System) {
+        self.on_exit.push((state, system));
+    }
+
+    pub(crate) fn add_on_transition(&mut self, from: S, to: S, system: System) {
+        self.on_transition.push((from, to, system));
+    }
+}
+
+/// A [`StateSchedule`] of any state type.
+pub(crate) trait Transitions {
+    /// Enters the initial state on the first call, and afterwards changes to the requested
+    /// state if there is one.
+    fn apply(&mut self, world: &mut World);
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<S: States> Transitions for StateSchedule<S> {
+    fn apply(&mut self, world: &mut World) {
+        let Some((_, state)) = world.query_mut::<&mut State<S>>().into_iter().next() else {
+            return;
+        };
+        let next = state.next.take();
+        let current = state.current.clone();
+
+        if !self.entered {
+            self.entered = true;
+            run(world, systems_for(&self.on_enter, &current));
+        }
+        let Some(next) = next.filter(|next| *next != current) else {
+            return;
+        };
+
+        run(world, systems_for(&self.on_exit, &current));
+        if let Some((_, state)) = world.query_mut::<&mut State<S>>().into_iter().next() {
+            state.current = next.clone();
+        }
+        let transitions = self.on_transition.iter();
+        run(
+            world,
+            transitions
+                .filter(|(from, to, _)| *from == current && *to == next)
+                .map(|(_, _, system)| system),
+        );
+        run(world, systems_for(&self.on_enter, &next));
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn systems_for<'a, S: States>(
+    systems: &'a [(S, System)],
+    state: &'a S,
+) -> impl Iterator<Item = &'a System> {
+    systems
+        .iter()
+        .filter(move |(s, _)| s == state)
+        .map(|(_, system)| system)
+}
+
+fn run<'a>(world: &mut World, systems: impl Iterator<Item = &'a System>) {
+    for system in systems {
+        if let Err(e) = system(world) {
+            panic!("system errors aren't supported yet: {e:?}");
+        }
+    }
+}
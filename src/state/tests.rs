@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::app::{App, DEFAULT_STAGE};
+    use crate::resource;
+    use crate::state::{in_state, State};
+    use hecs::World;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Screen {
+        Menu,
+        Playing,
+        Paused,
+    }
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    fn log(entry: &'static str) -> impl Fn(&mut World) + 'static {
+        move |world| resource::get_mut::<Log>(world).unwrap().0.push(entry)
+    }
+
+    fn take_log(app: &mut App) -> Vec<&'static str> {
+        std::mem::take(&mut resource::get_mut::<Log>(&app.world).unwrap().0)
+    }
+
+    #[test]
+    fn test_transition_order() {
+        let mut app = App::new();
+        app.init_resource::<Log>()
+            .add_state(Screen::Menu)
+            .on_enter(Screen::Menu, log("enter menu"))
+            .on_exit(Screen::Menu, log("exit menu"))
+            .on_transition(Screen::Menu, Screen::Playing, log("menu to playing"))
+            .on_transition(Screen::Menu, Screen::Paused, log("menu to paused"))
+            .on_enter(Screen::Playing, log("enter playing"));
+        app.step();
+        assert_eq!(vec!["enter menu"], take_log(&mut app));
+
+        State::request(&mut app.world, Screen::Playing);
+        app.step();
+        assert_eq!(
+            vec!["exit menu", "menu to playing", "enter playing"],
+            take_log(&mut app)
+        );
+        assert_eq!(Some(Screen::Playing), State::<Screen>::get(&app.world));
+
+        // Setting the current state does nothing.
+        State::request(&mut app.world, Screen::Playing);
+        app.step();
+        assert!(take_log(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_request_during_transition() {
+        let mut app = App::new();
+        app.add_state(Screen::Menu)
+            .on_enter(Screen::Playing, |world: &mut World| {
+                State::request(world, Screen::Paused);
+            });
+        State::request(&mut app.world, Screen::Playing);
+        app.step();
+        assert_eq!(Some(Screen::Playing), State::<Screen>::get(&app.world));
+        app.step();
+        assert_eq!(Some(Screen::Paused), State::<Screen>::get(&app.world));
+    }
+
+    #[test]
+    fn test_in_state() {
+        let mut app = App::new();
+        app.init_resource::<Log>()
+            .add_state(Screen::Menu)
+            .add_named_system(DEFAULT_STAGE, "move_players", log("move players"))
+            .run_if("move_players", in_state(Screen::Playing));
+        app.step();
+        assert!(take_log(&mut app).is_empty());
+
+        State::request(&mut app.world, Screen::Playing);
+        app.step();
+        assert_eq!(vec!["move players"], take_log(&mut app));
+        assert!(!in_state(Screen::Menu)(&app.world));
+    }
+}
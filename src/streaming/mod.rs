@@ -0,0 +1,243 @@
+//! Streams chunks of a large world in and out around a moving point, e.g. terrain around the
+//! camera, so only the nearby part of the world is in memory.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use glam::Vec3;
+
+mod tests;
+
+/// A chunk's position on the horizontal grid, in chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkCoord {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl ChunkCoord {
+    pub fn new(x: i32, z: i32) -> Self {
+        ChunkCoord { x, z }
+    }
+
+    /// The chunk `position` lies in.
+    pub fn containing(position: Vec3, chunk_size: f32) -> Self {
+        ChunkCoord {
+            x: (position.x / chunk_size).floor() as i32,
+            z: (position.z / chunk_size).floor() as i32,
+        }
+    }
+
+    /// The world space position of the chunk's corner with the lowest coordinates, at height 0.
+    pub fn origin(&self, chunk_size: f32) -> Vec3 {
+        Vec3::new(self.x as f32 * chunk_size, 0.0, self.z as f32 * chunk_size)
+    }
+
+    /// Distance in chunks along the furthest axis, so a radius covers a square.
+    pub fn distance(&self, other: ChunkCoord) -> u32 {
+        self.x.abs_diff(other.x).max(self.z.abs_diff(other.z))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamingSettings {
+    /// Width of a chunk in world units.
+    pub chunk_size: f32,
+    /// Chunks this many chunks or closer to the focus are loaded.
+    pub load_radius: u32,
+    /// Chunks further than this are unloaded. Keep it above `load_radius`, so moving back and
+    /// forth over a chunk border doesn't reload the same chunks.
+    pub unload_radius: u32,
+    /// Most chunks requested from the loader and most loaded chunks handed over per update.
+    pub max_loads_per_update: usize,
+    /// Most chunks unloaded per update.
+    pub max_unloads_per_update: usize,
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        StreamingSettings {
+            chunk_size: 64.0,
+            load_radius: 2,
+            unload_radius: 3,
+            max_loads_per_update: 2,
+            max_unloads_per_update: 4,
+        }
+    }
+}
+
+/// Returned by [`ChunkStreamer::update`].
+#[derive(Debug)]
+pub enum ChunkEvent<T> {
+    /// The chunk is available through [`ChunkStreamer::get`], e.g. to spawn its entities.
+    Loaded(ChunkCoord),
+    /// The chunk was dropped from the streamer, with its data to clean up after.
+    Unloaded(ChunkCoord, T),
+}
+
+/// Loads the chunks around a focus point on a background thread and unloads the ones left
+/// behind.
+///
+/// Each [`update`](ChunkStreamer::update) requests missing chunks nearest first, hands over
+/// chunks that finished loading and unloads far away ones, each limited by the
+/// [`StreamingSettings`] budgets so crossing into new chunks doesn't stall a frame. Chunks that
+/// are out of range by the time they finish loading are dropped without an event.
+///
+/// # Examples
+/// ```ignore
+/// let mut terrain = ChunkStreamer::new(StreamingSettings::default(), |coord| {
+///     Heightmap::generate(seed, coord)
+/// });
+///
+/// for event in terrain.update(camera.position()) {
+///     match event {
+///         ChunkEvent::Loaded(coord) => spawn_terrain(world, coord, terrain.get(coord).unwrap()),
+///         ChunkEvent::Unloaded(coord, _) => despawn_terrain(world, coord),
+///     }
+/// }
+/// ```
+pub struct ChunkStreamer<T> {
+    settings: StreamingSettings,
+    loaded: HashMap<ChunkCoord, T>,
+    /// Requested from the loader and not handed over yet.
+    pending: HashSet<ChunkCoord>,
+    /// Finished loading but over the budget of an earlier update.
+    ready: VecDeque<(ChunkCoord, T)>,
+    requests: Sender<ChunkCoord>,
+    results: Receiver<(ChunkCoord, T)>,
+}
+
+impl<T: Send + 'static> ChunkStreamer<T> {
+    /// Starts a loader thread that runs `load` for every requested chunk, in request order.
+    pub fn new(
+        settings: StreamingSettings,
+        load: impl Fn(ChunkCoord) -> T + Send + 'static,
+    ) -> Self {
+        let (requests, requested) = mpsc::channel::<ChunkCoord>();
+        let (loaded, results) = mpsc::channel();
+        // Stops once the streamer drops its sender.
+        thread::spawn(move || {
+            for coord in requested {
+                if loaded.send((coord, load(coord))).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            settings,
+            loaded: HashMap::new(),
+            pending: HashSet::new(),
+            ready: VecDeque::new(),
+            requests,
+            results,
+        }
+    }
+}
+
+impl<T> ChunkStreamer<T> {
+    pub fn settings(&self) -> &StreamingSettings {
+        &self.settings
+    }
+
+    /// Moves the focus to `focus` and does one update's worth of streaming work.
+    pub fn update(&mut self, focus: Vec3) -> Vec<ChunkEvent<T>> {
+        let center = ChunkCoord::containing(focus, self.settings.chunk_size);
+        let mut events = Vec::new();
+
+        self.ready.extend(self.results.try_iter());
+        let mut handed_over = 0;
+        while handed_over < self.settings.max_loads_per_update {
+            let Some((coord, chunk)) = self.ready.pop_front() else {
+                break;
+            };
+            self.pending.remove(&coord);
+            if coord.distance(center) > self.settings.unload_radius {
+                continue;
+            }
+            self.loaded.insert(coord, chunk);
+            events.push(ChunkEvent::Loaded(coord));
+            handed_over += 1;
+        }
+
+        let mut far: Vec<ChunkCoord> = self
+            .loaded
+            .keys()
+            .copied()
+            .filter(|coord| coord.distance(center) > self.settings.unload_radius)
+            .collect();
+        far.sort_by_key(|coord| std::cmp::Reverse(coord.distance(center)));
+        for coord in far.into_iter().take(self.settings.max_unloads_per_update) {
+            let chunk = self.loaded.remove(&coord).unwrap();
+            events.push(ChunkEvent::Unloaded(coord, chunk));
+        }
+
+        for coord in self
+            .missing(center)
+            .into_iter()
+            .take(self.settings.max_loads_per_update)
+        {
+            if self.requests.send(coord).is_ok() {
+                self.pending.insert(coord);
+            }
+        }
+
+        events
+    }
+
+    /// Blocks until every requested chunk finished loading, e.g. behind a loading screen so the
+    /// first frames don't show the world popping in. They are still handed over by
+    /// [`update`](ChunkStreamer::update), within its budget.
+    pub fn wait(&mut self) {
+        while self.ready.len() < self.pending.len() {
+            match self.results.recv() {
+                Ok(result) => self.ready.push_back(result),
+                Err(_) => break,
+            }
+        }
+    }
+
+    pub fn get(&self, coord: ChunkCoord) -> Option<&T> {
+        self.loaded.get(&coord)
+    }
+
+    pub fn get_mut(&mut self, coord: ChunkCoord) -> Option<&mut T> {
+        self.loaded.get_mut(&coord)
+    }
+
+    pub fn is_loaded(&self, coord: ChunkCoord) -> bool {
+        self.loaded.contains_key(&coord)
+    }
+
+    /// Every loaded chunk, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (ChunkCoord, &T)> {
+        self.loaded.iter().map(|(coord, chunk)| (*coord, chunk))
+    }
+
+    pub fn loaded_count(&self) -> usize {
+        self.loaded.len()
+    }
+
+    /// Chunks requested and not handed over yet.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Chunks within the load radius of `center` that are neither loaded nor pending, nearest
+    /// first.
+    fn missing(&self, center: ChunkCoord) -> Vec<ChunkCoord> {
+        let r = self.settings.load_radius as i32;
+        let mut missing: Vec<ChunkCoord> = (-r..=r)
+            .flat_map(|dz| (-r..=r).map(move |dx| ChunkCoord::new(center.x + dx, center.z + dz)))
+            .filter(|coord| !self.loaded.contains_key(coord) && !self.pending.contains(coord))
+            .collect();
+        missing.sort_by_key(|coord| {
+            let (dx, dz) = (coord.x - center.x, coord.z - center.z);
+            dx * dx + dz * dz
+        });
+        missing
+    }
+}
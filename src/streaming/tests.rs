@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use crate::streaming::{ChunkCoord, ChunkEvent, ChunkStreamer, StreamingSettings};
+    use glam::Vec3;
+
+    fn settings() -> StreamingSettings {
+        StreamingSettings {
+            chunk_size: 10.0,
+            load_radius: 1,
+            unload_radius: 2,
+            max_loads_per_update: 9,
+            max_unloads_per_update: 25,
+        }
+    }
+
+    /// Updates until nothing is pending, collecting the events.
+    fn settle(
+        streamer: &mut ChunkStreamer<ChunkCoord>,
+        focus: Vec3,
+    ) -> Vec<ChunkEvent<ChunkCoord>> {
+        let mut events = streamer.update(focus);
+        while streamer.pending_count() > 0 {
+            streamer.wait();
+            events.extend(streamer.update(focus));
+        }
+        events
+    }
+
+    #[test]
+    fn test_chunk_coord_containing() {
+        assert_eq!(
+            ChunkCoord::new(0, 0),
+            ChunkCoord::containing(Vec3::new(5.0, 0.0, 9.9), 10.0)
+        );
+        assert_eq!(
+            ChunkCoord::new(-1, 2),
+            ChunkCoord::containing(Vec3::new(-0.1, 3.0, 25.0), 10.0)
+        );
+        assert_eq!(2, ChunkCoord::new(-1, 0).distance(ChunkCoord::new(1, 1)));
+    }
+
+    #[test]
+    fn test_loads_around_focus() {
+        let mut streamer = ChunkStreamer::new(settings(), |coord| coord);
+        let events = settle(&mut streamer, Vec3::ZERO);
+
+        assert_eq!(9, events.len());
+        assert_eq!(9, streamer.loaded_count());
+        for (coord, chunk) in streamer.iter() {
+            assert_eq!(coord, *chunk);
+            assert!(coord.distance(ChunkCoord::new(0, 0)) <= 1);
+        }
+    }
+
+    #[test]
+    fn test_unloads_behind_focus() {
+        let mut streamer = ChunkStreamer::new(settings(), |coord| coord);
+        settle(&mut streamer, Vec3::ZERO);
+
+        // One chunk over stays within the unload radius, so nothing is dropped.
+        let events = settle(&mut streamer, Vec3::new(10.0, 0.0, 0.0));
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, ChunkEvent::Loaded(_))));
+
+        let events = settle(&mut streamer, Vec3::new(40.0, 0.0, 0.0));
+        let unloaded: Vec<ChunkCoord> = events
+            .iter()
+            .filter_map(|event| match event {
+                ChunkEvent::Unloaded(coord, chunk) => {
+                    assert_eq!(coord, chunk);
+                    Some(*coord)
+                }
+                _ => None,
+            })
+            .collect();
+        assert!(unloaded.contains(&ChunkCoord::new(-1, 0)));
+        assert!(!streamer.is_loaded(ChunkCoord::new(0, 0)));
+        assert!(streamer.is_loaded(ChunkCoord::new(4, 0)));
+    }
+
+    #[test]
+    fn test_budget_limits_loads() {
+        let mut streamer = ChunkStreamer::new(
+            StreamingSettings {
+                max_loads_per_update: 2,
+                ..settings()
+            },
+            |coord| coord,
+        );
+
+        assert!(streamer.update(Vec3::ZERO).is_empty());
+        assert_eq!(2, streamer.pending_count());
+        streamer.wait();
+
+        let events = streamer.update(Vec3::ZERO);
+        assert_eq!(2, events.len());
+        // The nearest chunk is requested first.
+        assert!(streamer.is_loaded(ChunkCoord::new(0, 0)));
+    }
+}
@@ -0,0 +1,363 @@
+//! Systems with typed parameters, fetched from the world before each run, instead of a raw
+//! `&mut World`.
+//!
+//! ```ignore
+//! fn move_players(
+//!     time: Res<Time>,
+//!     mut players: Query<(&mut Transform, &Velocity)>,
+//!     mut hits: EventReader<Hit>,
+//! ) {
+//!     for (_, (transform, velocity)) in players.iter() {
+//!         transform.translation += velocity.0 * time.delta_secs();
+//!     }
+//!     for hit in hits.iter() {
+//!         // ...
+//!     }
+//! }
+//!
+//! app.add_system(move_players);
+//! ```
+//!
+//! Parameters borrow the world at the same time, so two of them may not borrow the same
+//! component mutably, e.g. `Query<&mut Transform>` next to `Res<Transform>`. The
+//! [`Access`] of a system tells which components it reads and writes.
+
+use std::{
+    any::{type_name, TypeId},
+    cell::RefCell,
+    collections::HashSet,
+    error::Error,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use hecs::{Component, Entity, Fetch, QueryBorrow, QueryIter, Ref, RefMut, World};
+
+use crate::{app::System, resource};
+
+mod tests;
+
+/// The components a system reads and writes, e.g. to find systems that could run side by side.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Access {
+    reads: HashSet<TypeId>,
+    writes: HashSet<TypeId>,
+    /// The system takes the whole world, like one taking `&mut World`.
+    exclusive: bool,
+}
+
+impl Access {
+    /// Access to the whole world.
+    pub fn exclusive() -> Self {
+        Access {
+            exclusive: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_read(&mut self, ty: TypeId) {
+        self.reads.insert(ty);
+    }
+
+    pub fn add_write(&mut self, ty: TypeId) {
+        self.writes.insert(ty);
+    }
+
+    pub fn reads(&self, ty: TypeId) -> bool {
+        self.exclusive || self.reads.contains(&ty)
+    }
+
+    pub fn writes(&self, ty: TypeId) -> bool {
+        self.exclusive || self.writes.contains(&ty)
+    }
+
+    pub fn is_exclusive(&self) -> bool {
+        self.exclusive
+    }
+
+    /// Whether one of the two writes something the other uses.
+    pub fn conflicts_with(&self, other: &Access) -> bool {
+        if self.exclusive || other.exclusive {
+            return true;
+        }
+        let touches =
+            |access: &Access, ty: &TypeId| access.reads.contains(ty) || access.writes.contains(ty);
+        self.writes.iter().any(|ty| touches(other, ty))
+            || other.writes.iter().any(|ty| touches(self, ty))
+    }
+}
+
+/// Why a system's parameters couldn't be fetched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// A [`Res`] or [`ResMut`] whose component no entity has.
+    MissingResource(&'static str),
+    /// The component is already borrowed in a way that conflicts, e.g. by another parameter.
+    Borrowed(&'static str),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::MissingResource(name) => write!(f, "no resource of type {name}"),
+            ParamError::Borrowed(name) => write!(f, "{name} is already borrowed"),
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+/// A parameter of a typed system.
+pub trait SystemParam {
+    /// The parameter borrowing the world for `'w`.
+    type Item<'w>;
+
+    /// Kept by the system between runs, e.g. which events it has read.
+    type State: Default + 'static;
+
+    fn fetch<'w>(
+        world: &'w World,
+        state: &'w mut Self::State,
+    ) -> Result<Self::Item<'w>, ParamError>;
+
+    /// Adds what the parameter reads and writes to `access`.
+    fn access(access: &mut Access);
+}
+
+/// The entities matching `Q`, e.g. `Query<(&Transform, &mut Velocity)>`.
+pub struct Query<'w, Q: hecs::Query> {
+    borrow: QueryBorrow<'w, Q>,
+}
+
+impl<'w, Q: hecs::Query> Query<'w, Q> {
+    pub fn iter(&mut self) -> QueryIter<'_, Q> {
+        self.borrow.iter()
+    }
+}
+
+impl<'q, 'w, Q: hecs::Query> IntoIterator for &'q mut Query<'w, Q> {
+    type Item = <QueryIter<'q, Q> as Iterator>::Item;
+    type IntoIter = QueryIter<'q, Q>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Q: hecs::Query> SystemParam for Query<'_, Q> {
+    type Item<'w> = Query<'w, Q>;
+    type State = ();
+
+    fn fetch<'w>(world: &'w World, _: &'w mut ()) -> Result<Query<'w, Q>, ParamError> {
+        Ok(Query {
+            borrow: world.query::<Q>(),
+        })
+    }
+
+    fn access(access: &mut Access) {
+        Q::Fetch::for_each_borrow(|ty, unique| {
+            if unique {
+                access.add_write(ty);
+            } else {
+                access.add_read(ty);
+            }
+        });
+    }
+}
+
+/// Reads a resource, the component of a single entity like [`Time`](crate::time::Time). The
+/// system fails if there is none, take an `Option<Res<T>>` for resources that may be missing.
+pub struct Res<'w, T: Component> {
+    value: Ref<'w, T>,
+}
+
+impl<T: Component> Deref for Res<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Component> SystemParam for Res<'_, T> {
+    type Item<'w> = Res<'w, T>;
+    type State = Option<Entity>;
+
+    fn fetch<'w>(
+        world: &'w World,
+        entity: &'w mut Option<Entity>,
+    ) -> Result<Res<'w, T>, ParamError> {
+        let entity = resource_entity::<T>(world, entity)?;
+        let value = world
+            .get::<&T>(entity)
+            .map_err(|_| ParamError::Borrowed(type_name::<T>()))?;
+        Ok(Res { value })
+    }
+
+    fn access(access: &mut Access) {
+        access.add_read(TypeId::of::<T>());
+    }
+}
+
+/// Like [`Res`], but can change the resource.
+pub struct ResMut<'w, T: Component> {
+    value: RefMut<'w, T>,
+}
+
+impl<T: Component> Deref for ResMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Component> DerefMut for ResMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: Component> SystemParam for ResMut<'_, T> {
+    type Item<'w> = ResMut<'w, T>;
+    type State = Option<Entity>;
+
+    fn fetch<'w>(
+        world: &'w World,
+        entity: &'w mut Option<Entity>,
+    ) -> Result<ResMut<'w, T>, ParamError> {
+        let entity = resource_entity::<T>(world, entity)?;
+        let value = world
+            .get::<&mut T>(entity)
+            .map_err(|_| ParamError::Borrowed(type_name::<T>()))?;
+        Ok(ResMut { value })
+    }
+
+    fn access(access: &mut Access) {
+        access.add_write(TypeId::of::<T>());
+    }
+}
+
+impl<P: SystemParam> SystemParam for Option<P> {
+    type Item<'w> = Option<P::Item<'w>>;
+    type State = P::State;
+
+    fn fetch<'w>(world: &'w World, state: &'w mut P::State) -> Result<Self::Item<'w>, ParamError> {
+        match P::fetch(world, state) {
+            Ok(item) => Ok(Some(item)),
+            Err(ParamError::MissingResource(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn access(access: &mut Access) {
+        P::access(access);
+    }
+}
+
+/// The entity holding the resource of type `T`. Remembers it in `cached`, so systems only look
+/// for it again once it moved.
+pub(crate) fn resource_entity<T: Component>(
+    world: &World,
+    cached: &mut Option<Entity>,
+) -> Result<Entity, ParamError> {
+    let still_there = |entity| world.entity(entity).is_ok_and(|e| e.has::<T>());
+    if let Some(entity) = cached.filter(|&entity| still_there(entity)) {
+        return Ok(entity);
+    }
+    *cached = resource::entity::<T>(world);
+    cached.ok_or(ParamError::MissingResource(type_name::<T>()))
+}
+
+/// What a system may return: nothing, or a result whose error stops the app.
+pub trait SystemOutput {
+    fn into_result(self) -> Result<(), Box<dyn Error>>;
+}
+
+impl SystemOutput for () {
+    fn into_result(self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+impl<E: Into<Box<dyn Error>>> SystemOutput for Result<(), E> {
+    fn into_result(self) -> Result<(), Box<dyn Error>> {
+        self.map_err(Into::into)
+    }
+}
+
+/// Functions that can be added to an [`App`](crate::app::App) as systems: ones taking
+/// `&mut World`, boxed [`System`]s, and ones taking up to eight [`SystemParam`]s. `Marker` only
+/// tells the implementations apart.
+pub trait IntoSystem<Marker> {
+    /// What the system reads and writes. Systems taking the world have exclusive access.
+    fn access(&self) -> Access;
+
+    fn into_system(self) -> System;
+}
+
+/// Marker of systems taking `&mut World`.
+pub struct WorldSystem;
+
+impl<F, R> IntoSystem<(WorldSystem, R)> for F
+where
+    F: Fn(&mut World) -> R + 'static,
+    R: SystemOutput + 'static,
+{
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+
+    fn into_system(self) -> System {
+        Box::new(move |world| (self)(world).into_result())
+    }
+}
+
+macro_rules! impl_into_system {
+    ($($param:ident),*) => {
+        // The parameters are 'static so the boxed system is, which makes the compiler pick
+        // e.g. `Query<'static, &'static Transform>` and pass the shorter-lived `Item`s.
+        impl<F, R, $($param: SystemParam + 'static),*> IntoSystem<fn($($param,)*) -> R> for F
+        where
+            F: Fn($($param),*) -> R + 'static,
+            F: for<'w> Fn($($param::Item<'w>),*) -> R,
+            R: SystemOutput + 'static,
+        {
+            fn access(&self) -> Access {
+                #[allow(unused_mut)]
+                let mut access = Access::default();
+                $($param::access(&mut access);)*
+                access
+            }
+
+            #[allow(non_snake_case, unused_variables)]
+            fn into_system(self) -> System {
+                // Calls `f` through its higher-ranked signature, which the compiler can't pick
+                // on its own with both bounds in scope.
+                #[allow(clippy::too_many_arguments)]
+                fn call<R, $($param),*>(f: impl Fn($($param),*) -> R, $($param: $param),*) -> R {
+                    f($($param),*)
+                }
+
+                let states = RefCell::new(($($param::State::default(),)*));
+                Box::new(move |world| {
+                    let world: &World = world;
+                    let mut states = states.borrow_mut();
+                    let ($($param,)*) = &mut *states;
+                    $(let $param = $param::fetch(world, $param)?;)*
+                    call(&self, $($param),*).into_result()
+                })
+            }
+        }
+    };
+}
+
+impl_into_system!();
+impl_into_system!(P0);
+impl_into_system!(P0, P1);
+impl_into_system!(P0, P1, P2);
+impl_into_system!(P0, P1, P2, P3);
+impl_into_system!(P0, P1, P2, P3, P4);
+impl_into_system!(P0, P1, P2, P3, P4, P5);
+impl_into_system!(P0, P1, P2, P3, P4, P5, P6);
+impl_into_system!(P0, P1, P2, P3, P4, P5, P6, P7);
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use crate::app::App;
+    use crate::resource;
+    use crate::system::{Res, ResMut};
+
+    struct Score(u32);
+
+    #[derive(Default)]
+    struct Seen(Vec<u32>);
+
+    fn record(score: Res<Score>, mut seen: ResMut<Seen>) {
+        seen.0.push(score.0);
+    }
+
+    #[test]
+    fn test_res_follows_moved_resource() {
+        let mut app = App::new();
+        app.insert_resource(Score(1))
+            .init_resource::<Seen>()
+            .add_system(record);
+        app.step();
+
+        let old = resource::entity::<Score>(&app.world).unwrap();
+        // Despawns the resource's entity, so the new one lands on another.
+        resource::remove::<Score>(&mut app.world);
+        resource::insert(&mut app.world, Score(2));
+        assert_ne!(Some(old), resource::entity::<Score>(&app.world));
+        app.step();
+        assert_eq!(vec![1, 2], resource::get::<Seen>(&app.world).unwrap().0);
+    }
+}
@@ -0,0 +1,357 @@
+//! Time as seen by systems, kept on a single entity of the world and advanced by
+//! [`App::update`](crate::app::App::update), and timers driven by it.
+//!
+//! ```ignore
+//! fn spin(world: &mut World) -> Result<(), Box<dyn Error>> {
+//!     let dt = Time::get(world).delta_secs();
+//!     for (_, transform) in world.query_mut::<&mut Transform>() {
+//!         transform.rotation *= Quat::from_rotation_y(dt);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::{error::Error, time::Duration};
+
+use hecs::World;
+
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Time {
+    delta: Duration,
+    raw_delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+    scale: f64,
+    fixed_delta: Option<Duration>,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Time {
+            delta: Duration::ZERO,
+            raw_delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame: 0,
+            scale: 1.0,
+            fixed_delta: None,
+        }
+    }
+}
+
+impl Time {
+    /// The time of `world`, or the time before the first update if it has none.
+    pub fn get(world: &World) -> Time {
+        world
+            .query::<&Time>()
+            .iter()
+            .next()
+            .map_or_else(Time::default, |(_, time)| *time)
+    }
+
+    /// Speeds up or slows down the time of `world` from the next update on, e.g. 0 to pause or
+    /// 0.5 for slow motion. The fixed stage ticks less often, each tick still covers
+    /// [`Time::fixed_delta`].
+    pub fn set_scale(world: &mut World, scale: f64) {
+        assert!(scale >= 0.0, "time can't run backwards");
+        with_time(world, |time| time.scale = scale);
+    }
+
+    /// Game time since the last update, scaled. Zero on the first update.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Real time since the last update, ignoring the scale, e.g. for UI animations that keep
+    /// running while the game is paused.
+    pub fn raw_delta(&self) -> Duration {
+        self.raw_delta
+    }
+
+    /// Game time since the first update, the sum of the deltas.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_secs(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    /// How many updates have run, counting the current one.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// The game time every run of `FIXED_STAGE` covers, `None` without a tick rate. Systems in
+    /// the fixed stage should step by this instead of [`Time::delta`], so the simulation is
+    /// the same however often the app updates.
+    pub fn fixed_delta(&self) -> Option<Duration> {
+        self.fixed_delta
+    }
+}
+
+fn with_time<R>(world: &mut World, f: impl FnOnce(&mut Time) -> R) -> R {
+    if let Some((_, time)) = world.query_mut::<&mut Time>().into_iter().next() {
+        return f(time);
+    }
+    let mut time = Time::default();
+    let result = f(&mut time);
+    world.spawn((time,));
+    result
+}
+
+/// Starts update `frame`, `raw_delta` of real time after the last one. Returns the scaled delta
+/// to feed the fixed stage.
+pub(crate) fn advance(
+    world: &mut World,
+    frame: u64,
+    raw_delta: Duration,
+    fixed_step: Option<Duration>,
+) -> Duration {
+    with_time(world, |time| {
+        time.frame = frame;
+        time.raw_delta = raw_delta;
+        time.delta = raw_delta.mul_f64(time.scale);
+        time.elapsed += time.delta;
+        time.fixed_delta = fixed_step;
+        time.delta
+    })
+}
+
+/// Whether a [`Timer`] stops once it finishes or starts over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimerMode {
+    #[default]
+    Once,
+    Repeating,
+}
+
+/// Counts down a duration, e.g. a cooldown or a delay. Ticked by [`tick_timers`] when it is a
+/// component, or by hand with [`Timer::tick`].
+///
+/// ```ignore
+/// world.spawn((Weapon, Timer::from_secs(0.5, TimerMode::Once)));
+///
+/// // In a system after `tick_timers`.
+/// for (_, timer) in world.query_mut::<&mut Timer>() {
+///     if fire_pressed && timer.finished() {
+///         fire();
+///         timer.reset();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timer {
+    duration: Duration,
+    elapsed: Duration,
+    mode: TimerMode,
+    paused: bool,
+    finished: bool,
+    times_finished: u32,
+}
+
+impl Timer {
+    pub fn new(duration: Duration, mode: TimerMode) -> Self {
+        Timer {
+            duration,
+            elapsed: Duration::ZERO,
+            mode,
+            paused: false,
+            finished: false,
+            times_finished: 0,
+        }
+    }
+
+    pub fn from_secs(secs: f32, mode: TimerMode) -> Self {
+        Timer::new(Duration::from_secs_f32(secs), mode)
+    }
+
+    /// Advances the timer by `delta` unless it is paused. A repeating timer keeps the time past
+    /// the end, so it doesn't drift.
+    pub fn tick(&mut self, delta: Duration) -> &Self {
+        self.times_finished = 0;
+        if self.paused {
+            return self;
+        }
+        if self.mode == TimerMode::Once && self.finished {
+            return self;
+        }
+
+        self.elapsed += delta;
+        if self.elapsed < self.duration {
+            if self.mode == TimerMode::Repeating {
+                self.finished = false;
+            }
+            return self;
+        }
+
+        self.finished = true;
+        match self.mode {
+            TimerMode::Once => {
+                self.elapsed = self.duration;
+                self.times_finished = 1;
+            }
+            // A zero duration finishes once per tick instead of infinitely often.
+            TimerMode::Repeating if self.duration.is_zero() => {
+                self.elapsed = Duration::ZERO;
+                self.times_finished = 1;
+            }
+            TimerMode::Repeating => {
+                let times = self.elapsed.as_nanos() / self.duration.as_nanos();
+                self.times_finished = times.try_into().unwrap_or(u32::MAX);
+                self.elapsed -= self.duration * self.times_finished;
+            }
+        }
+        self
+    }
+
+    /// Whether the timer has run out. A repeating timer is finished only on the ticks it wraps.
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Whether the last tick finished the timer.
+    pub fn just_finished(&self) -> bool {
+        self.times_finished > 0
+    }
+
+    /// How often the last tick finished the timer, more than once if a repeating timer's
+    /// duration is shorter than the tick.
+    pub fn times_finished_this_tick(&self) -> u32 {
+        self.times_finished
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed)
+    }
+
+    /// How far the timer got, from 0 to 1, e.g. for a progress bar.
+    pub fn fraction(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Changes the duration, keeping the elapsed time.
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    pub fn mode(&self) -> TimerMode {
+        self.mode
+    }
+
+    /// Starts over from zero, not paused and not finished.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.paused = false;
+        self.finished = false;
+        self.times_finished = 0;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// Counts up from zero, e.g. how long a button has been held or a level took.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stopwatch {
+    elapsed: Duration,
+    paused: bool,
+}
+
+impl Stopwatch {
+    pub fn new() -> Self {
+        Stopwatch::default()
+    }
+
+    /// Advances the stopwatch by `delta` unless it is paused.
+    pub fn tick(&mut self, delta: Duration) -> &Self {
+        if !self.paused {
+            self.elapsed += delta;
+        }
+        self
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn unpause(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+}
+
+/// A system that ticks every [`Timer`] and [`Stopwatch`] component by [`Time::delta`]. Add it
+/// before the systems reading them, to a stage that runs once per update. In
+/// [`FIXED_STAGE`](crate::app::FIXED_STAGE) it would tick by a whole update on every tick, use
+/// [`tick_fixed_timers`] there.
+///
+/// ```ignore
+/// app.add_named_system(DEFAULT_STAGE, "timers", Box::new(time::tick_timers));
+/// ```
+pub fn tick_timers(world: &mut World) -> Result<(), Box<dyn Error>> {
+    let delta = Time::get(world).delta();
+    tick_all(world, delta);
+    Ok(())
+}
+
+/// Like [`tick_timers`], for [`FIXED_STAGE`](crate::app::FIXED_STAGE): ticks by
+/// [`Time::fixed_delta`], or by [`Time::delta`] without a tick rate.
+pub fn tick_fixed_timers(world: &mut World) -> Result<(), Box<dyn Error>> {
+    let time = Time::get(world);
+    tick_all(world, time.fixed_delta().unwrap_or(time.delta()));
+    Ok(())
+}
+
+fn tick_all(world: &mut World, delta: Duration) {
+    for (_, timer) in world.query_mut::<&mut Timer>() {
+        timer.tick(delta);
+    }
+    for (_, stopwatch) in world.query_mut::<&mut Stopwatch>() {
+        stopwatch.tick(delta);
+    }
+}
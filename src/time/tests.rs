@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use crate::app::{App, FIXED_STAGE};
+    use crate::time::{self, Stopwatch, Timer, TimerMode};
+    use std::time::Duration;
+
+    #[test]
+    fn test_repeating_timer_wraps() {
+        let mut timer = Timer::new(Duration::from_millis(100), TimerMode::Repeating);
+        timer.tick(Duration::from_millis(350));
+        assert!(timer.finished());
+        assert_eq!(3, timer.times_finished_this_tick());
+        assert_eq!(Duration::from_millis(50), timer.elapsed());
+
+        timer.tick(Duration::from_millis(10));
+        assert!(!timer.finished());
+        assert!(!timer.just_finished());
+    }
+
+    #[test]
+    fn test_once_timer_stays_finished() {
+        let mut timer = Timer::new(Duration::from_millis(100), TimerMode::Once);
+        timer.tick(Duration::from_millis(350));
+        assert!(timer.just_finished());
+        assert_eq!(1, timer.times_finished_this_tick());
+        assert_eq!(Duration::from_millis(100), timer.elapsed());
+
+        timer.tick(Duration::from_millis(10));
+        assert!(timer.finished());
+        assert!(!timer.just_finished());
+    }
+
+    #[test]
+    fn test_stopwatch_pause() {
+        let mut stopwatch = Stopwatch::new();
+        stopwatch.tick(Duration::from_millis(100));
+        stopwatch.pause();
+        stopwatch.tick(Duration::from_millis(100));
+        assert_eq!(Duration::from_millis(100), stopwatch.elapsed());
+        stopwatch.unpause();
+        stopwatch.tick(Duration::from_millis(100));
+        assert_eq!(Duration::from_millis(200), stopwatch.elapsed());
+    }
+
+    #[test]
+    fn test_fixed_timers_tick_by_fixed_step() {
+        let mut app = App::new();
+        let timer = app
+            .world
+            .spawn((Timer::new(Duration::from_secs(1), TimerMode::Once),));
+        app.set_tick_rate(Some(10.0)).add_named_system(
+            FIXED_STAGE,
+            "timers",
+            time::tick_fixed_timers,
+        );
+        app.run_fixed(5);
+        let elapsed = app.world.get::<&Timer>(timer).unwrap().elapsed();
+        assert!((elapsed.as_secs_f64() - 0.5).abs() < 1e-6);
+    }
+}
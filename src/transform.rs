@@ -0,0 +1,126 @@
+//! Placement of entities in the world, with interpolation between fixed simulation ticks.
+//!
+//! Simulation systems move the [`Transform`]. When [`App`](crate::app::App) runs at a fixed tick
+//! rate it also keeps the [`PreviousTransform`] of every entity and writes a [`RenderTransform`]
+//! blended between the two each frame, so rendering faster than the simulation stays smooth.
+
+use glam::{Mat4, Quat, Vec3};
+use hecs::World;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::IDENTITY
+    }
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Transform {
+            translation,
+            ..Transform::IDENTITY
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The model matrix, e.g. for `Cube::model`.
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// `child`, relative to `self`, as a transform relative to what `self` is relative to.
+    pub fn mul_transform(&self, child: &Transform) -> Transform {
+        Transform {
+            translation: self.translation + self.rotation * (self.scale * child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    /// Blends from `self` at `alpha` 0 to `other` at `alpha` 1.
+    pub fn lerp(&self, other: &Transform, alpha: f32) -> Transform {
+        Transform {
+            translation: self.translation.lerp(other.translation, alpha),
+            rotation: self.rotation.slerp(other.rotation, alpha),
+            scale: self.scale.lerp(other.scale, alpha),
+        }
+    }
+}
+
+/// The [`Transform`] before the last simulation tick. Added and updated by `App`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PreviousTransform(pub Transform);
+
+/// The [`Transform`] to draw this frame. Without a fixed tick rate it is a copy of the
+/// `Transform`. Added and updated by `App`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RenderTransform(pub Transform);
+
+/// Remembers every `Transform` as its `PreviousTransform` before a simulation tick.
+pub(crate) fn snapshot_previous(world: &mut World) {
+    let mut missing = Vec::new();
+    for (entity, (transform, previous)) in world
+        .query_mut::<(&Transform, Option<&mut PreviousTransform>)>()
+        .into_iter()
+    {
+        match previous {
+            Some(previous) => previous.0 = *transform,
+            None => missing.push((entity, *transform)),
+        }
+    }
+    for (entity, transform) in missing {
+        world
+            .insert_one(entity, PreviousTransform(transform))
+            .unwrap();
+    }
+}
+
+/// Writes the `RenderTransform` of every entity, `alpha` of the way from its `PreviousTransform`
+/// to its `Transform`.
+pub(crate) fn interpolate(world: &mut World, alpha: f32) {
+    let mut missing = Vec::new();
+    for (entity, (transform, previous, render)) in world
+        .query_mut::<(
+            &Transform,
+            Option<&PreviousTransform>,
+            Option<&mut RenderTransform>,
+        )>()
+        .into_iter()
+    {
+        let blended = match previous {
+            Some(previous) => previous.0.lerp(transform, alpha),
+            None => *transform,
+        };
+        match render {
+            Some(render) => render.0 = blended,
+            None => missing.push((entity, blended)),
+        }
+    }
+    for (entity, transform) in missing {
+        world
+            .insert_one(entity, RenderTransform(transform))
+            .unwrap();
+    }
+}